@@ -0,0 +1,69 @@
+//! Compare generating commands with `Launcher::run` and
+//! `Launcher::build` against `Launcher::run_into` and
+//! `Launcher::build_into`.
+//!
+//! Run with `cargo bench --bench command_generation`.
+
+use docker_command::command_run::Command;
+use docker_command::{
+    BaseCommand, BuildOpt, Launcher, PublishPorts, RunOpt, Volume,
+};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 100_000;
+
+fn make_opt() -> RunOpt {
+    let mut opt = RunOpt::new("ghcr.io/owner/app:v1");
+    opt.detach = true;
+    opt.env = vec![("RUST_LOG".into(), "debug".into())];
+    opt.name = Some("app".into());
+    opt.publish = vec![PublishPorts {
+        container: 80.into(),
+        host: Some(8080.into()),
+        ..Default::default()
+    }];
+    opt.volumes = vec![Volume::bind("/data", "/data")];
+    opt.args = vec!["serve".into(), "--port=80".into()];
+    opt
+}
+
+fn time<F: FnMut()>(name: &str, mut f: F) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    let per_iter = elapsed / ITERATIONS;
+    println!("{:<10} {:>10?} total, {:>8?}/iter", name, elapsed, per_iter);
+}
+
+fn main() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let opt = make_opt();
+
+    time("run", || {
+        let cmd = launcher.run(black_box(opt.clone()));
+        black_box(&cmd);
+    });
+
+    let mut cmd = Command::new("");
+    time("run_into", || {
+        launcher.run_into(black_box(&opt), &mut cmd);
+        black_box(&cmd);
+    });
+
+    let mut opt = BuildOpt::new(".");
+    opt.tag = Some("ghcr.io/owner/app:v1".into());
+    opt.build_args = vec![("VERSION", "1").into()];
+
+    time("build", || {
+        let cmd = launcher.build(black_box(opt.clone()));
+        black_box(&cmd);
+    });
+
+    time("build_into", || {
+        launcher.build_into(black_box(&opt), &mut cmd);
+        black_box(&cmd);
+    });
+}
@@ -0,0 +1,1190 @@
+//! Arguments for each subcommand, without a [`Launcher`].
+//!
+//! Each `*_args` function returns the arguments that the [`Launcher`]
+//! method of the same name would add after the program, for use with
+//! other ways of running processes. Global options, [`Defaults`],
+//! Windows path translation, and hooks are not applied. The
+//! [`Launcher`] methods are built on the same code.
+//!
+//! The few options that podman spells differently are handled by
+//! passing the engine to [`build_args`] and [`run_args`].
+//!
+//! # Examples
+//!
+//! ```
+//! use docker_command::{args, BaseCommand, RunOpt};
+//! let mut opt = RunOpt::new("alpine");
+//! opt.remove = true;
+//! assert_eq!(
+//!     args::run_args(&opt, BaseCommand::Docker),
+//!     ["run", "--rm", "alpine"]
+//! );
+//! ```
+//!
+//! [`Defaults`]: crate::Defaults
+//! [`Launcher`]: crate::Launcher
+
+use crate::paths::{host_mount, host_volume};
+use crate::{
+    BakeOpt, BaseCommand, BuildOpt, ContainerName, ContainerRef, CpOpt,
+    CreateNetworkOpt, CreateVolumeOpt, ExecOpt, Filter, GenerateSystemdOpt,
+    ImageRef, ImagesOpt, InfoOpt, InspectOpt, KillOpt, ListNetworksOpt,
+    ListVolumesOpt, LoadOpt, Protocol, PruneOpt, PsOpt, PullOpt, PullPolicy,
+    PushOpt, RemoveContainersOpt, RemoveImagesOpt, RunOpt, SaveOpt,
+    ScoutCvesOpt, SearchOpt, StatsOpt, StopOpt, SystemDfOpt, SystemServiceOpt,
+    TrustInspectOpt, TrustSignOpt, WindowsPathStyle,
+};
+use command_run::Command;
+use std::ffi::OsString;
+
+/// Run `add` on an empty command and return the arguments it added.
+fn collect<F: FnOnce(&mut Command)>(add: F) -> Vec<OsString> {
+    let mut cmd = Command::new("");
+    add(&mut cmd);
+    cmd.args
+}
+
+macro_rules! args_fns {
+    ($($name:ident, $add:ident => $method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            #[doc = concat!(
+                "Arguments for [`Launcher::",
+                stringify!($method),
+                "`](crate::Launcher::",
+                stringify!($method),
+                ").",
+            )]
+            pub fn $name($($arg: $ty),*) -> Vec<OsString> {
+                collect(|cmd| $add(cmd, $($arg),*))
+            }
+        )*
+    };
+}
+
+args_fns! {
+    bake_args, add_bake_args => bake(opt: &BakeOpt);
+    cp_args, add_cp_args => cp(opt: &CpOpt);
+    create_network_args, add_create_network_args =>
+        create_network(opt: &CreateNetworkOpt);
+    create_volume_args, add_create_volume_args =>
+        create_volume(opt: &CreateVolumeOpt);
+    exec_args, add_exec_args => exec(opt: &ExecOpt);
+    generate_systemd_args, add_generate_systemd_args =>
+        generate_systemd(opt: &GenerateSystemdOpt);
+    images_args, add_images_args => images(opt: &ImagesOpt);
+    info_args, add_info_args => info(opt: &InfoOpt);
+    inspect_args, add_inspect_args => inspect(opt: &InspectOpt);
+    kill_args, add_kill_args => kill(opt: &KillOpt);
+    list_networks_args, add_list_networks_args =>
+        list_networks(opt: &ListNetworksOpt);
+    list_volumes_args, add_list_volumes_args =>
+        list_volumes(opt: &ListVolumesOpt);
+    load_args, add_load_args => load(opt: &LoadOpt);
+    port_args, add_port_args =>
+        port(container: &ContainerRef, private_port: Option<(u16, Protocol)>);
+    prune_containers_args, add_prune_containers_args =>
+        prune_containers(opt: &PruneOpt);
+    prune_images_args, add_prune_images_args => prune_images(opt: &PruneOpt);
+    prune_networks_args, add_prune_networks_args =>
+        prune_networks(opt: &PruneOpt);
+    prune_volumes_args, add_prune_volumes_args =>
+        prune_volumes(opt: &PruneOpt);
+    ps_args, add_ps_args => ps(opt: &PsOpt);
+    pull_args, add_pull_args => pull(opt: &PullOpt);
+    push_args, add_push_args => push(opt: &PushOpt);
+    remove_containers_args, add_remove_containers_args =>
+        remove_containers(opt: &RemoveContainersOpt);
+    remove_images_args, add_remove_images_args =>
+        remove_images(opt: &RemoveImagesOpt);
+    remove_network_args, add_remove_network_args =>
+        remove_network(name: &str);
+    remove_volume_args, add_remove_volume_args => remove_volume(name: &str);
+    rename_args, add_rename_args =>
+        rename(container: &ContainerRef, new_name: &ContainerName);
+    save_args, add_save_args => save(opt: &SaveOpt);
+    scout_cves_args, add_scout_cves_args => scout_cves(opt: &ScoutCvesOpt);
+    search_args, add_search_args => search(opt: &SearchOpt);
+    start_args, add_start_args => start(containers: &[ContainerRef]);
+    stats_args, add_stats_args => stats(opt: &StatsOpt);
+    stop_args, add_stop_args => stop(opt: &StopOpt);
+    system_df_args, add_system_df_args => system_df(opt: &SystemDfOpt);
+    system_migrate_args, add_system_migrate_args => system_migrate();
+    system_reset_args, add_system_reset_args => system_reset(force: bool);
+    system_service_args, add_system_service_args =>
+        system_service(opt: &SystemServiceOpt);
+    tag_args, add_tag_args => tag(source: &ImageRef, target: &ImageRef);
+    trust_inspect_args, add_trust_inspect_args =>
+        trust_inspect(opt: &TrustInspectOpt);
+    trust_sign_args, add_trust_sign_args => trust_sign(opt: &TrustSignOpt);
+    unshare_args, add_unshare_args => unshare(command: &Command);
+    wait_args, add_wait_args => wait(containers: &[ContainerRef]);
+}
+
+/// Arguments for [`Launcher::build`](crate::Launcher::build), with
+/// the syntax of `engine`.
+pub fn build_args(opt: &BuildOpt, engine: BaseCommand) -> Vec<OsString> {
+    collect(|cmd| add_build_args(cmd, opt, engine == BaseCommand::Podman))
+}
+
+/// Arguments for [`Launcher::run`](crate::Launcher::run), with the
+/// syntax of `engine`.
+pub fn run_args(opt: &RunOpt, engine: BaseCommand) -> Vec<OsString> {
+    collect(|cmd| add_run_args(cmd, opt, engine == BaseCommand::Podman, None))
+}
+
+/// Add a `--filter` argument for each filter.
+fn add_filter_args(cmd: &mut Command, filters: &[Filter]) {
+    for filter in filters {
+        cmd.add_arg_pair("--filter", filter.arg());
+    }
+}
+
+/// Add the arguments for `Launcher::bake` to `cmd`.
+pub(crate) fn add_bake_args(cmd: &mut Command, opt: &BakeOpt) {
+    cmd.add_arg_pair("buildx", "bake");
+
+    // --file
+    for file in &opt.files {
+        cmd.add_arg_pair("--file", file);
+    }
+
+    // --load
+    if opt.load {
+        cmd.add_arg("--load");
+    }
+
+    // --push
+    if opt.push {
+        cmd.add_arg("--push");
+    }
+
+    // --set
+    for (key, value) in &opt.set {
+        cmd.add_arg_pair("--set", format!("{}={}", key, value));
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_args(&opt.targets);
+}
+
+/// Add the arguments for `Launcher::build` to `cmd`.
+pub(crate) fn add_build_args(cmd: &mut Command, opt: &BuildOpt, podman: bool) {
+    cmd.add_arg("build");
+
+    // --annotation
+    for (key, value) in &opt.annotations {
+        cmd.add_arg_pair("--annotation", format!("{}={}", key, value));
+    }
+
+    // --build-arg
+    for build_arg in &opt.build_args {
+        cmd.add_arg_pair("--build-arg", build_arg.arg());
+    }
+
+    // --disable-content-trust
+    if let Some(disable) = opt.disable_content_trust {
+        cmd.add_arg(format!("--disable-content-trust={}", disable));
+    }
+
+    // --file
+    if let Some(dockerfile) = &opt.dockerfile {
+        cmd.add_arg_pair("--file", dockerfile);
+    }
+
+    // --iidfile
+    if let Some(iidfile) = &opt.iidfile {
+        cmd.add_arg_pair("--iidfile", iidfile);
+    }
+
+    // --label
+    for (key, value) in &opt.labels {
+        cmd.add_arg_pair("--label", format!("{}={}", key, value));
+    }
+
+    // --network
+    if let Some(network) = &opt.network {
+        cmd.add_arg_pair("--network", network.arg());
+    }
+
+    // --no-cache
+    if opt.no_cache {
+        cmd.add_arg("--no-cache");
+    }
+
+    // --no-cache-filter
+    for stage in &opt.no_cache_filter {
+        cmd.add_arg_pair("--no-cache-filter", stage);
+    }
+
+    // --provenance
+    if let Some(provenance) = &opt.provenance {
+        cmd.add_arg(format!("--provenance={}", provenance));
+    }
+
+    // --pull
+    if let Some(pull) = opt.pull {
+        if podman {
+            cmd.add_arg(format!("--pull={}", pull.as_str()));
+        } else if pull == PullPolicy::Always {
+            cmd.add_arg("--pull");
+        } else {
+            cmd.add_arg("--pull=false");
+        }
+    }
+
+    // --quiet
+    if opt.quiet {
+        cmd.add_arg("--quiet");
+    }
+
+    // --sbom
+    if let Some(sbom) = &opt.sbom {
+        cmd.add_arg(format!("--sbom={}", sbom));
+    }
+
+    // --tag
+    if let Some(tag) = &opt.tag {
+        cmd.add_arg_pair("--tag", tag);
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(&opt.context);
+}
+
+/// Add the arguments for `Launcher::cp` to `cmd`.
+pub(crate) fn add_cp_args(cmd: &mut Command, opt: &CpOpt) {
+    cmd.add_arg("cp");
+
+    // --archive
+    if opt.archive {
+        cmd.add_arg("--archive");
+    }
+
+    // --follow-link
+    if opt.follow_link {
+        cmd.add_arg("--follow-link");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(opt.src.arg());
+    cmd.add_arg(opt.dst.arg());
+}
+
+/// Add the arguments for `Launcher::create_network` to `cmd`.
+pub(crate) fn add_create_network_args(
+    cmd: &mut Command,
+    opt: &CreateNetworkOpt,
+) {
+    cmd.add_arg_pair("network", "create");
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(&opt.name);
+}
+
+/// Add the arguments for `Launcher::create_volume` to `cmd`.
+pub(crate) fn add_create_volume_args(cmd: &mut Command, opt: &CreateVolumeOpt) {
+    cmd.add_arg_pair("volume", "create");
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(&opt.name);
+}
+
+/// Add the arguments for `Launcher::exec` to `cmd`.
+pub(crate) fn add_exec_args(cmd: &mut Command, opt: &ExecOpt) {
+    cmd.add_arg("exec");
+
+    // --detach
+    if opt.detach {
+        cmd.add_arg("--detach");
+    }
+
+    // --env
+    for (key, value) in &opt.env {
+        let mut arg = OsString::new();
+        arg.push(key);
+        arg.push("=");
+        arg.push(value);
+        cmd.add_arg_pair("--env", arg);
+    }
+
+    // --interactive
+    if opt.interactive {
+        cmd.add_arg("--interactive");
+    }
+
+    // --tty
+    if opt.tty {
+        cmd.add_arg("--tty");
+    }
+
+    // --user
+    if let Some(user) = &opt.user {
+        cmd.add_arg_pair("--user", user.arg());
+    }
+
+    // --workdir
+    if let Some(workdir) = &opt.workdir {
+        cmd.add_arg_pair("--workdir", workdir);
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(opt.container.to_string());
+    cmd.add_arg(&opt.command);
+    cmd.add_args(&opt.args);
+}
+
+/// Add the arguments for `Launcher::generate_systemd` to `cmd`.
+pub(crate) fn add_generate_systemd_args(
+    cmd: &mut Command,
+    opt: &GenerateSystemdOpt,
+) {
+    cmd.add_arg_pair("generate", "systemd");
+
+    // --files
+    if opt.files {
+        cmd.add_arg("--files");
+    }
+
+    // --name
+    if opt.name {
+        cmd.add_arg("--name");
+    }
+
+    // --new
+    if opt.new {
+        cmd.add_arg("--new");
+    }
+
+    // --restart-policy
+    if let Some(policy) = &opt.restart_policy {
+        cmd.add_arg_pair("--restart-policy", policy);
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(&opt.container);
+}
+
+/// Add the arguments for `Launcher::images` to `cmd`.
+pub(crate) fn add_images_args(cmd: &mut Command, opt: &ImagesOpt) {
+    cmd.add_arg("images");
+
+    // --all
+    if opt.all {
+        cmd.add_arg("--all");
+    }
+
+    // --digests
+    if opt.digests {
+        cmd.add_arg("--digests");
+    }
+
+    add_filter_args(cmd, &opt.filters);
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --no-trunc
+    if opt.no_trunc {
+        cmd.add_arg("--no-trunc");
+    }
+
+    // --quiet
+    if opt.quiet {
+        cmd.add_arg("--quiet");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    if let Some(repository) = &opt.repository {
+        cmd.add_arg(repository);
+    }
+}
+
+/// Add the arguments for `Launcher::info` to `cmd`.
+pub(crate) fn add_info_args(cmd: &mut Command, opt: &InfoOpt) {
+    cmd.add_arg("info");
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+}
+
+/// Add the arguments for `Launcher::inspect` to `cmd`.
+pub(crate) fn add_inspect_args(cmd: &mut Command, opt: &InspectOpt) {
+    cmd.add_arg("inspect");
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --size
+    if opt.size {
+        cmd.add_arg("--size");
+    }
+
+    // --type
+    if let Some(kind) = &opt.kind {
+        cmd.add_arg_pair("--type", kind.as_str());
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_args(&opt.objects);
+}
+
+/// Add the arguments for `Launcher::kill` to `cmd`.
+pub(crate) fn add_kill_args(cmd: &mut Command, opt: &KillOpt) {
+    cmd.add_arg("kill");
+
+    // --signal
+    if let Some(signal) = &opt.signal {
+        cmd.add_arg_pair("--signal", signal);
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    for container in &opt.containers {
+        cmd.add_arg(container.to_string());
+    }
+}
+
+/// Add the arguments for `Launcher::list_networks` to `cmd`.
+pub(crate) fn add_list_networks_args(cmd: &mut Command, opt: &ListNetworksOpt) {
+    cmd.add_arg_pair("network", "ls");
+
+    add_filter_args(cmd, &opt.filters);
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --no-trunc
+    if opt.no_trunc {
+        cmd.add_arg("--no-trunc");
+    }
+
+    // --quiet
+    if opt.quiet {
+        cmd.add_arg("--quiet");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+}
+
+/// Add the arguments for `Launcher::list_volumes` to `cmd`.
+pub(crate) fn add_list_volumes_args(cmd: &mut Command, opt: &ListVolumesOpt) {
+    cmd.add_arg_pair("volume", "ls");
+
+    add_filter_args(cmd, &opt.filters);
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --quiet
+    if opt.quiet {
+        cmd.add_arg("--quiet");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+}
+
+/// Add the arguments for `Launcher::load` to `cmd`.
+pub(crate) fn add_load_args(cmd: &mut Command, opt: &LoadOpt) {
+    cmd.add_arg("load");
+
+    // --input
+    if let Some(input) = &opt.input {
+        cmd.add_arg_pair("--input", input);
+    }
+
+    // --quiet
+    if opt.quiet {
+        cmd.add_arg("--quiet");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+}
+
+/// Add the arguments for `Launcher::port` to `cmd`.
+pub(crate) fn add_port_args(
+    cmd: &mut Command,
+    container: &ContainerRef,
+    private_port: Option<(u16, Protocol)>,
+) {
+    cmd.add_arg("port");
+    cmd.add_arg(container.to_string());
+    if let Some((port, protocol)) = private_port {
+        cmd.add_arg(format!("{}/{}", port, protocol));
+    }
+}
+
+/// Add the arguments for `<object> prune` to `cmd`.
+pub(crate) fn add_prune_args(cmd: &mut Command, object: &str, opt: &PruneOpt) {
+    cmd.add_arg_pair(object, "prune");
+
+    // --all
+    if opt.all {
+        cmd.add_arg("--all");
+    }
+
+    add_filter_args(cmd, &opt.filters);
+
+    // --force
+    if opt.force {
+        cmd.add_arg("--force");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+}
+
+/// Add the arguments for `Launcher::prune_containers` to `cmd`.
+fn add_prune_containers_args(cmd: &mut Command, opt: &PruneOpt) {
+    add_prune_args(cmd, "container", opt);
+}
+
+/// Add the arguments for `Launcher::prune_images` to `cmd`.
+fn add_prune_images_args(cmd: &mut Command, opt: &PruneOpt) {
+    add_prune_args(cmd, "image", opt);
+}
+
+/// Add the arguments for `Launcher::prune_networks` to `cmd`.
+fn add_prune_networks_args(cmd: &mut Command, opt: &PruneOpt) {
+    add_prune_args(cmd, "network", opt);
+}
+
+/// Add the arguments for `Launcher::prune_volumes` to `cmd`.
+fn add_prune_volumes_args(cmd: &mut Command, opt: &PruneOpt) {
+    add_prune_args(cmd, "volume", opt);
+}
+
+/// Add the arguments for `Launcher::ps` to `cmd`.
+pub(crate) fn add_ps_args(cmd: &mut Command, opt: &PsOpt) {
+    cmd.add_arg("ps");
+
+    // --all
+    if opt.all {
+        cmd.add_arg("--all");
+    }
+
+    add_filter_args(cmd, &opt.filters);
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --no-trunc
+    if opt.no_trunc {
+        cmd.add_arg("--no-trunc");
+    }
+
+    // --quiet
+    if opt.quiet {
+        cmd.add_arg("--quiet");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+}
+
+/// Add the arguments for `Launcher::pull` to `cmd`.
+pub(crate) fn add_pull_args(cmd: &mut Command, opt: &PullOpt) {
+    cmd.add_arg("pull");
+
+    // --all-tags
+    if opt.all_tags {
+        cmd.add_arg("--all-tags");
+    }
+
+    // --disable-content-trust
+    if let Some(disable) = opt.disable_content_trust {
+        cmd.add_arg(format!("--disable-content-trust={}", disable));
+    }
+
+    // --platform
+    if let Some(platform) = &opt.platform {
+        cmd.add_arg_pair("--platform", platform);
+    }
+
+    // --quiet
+    if opt.quiet {
+        cmd.add_arg("--quiet");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(opt.image.to_string());
+}
+
+/// Add the arguments for `Launcher::push` to `cmd`.
+pub(crate) fn add_push_args(cmd: &mut Command, opt: &PushOpt) {
+    cmd.add_arg("push");
+
+    // --all-tags
+    if opt.all_tags {
+        cmd.add_arg("--all-tags");
+    }
+
+    // --disable-content-trust
+    if let Some(disable) = opt.disable_content_trust {
+        cmd.add_arg(format!("--disable-content-trust={}", disable));
+    }
+
+    // --quiet
+    if opt.quiet {
+        cmd.add_arg("--quiet");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(opt.image.to_string());
+}
+
+/// Add the arguments for `Launcher::remove_containers` to `cmd`.
+pub(crate) fn add_remove_containers_args(
+    cmd: &mut Command,
+    opt: &RemoveContainersOpt,
+) {
+    cmd.add_arg("rm");
+
+    // --force
+    if opt.force {
+        cmd.add_arg("--force");
+    }
+
+    // --volumes
+    if opt.volumes {
+        cmd.add_arg("--volumes");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    for container in &opt.containers {
+        cmd.add_arg(container.to_string());
+    }
+}
+
+/// Add the arguments for `Launcher::remove_images` to `cmd`.
+pub(crate) fn add_remove_images_args(cmd: &mut Command, opt: &RemoveImagesOpt) {
+    cmd.add_arg("rmi");
+
+    // --force
+    if opt.force {
+        cmd.add_arg("--force");
+    }
+
+    // --no-prune
+    if opt.no_prune {
+        cmd.add_arg("--no-prune");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    for image in &opt.images {
+        cmd.add_arg(image);
+    }
+}
+
+/// Add the arguments for `Launcher::remove_network` to `cmd`.
+pub(crate) fn add_remove_network_args(cmd: &mut Command, name: &str) {
+    cmd.add_arg_pair("network", "rm");
+    cmd.add_arg(name);
+}
+
+/// Add the arguments for `Launcher::remove_volume` to `cmd`.
+pub(crate) fn add_remove_volume_args(cmd: &mut Command, name: &str) {
+    cmd.add_arg_pair("volume", "rm");
+    cmd.add_arg(name);
+}
+
+/// Add the arguments for `Launcher::rename` to `cmd`.
+pub(crate) fn add_rename_args(
+    cmd: &mut Command,
+    container: &ContainerRef,
+    new_name: &ContainerName,
+) {
+    cmd.add_arg("rename");
+    cmd.add_arg(container.to_string());
+    cmd.add_arg(new_name.as_str());
+}
+
+/// Add the arguments for `Launcher::run` to `cmd`, translating
+/// host paths with `windows_paths`.
+pub(crate) fn add_run_args(
+    cmd: &mut Command,
+    opt: &RunOpt,
+    podman: bool,
+    windows_paths: Option<&WindowsPathStyle>,
+) {
+    // Reserve space for the options that repeat. The others add at
+    // most a few arguments each, which the first growth covers.
+    let pairs = opt.env.len()
+        + opt.device_read_bps.len()
+        + opt.device_write_bps.len()
+        + opt.gidmap.len()
+        + opt.labels.len()
+        + opt.mounts.len()
+        + opt.publish.len()
+        + opt.secrets.len()
+        + opt.security_opts.len()
+        + opt.uidmap.len()
+        + opt.volumes.len();
+    cmd.args
+        .reserve(2 * pairs + opt.extra_args.len() + opt.args.len() + 3);
+    cmd.add_arg("run");
+
+    // --cgroup-parent
+    if let Some(parent) = &opt.cgroup_parent {
+        cmd.add_arg_pair("--cgroup-parent", parent);
+    }
+
+    // --cpu-period
+    if let Some(period) = opt.cpu_period {
+        cmd.add_arg_pair("--cpu-period", period.as_micros().to_string());
+    }
+
+    // --cpu-quota
+    if let Some(quota) = opt.cpu_quota {
+        cmd.add_arg_pair("--cpu-quota", quota.as_micros().to_string());
+    }
+
+    // --detach
+    if opt.detach {
+        cmd.add_arg("--detach");
+    }
+
+    // --device-read-bps
+    for throttle in &opt.device_read_bps {
+        cmd.add_arg_pair("--device-read-bps", throttle.arg());
+    }
+
+    // --device-write-bps
+    for throttle in &opt.device_write_bps {
+        cmd.add_arg_pair("--device-write-bps", throttle.arg());
+    }
+
+    // --disable-content-trust
+    if let Some(disable) = opt.disable_content_trust {
+        cmd.add_arg(format!("--disable-content-trust={}", disable));
+    }
+
+    // --env
+    for (key, value) in &opt.env {
+        let mut arg = OsString::new();
+        arg.push(key);
+        arg.push("=");
+        arg.push(value);
+        cmd.add_arg_pair("--env", arg);
+    }
+
+    // --gidmap
+    for map in &opt.gidmap {
+        cmd.add_arg_pair("--gidmap", map.arg());
+    }
+
+    // --init
+    if opt.init {
+        cmd.add_arg("--init");
+    }
+
+    // --init-path
+    if let Some(path) = &opt.init_path {
+        if podman {
+            cmd.add_arg_pair("--init-path", path);
+        }
+    }
+
+    // --interactive
+    if opt.interactive {
+        cmd.add_arg("--interactive");
+    }
+
+    // --ipc
+    if let Some(ipc) = &opt.ipc {
+        cmd.add_arg_pair("--ipc", ipc.arg());
+    }
+
+    // --label
+    for (key, value) in &opt.labels {
+        cmd.add_arg_pair("--label", format!("{}={}", key, value));
+    }
+
+    // --mount
+    for mount in &opt.mounts {
+        cmd.add_arg_pair("--mount", host_mount(windows_paths, mount).arg());
+    }
+
+    // --name
+    if let Some(name) = &opt.name {
+        cmd.add_arg_pair("--name", name);
+    }
+
+    // --network
+    if let Some(network) = &opt.network {
+        cmd.add_arg_pair("--network", network.arg());
+    }
+    for network in &opt.networks {
+        cmd.add_arg_pair("--network", network.arg());
+    }
+
+    // --publish
+    for publish in &opt.publish {
+        cmd.add_arg_pair("--publish", publish.arg());
+    }
+
+    // --publish-all
+    if opt.publish_all {
+        cmd.add_arg("--publish-all");
+    }
+
+    // --pull
+    if let Some(pull) = opt.pull {
+        cmd.add_arg(format!("--pull={}", pull.as_str()));
+    }
+
+    // --read-only
+    if opt.read_only {
+        cmd.add_arg("--read-only");
+    }
+
+    // --restart
+    if let Some(restart) = &opt.restart {
+        cmd.add_arg_pair("--restart", restart.arg());
+    }
+
+    // --rm
+    if opt.remove {
+        cmd.add_arg("--rm");
+    }
+
+    // --secret (and the options used for other kinds of secret)
+    for secret in &opt.secrets {
+        cmd.add_arg_pair(secret.flag(), secret.arg());
+    }
+
+    // --security-opt
+    for security_opt in &opt.security_opts {
+        cmd.add_arg_pair("--security-opt", security_opt.arg());
+    }
+
+    // --tty
+    if opt.tty {
+        cmd.add_arg("--tty");
+    }
+
+    // --uidmap
+    for map in &opt.uidmap {
+        cmd.add_arg_pair("--uidmap", map.arg());
+    }
+
+    // --user
+    if let Some(user) = &opt.user {
+        cmd.add_arg_pair("--user", user.arg());
+    }
+
+    // --userns
+    if let Some(userns) = &opt.userns {
+        cmd.add_arg(format!("--userns={}", userns.arg()));
+    }
+
+    // --volume
+    for vol in &opt.volumes {
+        cmd.add_arg_pair(
+            "--volume",
+            host_volume(windows_paths, vol).arg_with(podman),
+        );
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    // Add image and command+args
+    cmd.add_arg(opt.image.to_string());
+    if let Some(command) = &opt.command {
+        cmd.add_arg(command);
+    }
+    cmd.add_args(&opt.args);
+}
+
+/// Add the arguments for `Launcher::save` to `cmd`.
+pub(crate) fn add_save_args(cmd: &mut Command, opt: &SaveOpt) {
+    cmd.add_arg("save");
+
+    // --output
+    if let Some(output) = &opt.output {
+        cmd.add_arg_pair("--output", output);
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    for image in &opt.images {
+        cmd.add_arg(image.to_string());
+    }
+}
+
+/// Add the arguments for `Launcher::scout_cves` to `cmd`.
+pub(crate) fn add_scout_cves_args(cmd: &mut Command, opt: &ScoutCvesOpt) {
+    cmd.add_arg_pair("scout", "cves");
+
+    // --exit-code
+    if opt.exit_code {
+        cmd.add_arg("--exit-code");
+    }
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --only-fixed
+    if opt.only_fixed {
+        cmd.add_arg("--only-fixed");
+    }
+
+    // --only-severity
+    if !opt.only_severity.is_empty() {
+        cmd.add_arg_pair("--only-severity", opt.only_severity.join(","));
+    }
+
+    // --output
+    if let Some(output) = &opt.output {
+        cmd.add_arg_pair("--output", output);
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(opt.image.to_string());
+}
+
+/// Add the arguments for `Launcher::search` to `cmd`.
+pub(crate) fn add_search_args(cmd: &mut Command, opt: &SearchOpt) {
+    cmd.add_arg("search");
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --limit
+    if let Some(limit) = opt.limit {
+        cmd.add_arg_pair("--limit", limit.to_string());
+    }
+
+    // --list-tags
+    if opt.list_tags {
+        cmd.add_arg("--list-tags");
+    }
+
+    // --no-trunc
+    if opt.no_trunc {
+        cmd.add_arg("--no-trunc");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(&opt.term);
+}
+
+/// Add the arguments for `Launcher::start` to `cmd`.
+pub(crate) fn add_start_args(cmd: &mut Command, containers: &[ContainerRef]) {
+    cmd.add_arg("start");
+    for container in containers {
+        cmd.add_arg(container.to_string());
+    }
+}
+
+/// Add the arguments for `Launcher::stats` to `cmd`.
+pub(crate) fn add_stats_args(cmd: &mut Command, opt: &StatsOpt) {
+    cmd.add_arg("stats");
+
+    // --all
+    if opt.all {
+        cmd.add_arg("--all");
+    }
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --no-stream
+    if opt.no_stream {
+        cmd.add_arg("--no-stream");
+    }
+
+    // --no-trunc
+    if opt.no_trunc {
+        cmd.add_arg("--no-trunc");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    for container in &opt.containers {
+        cmd.add_arg(container.to_string());
+    }
+}
+
+/// Add the arguments for `Launcher::stop` to `cmd`.
+pub(crate) fn add_stop_args(cmd: &mut Command, opt: &StopOpt) {
+    cmd.add_arg("stop");
+
+    if let Some(time) = opt.time {
+        cmd.add_arg_pair("--time", time.secs_arg());
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    for container in &opt.containers {
+        cmd.add_arg(container.to_string());
+    }
+}
+
+/// Add the arguments for `Launcher::system_df` to `cmd`.
+pub(crate) fn add_system_df_args(cmd: &mut Command, opt: &SystemDfOpt) {
+    cmd.add_arg_pair("system", "df");
+
+    // --format
+    if let Some(format) = &opt.format {
+        cmd.add_arg_pair("--format", format);
+    }
+
+    // --verbose
+    if opt.verbose {
+        cmd.add_arg("--verbose");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+}
+
+/// Add the arguments for `Launcher::system_migrate` to `cmd`.
+pub(crate) fn add_system_migrate_args(cmd: &mut Command) {
+    cmd.add_arg_pair("system", "migrate");
+}
+
+/// Add the arguments for `Launcher::system_reset` to `cmd`.
+pub(crate) fn add_system_reset_args(cmd: &mut Command, force: bool) {
+    cmd.add_arg_pair("system", "reset");
+    if force {
+        cmd.add_arg("--force");
+    }
+}
+
+/// Add the arguments for `Launcher::system_service` to `cmd`.
+pub(crate) fn add_system_service_args(
+    cmd: &mut Command,
+    opt: &SystemServiceOpt,
+) {
+    cmd.add_arg_pair("system", "service");
+
+    // --time
+    if let Some(time) = opt.time {
+        cmd.add_arg_pair("--time", time.secs_arg());
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    if let Some(uri) = &opt.uri {
+        cmd.add_arg(uri);
+    }
+}
+
+/// Add the arguments for `Launcher::tag` to `cmd`.
+pub(crate) fn add_tag_args(
+    cmd: &mut Command,
+    source: &ImageRef,
+    target: &ImageRef,
+) {
+    cmd.add_arg("tag");
+    cmd.add_arg(source.to_string());
+    cmd.add_arg(target.to_string());
+}
+
+/// Add the arguments for `Launcher::trust_inspect` to `cmd`.
+pub(crate) fn add_trust_inspect_args(cmd: &mut Command, opt: &TrustInspectOpt) {
+    cmd.add_arg_pair("trust", "inspect");
+
+    // --pretty
+    if opt.pretty {
+        cmd.add_arg("--pretty");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    for image in &opt.images {
+        cmd.add_arg(image.to_string());
+    }
+}
+
+/// Add the arguments for `Launcher::trust_sign` to `cmd`.
+pub(crate) fn add_trust_sign_args(cmd: &mut Command, opt: &TrustSignOpt) {
+    cmd.add_arg_pair("trust", "sign");
+
+    // --local
+    if opt.local {
+        cmd.add_arg("--local");
+    }
+
+    // Extra arguments
+    cmd.add_args(&opt.extra_args);
+
+    cmd.add_arg(opt.image.to_string());
+}
+
+/// Add the arguments for `Launcher::unshare` to `cmd`. Only the
+/// program and arguments of `command` are used.
+pub(crate) fn add_unshare_args(cmd: &mut Command, command: &Command) {
+    cmd.add_arg("unshare");
+    cmd.add_arg(&command.program);
+    cmd.add_args(&command.args);
+}
+
+/// Add the arguments for `Launcher::wait` to `cmd`.
+pub(crate) fn add_wait_args(cmd: &mut Command, containers: &[ContainerRef]) {
+    cmd.add_arg("wait");
+    for container in containers {
+        cmd.add_arg(container.to_string());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    CommandExt, Error, ImageRef, InspectOpt, Launcher, ObjectKind,
+    RestartPolicy, RunOpt, ValidationError,
+};
+use command_run::Command;
+
+/// Way in which a container differs from the options it should have
+/// been created with. See [`Launcher::audit`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Drift {
+    /// No container with the name exists.
+    Missing,
+
+    /// The container has a different restart policy.
+    RestartPolicy {
+        /// Policy from the desired options.
+        expected: RestartPolicy,
+
+        /// Policy of the container.
+        actual: RestartPolicy,
+    },
+
+    /// The container was created from a different image than the one
+    /// the desired image reference currently points to locally.
+    Image {
+        /// ID of the desired image, or `None` if the image is not
+        /// present locally.
+        expected: Option<String>,
+
+        /// ID of the container's image.
+        actual: String,
+    },
+}
+
+/// Treat `on-failure:0` the same as `on-failure`; docker reports an
+/// unlimited retry count as zero.
+fn normalize(policy: RestartPolicy) -> RestartPolicy {
+    match policy {
+        RestartPolicy::OnFailure(Some(0)) => RestartPolicy::OnFailure(None),
+        policy => policy,
+    }
+}
+
+/// Strip the `sha256:` prefix that docker includes and podman does
+/// not.
+fn image_id(id: &str) -> &str {
+    id.strip_prefix("sha256:").unwrap_or(id)
+}
+
+/// Get the name from `opt`, which is required to find the container.
+pub(crate) fn container_name(opt: &RunOpt) -> Result<String, ValidationError> {
+    opt.name.clone().ok_or_else(|| {
+        ValidationError::new(
+            "run options",
+            opt.image.to_string(),
+            "no container name",
+        )
+    })
+}
+
+impl Launcher {
+    /// Run `inspect` with `format`, returning the command and its
+    /// output, or None if the object does not exist.
+    pub(crate) fn inspect_format(
+        &self,
+        kind: ObjectKind,
+        object: String,
+        format: &str,
+    ) -> Result<Option<(Command, String)>, Error> {
+        let mut opt = InspectOpt::new([object]);
+        opt.format = Some(format.into());
+        opt.kind = Some(kind);
+        let mut cmd = self.enforce(self.inspect(opt))?;
+        cmd.check = false;
+        cmd.capture = true;
+        let output = cmd.run_logged()?;
+        if output.status.success() {
+            let output = output.stdout_string_lossy().trim().to_string();
+            Ok(Some((cmd, output)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Compare existing containers against the options they should
+    /// have, for simple drift detection.
+    ///
+    /// Each of `desired` must have a [`RunOpt::name`]. The container
+    /// with that name is checked for:
+    ///
+    /// * Its restart policy, compared with [`RunOpt::restart`] (`None`
+    ///   is the same as [`RestartPolicy::No`]).
+    /// * Its image, compared with the ID that [`RunOpt::image`]
+    ///   currently refers to locally. This detects containers that
+    ///   are still running an old image after a pull.
+    ///
+    /// Returns each container name with the differences found, leaving
+    /// out containers that match.
+    pub fn audit(
+        &self,
+        desired: &[RunOpt],
+    ) -> Result<Vec<(String, Vec<Drift>)>, Error> {
+        let mut report = Vec::new();
+        for opt in desired {
+            let name = container_name(opt)?;
+            let drift = self.container_drift(&name, opt)?;
+            if !drift.is_empty() {
+                report.push((name, drift));
+            }
+        }
+        Ok(report)
+    }
+
+    pub(crate) fn container_drift(
+        &self,
+        name: &str,
+        opt: &RunOpt,
+    ) -> Result<Vec<Drift>, Error> {
+        let format = "{{.HostConfig.RestartPolicy.Name}} \
+                      {{.HostConfig.RestartPolicy.MaximumRetryCount}} \
+                      {{.Image}}";
+        let Some((cmd, output)) =
+            self.inspect_format(ObjectKind::Container, name.into(), format)?
+        else {
+            return Ok(vec![Drift::Missing]);
+        };
+        let unexpected = || Error::UnexpectedOutput {
+            command: cmd.command_line_shell(),
+            output: output.clone(),
+        };
+        let mut fields = output.split(' ');
+        let (Some(policy), Some(count), Some(image), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(unexpected());
+        };
+
+        let mut drift = Vec::new();
+        let actual = match (policy, count) {
+            ("" | "no", _) => RestartPolicy::No,
+            ("on-failure", count) => RestartPolicy::OnFailure(Some(
+                count.parse().map_err(|_| unexpected())?,
+            )),
+            (policy, _) => policy.parse().map_err(|_| unexpected())?,
+        };
+        let expected = opt.restart.unwrap_or_default();
+        if normalize(actual) != normalize(expected) {
+            drift.push(Drift::RestartPolicy {
+                expected,
+                actual: normalize(actual),
+            });
+        }
+
+        let expected = self.local_image_id(&opt.image)?;
+        if expected.as_deref().map(image_id) != Some(image_id(image)) {
+            drift.push(Drift::Image {
+                expected,
+                actual: image.to_string(),
+            });
+        }
+        Ok(drift)
+    }
+
+    /// Get the ID of `image`, or None if it is not present locally.
+    fn local_image_id(
+        &self,
+        image: &ImageRef,
+    ) -> Result<Option<String>, Error> {
+        Ok(self
+            .inspect_format(ObjectKind::Image, image.to_string(), "{{.Id}}")?
+            .map(|(_, id)| id))
+    }
+}
@@ -0,0 +1,117 @@
+use crate::CommandExt;
+use command_run::{Command, Output};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Set of commands to run concurrently.
+///
+/// Commands are started in the order they were added, with at most
+/// [`Batch::concurrency`] running at a time. Each command is run with
+/// [`CommandExt::run_logged`], and a failing command does not prevent
+/// the others from running.
+///
+/// # Examples
+///
+/// ```no_run
+/// use docker_command::{Batch, BaseCommand, Launcher, PullOpt};
+/// let launcher = Launcher::from(BaseCommand::Docker);
+/// let batch: Batch = ["alpine", "debian", "fedora"]
+///     .into_iter()
+///     .map(|image| launcher.pull(PullOpt::new(image)))
+///     .collect();
+/// for result in batch.with_concurrency(2).run() {
+///     result.unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Batch {
+    commands: Vec<Command>,
+    concurrency: usize,
+}
+
+impl Batch {
+    /// Create an empty batch. The concurrency defaults to the number of
+    /// CPUs available.
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            concurrency: thread::available_parallelism()
+                .map(NonZeroUsize::get)
+                .unwrap_or(1),
+        }
+    }
+
+    /// Set the maximum number of commands running at once. Values less
+    /// than one are treated as one.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Maximum number of commands running at once.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Commands in the batch.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Add a command to the batch.
+    pub fn push(&mut self, command: Command) {
+        self.commands.push(command);
+    }
+
+    /// Run all the commands and wait for them to finish.
+    ///
+    /// The results are in the same order as the commands were added.
+    pub fn run(self) -> Vec<Result<Output, command_run::Error>> {
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<Output, command_run::Error>>>> =
+            Mutex::new(self.commands.iter().map(|_| None).collect());
+        let workers = self.concurrency.min(self.commands.len());
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(cmd) = self.commands.get(i) else {
+                        break;
+                    };
+                    let result = cmd.run_logged();
+                    results.lock().unwrap()[i] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("every command was run"))
+            .collect()
+    }
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extend<Command> for Batch {
+    fn extend<I: IntoIterator<Item = Command>>(&mut self, iter: I) {
+        self.commands.extend(iter);
+    }
+}
+
+impl FromIterator<Command> for Batch {
+    fn from_iter<I: IntoIterator<Item = Command>>(iter: I) -> Self {
+        let mut batch = Self::new();
+        batch.extend(iter);
+        batch
+    }
+}
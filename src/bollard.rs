@@ -0,0 +1,238 @@
+//! Conversions to the request types of the [`bollard`] Docker API
+//! client. Requires the `bollard` feature.
+//!
+//! Options that have no equivalent in the API (such as
+//! [`RunOpt::detach`], [`RunOpt::uidmap`], and `extra_args`) are
+//! ignored.
+//!
+//! [`bollard`]: https://docs.rs/bollard
+
+use crate::{
+    BuildArg, BuildOpt, DeviceThrottle, Mount, MountType, PullPolicy,
+    RestartPolicy, RunOpt,
+};
+use ::bollard::models::{
+    self, ContainerCreateBody, EndpointIpamConfig, EndpointSettings,
+    HostConfig, NetworkingConfig, PortBinding, PortMap, ThrottleDevice,
+};
+use ::bollard::query_parameters::{
+    BuildImageOptions, BuildImageOptionsBuilder,
+};
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn lossy<S: AsRef<std::ffi::OsStr>>(s: S) -> String {
+    s.as_ref().to_string_lossy().into_owned()
+}
+
+fn micros(duration: Duration) -> i64 {
+    i64::try_from(duration.as_micros()).unwrap_or(i64::MAX)
+}
+
+fn throttles(throttles: &[DeviceThrottle]) -> Vec<ThrottleDevice> {
+    throttles
+        .iter()
+        .map(|throttle| ThrottleDevice {
+            path: Some(lossy(&throttle.device)),
+            rate: Some(i64::try_from(throttle.rate).unwrap_or(i64::MAX)),
+        })
+        .collect()
+}
+
+fn restart_policy(policy: RestartPolicy) -> models::RestartPolicy {
+    use models::RestartPolicyNameEnum as Name;
+    let (name, count) = match policy {
+        RestartPolicy::No => (Name::NO, None),
+        RestartPolicy::Always => (Name::ALWAYS, None),
+        RestartPolicy::UnlessStopped => (Name::UNLESS_STOPPED, None),
+        RestartPolicy::OnFailure(count) => (Name::ON_FAILURE, count),
+    };
+    models::RestartPolicy {
+        name: Some(name),
+        maximum_retry_count: count.map(i64::from),
+    }
+}
+
+impl From<&Mount> for models::Mount {
+    fn from(mount: &Mount) -> Self {
+        Self {
+            typ: Some(match mount.kind {
+                MountType::Bind => models::MountType::BIND,
+                MountType::Volume => models::MountType::VOLUME,
+                MountType::Tmpfs => models::MountType::TMPFS,
+            }),
+            source: mount.source.as_ref().map(lossy),
+            target: Some(lossy(&mount.target)),
+            read_only: Some(mount.read_only),
+            ..Default::default()
+        }
+    }
+}
+
+/// Convert to the body of a create-container request. The container
+/// name is passed separately in the API, so [`RunOpt::name`] is not
+/// included.
+impl From<&RunOpt> for ContainerCreateBody {
+    fn from(opt: &RunOpt) -> Self {
+        let mut cmd: Vec<String> = Vec::new();
+        if let Some(command) = &opt.command {
+            cmd.push(lossy(command));
+        }
+        cmd.extend(opt.args.iter().map(lossy));
+
+        let mut exposed_ports = Vec::new();
+        let mut port_bindings = PortMap::new();
+        for publish in &opt.publish {
+            let container_ports = publish.container.0.clone();
+            for (i, port) in container_ports.enumerate() {
+                let key = format!("{}/{}", port, publish.protocol.as_str());
+                let host_port = publish.host.as_ref().map(|host| {
+                    // Map ranges port by port.
+                    (host.0.start() + i as u16).to_string()
+                });
+                if !exposed_ports.contains(&key) {
+                    exposed_ports.push(key.clone());
+                }
+                port_bindings
+                    .entry(key)
+                    .or_default()
+                    .get_or_insert_with(Vec::new)
+                    .push(PortBinding {
+                        host_ip: publish.ip.clone(),
+                        host_port,
+                    });
+            }
+        }
+
+        let endpoints: HashMap<String, EndpointSettings> = opt
+            .networks
+            .iter()
+            .map(|network| {
+                let ipam_config =
+                    if network.ip.is_some() || network.ip6.is_some() {
+                        Some(EndpointIpamConfig {
+                            ipv4_address: network.ip.clone(),
+                            ipv6_address: network.ip6.clone(),
+                            ..Default::default()
+                        })
+                    } else {
+                        None
+                    };
+                let settings = EndpointSettings {
+                    aliases: if network.aliases.is_empty() {
+                        None
+                    } else {
+                        Some(network.aliases.clone())
+                    },
+                    ipam_config,
+                    ..Default::default()
+                };
+                (network.name.clone(), settings)
+            })
+            .collect();
+
+        let host_config = HostConfig {
+            auto_remove: Some(opt.remove),
+            binds: Some(opt.volumes.iter().map(|v| lossy(v.arg())).collect()),
+            blkio_device_read_bps: Some(throttles(&opt.device_read_bps)),
+            blkio_device_write_bps: Some(throttles(&opt.device_write_bps)),
+            cgroup_parent: opt.cgroup_parent.clone(),
+            cpu_period: opt.cpu_period.map(micros),
+            cpu_quota: opt.cpu_quota.map(micros),
+            init: Some(opt.init),
+            ipc_mode: opt.ipc.as_ref().map(|ipc| ipc.arg()),
+            mounts: Some(opt.mounts.iter().map(Into::into).collect()),
+            network_mode: opt
+                .network
+                .as_ref()
+                .map(|network| network.arg())
+                .or_else(|| opt.networks.first().map(|n| n.name.clone())),
+            port_bindings: Some(port_bindings),
+            publish_all_ports: Some(opt.publish_all),
+            readonly_rootfs: Some(opt.read_only),
+            restart_policy: opt.restart.map(restart_policy),
+            security_opt: Some(
+                opt.security_opts.iter().map(|o| o.arg()).collect(),
+            ),
+            userns_mode: opt.userns.as_ref().map(|userns| userns.arg()),
+            ..Default::default()
+        };
+
+        Self {
+            image: Some(opt.image.to_string()),
+            cmd: if cmd.is_empty() { None } else { Some(cmd) },
+            env: Some(
+                opt.env
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", lossy(k), lossy(v)))
+                    .collect(),
+            ),
+            exposed_ports: Some(exposed_ports),
+            labels: if opt.labels.is_empty() {
+                None
+            } else {
+                Some(opt.labels.iter().cloned().collect())
+            },
+            open_stdin: Some(opt.interactive),
+            tty: Some(opt.tty),
+            user: opt.user.as_ref().map(|user| user.arg()),
+            host_config: Some(host_config),
+            networking_config: if endpoints.is_empty() {
+                None
+            } else {
+                Some(NetworkingConfig {
+                    endpoints_config: Some(endpoints),
+                })
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Convert to the query parameters of a build-image request. The build
+/// context is sent separately as a tarball, so [`BuildOpt::context`]
+/// is only used to make [`BuildOpt::dockerfile`] relative to it.
+impl From<&BuildOpt> for BuildImageOptions {
+    fn from(opt: &BuildOpt) -> Self {
+        let mut builder = BuildImageOptionsBuilder::new()
+            .nocache(opt.no_cache)
+            .q(opt.quiet);
+        if let Some(dockerfile) = &opt.dockerfile {
+            let relative =
+                dockerfile.strip_prefix(&opt.context).unwrap_or(dockerfile);
+            builder = builder.dockerfile(&lossy(relative));
+        }
+        if !opt.labels.is_empty() {
+            let labels: HashMap<String, String> =
+                opt.labels.iter().cloned().collect();
+            builder = builder.labels(&labels);
+        }
+        if let Some(network) = &opt.network {
+            builder = builder.networkmode(&network.arg());
+        }
+        if opt.pull == Some(PullPolicy::Always) {
+            builder = builder.pull("true");
+        }
+        if let Some(tag) = &opt.tag {
+            builder = builder.t(tag);
+        }
+        if !opt.build_args.is_empty() {
+            // The API has no pass-through form, so inherited values are
+            // read from the environment here.
+            let build_args: HashMap<String, String> = opt
+                .build_args
+                .iter()
+                .filter_map(|build_arg| match build_arg {
+                    BuildArg::Set(key, value) => {
+                        Some((key.clone(), value.clone()))
+                    }
+                    BuildArg::Inherit(key) => std::env::var(key)
+                        .ok()
+                        .map(|value| (key.clone(), value)),
+                })
+                .collect();
+            builder = builder.buildargs(&build_args);
+        }
+        builder.build()
+    }
+}
@@ -0,0 +1,171 @@
+use crate::child::{RunningChild, POLL_INTERVAL};
+use crate::{CommandExt, Error};
+use command_run::{Command, Output};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+/// Handle for stopping a [`SpawnedCommand`].
+///
+/// The token can be cloned and sent to other threads or tasks;
+/// cancelling any clone cancels the command. [`CancelToken::cancel`]
+/// never blocks, so it is safe to call from async code.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Create a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of the command.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether [`CancelToken::cancel`] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Command running in a subprocess that can be stopped with a
+/// [`CancelToken`].
+///
+/// This is intended for commands that run until stopped, such as
+/// `logs --follow` or `events`, as well as long-running commands such
+/// as `build`. Create one with [`CommandExt::spawn_cancellable`].
+///
+/// When cancelled, the process is sent `SIGTERM`, and then `SIGKILL`
+/// if it has not exited after the grace period.
+///
+/// # Examples
+///
+/// ```no_run
+/// use docker_command::{BaseCommand, CommandExt, Launcher, PullOpt};
+/// use std::{thread, time::Duration};
+///
+/// let launcher = Launcher::from(BaseCommand::Docker);
+/// let pull = launcher.pull(PullOpt::new("alpine"));
+/// let spawned = pull.spawn_cancellable().unwrap();
+/// let token = spawned.cancel_token();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_secs(5));
+///     token.cancel();
+/// });
+/// let result = spawned.wait();
+/// ```
+#[derive(Debug)]
+pub struct SpawnedCommand {
+    child: RunningChild,
+    command: String,
+    token: CancelToken,
+    grace_period: Duration,
+}
+
+impl SpawnedCommand {
+    /// Grace period used if [`SpawnedCommand::with_grace_period`] is
+    /// not called.
+    pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+    /// Set how long to wait after `SIGTERM` before sending `SIGKILL`.
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Use `token` to cancel the command instead of the token created
+    /// when the command was spawned. This allows one token to stop
+    /// several commands.
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Get a token that cancels this command.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.token.clone()
+    }
+
+    /// ID of the subprocess.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Wait for the command to finish, blocking the current thread.
+    ///
+    /// If the command is cancelled first, it is stopped and
+    /// [`Error::Cancelled`] is returned.
+    pub fn wait(mut self) -> Result<Output, Error> {
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                return Ok(self.child.finish(status)?);
+            }
+            if self.token.is_cancelled() {
+                self.child.terminate(self.grace_period)?;
+                return Err(Error::Cancelled {
+                    command: self.command,
+                });
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Wait for the command to finish without blocking an async
+    /// runtime.
+    ///
+    /// The waiting is done by [`SpawnedCommand::wait`] on a background
+    /// thread, so this works with any executor.
+    pub fn wait_async(self) -> WaitFuture {
+        let shared = Arc::new(Mutex::new(WaitState::default()));
+        let state = shared.clone();
+        thread::spawn(move || {
+            let result = self.wait();
+            let mut state = state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+        WaitFuture(shared)
+    }
+}
+
+#[derive(Default)]
+struct WaitState {
+    result: Option<Result<Output, Error>>,
+    waker: Option<Waker>,
+}
+
+/// Future returned by [`SpawnedCommand::wait_async`].
+pub struct WaitFuture(Arc<Mutex<WaitState>>);
+
+impl Future for WaitFuture {
+    type Output = Result<Output, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Start `cmd` without waiting for it to finish.
+pub(crate) fn spawn(cmd: &Command) -> Result<SpawnedCommand, Error> {
+    Ok(SpawnedCommand {
+        child: RunningChild::spawn(cmd)?,
+        command: cmd.command_line_shell(),
+        token: CancelToken::new(),
+        grace_period: SpawnedCommand::DEFAULT_GRACE_PERIOD,
+    })
+}
@@ -0,0 +1,317 @@
+use crate::{BuildOpt, RunOpt, SecretSource, UserNamespace, ValidationError};
+use std::fmt;
+use std::str::FromStr;
+
+/// Container engine CLI, for checking [`Capability`] support.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Backend {
+    /// Docker.
+    Docker,
+
+    /// Podman.
+    Podman,
+
+    /// nerdctl, the containerd CLI.
+    Nerdctl,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+            Self::Nerdctl => "nerdctl",
+        })
+    }
+}
+
+/// Version of a [`Backend`], e.g. `24.0.7`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Version {
+    /// Major version.
+    pub major: u32,
+
+    /// Minor version.
+    pub minor: u32,
+
+    /// Patch version.
+    pub patch: u32,
+}
+
+impl Version {
+    /// Create a version from its parts.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = ValidationError;
+
+    /// Parse a version such as `24.0.7`, `v1.7.0`, or `4.9.3-dev`.
+    /// Anything after the numbers is ignored, and missing minor or
+    /// patch numbers are zero.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ValidationError::new("version", s, "expected x.y.z");
+        let s = s.strip_prefix('v').unwrap_or(s);
+        let end = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let mut parts = s[..end].split('.');
+        let mut next = |required| match parts.next() {
+            Some(part) => part.parse().map_err(|_| err()),
+            None if required => Err(err()),
+            None => Ok(0),
+        };
+        Ok(Self::new(next(true)?, next(false)?, next(false)?))
+    }
+}
+
+/// Option whose support differs between backends.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{Backend, Capability, Version};
+/// let cap = Capability::MultipleNetworks;
+/// assert!(!cap.supported_by(Backend::Docker, Version::new(24, 0, 7)));
+/// assert!(cap.supported_by(Backend::Docker, Version::new(25, 0, 0)));
+/// assert!(cap.supported_by(Backend::Podman, Version::new(4, 9, 3)));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Capability {
+    /// [`RunOpt::init_path`].
+    InitPath,
+
+    /// [`RunOpt::disable_content_trust`] and
+    /// [`BuildOpt::disable_content_trust`].
+    DisableContentTrust,
+
+    /// [`RunOpt::uidmap`] and [`RunOpt::gidmap`].
+    IdMaps,
+
+    /// [`UserNamespace::KeepId`] in [`RunOpt::userns`].
+    KeepId,
+
+    /// [`SecretSource::Podman`] in [`RunOpt::secrets`].
+    PodmanSecrets,
+
+    /// More than one entry in [`RunOpt::networks`].
+    MultipleNetworks,
+
+    /// Consistency options such as [`VolumeOption::Cached`] in
+    /// [`RunOpt::volumes`]. Podman accepts but ignores them, so they
+    /// are left out of podman commands.
+    ///
+    /// [`VolumeOption::Cached`]: crate::VolumeOption::Cached
+    VolumeConsistency,
+
+    /// [`BuildOpt::annotations`].
+    BuildAnnotations,
+
+    /// [`BuildOpt::sbom`] and [`BuildOpt::provenance`].
+    BuildAttestations,
+
+    /// [`BuildOpt::no_cache_filter`].
+    NoCacheFilter,
+}
+
+/// Minimum version of each backend that supports each capability. A
+/// backend that is not listed does not support it.
+const MATRIX: &[(Capability, &[(Backend, Version)])] = {
+    use Backend::*;
+    use Capability::*;
+    &[
+        (InitPath, &[(Podman, Version::new(1, 0, 0))]),
+        (DisableContentTrust, &[(Docker, Version::new(1, 8, 0))]),
+        (IdMaps, &[(Podman, Version::new(1, 0, 0))]),
+        (KeepId, &[(Podman, Version::new(1, 5, 0))]),
+        (PodmanSecrets, &[(Podman, Version::new(3, 1, 0))]),
+        (
+            MultipleNetworks,
+            &[
+                (Docker, Version::new(25, 0, 0)),
+                (Podman, Version::new(1, 0, 0)),
+                (Nerdctl, Version::new(0, 8, 0)),
+            ],
+        ),
+        (VolumeConsistency, &[(Docker, Version::new(17, 4, 0))]),
+        (
+            BuildAnnotations,
+            &[
+                (Docker, Version::new(25, 0, 0)),
+                (Podman, Version::new(1, 0, 0)),
+            ],
+        ),
+        (BuildAttestations, &[(Docker, Version::new(23, 0, 0))]),
+        (NoCacheFilter, &[(Docker, Version::new(23, 0, 0))]),
+    ]
+};
+
+impl Capability {
+    /// All capabilities.
+    pub const ALL: &'static [Self] = &[
+        Self::InitPath,
+        Self::DisableContentTrust,
+        Self::IdMaps,
+        Self::KeepId,
+        Self::PodmanSecrets,
+        Self::MultipleNetworks,
+        Self::VolumeConsistency,
+        Self::BuildAnnotations,
+        Self::BuildAttestations,
+        Self::NoCacheFilter,
+    ];
+
+    /// Get the oldest version of `backend` that supports the
+    /// capability, or None if no version does.
+    pub fn min_version(&self, backend: Backend) -> Option<Version> {
+        MATRIX
+            .iter()
+            .find(|(cap, _)| cap == self)
+            .and_then(|(_, versions)| {
+                versions.iter().find(|(other, _)| *other == backend)
+            })
+            .map(|(_, version)| *version)
+    }
+
+    /// Whether `version` of `backend` supports the capability.
+    pub fn supported_by(&self, backend: Backend, version: Version) -> bool {
+        self.min_version(backend)
+            .map(|min| version >= min)
+            .unwrap_or(false)
+    }
+
+    /// Get the name of the option, e.g. `init_path`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::InitPath => "init_path",
+            Self::DisableContentTrust => "disable_content_trust",
+            Self::IdMaps => "uidmap/gidmap",
+            Self::KeepId => "userns=keep-id",
+            Self::PodmanSecrets => "podman secrets",
+            Self::MultipleNetworks => "multiple networks",
+            Self::VolumeConsistency => "volume consistency",
+            Self::BuildAnnotations => "annotations",
+            Self::BuildAttestations => "sbom/provenance",
+            Self::NoCacheFilter => "no_cache_filter",
+        }
+    }
+
+    /// Get the capabilities that `opt` uses.
+    pub fn used_by_run(opt: &RunOpt) -> Vec<Self> {
+        let mut out = Vec::new();
+        if opt.init_path.is_some() {
+            out.push(Self::InitPath);
+        }
+        if opt.disable_content_trust.is_some() {
+            out.push(Self::DisableContentTrust);
+        }
+        if !opt.uidmap.is_empty() || !opt.gidmap.is_empty() {
+            out.push(Self::IdMaps);
+        }
+        if matches!(opt.userns, Some(UserNamespace::KeepId { .. })) {
+            out.push(Self::KeepId);
+        }
+        if opt
+            .secrets
+            .iter()
+            .any(|secret| matches!(secret, SecretSource::Podman { .. }))
+        {
+            out.push(Self::PodmanSecrets);
+        }
+        if opt.networks.len() > 1 {
+            out.push(Self::MultipleNetworks);
+        }
+        if opt
+            .volumes
+            .iter()
+            .any(|vol| vol.options.iter().any(|opt| opt.is_consistency()))
+        {
+            out.push(Self::VolumeConsistency);
+        }
+        out
+    }
+
+    /// Get the capabilities that `opt` uses.
+    pub fn used_by_build(opt: &BuildOpt) -> Vec<Self> {
+        let mut out = Vec::new();
+        if !opt.annotations.is_empty() {
+            out.push(Self::BuildAnnotations);
+        }
+        if opt.disable_content_trust.is_some() {
+            out.push(Self::DisableContentTrust);
+        }
+        if opt.sbom.is_some() || opt.provenance.is_some() {
+            out.push(Self::BuildAttestations);
+        }
+        if !opt.no_cache_filter.is_empty() {
+            out.push(Self::NoCacheFilter);
+        }
+        out
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Return an error for the first of `used` that `version` of
+/// `backend` does not support.
+fn check(
+    used: Vec<Capability>,
+    backend: Backend,
+    version: Version,
+) -> Result<(), ValidationError> {
+    match used
+        .into_iter()
+        .find(|cap| !cap.supported_by(backend, version))
+    {
+        Some(cap) => Err(ValidationError::new(
+            "option",
+            cap.name(),
+            match cap.min_version(backend) {
+                Some(min) => format!("requires {} {} or newer", backend, min),
+                None => format!("not supported by {}", backend),
+            },
+        )),
+        None => Ok(()),
+    }
+}
+
+impl RunOpt {
+    /// Check that `version` of `backend` supports all the options
+    /// that are set. See [`Capability`].
+    pub fn validate_for(
+        &self,
+        backend: Backend,
+        version: Version,
+    ) -> Result<(), ValidationError> {
+        check(Capability::used_by_run(self), backend, version)
+    }
+}
+
+impl BuildOpt {
+    /// Check that `version` of `backend` supports all the options
+    /// that are set. See [`Capability`].
+    pub fn validate_for(
+        &self,
+        backend: Backend,
+        version: Version,
+    ) -> Result<(), ValidationError> {
+        check(Capability::used_by_build(self), backend, version)
+    }
+}
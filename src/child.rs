@@ -0,0 +1,215 @@
+use command_run::{Command, ErrorKind, Output};
+use std::io::{self, Read};
+use std::process::{self, Child, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often to check whether a child process has exited.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Read all of `reader` on a new thread.
+fn read_in_thread<R>(mut reader: R) -> JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut data = Vec::new();
+        // Anything read before an error is still returned.
+        let _ = reader.read_to_end(&mut data);
+        data
+    })
+}
+
+/// Read `reader` in chunks, sending each to `chunks`. Stops at EOF, on
+/// an error, or if the receiver is dropped.
+fn forward<R: Read>(mut reader: R, chunks: Sender<Vec<u8>>) {
+    let mut buf = [0; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return,
+            Ok(n) => {
+                if chunks.send(buf[..n].to_vec()).is_err() {
+                    return;
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => return,
+        }
+    }
+}
+
+/// Read `stdout` and `stderr` on new threads, sending chunks of either
+/// to the returned channel as they arrive. The channel is closed once
+/// both have been read to the end.
+///
+/// The two streams are interleaved chunk by chunk, so output written
+/// close together may not be in exactly the order it was written.
+pub(crate) fn combine_output<A, B>(stdout: A, stderr: B) -> Receiver<Vec<u8>>
+where
+    A: Read + Send + 'static,
+    B: Read + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    let tx2 = tx.clone();
+    thread::spawn(move || forward(stdout, tx));
+    thread::spawn(move || forward(stderr, tx2));
+    rx
+}
+
+/// Child process started from a [`Command`], with its output (if
+/// captured) read on background threads so that the process cannot
+/// block on a full pipe.
+#[derive(Debug)]
+pub(crate) struct RunningChild {
+    command: Command,
+    child: Child,
+    stdout: Option<JoinHandle<Vec<u8>>>,
+    stderr: Option<JoinHandle<Vec<u8>>>,
+}
+
+impl RunningChild {
+    /// Start `cmd`. The `capture` and `combine_output` fields are
+    /// respected; logging is left to the caller.
+    pub(crate) fn spawn(cmd: &Command) -> Result<Self, command_run::Error> {
+        let to_error = |err| command_run::Error {
+            command: cmd.clone(),
+            kind: ErrorKind::Run(err),
+        };
+
+        let mut proc: process::Command = cmd.into();
+        let mut combined = None;
+        if cmd.capture && cmd.combine_output {
+            // Share one pipe so that the output stays in the order it
+            // was written.
+            let (reader, writer) = io::pipe().map_err(to_error)?;
+            proc.stdout(writer.try_clone().map_err(to_error)?)
+                .stderr(writer);
+            combined = Some(reader);
+        } else if cmd.capture {
+            proc.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+
+        let mut child = proc.spawn().map_err(to_error)?;
+        // Close this process's copy of the write end, so that reading
+        // stops once the child exits.
+        drop(proc);
+        let (stdout, stderr) = match combined {
+            Some(reader) => (Some(read_in_thread(reader)), None),
+            None => (
+                child.stdout.take().map(read_in_thread),
+                child.stderr.take().map(read_in_thread),
+            ),
+        };
+        Ok(Self {
+            command: cmd.clone(),
+            child,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// ID of the child process.
+    pub(crate) fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Check whether the child has exited, without blocking.
+    pub(crate) fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    /// Wait for the child to exit, or until `deadline` if set. Returns
+    /// `None` if the deadline passed first.
+    pub(crate) fn wait_until(
+        &mut self,
+        deadline: Option<Instant>,
+    ) -> io::Result<Option<ExitStatus>> {
+        let Some(deadline) = deadline else {
+            return self.child.wait().map(Some);
+        };
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                return Ok(Some(status));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    /// Send `SIGTERM` to the child, then wait for it to exit. If it is
+    /// still running after `grace_period`, or the signal could not be
+    /// sent, it is killed.
+    ///
+    /// Unlike `SIGKILL`, `SIGTERM` is passed on by `sudo` to the
+    /// program it runs.
+    pub(crate) fn terminate(
+        &mut self,
+        grace_period: Duration,
+    ) -> io::Result<()> {
+        if !self.send_sigterm() {
+            self.kill()?;
+            return Ok(());
+        }
+
+        let deadline = Instant::now().checked_add(grace_period);
+        if self.wait_until(deadline)?.is_none() {
+            self.kill()?;
+        }
+        Ok(())
+    }
+
+    /// Ask the child to stop with `SIGTERM`. Returns false if the
+    /// signal was not delivered.
+    #[cfg(unix)]
+    fn send_sigterm(&self) -> bool {
+        let mut kill = Command::with_args(
+            "kill",
+            ["-TERM".to_string(), self.child.id().to_string()],
+        );
+        kill.log_command = false;
+        kill.check = false;
+        kill.capture = true;
+        matches!(kill.run(), Ok(output) if output.status.success())
+    }
+
+    /// There is no `SIGTERM` on this platform.
+    #[cfg(not(unix))]
+    fn send_sigterm(&self) -> bool {
+        false
+    }
+
+    /// Forcibly kill the child and wait for it to exit.
+    pub(crate) fn kill(&mut self) -> io::Result<ExitStatus> {
+        // This fails if the child already exited, which is fine.
+        let _ = self.child.kill();
+        self.child.wait()
+    }
+
+    /// Collect the output of the child, which must have exited with
+    /// `status`. An error is returned if `check` is set and the child
+    /// was not successful.
+    pub(crate) fn finish(
+        self,
+        status: ExitStatus,
+    ) -> Result<Output, command_run::Error> {
+        let join = |handle: Option<JoinHandle<Vec<u8>>>| {
+            handle.and_then(|h| h.join().ok()).unwrap_or_default()
+        };
+        let output = Output {
+            status,
+            stdout: join(self.stdout),
+            stderr: join(self.stderr),
+        };
+        if self.command.check && !status.success() {
+            return Err(command_run::Error {
+                command: self.command,
+                kind: ErrorKind::Exit(status),
+            });
+        }
+        Ok(output)
+    }
+}
@@ -0,0 +1,1037 @@
+//! Read and write compose files. Requires the `compose` feature.
+//!
+//! [`ComposeProject`] exports [`RunOpt`]s as a `docker-compose.yml`
+//! file. Options that have no equivalent in the compose file format
+//! (such as [`RunOpt::detach`], [`RunOpt::remove`], and `extra_args`)
+//! are ignored.
+//!
+//! [`File`] is a model of a compose file that can be read, edited,
+//! and written, and whose services can be converted to [`RunOpt`]s to
+//! run them through a [`Launcher`](crate::Launcher).
+
+use crate::runner::stdout;
+use crate::{
+    CommandExt, Compose, ComposeConfigOpt, Error, Interpolator, IpcMode, Mount,
+    NameOrId, NetworkAttachment, NetworkMode, PublishPorts, PullPolicy,
+    RestartPolicy, RunOpt, SecurityOpt, UserAndGroup, UserNamespace,
+    ValidationError, Volume,
+};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+
+fn lossy<S: AsRef<OsStr>>(s: S) -> Value {
+    Value::String(s.as_ref().to_string_lossy().into_owned())
+}
+
+fn insert<K: Into<String>, V: Into<Value>>(
+    map: &mut Mapping,
+    key: K,
+    value: V,
+) {
+    map.insert(Value::String(key.into()), value.into());
+}
+
+/// Set of services, networks, and volumes to write as a compose file.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{ComposeProject, RunOpt};
+/// let mut project = ComposeProject::default();
+/// let mut web = RunOpt::new("nginx:1");
+/// web.network = Some("backend".into());
+/// project.add_service("web", web);
+/// project.networks.push("backend".into());
+/// assert_eq!(
+///     project.to_yaml().unwrap(),
+///     "services:\n  web:\n    image: nginx:1\n    networks:\n    - backend\nnetworks:\n  backend: {}\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ComposeProject {
+    /// Services, in the order they are written, keyed by service name.
+    pub services: Vec<(String, RunOpt)>,
+
+    /// Networks to declare.
+    pub networks: Vec<String>,
+
+    /// Named volumes to declare.
+    pub volumes: Vec<String>,
+}
+
+impl ComposeProject {
+    /// Add a service.
+    pub fn add_service<S: Into<String>>(&mut self, name: S, opt: RunOpt) {
+        self.services.push((name.into(), opt));
+    }
+
+    /// Convert to a YAML value.
+    pub fn to_value(&self) -> Value {
+        let mut root = Mapping::new();
+
+        let mut services = Mapping::new();
+        for (name, opt) in &self.services {
+            insert(&mut services, name.as_str(), service(opt));
+        }
+        insert(&mut root, "services", services);
+
+        for (key, names) in
+            [("networks", &self.networks), ("volumes", &self.volumes)]
+        {
+            if !names.is_empty() {
+                let mut map = Mapping::new();
+                for name in names {
+                    insert(&mut map, name.as_str(), Mapping::new());
+                }
+                insert(&mut root, key, map);
+            }
+        }
+
+        Value::Mapping(root)
+    }
+
+    /// Format as the contents of a `docker-compose.yml` file.
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(&self.to_value())
+    }
+}
+
+/// Convert one `RunOpt` to a service definition.
+fn service(opt: &RunOpt) -> Mapping {
+    let mut map = Mapping::new();
+    insert(&mut map, "image", opt.image.to_string());
+
+    if let Some(name) = &opt.name {
+        insert(&mut map, "container_name", name.as_str());
+    }
+
+    let mut command: Vec<Value> = Vec::new();
+    if let Some(cmd) = &opt.command {
+        command.push(lossy(cmd));
+    }
+    command.extend(opt.args.iter().map(lossy));
+    if !command.is_empty() {
+        insert(&mut map, "command", command);
+    }
+
+    let mut blkio = Mapping::new();
+    for (key, throttles) in [
+        ("device_read_bps", &opt.device_read_bps),
+        ("device_write_bps", &opt.device_write_bps),
+    ] {
+        if !throttles.is_empty() {
+            let list: Vec<Value> = throttles
+                .iter()
+                .map(|throttle| {
+                    let mut entry = Mapping::new();
+                    entry.insert("path".into(), lossy(&throttle.device));
+                    insert(&mut entry, "rate", throttle.rate);
+                    Value::Mapping(entry)
+                })
+                .collect();
+            insert(&mut blkio, key, list);
+        }
+    }
+    if !blkio.is_empty() {
+        insert(&mut map, "blkio_config", blkio);
+    }
+
+    if let Some(parent) = &opt.cgroup_parent {
+        insert(&mut map, "cgroup_parent", parent.as_str());
+    }
+
+    if let Some(period) = opt.cpu_period {
+        insert(&mut map, "cpu_period", period.as_micros() as u64);
+    }
+
+    if let Some(quota) = opt.cpu_quota {
+        insert(&mut map, "cpu_quota", quota.as_micros() as u64);
+    }
+
+    if !opt.env.is_empty() {
+        let mut env = Mapping::new();
+        for (key, value) in &opt.env {
+            env.insert(lossy(key), lossy(value));
+        }
+        insert(&mut map, "environment", env);
+    }
+
+    if opt.init {
+        insert(&mut map, "init", true);
+    }
+
+    if let Some(ipc) = &opt.ipc {
+        insert(&mut map, "ipc", ipc.arg());
+    }
+
+    if !opt.labels.is_empty() {
+        let mut labels = Mapping::new();
+        for (key, value) in &opt.labels {
+            insert(&mut labels, key.as_str(), value.as_str());
+        }
+        insert(&mut map, "labels", labels);
+    }
+
+    let mut networks = Vec::new();
+    match &opt.network {
+        Some(NetworkMode::Named(name)) if !name.starts_with("service:") => {
+            networks.push(NetworkAttachment::new(name.as_str()));
+        }
+        Some(mode) => insert(&mut map, "network_mode", mode.arg()),
+        None => {}
+    }
+    networks.extend(opt.networks.iter().cloned());
+    if networks
+        .iter()
+        .all(|n| n.aliases.is_empty() && n.ip.is_none() && n.ip6.is_none())
+    {
+        if !networks.is_empty() {
+            let names: Vec<Value> = networks
+                .iter()
+                .map(|n| Value::from(n.name.as_str()))
+                .collect();
+            insert(&mut map, "networks", names);
+        }
+    } else {
+        // Use the long syntax to include aliases and addresses.
+        let mut long = Mapping::new();
+        for network in &networks {
+            let mut settings = Mapping::new();
+            if !network.aliases.is_empty() {
+                insert(&mut settings, "aliases", network.aliases.clone());
+            }
+            if let Some(ip) = &network.ip {
+                insert(&mut settings, "ipv4_address", ip.as_str());
+            }
+            if let Some(ip6) = &network.ip6 {
+                insert(&mut settings, "ipv6_address", ip6.as_str());
+            }
+            let value = if settings.is_empty() {
+                Value::Null
+            } else {
+                Value::Mapping(settings)
+            };
+            insert(&mut long, network.name.as_str(), value);
+        }
+        insert(&mut map, "networks", long);
+    }
+
+    if !opt.publish.is_empty() {
+        let ports: Vec<Value> =
+            opt.publish.iter().map(|p| Value::from(p.arg())).collect();
+        insert(&mut map, "ports", ports);
+    }
+
+    if let Some(pull) = opt.pull {
+        insert(&mut map, "pull_policy", pull.as_str());
+    }
+
+    if opt.read_only {
+        insert(&mut map, "read_only", true);
+    }
+
+    if let Some(restart) = &opt.restart {
+        insert(&mut map, "restart", restart.arg());
+    }
+
+    if !opt.security_opts.is_empty() {
+        let security_opts: Vec<Value> = opt
+            .security_opts
+            .iter()
+            .map(|o| Value::from(o.arg()))
+            .collect();
+        insert(&mut map, "security_opt", security_opts);
+    }
+
+    if opt.interactive {
+        insert(&mut map, "stdin_open", true);
+    }
+
+    if opt.tty {
+        insert(&mut map, "tty", true);
+    }
+
+    if let Some(user) = &opt.user {
+        insert(&mut map, "user", user.arg());
+    }
+
+    if let Some(userns) = &opt.userns {
+        insert(&mut map, "userns_mode", userns.arg());
+    }
+
+    let mut volumes: Vec<Value> =
+        opt.volumes.iter().map(|v| lossy(v.arg())).collect();
+    for mount in &opt.mounts {
+        let mut long = Mapping::new();
+        insert(&mut long, "type", mount.kind.as_str());
+        if let Some(source) = &mount.source {
+            long.insert("source".into(), lossy(source));
+        }
+        long.insert("target".into(), lossy(&mount.target));
+        if mount.read_only {
+            insert(&mut long, "read_only", true);
+        }
+        volumes.push(Value::Mapping(long));
+    }
+    if !volumes.is_empty() {
+        insert(&mut map, "volumes", volumes);
+    }
+
+    map
+}
+
+/// Value that may be written as a single string or a list of strings,
+/// such as `command`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StringOrList {
+    /// Single string. For `command`, this is split into words like a
+    /// shell would, without expanding anything.
+    String(String),
+
+    /// List of strings.
+    List(Vec<String>),
+}
+
+/// `key=value` pairs that may be written as a list or a mapping, such
+/// as `environment` and `labels`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Pairs {
+    /// List of `key=value` strings. A string without `=` has no value.
+    List(Vec<String>),
+
+    /// Mapping from key to value. Values may be null, numbers, or
+    /// booleans as well as strings.
+    Map(BTreeMap<String, Value>),
+}
+
+impl Pairs {
+    /// Get the pairs as strings, with None for keys without a value.
+    pub fn to_pairs(&self) -> Vec<(String, Option<String>)> {
+        match self {
+            Self::List(list) => list
+                .iter()
+                .map(|item| match item.split_once('=') {
+                    Some((key, value)) => (key.into(), Some(value.into())),
+                    None => (item.clone(), None),
+                })
+                .collect(),
+            Self::Map(map) => map
+                .iter()
+                .map(|(key, value)| (key.clone(), scalar(value)))
+                .collect(),
+        }
+    }
+}
+
+/// Format a scalar YAML value as a string.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Health check of a [`Service`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct Healthcheck {
+    /// Command to run, either a string run with the shell or a list
+    /// starting with `CMD`, `CMD-SHELL`, or `NONE`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test: Option<StringOrList>,
+
+    /// Time between checks, e.g. `30s`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+
+    /// Time a check may take, e.g. `10s`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+
+    /// Consecutive failures needed to be unhealthy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+
+    /// Time to wait before failures count, e.g. `5s`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_period: Option<String>,
+
+    /// Disable any health check in the image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable: Option<bool>,
+}
+
+/// Condition on a dependency in [`Service::depends_on`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct Dependency {
+    /// `service_started`, `service_healthy`, or
+    /// `service_completed_successfully`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+
+    /// Other keys, kept as-is.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+/// Services that a [`Service`] depends on, as a list of names or a
+/// mapping from name to [`Dependency`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DependsOn {
+    /// List of service names.
+    List(Vec<String>),
+
+    /// Mapping from service name to condition.
+    Map(BTreeMap<String, Dependency>),
+}
+
+impl DependsOn {
+    /// Get the names of the services depended on.
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Self::List(list) => list.iter().map(String::as_str).collect(),
+            Self::Map(map) => map.keys().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Settings of a [`Service`] on one network.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct ServiceNetwork {
+    /// Additional names for the service on the network.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+
+    /// IPv4 address.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv4_address: Option<String>,
+
+    /// IPv6 address.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv6_address: Option<String>,
+
+    /// Other keys, kept as-is.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+/// Networks of a [`Service`], as a list of names or a mapping from
+/// name to settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ServiceNetworks {
+    /// List of network names.
+    List(Vec<String>),
+
+    /// Mapping from network name to settings, which may be null.
+    Map(BTreeMap<String, Option<ServiceNetwork>>),
+}
+
+/// Entry in [`Service::volumes`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ServiceVolume {
+    /// Short syntax, as for `--volume`.
+    Short(String),
+
+    /// Long syntax.
+    Long(VolumeMount),
+}
+
+/// Long syntax of a [`ServiceVolume`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct VolumeMount {
+    /// `bind`, `volume`, or `tmpfs`.
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Host path or volume name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    /// Path in the container.
+    pub target: String,
+
+    /// Mount read-only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+
+    /// Other keys, kept as-is.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+/// Service in a compose [`File`].
+///
+/// Only commonly used keys have fields; the rest are kept in `other`
+/// so that reading and writing a file does not lose them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct Service {
+    /// Image to run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    /// Name of the container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+
+    /// Command and arguments, overriding the image's `CMD`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<StringOrList>,
+
+    /// Services to start before this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<DependsOn>,
+
+    /// Environment variables.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Pairs>,
+
+    /// Health check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<Healthcheck>,
+
+    /// Run an init process.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init: Option<bool>,
+
+    /// IPC namespace mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipc: Option<String>,
+
+    /// Container labels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Pairs>,
+
+    /// Networking mode, such as `host`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+
+    /// Networks to connect to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub networks: Option<ServiceNetworks>,
+
+    /// Published ports, as strings or numbers in the short syntax or
+    /// mappings in the long syntax.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<Value>,
+
+    /// When to pull the image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull_policy: Option<String>,
+
+    /// Mount the root filesystem read-only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+
+    /// Restart policy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<String>,
+
+    /// Security options.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub security_opt: Vec<String>,
+
+    /// Keep stdin open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdin_open: Option<bool>,
+
+    /// Allocate a pseudo-TTY.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tty: Option<bool>,
+
+    /// User (and optionally group) to run as.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    /// User namespace mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userns_mode: Option<String>,
+
+    /// Volumes and bind mounts.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<ServiceVolume>,
+
+    /// Other keys, kept as-is.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+/// Split `s` into words like a shell would, handling quotes and
+/// backslashes but not expanding anything.
+fn split_words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// Convert a `ports` entry to the `--publish` syntax.
+fn port_spec(value: &Value) -> Result<String, ValidationError> {
+    let err = || {
+        ValidationError::new(
+            "published port",
+            serde_yaml::to_string(value).unwrap_or_default().trim(),
+            "expected a string, number, or mapping with a target",
+        )
+    };
+    if let Some(s) = scalar(value) {
+        return Ok(s);
+    }
+    let map = value.as_mapping().ok_or_else(err)?;
+    let get = |key: &str| map.get(key).and_then(scalar);
+    let mut out = String::new();
+    if let Some(ip) = get("host_ip") {
+        if ip.contains(':') {
+            out.push_str(&format!("[{}]:", ip));
+        } else {
+            out.push_str(&format!("{}:", ip));
+        }
+    }
+    if let Some(published) = get("published") {
+        out.push_str(&format!("{}:", published));
+    } else if !out.is_empty() {
+        out.push(':');
+    }
+    out.push_str(&get("target").ok_or_else(err)?);
+    if let Some(protocol) = get("protocol") {
+        out.push_str(&format!("/{}", protocol));
+    }
+    Ok(out)
+}
+
+/// Convert a health check to `docker run` options.
+fn health_args(check: &Healthcheck) -> Vec<String> {
+    let mut args = Vec::new();
+    let test = match &check.test {
+        Some(StringOrList::String(s)) => Some(s.clone()),
+        Some(StringOrList::List(list)) => match list.split_first() {
+            Some((kind, rest)) if kind == "CMD" => Some(
+                rest.iter()
+                    .map(crate::shell::quote)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            Some((kind, rest)) if kind == "CMD-SHELL" => Some(rest.join(" ")),
+            _ => None,
+        },
+        None => None,
+    };
+    let none = matches!(
+        &check.test,
+        Some(StringOrList::List(list)) if list.first().map(String::as_str) == Some("NONE")
+    );
+    if check.disable == Some(true) || none {
+        args.push("--no-healthcheck".into());
+        return args;
+    }
+    if let Some(test) = test {
+        args.push(format!("--health-cmd={}", test));
+    }
+    for (flag, value) in [
+        ("interval", &check.interval),
+        ("timeout", &check.timeout),
+        ("start-period", &check.start_period),
+    ] {
+        if let Some(value) = value {
+            args.push(format!("--health-{}={}", flag, value));
+        }
+    }
+    if let Some(retries) = check.retries {
+        args.push(format!("--health-retries={}", retries));
+    }
+    args
+}
+
+impl Service {
+    /// Create a service from `opt`, with the same contents that
+    /// [`ComposeProject`] writes for it.
+    pub fn from_run_opt(opt: &RunOpt) -> Self {
+        serde_yaml::from_value(Value::Mapping(service(opt)))
+            .expect("service mapping does not match the model")
+    }
+
+    /// Convert to options for running the service with a launcher.
+    ///
+    /// A health check is passed with `--health-*` options in
+    /// `extra_args`. `depends_on` and keys in `other` are not used; a
+    /// service without an `image` is an error, since building is not
+    /// supported. Environment variables without a value are taken from
+    /// the current environment, as compose does, and left out if not
+    /// set there.
+    pub fn to_run_opt(&self) -> Result<RunOpt, ValidationError> {
+        let image = self.image.as_deref().ok_or_else(|| {
+            ValidationError::new("image reference", "", "missing image")
+        })?;
+        let mut opt = RunOpt::new(image.parse::<crate::ImageRef>()?);
+        opt.name.clone_from(&self.container_name);
+
+        match &self.command {
+            Some(StringOrList::String(s)) => {
+                let mut words = split_words(s).into_iter();
+                opt.command = words.next().map(Into::into);
+                opt.args = words.map(Into::into).collect();
+            }
+            Some(StringOrList::List(list)) => {
+                if let Some((command, args)) = list.split_first() {
+                    opt.command = Some(command.into());
+                    opt.args = args.iter().map(Into::into).collect();
+                }
+            }
+            None => {}
+        }
+
+        if let Some(env) = &self.environment {
+            for (key, value) in env.to_pairs() {
+                let value = match value {
+                    Some(value) => Some(value.into()),
+                    None => std::env::var_os(&key),
+                };
+                if let Some(value) = value {
+                    opt.env.push((key.into(), value));
+                }
+            }
+        }
+
+        if let Some(check) = &self.healthcheck {
+            opt.extra_args
+                .extend(health_args(check).into_iter().map(Into::into));
+        }
+
+        opt.init = self.init.unwrap_or_default();
+
+        if let Some(ipc) = &self.ipc {
+            opt.ipc = Some(ipc.parse::<IpcMode>()?);
+        }
+
+        if let Some(labels) = &self.labels {
+            opt.labels = labels
+                .to_pairs()
+                .into_iter()
+                .map(|(key, value)| (key, value.unwrap_or_default()))
+                .collect();
+        }
+
+        if let Some(mode) = &self.network_mode {
+            opt.network = Some(mode.parse::<NetworkMode>()?);
+        }
+
+        match &self.networks {
+            Some(ServiceNetworks::List(list)) => {
+                opt.networks =
+                    list.iter().map(NetworkAttachment::new).collect();
+            }
+            Some(ServiceNetworks::Map(map)) => {
+                for (name, settings) in map {
+                    let mut network = NetworkAttachment::new(name);
+                    if let Some(settings) = settings {
+                        network.aliases.clone_from(&settings.aliases);
+                        network.ip.clone_from(&settings.ipv4_address);
+                        network.ip6.clone_from(&settings.ipv6_address);
+                    }
+                    opt.networks.push(network);
+                }
+            }
+            None => {}
+        }
+
+        for port in &self.ports {
+            opt.publish.push(port_spec(port)?.parse::<PublishPorts>()?);
+        }
+
+        if let Some(policy) = &self.pull_policy {
+            opt.pull = Some(match policy.as_str() {
+                "always" => PullPolicy::Always,
+                "missing" | "if_not_present" => PullPolicy::Missing,
+                "never" => PullPolicy::Never,
+                _ => {
+                    return Err(ValidationError::new(
+                        "pull policy",
+                        policy,
+                        "expected always, missing, or never",
+                    ))
+                }
+            });
+        }
+
+        opt.read_only = self.read_only.unwrap_or_default();
+
+        if let Some(restart) = &self.restart {
+            opt.restart = Some(restart.parse::<RestartPolicy>()?);
+        }
+
+        opt.security_opts = self
+            .security_opt
+            .iter()
+            .map(|s| SecurityOpt::Other(s.clone()))
+            .collect();
+        opt.interactive = self.stdin_open.unwrap_or_default();
+        opt.tty = self.tty.unwrap_or_default();
+
+        if let Some(user) = &self.user {
+            let id = |s: &str| match s.parse::<u32>() {
+                Ok(id) => NameOrId::Id(id),
+                Err(_) => NameOrId::Name(s.into()),
+            };
+            opt.user = Some(match user.split_once(':') {
+                Some((user, group)) => UserAndGroup {
+                    user: id(user),
+                    group: Some(id(group)),
+                },
+                None => UserAndGroup {
+                    user: id(user),
+                    group: None,
+                },
+            });
+        }
+
+        if let Some(userns) = &self.userns_mode {
+            opt.userns = Some(UserNamespace::Other(userns.clone()));
+        }
+
+        for volume in &self.volumes {
+            match volume {
+                ServiceVolume::Short(s) => {
+                    opt.volumes.push(s.parse::<Volume>()?);
+                }
+                ServiceVolume::Long(long) => opt.mounts.push(Mount {
+                    kind: long.kind.parse()?,
+                    source: long.source.as_ref().map(Into::into),
+                    target: long.target.clone().into(),
+                    read_only: long.read_only.unwrap_or_default(),
+                    options: Vec::new(),
+                }),
+            }
+        }
+
+        Ok(opt)
+    }
+}
+
+/// Top-level network or volume in a compose [`File`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct Resource {
+    /// Driver to create the resource with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+
+    /// Whether the resource is created outside of compose.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external: Option<bool>,
+
+    /// Actual name, if different from the key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Labels to add.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Pairs>,
+
+    /// Other keys, kept as-is.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+/// Model of a compose file.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::compose::File;
+/// let file = File::from_yaml(
+///     "services:
+///   db:
+///     image: postgres:16
+///   web:
+///     image: nginx:1
+///     depends_on: [db]
+///     ports: ['8080:80']
+/// ",
+/// )
+/// .unwrap();
+/// let opts = file.run_opts(&["web"]).unwrap();
+/// let names: Vec<_> = opts.iter().map(|(name, _)| name.as_str()).collect();
+/// assert_eq!(names, ["db", "web"]);
+/// assert_eq!(opts[1].1.publish[0].arg(), "8080:80");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct File {
+    /// Project name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Services, by name.
+    pub services: BTreeMap<String, Service>,
+
+    /// Networks, by name. A null value uses the defaults.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub networks: BTreeMap<String, Option<Resource>>,
+
+    /// Named volumes, by name. A null value uses the defaults.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub volumes: BTreeMap<String, Option<Resource>>,
+
+    /// Other keys, such as `secrets` and `x-` extensions, kept as-is.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+impl File {
+    /// Parse a compose file.
+    pub fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_str(yaml)
+    }
+
+    /// Format as the contents of a compose file.
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(self)
+    }
+
+    /// Parse a compose file after substituting variables from `vars`
+    /// (see [`Interpolator::interpolate`]).
+    ///
+    /// The whole text is interpolated, including keys and comments, so
+    /// a `$` that is not part of a variable must be written as `$$`.
+    pub fn from_yaml_interpolated(
+        yaml: &str,
+        vars: &Interpolator,
+    ) -> Result<Self, Error> {
+        let yaml = vars.interpolate(yaml)?;
+        Self::from_yaml(&yaml).map_err(|err| {
+            ValidationError::new("compose file", "", err.to_string()).into()
+        })
+    }
+
+    /// Get the options for running `services` and the services they
+    /// depend on (see [`Service::depends_on`]), with each service after
+    /// its dependencies. If `services` is empty, all services are
+    /// included.
+    ///
+    /// Returns an error for an unknown service, a dependency cycle, or
+    /// a service that cannot be converted (see
+    /// [`Service::to_run_opt`]).
+    pub fn run_opts(
+        &self,
+        services: &[&str],
+    ) -> Result<Vec<(String, RunOpt)>, ValidationError> {
+        fn visit<'a>(
+            file: &'a File,
+            name: &'a str,
+            visiting: &mut Vec<&'a str>,
+            order: &mut Vec<&'a str>,
+        ) -> Result<(), ValidationError> {
+            if order.contains(&name) {
+                return Ok(());
+            }
+            if visiting.contains(&name) {
+                return Err(ValidationError::new(
+                    "compose service",
+                    name,
+                    "dependency cycle",
+                ));
+            }
+            let service = file.services.get(name).ok_or_else(|| {
+                ValidationError::new("compose service", name, "not found")
+            })?;
+            visiting.push(name);
+            if let Some(depends_on) = &service.depends_on {
+                for dep in depends_on.names() {
+                    visit(file, dep, visiting, order)?;
+                }
+            }
+            visiting.pop();
+            order.push(name);
+            Ok(())
+        }
+
+        let mut order = Vec::new();
+        let all: Vec<&str> = self.services.keys().map(String::as_str).collect();
+        let services = if services.is_empty() { &all } else { services };
+        for name in services {
+            visit(self, name, &mut Vec::new(), &mut order)?;
+        }
+        order
+            .into_iter()
+            .map(|name| {
+                let opt = self.services[name].to_run_opt()?;
+                Ok((name.to_string(), opt))
+            })
+            .collect()
+    }
+}
+
+impl From<&ComposeProject> for File {
+    fn from(project: &ComposeProject) -> Self {
+        Self {
+            services: project
+                .services
+                .iter()
+                .map(|(name, opt)| (name.clone(), Service::from_run_opt(opt)))
+                .collect(),
+            networks: project
+                .networks
+                .iter()
+                .map(|name| (name.clone(), None))
+                .collect(),
+            volumes: project
+                .volumes
+                .iter()
+                .map(|name| (name.clone(), None))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Compose {
+    /// Run `compose config` and parse the resolved configuration, with
+    /// variables substituted and defaults filled in by compose.
+    pub fn load_config(&self) -> Result<File, Error> {
+        let cmd = self.config(ComposeConfigOpt::new());
+        let output = stdout(cmd.clone())?;
+        File::from_yaml(&output).map_err(|_| Error::UnexpectedOutput {
+            command: cmd.command_line_shell(),
+            output,
+        })
+    }
+}
@@ -0,0 +1,503 @@
+use crate::{discovery, Launcher};
+use command_run::Command;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Which compose implementation a [`Compose`] runs.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ComposeFlavor {
+    /// The Compose v2 plugin, `docker compose`.
+    Plugin,
+
+    /// The standalone Compose v1 program, `docker-compose`.
+    Standalone,
+
+    /// `podman-compose`.
+    Podman,
+}
+
+/// When to use ANSI control characters in compose output.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Ansi {
+    /// Never (useful in CI logs).
+    Never,
+
+    /// Always.
+    Always,
+
+    /// Only when writing to a terminal.
+    Auto,
+}
+
+impl Ansi {
+    /// Get the mode as used on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Always => "always",
+            Self::Auto => "auto",
+        }
+    }
+}
+
+/// Options for all compose commands, added before the subcommand.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ComposeOpt {
+    /// When to use ANSI control characters. Compose v1 only supports
+    /// turning them off, with `--no-ansi`; podman-compose has no
+    /// equivalent, so this is ignored there.
+    pub ansi: Option<Ansi>,
+
+    /// Compose files to use. If empty, the default files (such as
+    /// `compose.yaml`) are used.
+    pub files: Vec<PathBuf>,
+
+    /// Project name. If not set, the directory name is used.
+    pub project_name: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for `compose up`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ComposeUpOpt {
+    /// Build images before starting containers.
+    pub build: bool,
+
+    /// Run containers in the background.
+    pub detach: bool,
+
+    /// Remove containers for services not defined in the compose file.
+    pub remove_orphans: bool,
+
+    /// Wait for services to be running or healthy. Requires the
+    /// Compose v2 plugin, and implies `detach`; ignored by other
+    /// flavors.
+    pub wait: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+
+    /// Services to start. If empty, all services are started.
+    pub services: Vec<String>,
+}
+
+impl ComposeUpOpt {
+    /// Create a `ComposeUpOpt` with all fields set to their defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Options for `compose down`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ComposeDownOpt {
+    /// Remove containers for services not defined in the compose file.
+    pub remove_orphans: bool,
+
+    /// Remove named volumes declared in the compose file and anonymous
+    /// volumes attached to containers.
+    pub volumes: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl ComposeDownOpt {
+    /// Create a `ComposeDownOpt` with all fields set to their defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Options for `compose config`, which validates the compose files
+/// and prints the resolved configuration.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ComposeConfigOpt {
+    /// Output format, `yaml` or `json`. Requires the Compose v2
+    /// plugin; ignored by other flavors, which always print YAML.
+    pub format: Option<String>,
+
+    /// Print the configuration without substituting variables. Not
+    /// supported by podman-compose, where it is ignored.
+    pub no_interpolate: bool,
+
+    /// Only validate, without printing anything.
+    pub quiet: bool,
+
+    /// Pin image tags to digests. Not supported by podman-compose,
+    /// where it is ignored.
+    pub resolve_image_digests: bool,
+
+    /// Print the service names, one per line, instead of the
+    /// configuration.
+    pub services: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl ComposeConfigOpt {
+    /// Create a `ComposeConfigOpt` with all fields set to their
+    /// defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Options for `compose logs`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ComposeLogsOpt {
+    /// Keep following the output until the containers stop.
+    pub follow: bool,
+
+    /// Don't color the `service |` prefixes.
+    pub no_color: bool,
+
+    /// Only show output since this time, e.g. `10m` or an RFC 3339
+    /// timestamp. Not supported by podman-compose before 1.1.
+    pub since: Option<String>,
+
+    /// Number of lines to show from the end of each container's log.
+    /// If not set, all lines are shown.
+    pub tail: Option<u64>,
+
+    /// Add timestamps to each line.
+    pub timestamps: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+
+    /// Services to show. If empty, all services are shown.
+    pub services: Vec<String>,
+}
+
+impl ComposeLogsOpt {
+    /// Create a `ComposeLogsOpt` with all fields set to their defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Wrapper for creating compose commands that smooths over the
+/// differences between the compose implementations. See
+/// [`Launcher::compose_auto`].
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{
+///     Ansi, BaseCommand, ComposeFlavor, ComposeOpt, ComposeUpOpt, Launcher,
+/// };
+/// let mut opt = ComposeOpt::default();
+/// opt.ansi = Some(Ansi::Never);
+/// let mut up = ComposeUpOpt::new();
+/// up.detach = true;
+///
+/// let launcher = Launcher::from(BaseCommand::Docker);
+/// let plugin = launcher.compose(ComposeFlavor::Plugin, opt.clone());
+/// assert_eq!(
+///     plugin.up(up.clone()).command_line_lossy(),
+///     "docker compose --ansi never up --detach"
+/// );
+/// let standalone = launcher.compose(ComposeFlavor::Standalone, opt);
+/// assert_eq!(
+///     standalone.up(up).command_line_lossy(),
+///     "docker-compose --no-ansi up --detach"
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Compose {
+    base_command: Command,
+    flavor: ComposeFlavor,
+
+    /// Project name, for flavors that prefix container names with it.
+    pub(crate) project: Option<String>,
+}
+
+/// Get the project name that compose uses for `opt`: the
+/// `--project-name` option, then `$COMPOSE_PROJECT_NAME`, then the name
+/// of the directory containing the first compose file (or the current
+/// directory), lowercased and with characters other than letters,
+/// digits, `-`, and `_` removed.
+fn project_name(opt: &ComposeOpt) -> Option<String> {
+    if let Some(name) = &opt.project_name {
+        return Some(name.clone());
+    }
+    if let Ok(name) = env::var("COMPOSE_PROJECT_NAME") {
+        return Some(name);
+    }
+    let dir = match opt.files.first() {
+        Some(file) => file.parent().map(Path::to_path_buf),
+        None => env::current_dir().ok(),
+    }?;
+    let dir = if dir.as_os_str().is_empty() {
+        env::current_dir().ok()?
+    } else {
+        dir.canonicalize().unwrap_or(dir)
+    };
+    let name: String = dir
+        .file_name()?
+        .to_string_lossy()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Replace the `docker` or `podman` program in `base` with `program`,
+/// keeping any wrapper such as `sudo`.
+fn replace_program(base: &Command, program: &str) -> Command {
+    let mut cmd = base.clone();
+    let is_engine = |word: &OsStr| word == "docker" || word == "podman";
+    if is_engine(cmd.program.as_os_str()) {
+        cmd.program = program.into();
+    } else if let Some(arg) = cmd.args.iter_mut().find(|arg| is_engine(arg)) {
+        *arg = program.into();
+    }
+    cmd
+}
+
+impl Launcher {
+    /// Create a [`Compose`] that runs `flavor` with the global options
+    /// in `opt`.
+    ///
+    /// The launcher's global options are only passed to the Compose
+    /// v2 plugin, which runs as part of the `docker` command. The
+    /// other flavors replace `docker` or `podman` in the base command
+    /// with their own program.
+    pub fn compose(&self, flavor: ComposeFlavor, opt: ComposeOpt) -> Compose {
+        let mut cmd = match flavor {
+            ComposeFlavor::Plugin => {
+                let mut cmd = self.base();
+                cmd.add_arg("compose");
+                cmd
+            }
+            ComposeFlavor::Standalone => {
+                replace_program(&self.base_command, "docker-compose")
+            }
+            ComposeFlavor::Podman => {
+                replace_program(&self.base_command, "podman-compose")
+            }
+        };
+
+        // --ansi
+        match (flavor, opt.ansi) {
+            (ComposeFlavor::Plugin, Some(ansi)) => {
+                cmd.add_arg_pair("--ansi", ansi.as_str());
+            }
+            (ComposeFlavor::Standalone, Some(Ansi::Never)) => {
+                cmd.add_arg("--no-ansi");
+            }
+            _ => {}
+        }
+
+        // --file
+        for file in &opt.files {
+            cmd.add_arg_pair("--file", file);
+        }
+
+        // --project-name
+        if let Some(name) = &opt.project_name {
+            cmd.add_arg_pair("--project-name", name);
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        // Compose v2 only uses the service and replica in log
+        // prefixes, so the project is not needed there.
+        let project = match flavor {
+            ComposeFlavor::Plugin => None,
+            _ => project_name(&opt),
+        };
+
+        Compose {
+            base_command: cmd,
+            flavor,
+            project,
+        }
+    }
+
+    /// Find a compose implementation for this launcher's engine:
+    ///
+    /// * For podman, `podman-compose` if it is in the `$PATH`.
+    /// * Otherwise the Compose v2 plugin if `docker compose version`
+    ///   succeeds.
+    /// * Otherwise `docker-compose` if it is in the `$PATH`.
+    ///
+    /// Returns None if none of these is available.
+    pub fn compose_auto(&self, opt: ComposeOpt) -> Option<Compose> {
+        let flavor = if self.is_podman() {
+            discovery::is_exe_in_path("podman-compose")
+                .then_some(ComposeFlavor::Podman)?
+        } else if self.has_compose_plugin() {
+            ComposeFlavor::Plugin
+        } else {
+            discovery::is_exe_in_path("docker-compose")
+                .then_some(ComposeFlavor::Standalone)?
+        };
+        Some(self.compose(flavor, opt))
+    }
+
+    /// Whether `docker compose version` succeeds.
+    fn has_compose_plugin(&self) -> bool {
+        let mut cmd = self.base();
+        cmd.add_args(["compose", "version"]);
+        cmd.log_command = false;
+        cmd.capture = true;
+        cmd.run().is_ok()
+    }
+}
+
+impl Compose {
+    /// Get the base command, including the global options.
+    pub fn base_command(&self) -> &Command {
+        &self.base_command
+    }
+
+    /// Get the compose implementation.
+    pub fn flavor(&self) -> ComposeFlavor {
+        self.flavor
+    }
+
+    /// Create a [`Command`] for creating and starting the services.
+    pub fn up(&self, opt: ComposeUpOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("up");
+
+        // --build
+        if opt.build {
+            cmd.add_arg("--build");
+        }
+
+        // --detach
+        if opt.detach {
+            cmd.add_arg("--detach");
+        }
+
+        // --remove-orphans
+        if opt.remove_orphans {
+            cmd.add_arg("--remove-orphans");
+        }
+
+        // --wait
+        if opt.wait && self.flavor == ComposeFlavor::Plugin {
+            cmd.add_arg("--wait");
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_args(&opt.services);
+        cmd
+    }
+
+    /// Create a [`Command`] for validating the compose files and
+    /// printing the resolved configuration. (`compose convert` is an
+    /// older name for the same command.)
+    pub fn config(&self, opt: ComposeConfigOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("config");
+        let is_podman = self.flavor == ComposeFlavor::Podman;
+
+        // --format
+        if let Some(format) = &opt.format {
+            if self.flavor == ComposeFlavor::Plugin {
+                cmd.add_arg_pair("--format", format);
+            }
+        }
+
+        // --no-interpolate
+        if opt.no_interpolate && !is_podman {
+            cmd.add_arg("--no-interpolate");
+        }
+
+        // --quiet
+        if opt.quiet {
+            cmd.add_arg("--quiet");
+        }
+
+        // --resolve-image-digests
+        if opt.resolve_image_digests && !is_podman {
+            cmd.add_arg("--resolve-image-digests");
+        }
+
+        // --services
+        if opt.services {
+            cmd.add_arg("--services");
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd
+    }
+
+    /// Create a [`Command`] for showing the output of the services.
+    /// See [`Compose::follow_logs`] to split it up by service.
+    pub fn logs(&self, opt: ComposeLogsOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("logs");
+
+        // --follow
+        if opt.follow {
+            cmd.add_arg("--follow");
+        }
+
+        // --no-color
+        if opt.no_color {
+            cmd.add_arg("--no-color");
+        }
+
+        // --since
+        if let Some(since) = &opt.since {
+            cmd.add_arg_pair("--since", since);
+        }
+
+        // --tail
+        if let Some(tail) = opt.tail {
+            cmd.add_arg_pair("--tail", tail.to_string());
+        }
+
+        // --timestamps
+        if opt.timestamps {
+            cmd.add_arg("--timestamps");
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_args(&opt.services);
+        cmd
+    }
+
+    /// Create a [`Command`] for stopping and removing the services.
+    pub fn down(&self, opt: ComposeDownOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("down");
+
+        // --remove-orphans
+        if opt.remove_orphans {
+            cmd.add_arg("--remove-orphans");
+        }
+
+        // --volumes
+        if opt.volumes {
+            cmd.add_arg("--volumes");
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{Compose, ComposeLogsOpt, Error};
+use command_run::{Command, ErrorKind};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+use std::process::{self, Stdio};
+
+/// One line of `compose logs` output, split into the service that
+/// produced it and the message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServiceLogLine {
+    /// Service name, e.g. `web`.
+    pub service: String,
+
+    /// Container name from the prefix, e.g. `web-1`.
+    pub container: String,
+
+    /// The line without its prefix and with ANSI escape sequences
+    /// removed.
+    pub message: String,
+}
+
+/// Receives [`ServiceLogLine`]s from a [`ServiceLogParser`].
+///
+/// This is implemented for closures that take a `&ServiceLogLine`.
+pub trait ServiceLogObserver {
+    /// Called for each line of output that has a service prefix.
+    fn on_line(&mut self, line: &ServiceLogLine);
+}
+
+impl<F> ServiceLogObserver for F
+where
+    F: FnMut(&ServiceLogLine),
+{
+    fn on_line(&mut self, line: &ServiceLogLine) {
+        self(line)
+    }
+}
+
+/// Remove ANSI escape sequences (such as the colors compose uses for
+/// prefixes) from `s`.
+fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains('\x1b') {
+        return Cow::Borrowed(s);
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        // A CSI sequence has parameters and intermediates, then a final
+        // byte in the range `@` to `~`. Other escapes are two
+        // characters long.
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Get the service name from a container name by removing the replica
+/// number, e.g. `web-1` (Compose v2) or `web_1` (Compose v1), and the
+/// `project_` prefix if `project` is set, e.g. `project_web_1`.
+fn service_name<'a>(container: &'a str, project: Option<&str>) -> &'a str {
+    let container = project
+        .and_then(|project| container.strip_prefix(project))
+        .and_then(|rest| rest.strip_prefix('_'))
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(container);
+    let base = container.trim_end_matches(|c: char| c.is_ascii_digit());
+    match base.strip_suffix(['-', '_']) {
+        Some(service)
+            if base.len() < container.len() && !service.is_empty() =>
+        {
+            service
+        }
+        _ => container,
+    }
+}
+
+/// Parse a single line of output. See [`service_name`] for `project`.
+fn parse_line(line: &str, project: Option<&str>) -> Option<ServiceLogLine> {
+    let line = strip_ansi(line);
+    let line = line.trim_end_matches('\r');
+    let (prefix, message) = line.split_once('|')?;
+    let container = prefix.trim();
+    if container.is_empty() || container.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(ServiceLogLine {
+        service: service_name(container, project).to_string(),
+        container: container.to_string(),
+        message: message.strip_prefix(' ').unwrap_or(message).to_string(),
+    })
+}
+
+/// Parser that splits the output of `compose logs` into lines per
+/// service, using the `container | message` prefix that compose adds
+/// to each line.
+///
+/// Output can be fed in arbitrary chunks. ANSI escape sequences are
+/// removed, so the output of `compose logs` can be parsed with or
+/// without [`ComposeLogsOpt::no_color`]. Lines without a prefix are
+/// ignored.
+///
+/// Compose v2 prefixes lines with the service and replica, e.g.
+/// `web-1`. Compose v1 and podman-compose use the full container name,
+/// e.g. `project_web_1`; use [`ServiceLogParser::with_project`] so that
+/// the project is removed from the service name.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{ServiceLogLine, ServiceLogParser};
+///
+/// let mut lines = Vec::new();
+/// let mut parser = ServiceLogParser::new();
+/// parser.feed(
+///     b"\x1b[36mweb-1  |\x1b[0m listening on :80\ndb-1   | ready\n",
+///     &mut |line: &ServiceLogLine| lines.push(line.clone()),
+/// );
+/// assert_eq!(lines[0].service, "web");
+/// assert_eq!(lines[0].container, "web-1");
+/// assert_eq!(lines[0].message, "listening on :80");
+/// assert_eq!(lines[1].service, "db");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ServiceLogParser {
+    partial: Vec<u8>,
+    project: Option<String>,
+}
+
+impl ServiceLogParser {
+    /// Create a parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove the `project_` prefix that Compose v1 puts on container
+    /// names when getting the service name.
+    pub fn with_project<S: Into<String>>(mut self, project: S) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    /// Parse a single line of output. No project is removed, so the
+    /// service name is only right for Compose v2 prefixes.
+    pub fn parse_line(line: &str) -> Option<ServiceLogLine> {
+        parse_line(line, None)
+    }
+
+    /// Feed a chunk of output to the parser, passing any complete lines
+    /// that have a prefix to `observer`.
+    pub fn feed(&mut self, data: &[u8], observer: &mut dyn ServiceLogObserver) {
+        for &byte in data {
+            if byte == b'\n' {
+                self.flush(observer);
+            } else {
+                self.partial.push(byte);
+            }
+        }
+    }
+
+    /// Parse any remaining output that did not end with a newline.
+    pub fn flush(&mut self, observer: &mut dyn ServiceLogObserver) {
+        if !self.partial.is_empty() {
+            let line = String::from_utf8_lossy(&self.partial);
+            if let Some(line) = parse_line(&line, self.project.as_deref()) {
+                observer.on_line(&line);
+            }
+            self.partial.clear();
+        }
+    }
+}
+
+type Callback = Box<dyn FnMut(&ServiceLogLine) + Send>;
+
+/// [`ServiceLogObserver`] that passes each line to a callback chosen
+/// by its service.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{ServiceLogObserver, ServiceLogParser, ServiceLogRouter};
+/// use std::sync::mpsc;
+///
+/// let (web_tx, web_rx) = mpsc::channel();
+/// let (other_tx, other_rx) = mpsc::channel();
+/// let mut router = ServiceLogRouter::new()
+///     .with_service("web", move |line| web_tx.send(line.message.clone()).unwrap())
+///     .with_fallback(move |line| other_tx.send(line.service.clone()).unwrap());
+///
+/// let mut parser = ServiceLogParser::new();
+/// parser.feed(b"web-1 | GET /\ndb-1 | ready\n", &mut router);
+/// assert_eq!(web_rx.try_recv().unwrap(), "GET /");
+/// assert_eq!(other_rx.try_recv().unwrap(), "db");
+/// ```
+#[derive(Default)]
+pub struct ServiceLogRouter {
+    routes: BTreeMap<String, Callback>,
+    fallback: Option<Callback>,
+}
+
+impl ServiceLogRouter {
+    /// Create a router with no routes; lines are dropped until
+    /// callbacks are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pass lines from `service` to `callback`, replacing any callback
+    /// already set for it.
+    pub fn with_service<S, F>(mut self, service: S, callback: F) -> Self
+    where
+        S: Into<String>,
+        F: FnMut(&ServiceLogLine) + Send + 'static,
+    {
+        self.routes.insert(service.into(), Box::new(callback));
+        self
+    }
+
+    /// Pass lines from services that have no callback to `callback`.
+    pub fn with_fallback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&ServiceLogLine) + Send + 'static,
+    {
+        self.fallback = Some(Box::new(callback));
+        self
+    }
+}
+
+impl fmt::Debug for ServiceLogRouter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ServiceLogRouter")
+            .field("routes", &self.routes.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl ServiceLogObserver for ServiceLogRouter {
+    fn on_line(&mut self, line: &ServiceLogLine) {
+        if let Some(callback) = self
+            .routes
+            .get_mut(&line.service)
+            .or(self.fallback.as_mut())
+        {
+            callback(line);
+        }
+    }
+}
+
+impl Compose {
+    /// Run `compose logs --follow` with the options in `opt`, passing
+    /// each line of output to `observer` as it is produced.
+    ///
+    /// This blocks until compose exits, which happens when all the
+    /// containers have stopped. To stop earlier, run it on another
+    /// thread and stop the services, or set
+    /// [`ComposeLogsOpt::extra_args`] to `--until` a time.
+    ///
+    /// For Compose v1 and podman-compose, the project name is removed
+    /// from the container names to get the service names. It is taken
+    /// from [`ComposeOpt::project_name`], `$COMPOSE_PROJECT_NAME`, or
+    /// the directory name, so it is only right if compose would choose
+    /// the same one.
+    ///
+    /// [`ComposeOpt::project_name`]: crate::ComposeOpt::project_name
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use docker_command::{
+    ///     BaseCommand, ComposeLogsOpt, ComposeOpt, Launcher, ServiceLogRouter,
+    /// };
+    ///
+    /// let launcher = Launcher::from(BaseCommand::Docker);
+    /// let compose = launcher.compose_auto(ComposeOpt::default()).unwrap();
+    /// let mut router = ServiceLogRouter::new()
+    ///     .with_service("web", |line| println!("web: {}", line.message))
+    ///     .with_service("db", |line| println!("db: {}", line.message));
+    /// compose.follow_logs(ComposeLogsOpt::new(), &mut router).unwrap();
+    /// ```
+    pub fn follow_logs(
+        &self,
+        mut opt: ComposeLogsOpt,
+        observer: &mut dyn ServiceLogObserver,
+    ) -> Result<(), Error> {
+        opt.follow = true;
+        let cmd = self.logs(opt);
+        let mut parser = ServiceLogParser::new();
+        if let Some(project) = &self.project {
+            parser = parser.with_project(project);
+        }
+        run(&cmd, parser, observer)
+    }
+}
+
+/// Run `cmd`, passing its stdout through `parser` as it is produced.
+/// Stderr is inherited.
+fn run(
+    cmd: &Command,
+    mut parser: ServiceLogParser,
+    observer: &mut dyn ServiceLogObserver,
+) -> Result<(), Error> {
+    let to_error = |err| command_run::Error {
+        command: cmd.clone(),
+        kind: ErrorKind::Run(err),
+    };
+
+    crate::log_command(cmd);
+    let mut proc: process::Command = cmd.into();
+    proc.stdout(Stdio::piped());
+    let mut child = proc.spawn().map_err(to_error)?;
+    let Some(mut stdout) = child.stdout.take() else {
+        unreachable!("stdout is piped");
+    };
+
+    let mut buf = [0; 8192];
+    loop {
+        let n = stdout.read(&mut buf).map_err(to_error)?;
+        if n == 0 {
+            break;
+        }
+        parser.feed(&buf[..n], observer);
+    }
+    parser.flush(observer);
+
+    let status = child.wait().map_err(to_error)?;
+    if cmd.check && !status.success() {
+        return Err(Error::Run(command_run::Error {
+            command: cmd.clone(),
+            kind: ErrorKind::Exit(status),
+        }));
+    }
+    Ok(())
+}
@@ -0,0 +1,375 @@
+use crate::{discovery, AutoOpt, Error, GlobalOpts, Launcher, ValidationError};
+use std::path::Path;
+use std::str::FromStr;
+use std::{env, fs};
+
+/// Prefix of the environment variables read by
+/// [`LauncherConfig::from_env`].
+pub const ENV_PREFIX: &str = "DOCKER_COMMAND_";
+
+/// Container engine selected by a [`LauncherConfig`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Engine {
+    /// Use podman if it is in the `$PATH`, otherwise docker.
+    #[default]
+    Auto,
+
+    /// Docker.
+    Docker,
+
+    /// Podman.
+    Podman,
+}
+
+impl FromStr for Engine {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "docker" => Ok(Self::Docker),
+            "podman" => Ok(Self::Podman),
+            _ => Err(ValidationError::new(
+                "engine",
+                s,
+                "expected auto, docker, or podman",
+            )),
+        }
+    }
+}
+
+/// Whether a [`LauncherConfig`] or [`AutoOpt`] runs the engine with
+/// `sudo`.
+///
+/// [`AutoOpt`]: crate::AutoOpt
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SudoPolicy {
+    /// Use `sudo` for docker if the current user is not in the
+    /// `docker` group. Podman is run without `sudo`.
+    #[default]
+    Auto,
+
+    /// Always use `sudo`.
+    Always,
+
+    /// Never use `sudo`.
+    Never,
+}
+
+impl FromStr for SudoPolicy {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(ValidationError::new(
+                "sudo policy",
+                s,
+                "expected auto, always, or never",
+            )),
+        }
+    }
+}
+
+/// Configuration for constructing a [`Launcher`], so that the container
+/// engine can be chosen by whoever runs an application rather than in
+/// its code.
+///
+/// The configuration can be read from a file with
+/// [`LauncherConfig::parse`] or from environment variables with
+/// [`LauncherConfig::from_env`]. The file format is a small subset of
+/// TOML: `key = value` lines where the value is a quoted string, a
+/// boolean, or a single-line array of strings. Blank lines and `#`
+/// comments are ignored. The keys are:
+///
+/// | Key           | Value                                  |
+/// |---------------|----------------------------------------|
+/// | `engine`      | `"auto"`, `"docker"`, or `"podman"`    |
+/// | `sudo`        | `"auto"`, `"always"`, or `"never"`     |
+/// | `docker_group`| [`LauncherConfig::docker_group`]       |
+/// | `host`        | [`GlobalOpts::host`]                   |
+/// | `context`     | [`GlobalOpts::context`]                |
+/// | `config`      | [`GlobalOpts::config`]                 |
+/// | `debug`       | [`GlobalOpts::debug`]                  |
+/// | `log_level`   | [`GlobalOpts::log_level`]              |
+/// | `global_args` | [`GlobalOpts::extra_args`]             |
+/// | `platform`    | [`LauncherConfig::platform`]           |
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{Engine, LauncherConfig, StopOpt};
+/// let config = LauncherConfig::parse(
+///     r#"
+///     ## Talk to the build machine.
+///     engine = "docker"
+///     sudo = "never"
+///     host = "ssh://builder"
+///     "#,
+/// )
+/// .unwrap();
+/// assert_eq!(config.engine, Engine::Docker);
+/// let launcher = config.launcher().unwrap();
+/// assert_eq!(
+///     launcher.stop(StopOpt::default()).command_line_lossy(),
+///     "docker --host ssh://builder stop"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct LauncherConfig {
+    /// Container engine to use.
+    pub engine: Engine,
+
+    /// Whether to run the engine with `sudo`.
+    pub sudo: SudoPolicy,
+
+    /// Group that allows using docker without `sudo` when `sudo` is
+    /// [`SudoPolicy::Auto`]. If None, [`AutoOpt::docker_group`] is
+    /// used.
+    ///
+    /// [`AutoOpt::docker_group`]: crate::AutoOpt::docker_group
+    pub docker_group: Option<String>,
+
+    /// Global options added to every command.
+    pub global_opts: GlobalOpts,
+
+    /// Default platform for pulls, builds, and runs, e.g.
+    /// `linux/arm64`. This is passed in the `DOCKER_DEFAULT_PLATFORM`
+    /// environment variable, which is kept when docker is run with
+    /// `sudo` (see [`Launcher::with_env`]). Podman ignores that variable, so
+    /// [`LauncherConfig::launcher`] returns an error if this is set and
+    /// the engine is podman.
+    pub platform: Option<String>,
+}
+
+/// Value in a config file.
+enum Value {
+    String(String),
+    Bool(bool),
+    List(Vec<String>),
+}
+
+impl LauncherConfig {
+    /// Parse the contents of a config file.
+    pub fn parse(input: &str) -> Result<Self, ValidationError> {
+        let mut config = Self::default();
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |reason: &str| {
+                ValidationError::new("config line", line, reason)
+            };
+            let (key, value) =
+                line.split_once('=').ok_or_else(|| err("expected '='"))?;
+            let (value, rest) = parse_value(value.trim_start())
+                .ok_or_else(|| err("invalid value"))?;
+            let rest = rest.trim_start();
+            if !rest.is_empty() && !rest.starts_with('#') {
+                return Err(err("unexpected text after value"));
+            }
+            config.set(key.trim(), value)?;
+        }
+        Ok(config)
+    }
+
+    /// Read the configuration from environment variables named
+    /// [`ENV_PREFIX`] followed by the upper-case key, e.g.
+    /// `DOCKER_COMMAND_ENGINE=podman`. `global_args` is not read from
+    /// the environment, and `debug` takes `true` or `false`.
+    pub fn from_env() -> Result<Self, ValidationError> {
+        let mut config = Self::default();
+        for key in [
+            "engine",
+            "sudo",
+            "docker_group",
+            "host",
+            "context",
+            "config",
+            "debug",
+            "log_level",
+            "platform",
+        ] {
+            let name = format!("{}{}", ENV_PREFIX, key.to_ascii_uppercase());
+            let value = match env::var(&name) {
+                Ok(value) => value,
+                Err(env::VarError::NotPresent) => continue,
+                Err(env::VarError::NotUnicode(value)) => {
+                    return Err(ValidationError::new(
+                        "environment variable",
+                        value.to_string_lossy(),
+                        "not valid UTF-8",
+                    ))
+                }
+            };
+            let value = if key == "debug" {
+                match value.as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    _ => {
+                        return Err(ValidationError::new(
+                            "environment variable",
+                            name,
+                            "expected true or false",
+                        ))
+                    }
+                }
+            } else {
+                Value::String(value)
+            };
+            config.set(key, value)?;
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: Value) -> Result<(), ValidationError> {
+        let opts = &mut self.global_opts;
+        match (key, value) {
+            ("engine", Value::String(s)) => self.engine = s.parse()?,
+            ("sudo", Value::String(s)) => self.sudo = s.parse()?,
+            ("docker_group", Value::String(s)) => self.docker_group = Some(s),
+            ("host", Value::String(s)) => opts.host = Some(s),
+            ("context", Value::String(s)) => opts.context = Some(s),
+            ("config", Value::String(s)) => opts.config = Some(s.into()),
+            ("debug", Value::Bool(b)) => opts.debug = b,
+            ("log_level", Value::String(s)) => {
+                opts.log_level = Some(s.parse()?)
+            }
+            ("global_args", Value::List(list)) => {
+                opts.extra_args = list.into_iter().map(Into::into).collect()
+            }
+            ("platform", Value::String(s)) => self.platform = Some(s),
+            (
+                "engine" | "sudo" | "docker_group" | "host" | "context"
+                | "config" | "debug" | "log_level" | "global_args" | "platform",
+                _,
+            ) => {
+                return Err(ValidationError::new(
+                    "config key",
+                    key,
+                    "wrong type of value",
+                ))
+            }
+            _ => {
+                return Err(ValidationError::new(
+                    "config key",
+                    key,
+                    "unknown key",
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a [`Launcher`] from the configuration.
+    ///
+    /// Returns an error if the engine is [`Engine::Auto`] and neither
+    /// podman nor docker is in the `$PATH`, or if
+    /// [`LauncherConfig::platform`] is set and the engine is podman.
+    pub fn launcher(&self) -> Result<Launcher, ValidationError> {
+        let mut opt = AutoOpt::new();
+        opt.sudo = self.sudo;
+        if let Some(group) = &self.docker_group {
+            opt.docker_group.clone_from(group);
+        }
+        let launcher = match self.engine {
+            Engine::Auto => Launcher::auto_with(&opt).ok_or_else(|| {
+                ValidationError::new(
+                    "engine",
+                    "auto",
+                    "neither podman nor docker is in the $PATH",
+                )
+            })?,
+            engine => discovery::engine_launcher(engine, &opt),
+        };
+
+        let mut launcher = launcher.with_global_opts(self.global_opts.clone());
+        if let Some(platform) = &self.platform {
+            if launcher.is_podman() {
+                return Err(ValidationError::new(
+                    "platform",
+                    platform,
+                    "podman does not read DOCKER_DEFAULT_PLATFORM",
+                ));
+            }
+            launcher = launcher.with_env("DOCKER_DEFAULT_PLATFORM", platform);
+        }
+        Ok(launcher)
+    }
+}
+
+/// Parse a value at the start of `s`, returning it and the rest of
+/// the input.
+fn parse_value(s: &str) -> Option<(Value, &str)> {
+    if let Some(rest) = s.strip_prefix("true") {
+        return Some((Value::Bool(true), rest));
+    }
+    if let Some(rest) = s.strip_prefix("false") {
+        return Some((Value::Bool(false), rest));
+    }
+    if let Some(mut rest) = s.strip_prefix('[') {
+        let mut list = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(rest) = rest.strip_prefix(']') {
+                return Some((Value::List(list), rest));
+            }
+            let (item, after) = parse_string(rest)?;
+            list.push(item);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return None;
+            }
+        }
+    }
+    let (string, rest) = parse_string(s)?;
+    Some((Value::String(string), rest))
+}
+
+/// Parse a double-quoted string with TOML's basic escapes, or a
+/// single-quoted literal string.
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    if let Some(rest) = s.strip_prefix('\'') {
+        let end = rest.find('\'')?;
+        return Some((rest[..end].to_string(), &rest[end + 1..]));
+    }
+
+    let rest = s.strip_prefix('"')?;
+    let mut out = String::new();
+    let mut chars = rest.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &rest[i + 1..])),
+            '\\' => out.push(match chars.next()?.1 {
+                '"' => '"',
+                '\\' => '\\',
+                'n' => '\n',
+                't' => '\t',
+                _ => return None,
+            }),
+            _ => out.push(c),
+        }
+    }
+    None
+}
+
+impl Launcher {
+    /// Create a `Launcher` from a config file. See [`LauncherConfig`]
+    /// for the format.
+    pub fn from_config<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let input = fs::read_to_string(path)?;
+        Ok(LauncherConfig::parse(&input)?.launcher()?)
+    }
+
+    /// Create a `Launcher` from `DOCKER_COMMAND_*` environment
+    /// variables. See [`LauncherConfig::from_env`].
+    pub fn from_env() -> Result<Self, Error> {
+        Ok(LauncherConfig::from_env()?.launcher()?)
+    }
+}
@@ -0,0 +1,219 @@
+use crate::ValidationError;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::process;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Validated container name.
+///
+/// Names must match `[a-zA-Z0-9][a-zA-Z0-9_.-]+`, the same pattern
+/// the Docker daemon enforces.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::ContainerName;
+/// assert!("my-app.1".parse::<ContainerName>().is_ok());
+/// assert!("".parse::<ContainerName>().is_err());
+/// assert!("-rm".parse::<ContainerName>().is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ContainerName(String);
+
+impl ContainerName {
+    /// Get the name as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Generate a name that is very unlikely to be in use: `prefix`
+    /// followed by a dash and eight random hex digits. If `prefix` is
+    /// empty, the name is just the random digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::ContainerName;
+    /// let a = ContainerName::generate("test").unwrap();
+    /// let b = ContainerName::generate("test").unwrap();
+    /// assert!(a.as_str().starts_with("test-"));
+    /// assert_ne!(a, b);
+    /// assert!(ContainerName::generate("-x").is_err());
+    /// ```
+    pub fn generate(prefix: &str) -> Result<Self, ValidationError> {
+        unique_name(prefix).parse()
+    }
+}
+
+impl fmt::Display for ContainerName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for ContainerName {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("container name", s, reason);
+        let mut chars = s.chars();
+        match chars.next() {
+            None => return Err(err("empty name")),
+            Some(c) if !c.is_ascii_alphanumeric() => {
+                return Err(err("must start with a letter or digit"))
+            }
+            _ => {}
+        }
+        if s.len() < 2 {
+            return Err(err("must be at least two characters"));
+        }
+        if !chars.all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c)) {
+            return Err(err("only [a-zA-Z0-9_.-] are allowed"));
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+/// `prefix` followed by a dash and eight random hex digits, or just
+/// the digits if `prefix` is empty.
+pub(crate) fn unique_name(prefix: &str) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    // RandomState is randomly seeded per process; the counter, time,
+    // and PID make each call differ within and across processes.
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    hasher.write_u32(process::id());
+    let suffix = format!("{:08x}", hasher.finish() as u32);
+
+    if prefix.is_empty() {
+        suffix
+    } else {
+        format!("{}-{}", prefix, suffix)
+    }
+}
+
+/// Validated container ID.
+///
+/// The ID is lowercase hex; either the full 64-character ID or any
+/// non-empty prefix of it, which Docker accepts as long as it is
+/// unique.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::ContainerId;
+/// let id: ContainerId = "0123456789abcdef".parse().unwrap();
+/// assert!(!id.is_full());
+/// assert_eq!(id.short(), "0123456789ab");
+/// assert!("xyz".parse::<ContainerId>().is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ContainerId(String);
+
+impl ContainerId {
+    /// Length of a full container ID.
+    pub const FULL_LEN: usize = 64;
+
+    /// Length of the short ID that Docker displays by default.
+    pub const SHORT_LEN: usize = 12;
+
+    /// Get the ID as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this is a full 64-character ID rather than a prefix.
+    pub fn is_full(&self) -> bool {
+        self.0.len() == Self::FULL_LEN
+    }
+
+    /// Get the short form of the ID (at most the first twelve
+    /// characters).
+    pub fn short(&self) -> &str {
+        &self.0[..self.0.len().min(Self::SHORT_LEN)]
+    }
+}
+
+impl fmt::Display for ContainerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for ContainerId {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("container ID", s, reason);
+        if s.is_empty() {
+            return Err(err("empty ID"));
+        }
+        if s.len() > Self::FULL_LEN {
+            return Err(err("longer than 64 characters"));
+        }
+        if !s
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+        {
+            return Err(err("must be lowercase hex"));
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+/// Reference to a container by either name or ID.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ContainerRef {
+    /// Container name.
+    Name(ContainerName),
+
+    /// Container ID or ID prefix.
+    Id(ContainerId),
+}
+
+impl fmt::Display for ContainerRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Name(name) => name.fmt(f),
+            Self::Id(id) => id.fmt(f),
+        }
+    }
+}
+
+impl From<ContainerName> for ContainerRef {
+    fn from(name: ContainerName) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl From<ContainerId> for ContainerRef {
+    fn from(id: ContainerId) -> Self {
+        Self::Id(id)
+    }
+}
+
+impl FromStr for ContainerRef {
+    type Err = ValidationError;
+
+    /// Parse as an ID if the input is a full or short (12 character)
+    /// hex ID, otherwise parse as a name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == ContainerId::FULL_LEN || s.len() == ContainerId::SHORT_LEN
+        {
+            if let Ok(id) = s.parse() {
+                return Ok(Self::Id(id));
+            }
+        }
+        Ok(Self::Name(s.parse()?))
+    }
+}
@@ -0,0 +1,165 @@
+//! Commands for `cosign`, which signs and verifies images in a
+//! registry. This is an alternative to [`Launcher::trust_sign`].
+//!
+//! [`Launcher::trust_sign`]: crate::Launcher::trust_sign
+
+use crate::ImageRef;
+use command_run::Command;
+use std::ffi::OsString;
+
+/// Options for signing an image.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SignOpt {
+    /// Image to sign. Signing by digest is recommended, since a tag
+    /// can be changed to point to a different image.
+    pub image: ImageRef,
+
+    /// Private key to sign with, as a path or KMS URI. If not set,
+    /// keyless signing is used.
+    pub key: Option<String>,
+
+    /// Annotations (`key=value`) to add to the signature.
+    pub annotations: Vec<String>,
+
+    /// Skip confirmation prompts.
+    pub yes: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl SignOpt {
+    /// Create a `SignOpt` for `image`. All other fields are set to
+    /// their defaults.
+    pub fn new<I: Into<ImageRef>>(image: I) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for verifying an image's signature.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct VerifyOpt {
+    /// Image to verify.
+    pub image: ImageRef,
+
+    /// Public key to verify with, as a path or KMS URI.
+    pub key: Option<String>,
+
+    /// For keyless signatures, the expected identity of the signer.
+    pub certificate_identity: Option<String>,
+
+    /// For keyless signatures, the expected OIDC issuer.
+    pub certificate_oidc_issuer: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl VerifyOpt {
+    /// Create a `VerifyOpt` for `image`. All other fields are set to
+    /// their defaults.
+    pub fn new<I: Into<ImageRef>>(image: I) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Wrapper for creating cosign commands.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cosign {
+    base_command: Command,
+}
+
+impl Cosign {
+    /// Create a new `Cosign` using the given base command, which is
+    /// usually just `cosign`.
+    pub fn new(base_command: Command) -> Self {
+        Self { base_command }
+    }
+
+    /// Get the base command.
+    pub fn base_command(&self) -> &Command {
+        &self.base_command
+    }
+
+    /// Create a [`Command`] for signing an image.
+    pub fn sign(&self, opt: SignOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("sign");
+
+        // --annotations
+        for annotation in &opt.annotations {
+            cmd.add_arg_pair("--annotations", annotation);
+        }
+
+        // --key
+        if let Some(key) = &opt.key {
+            cmd.add_arg_pair("--key", key);
+        }
+
+        // --yes
+        if opt.yes {
+            cmd.add_arg("--yes");
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_arg(opt.image.to_string());
+
+        cmd
+    }
+
+    /// Create a [`Command`] for verifying an image's signature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::cosign::{Cosign, VerifyOpt};
+    /// let mut opt = VerifyOpt::new("ghcr.io/owner/app:v1");
+    /// opt.key = Some("cosign.pub".into());
+    /// assert_eq!(
+    ///     Cosign::default().verify(opt).command_line_lossy(),
+    ///     "cosign verify --key cosign.pub ghcr.io/owner/app:v1"
+    /// );
+    /// ```
+    pub fn verify(&self, opt: VerifyOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("verify");
+
+        // --certificate-identity
+        if let Some(identity) = &opt.certificate_identity {
+            cmd.add_arg_pair("--certificate-identity", identity);
+        }
+
+        // --certificate-oidc-issuer
+        if let Some(issuer) = &opt.certificate_oidc_issuer {
+            cmd.add_arg_pair("--certificate-oidc-issuer", issuer);
+        }
+
+        // --key
+        if let Some(key) = &opt.key {
+            cmd.add_arg_pair("--key", key);
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_arg(opt.image.to_string());
+
+        cmd
+    }
+}
+
+impl Default for Cosign {
+    fn default() -> Self {
+        Self::new(Command::new("cosign"))
+    }
+}
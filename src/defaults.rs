@@ -0,0 +1,126 @@
+use crate::{
+    merge_pairs, BuildOpt, Launcher, NetworkMode, PullPolicy, RunOpt,
+    UserAndGroup,
+};
+use std::borrow::Cow;
+use std::ffi::OsString;
+
+/// Default options that a [`Launcher`] merges into every [`RunOpt`]
+/// and [`BuildOpt`] before rendering it. See
+/// [`Launcher::with_defaults`].
+///
+/// A default is only used where the options do not already set a
+/// value: `user`, `network`, and `pull` apply if the field is `None`,
+/// and each label or environment variable applies if its key is not
+/// already present. Default labels and variables are placed before the
+/// ones from the options.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Defaults {
+    /// User to run containers as. Not used for builds.
+    pub user: Option<UserAndGroup>,
+
+    /// Labels to add to containers and images.
+    pub labels: Vec<(String, String)>,
+
+    /// Network for containers, and for `RUN` instructions in builds.
+    pub network: Option<NetworkMode>,
+
+    /// When to pull images.
+    pub pull: Option<PullPolicy>,
+
+    /// Environment variables to set in containers. Not used for
+    /// builds.
+    pub env: Vec<(OsString, OsString)>,
+}
+
+impl Defaults {
+    /// Whether no defaults are set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Merge the defaults into `opt`.
+    pub fn apply_to_run(&self, opt: &mut RunOpt) {
+        if opt.user.is_none() {
+            opt.user.clone_from(&self.user);
+        }
+        merge_pairs(&self.labels, &mut opt.labels);
+        if opt.network.is_none() {
+            opt.network.clone_from(&self.network);
+        }
+        if opt.pull.is_none() {
+            opt.pull = self.pull;
+        }
+        merge_pairs(&self.env, &mut opt.env);
+    }
+
+    /// Merge the defaults into `opt`.
+    pub fn apply_to_build(&self, opt: &mut BuildOpt) {
+        merge_pairs(&self.labels, &mut opt.labels);
+        if opt.network.is_none() {
+            opt.network.clone_from(&self.network);
+        }
+        if opt.pull.is_none() {
+            opt.pull = self.pull;
+        }
+    }
+}
+
+impl Launcher {
+    /// Merge `defaults` into the options of every `run` and `build`
+    /// command created by this launcher. See [`Defaults`] for how
+    /// they are merged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, Defaults, Launcher, RunOpt};
+    /// let mut defaults = Defaults::default();
+    /// defaults.labels = vec![("team".into(), "infra".into())];
+    /// let launcher = Launcher::from(BaseCommand::Docker).with_defaults(defaults);
+    /// assert_eq!(
+    ///     launcher.run(RunOpt::new("alpine")).command_line_lossy(),
+    ///     "docker run --label team=infra alpine"
+    /// );
+    /// ```
+    pub fn with_defaults(mut self, defaults: Defaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Get the default options.
+    pub fn defaults(&self) -> &Defaults {
+        &self.defaults
+    }
+
+    /// Get `opt` with the defaults merged in, only copying it if there
+    /// are defaults to merge.
+    pub(crate) fn run_opt_with_defaults<'a>(
+        &self,
+        opt: &'a RunOpt,
+    ) -> Cow<'a, RunOpt> {
+        if self.defaults.is_empty() {
+            Cow::Borrowed(opt)
+        } else {
+            let mut opt = opt.clone();
+            self.defaults.apply_to_run(&mut opt);
+            Cow::Owned(opt)
+        }
+    }
+
+    /// Get `opt` with the defaults merged in, only copying it if there
+    /// are defaults to merge.
+    pub(crate) fn build_opt_with_defaults<'a>(
+        &self,
+        opt: &'a BuildOpt,
+    ) -> Cow<'a, BuildOpt> {
+        if self.defaults.is_empty() {
+            Cow::Borrowed(opt)
+        } else {
+            let mut opt = opt.clone();
+            self.defaults.apply_to_build(&mut opt);
+            Cow::Owned(opt)
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Find the container engines installed on this machine.
+//!
+//! [`Launcher::auto`] always prefers podman over docker. Use
+//! [`detect_engines`] to list everything that is installed instead,
+//! for example to let the user choose.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use docker_command::discovery::detect_engines;
+//! for engine in detect_engines() {
+//!     println!(
+//!         "{:?} {} at {}",
+//!         engine.engine,
+//!         engine.version.as_deref().unwrap_or("(unknown version)"),
+//!         engine.path.display()
+//!     );
+//! }
+//! ```
+//!
+//! [`Launcher::auto`]: crate::Launcher::auto
+
+use crate::{BaseCommand, Engine, Launcher, SudoPolicy};
+use command_run::Command;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Engines in the order [`Launcher::auto`] prefers them.
+///
+/// [`Launcher::auto`]: crate::Launcher::auto
+const ENGINES: [(Engine, &str); 2] =
+    [(Engine::Podman, "podman"), (Engine::Docker, "docker")];
+
+/// Options for [`Launcher::auto_with`].
+///
+/// [`Launcher::auto_with`]: crate::Launcher::auto_with
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct AutoOpt {
+    /// Engines to look for in the `$PATH`, most preferred first.
+    /// [`Engine::Auto`] entries are ignored. Defaults to podman, then
+    /// docker.
+    pub preference: Vec<Engine>,
+
+    /// Whether to run the chosen engine with `sudo`.
+    pub sudo: SudoPolicy,
+
+    /// Group that allows using docker without `sudo` when `sudo` is
+    /// [`SudoPolicy::Auto`]. Defaults to `docker`; some distributions
+    /// use `dockerroot`.
+    pub docker_group: String,
+}
+
+impl Default for AutoOpt {
+    fn default() -> Self {
+        Self {
+            preference: ENGINES.iter().map(|(engine, _)| *engine).collect(),
+            sudo: SudoPolicy::Auto,
+            docker_group: "docker".into(),
+        }
+    }
+}
+
+impl AutoOpt {
+    /// Create an `AutoOpt` with the default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Launcher {
+    /// Choose a base command as described by `opt`: the first engine
+    /// in [`AutoOpt::preference`] that is in the `$PATH`, with `sudo`
+    /// added according to [`AutoOpt::sudo`].
+    ///
+    /// If none of the engines is in the `$PATH`, returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{AutoOpt, Engine, Launcher, SudoPolicy};
+    /// let mut opt = AutoOpt::new();
+    /// opt.preference = vec![Engine::Docker, Engine::Podman];
+    /// opt.docker_group = "dockerroot".into();
+    /// if let Some(launcher) = Launcher::auto_with(&opt) {
+    ///     println!("{}", launcher.base_command().command_line_lossy());
+    /// }
+    /// ```
+    pub fn auto_with(opt: &AutoOpt) -> Option<Self> {
+        let engine = opt.preference.iter().find(|engine| {
+            ENGINES.iter().any(|(other, program)| {
+                other == *engine && is_exe_in_path(program)
+            })
+        })?;
+        Some(engine_launcher(*engine, opt))
+    }
+}
+
+/// Create a launcher for `engine`, which must not be [`Engine::Auto`],
+/// with `sudo` added according to [`AutoOpt::sudo`]. The engine is not
+/// looked up in the `$PATH`.
+pub(crate) fn engine_launcher(engine: Engine, opt: &AutoOpt) -> Launcher {
+    let program = if engine == Engine::Podman {
+        "podman"
+    } else {
+        "docker"
+    };
+    let sudo = match opt.sudo {
+        SudoPolicy::Always => true,
+        SudoPolicy::Never => false,
+        SudoPolicy::Auto => {
+            engine == Engine::Docker && !is_user_in_group(&opt.docker_group)
+        }
+    };
+    Launcher::new(if sudo {
+        Command::with_args("sudo", [program])
+    } else {
+        Command::new(program)
+    })
+}
+
+/// Container engine found by [`detect_engines`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct DetectedEngine {
+    /// Which engine this is; never [`Engine::Auto`].
+    pub engine: Engine,
+
+    /// Where the executable was found.
+    pub path: PathBuf,
+
+    /// Client version reported by `--version`, e.g. `24.0.7`, or None
+    /// if it could not be determined.
+    pub version: Option<String>,
+}
+
+impl DetectedEngine {
+    /// Create a [`Launcher`] for the engine, without `sudo`. The
+    /// executable is looked up in the `$PATH` when commands are run,
+    /// as with [`BaseCommand`].
+    pub fn launcher(&self) -> Launcher {
+        match self.engine {
+            Engine::Podman => BaseCommand::Podman.into(),
+            _ => BaseCommand::Docker.into(),
+        }
+    }
+}
+
+/// Find `exe_name` in the list of directories `paths`, formatted like
+/// the `$PATH` variable. On Windows, `exe_name` with an `.exe` suffix
+/// is found too.
+pub fn find_exe_in<S: AsRef<OsStr>>(
+    exe_name: S,
+    paths: &OsStr,
+) -> Option<PathBuf> {
+    let exe_name = exe_name.as_ref();
+    let mut with_suffix = OsString::from(exe_name);
+    with_suffix.push(env::consts::EXE_SUFFIX);
+    env::split_paths(paths).find_map(|dir| {
+        [dir.join(exe_name), dir.join(&with_suffix)]
+            .into_iter()
+            .find(|path| path.exists())
+    })
+}
+
+/// Find `exe_name` in the `$PATH`. See [`find_exe_in`].
+pub fn find_exe<S: AsRef<OsStr>>(exe_name: S) -> Option<PathBuf> {
+    find_exe_in(exe_name, &env::var_os("PATH")?)
+}
+
+/// Whether `exe_name` is in the `$PATH`.
+pub fn is_exe_in_path<S: AsRef<OsStr>>(exe_name: S) -> bool {
+    find_exe(exe_name).is_some()
+}
+
+// TODO: consider using nix or some other crate.
+/// Whether the current user is in `target_group`, according to the
+/// `groups` command.
+pub fn is_user_in_group(target_group: &str) -> bool {
+    let mut cmd = Command::new("groups");
+    cmd.log_command = false;
+    cmd.capture = true;
+    cmd.log_output_on_error = true;
+    let output = if let Ok(output) = cmd.run() {
+        output
+    } else {
+        return false;
+    };
+    let stdout = output.stdout_string_lossy();
+    stdout.split_whitespace().any(|group| group == target_group)
+}
+
+/// Get the version from the output of `docker --version` (e.g.
+/// `Docker version 24.0.7, build afdd53b`) or `podman --version`
+/// (e.g. `podman version 4.9.3`).
+fn parse_version(output: &str) -> Option<String> {
+    let (_, rest) = output.split_once(" version ")?;
+    let version = rest.split([',', ' ', '\n']).next()?.trim();
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// Run `path --version` and parse the output.
+fn version(path: &Path) -> Option<String> {
+    let mut cmd = Command::with_args(path, ["--version"]);
+    cmd.log_command = false;
+    cmd.capture = true;
+    let output = cmd.run().ok()?;
+    parse_version(&output.stdout_string_lossy())
+}
+
+/// Find the engines in the directories `paths`, formatted like the
+/// `$PATH` variable. See [`detect_engines`].
+pub fn detect_engines_in(paths: &OsStr) -> Vec<DetectedEngine> {
+    ENGINES
+        .iter()
+        .filter_map(|(engine, name)| {
+            let path = find_exe_in(name, paths)?;
+            Some(DetectedEngine {
+                engine: *engine,
+                version: version(&path),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Find the engines in the `$PATH`, in the order [`Launcher::auto`]
+/// prefers them. Each engine is run with `--version` to get its
+/// version.
+///
+/// [`Launcher::auto`]: crate::Launcher::auto
+pub fn detect_engines() -> Vec<DetectedEngine> {
+    env::var_os("PATH")
+        .map(|paths| detect_engines_in(&paths))
+        .unwrap_or_default()
+}
@@ -0,0 +1,186 @@
+use crate::ValidationError;
+use serde_json::Value;
+use std::fmt;
+
+/// Kind of object reported by `system df`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum DiskUsageKind {
+    /// Images.
+    Images,
+
+    /// Containers.
+    Containers,
+
+    /// Local volumes.
+    LocalVolumes,
+
+    /// Build cache.
+    BuildCache,
+
+    /// Any other kind, with the name as reported.
+    Other(String),
+}
+
+impl DiskUsageKind {
+    /// Get the kind as reported by `system df`, e.g. `Local Volumes`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Images => "Images",
+            Self::Containers => "Containers",
+            Self::LocalVolumes => "Local Volumes",
+            Self::BuildCache => "Build Cache",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for DiskUsageKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for DiskUsageKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "Images" => Self::Images,
+            "Containers" => Self::Containers,
+            "Local Volumes" => Self::LocalVolumes,
+            "Build Cache" => Self::BuildCache,
+            _ => Self::Other(s.to_string()),
+        }
+    }
+}
+
+/// Disk usage of one kind of object, as reported by `system df`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DiskUsage {
+    /// Kind of object.
+    pub kind: DiskUsageKind,
+
+    /// Total number of objects.
+    pub total: u64,
+
+    /// Number of objects in use.
+    pub active: u64,
+
+    /// Disk space used, in bytes.
+    pub size: u64,
+
+    /// Disk space that could be reclaimed by pruning, in bytes.
+    pub reclaimable: u64,
+}
+
+impl DiskUsage {
+    /// Parse the output of `system df --format json`.
+    ///
+    /// Docker prints one JSON object per line, with sizes formatted
+    /// for humans (so they are only accurate to a few significant
+    /// digits). Podman prints a JSON array and includes exact sizes,
+    /// which are used when present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{DiskUsage, DiskUsageKind};
+    /// let usage = DiskUsage::parse_json(
+    ///     r#"{"Active":"2","Reclaimable":"1.5GB (50%)","Size":"3GB","TotalCount":"5","Type":"Images"}"#,
+    /// ).unwrap();
+    /// assert_eq!(usage[0].kind, DiskUsageKind::Images);
+    /// assert_eq!(usage[0].total, 5);
+    /// assert_eq!(usage[0].size, 3_000_000_000);
+    /// assert_eq!(usage[0].reclaimable, 1_500_000_000);
+    /// ```
+    pub fn parse_json(output: &str) -> Result<Vec<Self>, ValidationError> {
+        let err = |reason: &str| {
+            ValidationError::new("disk usage", output.trim(), reason)
+        };
+
+        let output = output.trim();
+        let values: Vec<Value> = if output.starts_with('[') {
+            serde_json::from_str(output).map_err(|e| err(&e.to_string()))?
+        } else {
+            output
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<_, _>>()
+                .map_err(|e| err(&e.to_string()))?
+        };
+
+        values
+            .iter()
+            .map(|value| {
+                let kind = value
+                    .get("Type")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| err("missing Type"))?;
+                let field =
+                    |names: &[&str], parse: fn(&Value) -> Option<u64>| {
+                        names
+                            .iter()
+                            .find_map(|name| value.get(*name).and_then(parse))
+                            .ok_or_else(|| {
+                                err(&format!("missing or invalid {}", names[0]))
+                            })
+                    };
+                Ok(Self {
+                    kind: kind.into(),
+                    total: field(&["TotalCount", "Total"], count)?,
+                    active: field(&["Active"], count)?,
+                    size: field(&["RawSize", "Size"], size)?,
+                    reclaimable: field(
+                        &["RawReclaimable", "Reclaimable"],
+                        size,
+                    )?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Get a count from either a number or a string.
+fn count(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Get a size in bytes from either a number or a string such as
+/// `1.5GB` or `1.5GB (50%)`.
+fn size(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => parse_size(s),
+        _ => None,
+    }
+}
+
+/// Parse a human-readable size such as `1.5GB`, `10kB`, or `2MiB`
+/// into bytes. Anything after the first space (such as a percentage)
+/// is ignored.
+pub(crate) fn parse_size(s: &str) -> Option<u64> {
+    let s = s.split_whitespace().next()?;
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit {
+        "" | "B" => 1,
+        "kB" | "KB" => 1000,
+        "MB" => 1000u64.pow(2),
+        "GB" => 1000u64.pow(3),
+        "TB" => 1000u64.pow(4),
+        "PB" => 1000u64.pow(5),
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        "PiB" => 1 << 50,
+        _ => return None,
+    };
+    Some((number * multiplier as f64).round() as u64)
+}
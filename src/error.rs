@@ -0,0 +1,169 @@
+use crate::{PolicyViolation, PortConflict};
+use std::time::Duration;
+use std::{fmt, io};
+
+/// Error returned when a value fails to parse or validate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationError {
+    /// Description of the kind of value, e.g. "image reference".
+    pub what: &'static str,
+
+    /// The invalid value.
+    pub value: String,
+
+    /// Why the value is invalid.
+    pub reason: String,
+}
+
+impl ValidationError {
+    pub(crate) fn new<V, R>(what: &'static str, value: V, reason: R) -> Self
+    where
+        V: Into<String>,
+        R: Into<String>,
+    {
+        Self {
+            what,
+            value: value.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {} {:?}: {}", self.what, self.value, self.reason)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Error returned by the helpers that run commands.
+#[derive(Debug)]
+pub enum Error {
+    /// The command failed to run or exited unsuccessfully.
+    Run(command_run::Error),
+
+    /// The command succeeded but its output was not understood.
+    UnexpectedOutput {
+        /// Command line of the command that was run.
+        command: String,
+
+        /// The output that could not be understood.
+        output: String,
+    },
+
+    /// A value was invalid.
+    Invalid(ValidationError),
+
+    /// Reading or writing a file failed.
+    Io(io::Error),
+
+    /// The command did not finish in time and was killed.
+    TimedOut {
+        /// Command line of the command that was run.
+        command: String,
+
+        /// How long the command was allowed to run.
+        timeout: Duration,
+    },
+
+    /// The command is not allowed by a [`Policy`].
+    ///
+    /// [`Policy`]: crate::Policy
+    PolicyViolation(PolicyViolation),
+
+    /// A host port to be published is already in use. See
+    /// [`Launcher::check_ports`].
+    ///
+    /// [`Launcher::check_ports`]: crate::Launcher::check_ports
+    PortConflict(PortConflict),
+
+    /// A container stopped or failed its health check while it was
+    /// being waited on.
+    Unhealthy {
+        /// Name or ID of the container.
+        container: String,
+
+        /// Health status (e.g. `unhealthy`) or, for containers without
+        /// a health check, state (e.g. `exited`).
+        status: String,
+    },
+
+    /// The command was stopped with a [`CancelToken`].
+    ///
+    /// [`CancelToken`]: crate::CancelToken
+    Cancelled {
+        /// Command line of the command that was run.
+        command: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Run(err) => write!(f, "{}", err),
+            Self::UnexpectedOutput { command, output } => {
+                write!(f, "unexpected output from '{}': {:?}", command, output)
+            }
+            Self::Invalid(err) => write!(f, "{}", err),
+            Self::Io(err) => write!(f, "{}", err),
+            Self::PolicyViolation(err) => write!(f, "{}", err),
+            Self::PortConflict(err) => write!(f, "{}", err),
+            Self::TimedOut { command, timeout } => {
+                write!(f, "'{}' timed out after {:?}", command, timeout)
+            }
+            Self::Unhealthy { container, status } => {
+                write!(f, "container '{}' is {}", container, status)
+            }
+            Self::Cancelled { command } => {
+                write!(f, "'{}' was cancelled", command)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Run(err) => Some(err),
+            Self::UnexpectedOutput { .. } => None,
+            Self::Invalid(err) => Some(err),
+            Self::Io(err) => Some(err),
+            Self::PolicyViolation(err) => Some(err),
+            Self::PortConflict(err) => Some(err),
+            Self::TimedOut { .. }
+            | Self::Unhealthy { .. }
+            | Self::Cancelled { .. } => None,
+        }
+    }
+}
+
+impl From<command_run::Error> for Error {
+    fn from(err: command_run::Error) -> Self {
+        Self::Run(err)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(err: ValidationError) -> Self {
+        Self::Invalid(err)
+    }
+}
+
+impl From<PolicyViolation> for Error {
+    fn from(err: PolicyViolation) -> Self {
+        Self::PolicyViolation(err)
+    }
+}
+
+impl From<PortConflict> for Error {
+    fn from(err: PortConflict) -> Self {
+        Self::PortConflict(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
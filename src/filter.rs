@@ -0,0 +1,130 @@
+use std::fmt;
+
+/// Filter used when listing or pruning containers, images, networks,
+/// and volumes.
+///
+/// Not every filter is supported by every command; see the Docker
+/// documentation for the command in question. Filters with different
+/// keys are combined with AND, and filters with the same key are
+/// combined with OR.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::Filter;
+/// assert_eq!(Filter::Label("app=web".into()).arg(), "label=app=web");
+/// assert_eq!(Filter::Dangling(true).arg(), "dangling=true");
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Filter {
+    /// Containers created from an image or a descendant of it.
+    Ancestor(String),
+
+    /// Objects created before the given container or image.
+    Before(String),
+
+    /// Dangling images or volumes (not referenced by anything).
+    Dangling(bool),
+
+    /// Networks or volumes using the given driver.
+    Driver(String),
+
+    /// Containers that exited with the given code.
+    Exited(i32),
+
+    /// Containers with the given health status (`starting`, `healthy`,
+    /// `unhealthy`, or `none`).
+    Health(String),
+
+    /// Objects whose ID starts with the given value.
+    Id(String),
+
+    /// Objects with a label, specified as either `key` or
+    /// `key=value`.
+    Label(String),
+
+    /// Objects that do not have a label (prune commands only),
+    /// specified as either `key` or `key=value`.
+    LabelNot(String),
+
+    /// Objects whose name contains the given value.
+    Name(String),
+
+    /// Containers connected to the given network.
+    Network(String),
+
+    /// Images whose reference matches the given pattern.
+    Reference(String),
+
+    /// Objects created after the given container or image.
+    Since(String),
+
+    /// Containers with the given status (`created`, `running`,
+    /// `exited`, etc).
+    Status(String),
+
+    /// Objects created before the given timestamp or duration (prune
+    /// commands only).
+    Until(String),
+
+    /// Containers that mount the given volume or path.
+    Volume(String),
+
+    /// Any other `key=value` filter.
+    Custom(String, String),
+}
+
+impl Filter {
+    /// Key used on the command line, e.g. `label`.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Ancestor(_) => "ancestor",
+            Self::Before(_) => "before",
+            Self::Dangling(_) => "dangling",
+            Self::Driver(_) => "driver",
+            Self::Exited(_) => "exited",
+            Self::Health(_) => "health",
+            Self::Id(_) => "id",
+            Self::Label(_) => "label",
+            Self::LabelNot(_) => "label!",
+            Self::Name(_) => "name",
+            Self::Network(_) => "network",
+            Self::Reference(_) => "reference",
+            Self::Since(_) => "since",
+            Self::Status(_) => "status",
+            Self::Until(_) => "until",
+            Self::Volume(_) => "volume",
+            Self::Custom(key, _) => key,
+        }
+    }
+
+    /// Format as an argument for `--filter`.
+    pub fn arg(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}=", self.key())?;
+        match self {
+            Self::Dangling(dangling) => write!(f, "{}", dangling),
+            Self::Exited(code) => write!(f, "{}", code),
+            Self::Ancestor(value)
+            | Self::Before(value)
+            | Self::Driver(value)
+            | Self::Health(value)
+            | Self::Id(value)
+            | Self::Label(value)
+            | Self::LabelNot(value)
+            | Self::Name(value)
+            | Self::Network(value)
+            | Self::Reference(value)
+            | Self::Since(value)
+            | Self::Status(value)
+            | Self::Until(value)
+            | Self::Volume(value)
+            | Self::Custom(_, value) => write!(f, "{}", value),
+        }
+    }
+}
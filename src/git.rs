@@ -0,0 +1,122 @@
+//! Label images with git metadata. Requires the `git` feature.
+
+use crate::oci::OciMetadata;
+use crate::runner::stdout;
+use crate::{BuildArg, BuildOpt, Error};
+use command_run::Command;
+use std::path::Path;
+
+/// Run `git` with `args` in `dir` and return its trimmed stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String, Error> {
+    let mut cmd = Command::with_args("git", ["-C".as_ref(), dir.as_os_str()]);
+    cmd.add_args(args);
+    cmd.log_command = false;
+    stdout(cmd)
+}
+
+/// Convert an SSH remote such as `git@github.com:org/repo.git` to an
+/// HTTPS URL. Other remotes are returned without a `.git` suffix and
+/// with any credentials removed.
+fn remote_to_url(remote: &str) -> String {
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    if let Some((scheme, rest)) = remote.split_once("://") {
+        // Drop `user:token@` from HTTPS remotes.
+        let rest = rest.split_once('@').map_or(rest, |(_, host)| host);
+        let scheme = if scheme == "ssh" { "https" } else { scheme };
+        return format!("{}://{}", scheme, rest);
+    }
+    match remote.split_once('@') {
+        Some((_, host_path)) => match host_path.split_once(':') {
+            Some((host, path)) => format!("https://{}/{}", host, path),
+            None => remote.to_string(),
+        },
+        None => remote.to_string(),
+    }
+}
+
+/// State of a git checkout, discovered by running `git`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use docker_command::{BuildOpt, GitMetadata};
+/// let mut opt = BuildOpt::new(".");
+/// GitMetadata::discover(".").unwrap().add_to_build(&mut opt);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct GitMetadata {
+    /// Full hash of the `HEAD` commit.
+    pub commit: String,
+
+    /// Current branch, or `None` if `HEAD` is detached.
+    pub branch: Option<String>,
+
+    /// Whether there are uncommitted changes (including untracked
+    /// files).
+    pub dirty: bool,
+
+    /// URL of the `origin` remote, converted to HTTPS if it is an SSH
+    /// remote, or `None` if there is no `origin`.
+    pub source: Option<String>,
+}
+
+impl GitMetadata {
+    /// Get the metadata of the checkout containing `dir`.
+    pub fn discover<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let commit = git(dir, &["rev-parse", "HEAD"])?;
+        let branch = git(dir, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+        let dirty = !git(dir, &["status", "--porcelain"])?.is_empty();
+        // Not having an origin is not an error.
+        let source = git(dir, &["remote", "get-url", "origin"])
+            .ok()
+            .map(|remote| remote_to_url(&remote));
+        Ok(Self {
+            commit,
+            branch: if branch == "HEAD" { None } else { Some(branch) },
+            dirty,
+            source,
+        })
+    }
+
+    /// Revision to record: the commit hash, with `-dirty` appended if
+    /// there are uncommitted changes.
+    pub fn revision(&self) -> String {
+        if self.dirty {
+            format!("{}-dirty", self.commit)
+        } else {
+            self.commit.clone()
+        }
+    }
+
+    /// Get the OCI revision and source metadata.
+    pub fn oci_metadata(&self) -> OciMetadata {
+        OciMetadata {
+            revision: Some(self.revision()),
+            source: self.source.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Get build args `GIT_COMMIT`, `GIT_BRANCH` (if on a branch), and
+    /// `GIT_DIRTY` (`true` or `false`).
+    pub fn build_args(&self) -> Vec<BuildArg> {
+        let mut args = vec![BuildArg::from(("GIT_COMMIT", &*self.commit))];
+        if let Some(branch) = &self.branch {
+            args.push(("GIT_BRANCH", branch.as_str()).into());
+        }
+        args.push(("GIT_DIRTY", self.dirty.to_string()).into());
+        args
+    }
+
+    /// Add the OCI labels from [`GitMetadata::oci_metadata`] and the
+    /// build args from [`GitMetadata::build_args`] to `opt`.
+    ///
+    /// Docker warns about build args that the Dockerfile does not
+    /// declare with `ARG`; that warning is harmless.
+    pub fn add_to_build(&self, opt: &mut BuildOpt) {
+        opt.add_oci_labels(&self.oci_metadata());
+        opt.build_args.extend(self.build_args());
+    }
+}
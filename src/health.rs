@@ -0,0 +1,136 @@
+use crate::runner::stdout;
+use crate::{
+    CommandExt, ContainerRef, Error, InspectOpt, Launcher, ObjectKind,
+};
+use command_run::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often [`Launcher::wait_healthy`] checks the container.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Result of [`Launcher::poll_health`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum HealthOutcome {
+    /// The container's health check passed.
+    Healthy,
+
+    /// The container has no health check and is running.
+    Running,
+
+    /// The container's health check failed.
+    Unhealthy,
+
+    /// The container stopped before becoming healthy. Contains the
+    /// state, e.g. `exited`.
+    Stopped(String),
+
+    /// The container was still starting when the timeout passed.
+    /// Contains the last status, e.g. `starting`.
+    TimedOut(String),
+}
+
+impl HealthOutcome {
+    /// Whether the container is ready: healthy, or running without a
+    /// health check.
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Healthy | Self::Running)
+    }
+}
+
+impl Launcher {
+    /// Create a [`Command`] that prints the health status of
+    /// `container`, or its state if it has no health check.
+    fn health_command(&self, container: &ContainerRef) -> Command {
+        self.inspect(InspectOpt {
+            format: Some(
+                "{{if .State.Health}}{{.State.Health.Status}}\
+                 {{else}}{{.State.Status}}{{end}}"
+                    .into(),
+            ),
+            kind: Some(ObjectKind::Container),
+            ..InspectOpt::new([container.to_string()])
+        })
+    }
+
+    /// Check the health of `container` every `interval` until it is
+    /// ready, fails, or `timeout` passes.
+    ///
+    /// A container with a health check is ready once its status is
+    /// `healthy`. A container without one is ready as soon as it is
+    /// running. The status is always checked at least once, even if
+    /// `timeout` is zero. An error is only returned if `inspect` fails,
+    /// for example because the container does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use docker_command::{BaseCommand, HealthOutcome, Launcher};
+    /// use std::time::Duration;
+    ///
+    /// let launcher = Launcher::from(BaseCommand::Docker);
+    /// let outcome = launcher
+    ///     .poll_health(
+    ///         &"db".parse().unwrap(),
+    ///         Duration::from_secs(60),
+    ///         Duration::from_secs(1),
+    ///     )
+    ///     .unwrap();
+    /// match outcome {
+    ///     HealthOutcome::Healthy | HealthOutcome::Running => {}
+    ///     other => eprintln!("db is not ready: {:?}", other),
+    /// }
+    /// ```
+    pub fn poll_health(
+        &self,
+        container: &ContainerRef,
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<HealthOutcome, Error> {
+        let cmd = self.enforce(self.health_command(container))?;
+        let start = Instant::now();
+        loop {
+            let status = stdout(cmd.clone())?;
+            match status.as_str() {
+                "healthy" => return Ok(HealthOutcome::Healthy),
+                "running" => return Ok(HealthOutcome::Running),
+                "unhealthy" => return Ok(HealthOutcome::Unhealthy),
+                "starting" | "created" | "restarting" => {}
+                _ => return Ok(HealthOutcome::Stopped(status)),
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Ok(HealthOutcome::TimedOut(status));
+            }
+            thread::sleep(interval.min(timeout - elapsed));
+        }
+    }
+
+    /// Wait for `container` to become healthy, checking every half
+    /// second. See [`Launcher::poll_health`].
+    ///
+    /// Returns [`Error::Unhealthy`] if the container becomes unhealthy
+    /// or stops, and [`Error::TimedOut`] if it is not ready after
+    /// `timeout`.
+    pub fn wait_healthy(
+        &self,
+        container: &ContainerRef,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        match self.poll_health(container, timeout, POLL_INTERVAL)? {
+            HealthOutcome::Healthy | HealthOutcome::Running => Ok(()),
+            HealthOutcome::Unhealthy => Err(Error::Unhealthy {
+                container: container.to_string(),
+                status: "unhealthy".into(),
+            }),
+            HealthOutcome::Stopped(status) => Err(Error::Unhealthy {
+                container: container.to_string(),
+                status,
+            }),
+            HealthOutcome::TimedOut(_) => Err(Error::TimedOut {
+                command: self.health_command(container).command_line_shell(),
+                timeout,
+            }),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{Launcher, ValidationError};
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Prefix of Windows named pipe paths.
+const PIPE_PREFIX: &str = "//./pipe/";
+
+/// Address of the daemon (or podman service) that the client talks
+/// to. See [`Launcher::with_host`].
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::DaemonHost;
+/// assert_eq!(
+///     DaemonHost::NamedPipe("docker_engine".into()).arg(),
+///     "npipe:////./pipe/docker_engine"
+/// );
+/// let host: DaemonHost = "unix:///run/user/1000/podman/podman.sock"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(
+///     host,
+///     DaemonHost::Unix("/run/user/1000/podman/podman.sock".into())
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum DaemonHost {
+    /// Unix socket at the given path.
+    Unix(PathBuf),
+
+    /// Windows named pipe with the given name, e.g. `docker_engine`
+    /// for Docker Desktop.
+    NamedPipe(String),
+
+    /// TCP address, e.g. `tcp://host:2376`.
+    Tcp(String),
+
+    /// SSH destination, e.g. `ssh://user@host`.
+    Ssh(String),
+}
+
+impl DaemonHost {
+    /// Format as the value of `--host` or `--url`.
+    pub fn arg(&self) -> String {
+        self.to_string()
+    }
+
+    /// Find the socket of a local Docker daemon in a non-default
+    /// location, checking in order:
+    ///
+    /// * On Windows, the Docker Desktop named pipe.
+    /// * `$HOME/.docker/run/docker.sock` (Docker Desktop on macOS).
+    /// * `$HOME/.docker/desktop/docker.sock` (Docker Desktop on Linux).
+    /// * `$XDG_RUNTIME_DIR/docker.sock` (rootless Docker).
+    /// * `$HOME/.colima/default/docker.sock` (Colima).
+    /// * `$HOME/.rd/docker.sock` (Rancher Desktop).
+    ///
+    /// Returns None if none of these exist, in which case the client's
+    /// default (normally `/var/run/docker.sock`) is the best choice.
+    pub fn detect_docker() -> Option<Self> {
+        if cfg!(windows) {
+            return find_pipe("docker_engine");
+        }
+        let home = env::var_os("HOME").map(PathBuf::from);
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+        find_socket(
+            [
+                home.as_ref().map(|h| h.join(".docker/run/docker.sock")),
+                home.as_ref().map(|h| h.join(".docker/desktop/docker.sock")),
+                runtime_dir.map(|d| d.join("docker.sock")),
+                home.as_ref().map(|h| h.join(".colima/default/docker.sock")),
+                home.as_ref().map(|h| h.join(".rd/docker.sock")),
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+
+    /// Find the socket of a local podman service, checking in order:
+    ///
+    /// * On Windows, the named pipe of the default podman machine.
+    /// * `$XDG_RUNTIME_DIR/podman/podman.sock` (rootless).
+    /// * `/run/podman/podman.sock` (rootful).
+    ///
+    /// Returns None if none of these exist. The service must be
+    /// running (or socket activated) for the socket to exist; see
+    /// [`Launcher::system_service`].
+    pub fn detect_podman() -> Option<Self> {
+        if cfg!(windows) {
+            return find_pipe("podman-machine-default");
+        }
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+        find_socket(
+            [
+                runtime_dir.map(|d| d.join("podman/podman.sock")),
+                Some("/run/podman/podman.sock".into()),
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+}
+
+/// Get the named pipe `name` if it exists.
+fn find_pipe(name: &str) -> Option<DaemonHost> {
+    let path = format!(r"\\.\pipe\{}", name);
+    Path::new(&path)
+        .exists()
+        .then(|| DaemonHost::NamedPipe(name.into()))
+}
+
+/// Get the first of `paths` that is a socket.
+fn find_socket<I: IntoIterator<Item = PathBuf>>(
+    paths: I,
+) -> Option<DaemonHost> {
+    #[cfg(unix)]
+    fn is_socket(path: &Path) -> bool {
+        use std::os::unix::fs::FileTypeExt;
+        path.metadata()
+            .map(|meta| meta.file_type().is_socket())
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    fn is_socket(path: &Path) -> bool {
+        path.exists()
+    }
+
+    paths
+        .into_iter()
+        .find(|path| is_socket(path))
+        .map(DaemonHost::Unix)
+}
+
+impl fmt::Display for DaemonHost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "unix://{}", path.display()),
+            Self::NamedPipe(name) => {
+                write!(f, "npipe://{}{}", PIPE_PREFIX, name)
+            }
+            Self::Tcp(addr) => write!(f, "tcp://{}", addr),
+            Self::Ssh(dest) => write!(f, "ssh://{}", dest),
+        }
+    }
+}
+
+impl FromStr for DaemonHost {
+    type Err = ValidationError;
+
+    /// Parse a `unix://`, `npipe://`, `tcp://`, or `ssh://` URL.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("daemon host", s, reason);
+        let (scheme, rest) =
+            s.split_once("://").ok_or_else(|| err("missing scheme"))?;
+        if rest.is_empty() {
+            return Err(err("missing address"));
+        }
+        match scheme {
+            "unix" => Ok(Self::Unix(rest.into())),
+            "npipe" => {
+                let name = rest
+                    .replace('\\', "/")
+                    .strip_prefix(PIPE_PREFIX)
+                    .map(str::to_string)
+                    .ok_or_else(|| err("expected //./pipe/<name>"))?;
+                Ok(Self::NamedPipe(name))
+            }
+            "tcp" => Ok(Self::Tcp(rest.into())),
+            "ssh" => Ok(Self::Ssh(rest.into())),
+            _ => Err(err("unknown scheme")),
+        }
+    }
+}
+
+impl Launcher {
+    /// Connect to the daemon at `host`, e.g. one found with
+    /// [`DaemonHost::detect_docker`] or [`DaemonHost::detect_podman`].
+    ///
+    /// For docker this sets [`GlobalOpts::host`]. Podman's equivalent
+    /// is `--url`, which is added to [`GlobalOpts::extra_args`] and
+    /// makes podman act as a remote client.
+    ///
+    /// [`GlobalOpts::host`]: crate::GlobalOpts::host
+    /// [`GlobalOpts::extra_args`]: crate::GlobalOpts::extra_args
+    pub fn with_host(mut self, host: DaemonHost) -> Self {
+        if self.is_podman() {
+            self.global_opts
+                .extra_args
+                .push(format!("--url={}", host).into());
+        } else {
+            self.global_opts.host = Some(host.arg());
+        }
+        self
+    }
+}
@@ -0,0 +1,290 @@
+use crate::ValidationError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Registry used when an image reference does not specify one.
+pub const DEFAULT_REGISTRY: &str = "docker.io";
+
+/// Tag used when an image reference has neither a tag nor a digest.
+pub const DEFAULT_TAG: &str = "latest";
+
+/// Reference to a container image, in the form
+/// `[registry/]repository[:tag][@digest]`.
+///
+/// Converting from a string with `From` never fails; the string is
+/// split into its components but not validated. Use [`str::parse`]
+/// (via [`FromStr`]) to validate the reference as well.
+///
+/// Formatting with `Display` produces the reference exactly as it was
+/// given. Use [`ImageRef::normalize`] to fill in the defaults.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::ImageRef;
+/// let image: ImageRef = "ghcr.io/owner/app:v1".parse().unwrap();
+/// assert_eq!(image.registry(), Some("ghcr.io"));
+/// assert_eq!(image.repository(), "owner/app");
+/// assert_eq!(image.tag(), Some("v1"));
+/// assert_eq!(image.digest(), None);
+///
+/// let image = ImageRef::from("alpine");
+/// assert_eq!(image.to_string(), "alpine");
+/// assert_eq!(
+///     image.normalize().to_string(),
+///     "docker.io/library/alpine:latest"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ImageRef {
+    registry: Option<String>,
+    repository: String,
+    tag: Option<String>,
+    digest: Option<String>,
+}
+
+impl ImageRef {
+    /// Registry host (and optionally port), if specified.
+    pub fn registry(&self) -> Option<&str> {
+        self.registry.as_deref()
+    }
+
+    /// Repository path within the registry, e.g. `library/alpine`.
+    pub fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    /// Tag, if specified.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Digest (e.g. `sha256:...`), if specified.
+    pub fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+
+    /// Registry and repository without the tag or digest, e.g.
+    /// `ghcr.io/owner/app`.
+    pub fn name(&self) -> String {
+        match &self.registry {
+            Some(registry) => format!("{}/{}", registry, self.repository),
+            None => self.repository.clone(),
+        }
+    }
+
+    /// Get a copy of the reference with its tag set to `tag` and any
+    /// digest removed.
+    pub fn with_tag<S: Into<String>>(&self, tag: S) -> Self {
+        Self {
+            registry: self.registry.clone(),
+            repository: self.repository.clone(),
+            tag: Some(tag.into()),
+            digest: None,
+        }
+    }
+
+    /// Get a copy of the reference pinned to `digest` (e.g.
+    /// `sha256:...`), with any tag removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::ImageRef;
+    /// let image = ImageRef::from("ghcr.io/owner/app:v1");
+    /// assert_eq!(
+    ///     image.pinned("sha256:0123").to_string(),
+    ///     "ghcr.io/owner/app@sha256:0123"
+    /// );
+    /// ```
+    pub fn pinned<S: Into<String>>(&self, digest: S) -> Self {
+        Self {
+            registry: self.registry.clone(),
+            repository: self.repository.clone(),
+            tag: None,
+            digest: Some(digest.into()),
+        }
+    }
+
+    /// Get a copy of the reference with the defaults filled in:
+    ///
+    /// * The registry defaults to [`DEFAULT_REGISTRY`].
+    /// * Single-component repositories on the default registry get the
+    ///   `library/` prefix.
+    /// * If neither a tag nor a digest is set, the tag defaults to
+    ///   [`DEFAULT_TAG`].
+    pub fn normalize(&self) -> Self {
+        let registry = self.registry.as_deref().unwrap_or(DEFAULT_REGISTRY);
+        let repository =
+            if registry == DEFAULT_REGISTRY && !self.repository.contains('/') {
+                format!("library/{}", self.repository)
+            } else {
+                self.repository.clone()
+            };
+        let tag = if self.tag.is_none() && self.digest.is_none() {
+            Some(DEFAULT_TAG.to_string())
+        } else {
+            self.tag.clone()
+        };
+        Self {
+            registry: Some(registry.to_string()),
+            repository,
+            tag,
+            digest: self.digest.clone(),
+        }
+    }
+
+    /// Check that each component is well formed.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let err = |reason: &str| {
+            ValidationError::new("image reference", self.to_string(), reason)
+        };
+
+        if let Some(registry) = &self.registry {
+            let (host, port) = match registry.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (registry.as_str(), None),
+            };
+            if host.is_empty()
+                || !host
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+            {
+                return Err(err("invalid registry host"));
+            }
+            if let Some(port) = port {
+                if port.parse::<u16>().is_err() {
+                    return Err(err("invalid registry port"));
+                }
+            }
+        }
+
+        if self.repository.is_empty() {
+            return Err(err("empty repository"));
+        }
+        if self.name().len() > 255 {
+            return Err(err("name longer than 255 characters"));
+        }
+        for component in self.repository.split('/') {
+            let valid_char = |c: char| {
+                c.is_ascii_lowercase()
+                    || c.is_ascii_digit()
+                    || c == '.'
+                    || c == '_'
+                    || c == '-'
+            };
+            let alnum = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+            if !component.starts_with(alnum)
+                || !component.ends_with(alnum)
+                || !component.chars().all(valid_char)
+            {
+                return Err(err("invalid repository path component"));
+            }
+        }
+
+        if let Some(tag) = &self.tag {
+            let valid_char =
+                |c: char| c.is_ascii_alphanumeric() || "_.-".contains(c);
+            if tag.is_empty()
+                || tag.len() > 128
+                || tag.starts_with(['.', '-'])
+                || !tag.chars().all(valid_char)
+            {
+                return Err(err("invalid tag"));
+            }
+        }
+
+        if let Some(digest) = &self.digest {
+            let valid = match digest.split_once(':') {
+                Some((algorithm, hex)) => {
+                    !algorithm.is_empty()
+                        && algorithm.chars().all(|c| {
+                            c.is_ascii_lowercase()
+                                || c.is_ascii_digit()
+                                || "+._-".contains(c)
+                        })
+                        && hex.len() >= 32
+                        && hex.chars().all(|c| c.is_ascii_hexdigit())
+                }
+                None => false,
+            };
+            if !valid {
+                return Err(err("invalid digest"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ImageRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        if let Some(tag) = &self.tag {
+            write!(f, ":{}", tag)?;
+        }
+        if let Some(digest) = &self.digest {
+            write!(f, "@{}", digest)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for ImageRef {
+    fn from(s: &str) -> Self {
+        let (rest, digest) = match s.split_once('@') {
+            Some((rest, digest)) => (rest, Some(digest.to_string())),
+            None => (s, None),
+        };
+
+        // A colon after the last slash separates the tag. A colon
+        // before it is part of the registry's host:port.
+        let (name, tag) = match rest.rfind(':') {
+            Some(i) if !rest[i..].contains('/') => {
+                (&rest[..i], Some(rest[i + 1..].to_string()))
+            }
+            _ => (rest, None),
+        };
+
+        // The first component is a registry if it looks like a host.
+        let (registry, repository) = match name.split_once('/') {
+            Some((first, remainder))
+                if first.contains('.')
+                    || first.contains(':')
+                    || first == "localhost" =>
+            {
+                (Some(first.to_string()), remainder)
+            }
+            _ => (None, name),
+        };
+
+        Self {
+            registry,
+            repository: repository.to_string(),
+            tag,
+            digest,
+        }
+    }
+}
+
+impl From<String> for ImageRef {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<&String> for ImageRef {
+    fn from(s: &String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl FromStr for ImageRef {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let image = Self::from(s);
+        image.validate()?;
+        Ok(image)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Protocol, ValidationError};
+use serde_json::Value;
+
+/// Configuration of an image, as reported by `image inspect`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ImageConfig {
+    /// Digests of the image in the registries it was pulled from or
+    /// pushed to, e.g. `docker.io/library/alpine@sha256:...`.
+    pub repo_digests: Vec<String>,
+
+    /// Entrypoint of the image.
+    pub entrypoint: Vec<String>,
+
+    /// Default command, passed as arguments to the entrypoint if there
+    /// is one.
+    pub cmd: Vec<String>,
+
+    /// Ports exposed by the image, sorted by port number.
+    pub exposed_ports: Vec<(u16, Protocol)>,
+
+    /// Environment variables set by the image.
+    pub env: Vec<(String, String)>,
+
+    /// Working directory, if set.
+    pub working_dir: Option<String>,
+
+    /// User the image runs as, if set.
+    pub user: Option<String>,
+}
+
+impl ImageConfig {
+    /// Parse the output of `image inspect`, which is a JSON array with
+    /// one object per image. Only the first image is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::ImageConfig;
+    /// let config = ImageConfig::parse_json(
+    ///     r#"[{"RepoDigests":[],"Config":{"Entrypoint":["/init"],
+    ///          "Cmd":["serve"],"Env":["PATH=/bin"]}}]"#,
+    /// ).unwrap();
+    /// assert_eq!(config.default_command(), ["/init", "serve"]);
+    /// assert_eq!(config.env, [("PATH".into(), "/bin".into())]);
+    /// ```
+    pub fn parse_json(output: &str) -> Result<Self, ValidationError> {
+        let err = |reason: &str| {
+            ValidationError::new("image inspect output", output.trim(), reason)
+        };
+        let value: Value =
+            serde_json::from_str(output).map_err(|e| err(&e.to_string()))?;
+        let image = match &value {
+            Value::Array(images) => images.first(),
+            Value::Object(_) => Some(&value),
+            _ => None,
+        }
+        .ok_or_else(|| err("expected an image"))?;
+        let config = image.get("Config").unwrap_or(&Value::Null);
+
+        let strings = |value: Option<&Value>| -> Vec<String> {
+            value
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        };
+        let non_empty = |key: &str| {
+            config
+                .get(key)
+                .and_then(Value::as_str)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+
+        let mut exposed_ports = Vec::new();
+        if let Some(Value::Object(ports)) = config.get("ExposedPorts") {
+            for key in ports.keys() {
+                let (port, protocol) =
+                    key.split_once('/').unwrap_or((key, "tcp"));
+                let port = port.parse().map_err(|_| err("invalid port"))?;
+                exposed_ports.push((port, protocol.parse()?));
+            }
+        }
+        exposed_ports.sort_by_key(|(port, _)| *port);
+
+        Ok(Self {
+            repo_digests: strings(image.get("RepoDigests")),
+            entrypoint: strings(config.get("Entrypoint")),
+            cmd: strings(config.get("Cmd")),
+            exposed_ports,
+            env: strings(config.get("Env"))
+                .into_iter()
+                .map(|var| match var.split_once('=') {
+                    Some((key, value)) => (key.to_string(), value.to_string()),
+                    None => (var, String::new()),
+                })
+                .collect(),
+            working_dir: non_empty("WorkingDir"),
+            user: non_empty("User"),
+        })
+    }
+
+    /// The command the image runs by default: the entrypoint followed
+    /// by the default command.
+    pub fn default_command(&self) -> Vec<String> {
+        self.entrypoint.iter().chain(&self.cmd).cloned().collect()
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{Error, ValidationError};
+use std::collections::BTreeMap;
+use std::env;
+use std::path::Path;
+
+/// Variables for substituting into compose files, as compose does
+/// with the environment and the project's `.env` file.
+///
+/// Values set directly or from the environment take precedence over
+/// those read from an env file, matching compose.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::Interpolator;
+/// let mut vars = Interpolator::new();
+/// vars.parse_env_file("TAG=1.2\n# comment\nexport REGISTRY='ghcr.io/me'\n")
+///     .unwrap();
+/// assert_eq!(
+///     vars.interpolate("${REGISTRY}/app:${TAG:-latest} costs $$5").unwrap(),
+///     "ghcr.io/me/app:1.2 costs $5"
+/// );
+/// assert!(vars.interpolate("${PASSWORD:?must be set}").is_err());
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Interpolator {
+    vars: BTreeMap<String, String>,
+}
+
+impl Interpolator {
+    /// Create an `Interpolator` with no variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an `Interpolator` with the variables of the current
+    /// process. Variables that are not valid UTF-8 are skipped.
+    pub fn from_env() -> Self {
+        Self {
+            vars: env::vars_os()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
+        }
+    }
+
+    /// Set a variable, replacing any existing value.
+    pub fn set<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        self.vars.insert(key.into(), value.into());
+    }
+
+    /// Get the value of a variable.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(String::as_str)
+    }
+
+    /// Add the variables in `contents`, in the `.env` file format:
+    /// one `KEY=value` per line, with optional `export ` prefix,
+    /// single or double quotes around the value, and `#` comments.
+    /// Variables that are already set are not changed.
+    pub fn parse_env_file(
+        &mut self,
+        contents: &str,
+    ) -> Result<(), ValidationError> {
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                ValidationError::new(
+                    "env file line",
+                    line,
+                    "expected KEY=value",
+                )
+            })?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return Err(ValidationError::new(
+                    "env file line",
+                    line,
+                    "invalid key",
+                ));
+            }
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(quote @ ('\'' | '"')) => value
+                    .strip_prefix(quote)
+                    .and_then(|v| v.strip_suffix(quote))
+                    .ok_or_else(|| {
+                        ValidationError::new(
+                            "env file line",
+                            line,
+                            "unterminated quote",
+                        )
+                    })?,
+                // Unquoted values end at a comment.
+                _ => value.split(" #").next().unwrap_or_default().trim_end(),
+            };
+            self.vars
+                .entry(key.to_string())
+                .or_insert_with(|| value.to_string());
+        }
+        Ok(())
+    }
+
+    /// Add the variables in the env file at `path`. See
+    /// [`Interpolator::parse_env_file`].
+    pub fn load_env_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<(), Error> {
+        let contents = std::fs::read_to_string(path)?;
+        self.parse_env_file(&contents)?;
+        Ok(())
+    }
+
+    /// Substitute variables in `input`:
+    ///
+    /// * `$VAR` and `${VAR}` are replaced by the value, or nothing if
+    ///   unset.
+    /// * `${VAR:-default}` uses `default` if the variable is unset or
+    ///   empty, and `${VAR-default}` only if it is unset.
+    /// * `${VAR:?message}` fails if the variable is unset or empty,
+    ///   and `${VAR?message}` only if it is unset.
+    /// * `${VAR:+other}` uses `other` if the variable is set and not
+    ///   empty, and `${VAR+other}` if it is set.
+    /// * `$$` is a literal `$`.
+    ///
+    /// Defaults and alternatives may contain further substitutions.
+    pub fn interpolate(&self, input: &str) -> Result<String, ValidationError> {
+        let err = |reason: String| {
+            ValidationError::new("interpolation", input, reason)
+        };
+        let mut out = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(pos) = rest.find('$') {
+            out.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                out.push('$');
+                rest = after;
+            } else if let Some(braced) = rest.strip_prefix('{') {
+                let end = matching_brace(braced)
+                    .ok_or_else(|| err("unterminated ${".into()))?;
+                out.push_str(&self.expand(&braced[..end]).map_err(err)?);
+                rest = &braced[end + 1..];
+            } else {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                if end == 0 {
+                    out.push('$');
+                } else {
+                    out.push_str(self.get(&rest[..end]).unwrap_or_default());
+                }
+                rest = &rest[end..];
+            }
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Expand the inside of `${...}`.
+    fn expand(&self, expr: &str) -> Result<String, String> {
+        let end = expr
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(expr.len());
+        let (name, op) = expr.split_at(end);
+        if name.is_empty() {
+            return Err(format!("invalid substitution ${{{}}}", expr));
+        }
+        let value = self.get(name);
+        let (colon, op) = match op.strip_prefix(':') {
+            Some(op) => (true, op),
+            None => (false, op),
+        };
+        // With a colon, an empty value counts as unset.
+        let set = value.filter(|v| !colon || !v.is_empty());
+        let mut chars = op.chars();
+        let kind = chars.next();
+        let rest = chars.as_str();
+        let arg = || self.interpolate(rest).map_err(|err| err.reason);
+        match kind {
+            None if !colon => Ok(value.unwrap_or_default().to_string()),
+            Some('-') => match set {
+                Some(value) => Ok(value.to_string()),
+                None => arg(),
+            },
+            Some('?') => match set {
+                Some(value) => Ok(value.to_string()),
+                None => {
+                    let message = arg()?;
+                    Err(if message.is_empty() {
+                        format!("{} is not set", name)
+                    } else {
+                        format!("{}: {}", name, message)
+                    })
+                }
+            },
+            Some('+') => match set {
+                Some(_) => arg(),
+                None => Ok(String::new()),
+            },
+            _ => Err(format!("invalid substitution ${{{}}}", expr)),
+        }
+    }
+}
+
+/// Find the `}` that closes a `${`, allowing nested `${...}`.
+fn matching_brace(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    let bytes = s.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'{' if i > 0 && bytes[i - 1] == b'$' => depth += 1,
+            b'}' if depth == 0 => return Some(i),
+            b'}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
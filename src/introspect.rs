@@ -0,0 +1,274 @@
+use crate::Launcher;
+use command_run::Command;
+use std::ffi::{OsStr, OsString};
+
+/// Commands whose second word is also part of the subcommand, e.g.
+/// `network create`.
+const GROUPS: &[&str] = &[
+    "buildx",
+    "container",
+    "generate",
+    "image",
+    "network",
+    "scout",
+    "system",
+    "trust",
+    "volume",
+];
+
+/// Long options that never take a value in any subcommand. Together
+/// with [`SUBCOMMAND_SWITCHES`] this includes every such option that
+/// the builders in this crate add. Any other long option is assumed to
+/// take a value, either as `--flag=value` or as the next argument.
+const SWITCHES: &[&str] = &[
+    "--all",
+    "--all-tags",
+    "--archive",
+    "--build",
+    "--config",
+    "--debug",
+    "--detach",
+    "--digests",
+    "--exit-code",
+    "--files",
+    "--follow",
+    "--follow-link",
+    "--force",
+    "--ignore-unfixed",
+    "--init",
+    "--interactive",
+    "--list-tags",
+    "--load",
+    "--local",
+    "--new",
+    "--no-ansi",
+    "--no-cache",
+    "--no-color",
+    "--no-healthcheck",
+    "--no-interpolate",
+    "--no-prune",
+    "--no-stream",
+    "--no-trunc",
+    "--only-fixed",
+    "--oom-kill-disable",
+    "--pretty",
+    "--privileged",
+    "--publish-all",
+    "--push",
+    "--quiet",
+    "--raw",
+    "--read-only",
+    "--remove-orphans",
+    "--resolve-image-digests",
+    "--rm",
+    "--services",
+    "--size",
+    "--timestamps",
+    "--tls",
+    "--tlsverify",
+    "--tty",
+    "--verbose",
+    "--volumes",
+    "--wait",
+    "--yes",
+];
+
+/// Long options that never take a value in some subcommands but take
+/// one in others, by the last word of the subcommand. For example,
+/// `--pull` is a switch in `build` but takes a policy in `run`.
+const SUBCOMMAND_SWITCHES: &[(&str, &[&str])] =
+    &[("build", &["--pull"]), ("systemd", &["--name"])];
+
+/// Single-letter options that never take a value, by the last word of
+/// the subcommand. The same letter can mean different things in
+/// different subcommands, e.g. `-f` is `--force` in `rm` but `--file`
+/// in `build`. Any other short option is assumed to take a value,
+/// either attached as in `-m512m` or as the next argument.
+const SHORT_SWITCHES: &[(&str, &str)] = &[
+    ("attach", "i"),
+    ("build", "q"),
+    ("create", "dtiP"),
+    ("exec", "dit"),
+    ("images", "aq"),
+    ("logs", "ft"),
+    ("ls", "aq"),
+    ("prune", "af"),
+    ("ps", "alqs"),
+    ("pull", "aq"),
+    ("push", "aq"),
+    ("rm", "flv"),
+    ("rmi", "f"),
+    ("run", "dtiP"),
+    ("start", "ai"),
+];
+
+/// Structured view of a command created by a [`Launcher`], for
+/// checking what a command does without matching on strings.
+///
+/// This is created by parsing the arguments with
+/// [`Launcher::introspect`]. Options are recognized by their leading
+/// `-`; a long option takes a value if it is written as
+/// `--flag=value`, or if it is not one of the known switches such as
+/// `--rm`, in which case the next argument is its value. Short options
+/// can be grouped, as in `-it`, and their value can be attached, as in
+/// `-m512m`. Each is recorded separately, e.g. as `-i` and `-t`. The
+/// first argument that is not an option ends the options, so the
+/// arguments of the command run in a container are always positional.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{BaseCommand, Launcher, RunOpt};
+/// let launcher = Launcher::from(BaseCommand::Docker);
+/// let mut opt = RunOpt::new("alpine");
+/// opt.remove = true;
+/// opt.name = Some("web".into());
+/// opt.args = vec!["--privileged".into()];
+/// let cmd = launcher.introspect(&launcher.run(opt));
+/// assert_eq!(cmd.subcommand, ["run"]);
+/// assert_eq!(cmd.flag_value("--name").unwrap(), "web");
+/// assert!(cmd.has_flag("--rm"));
+/// assert!(!cmd.has_flag("--privileged"));
+/// assert_eq!(cmd.positionals, ["alpine", "--privileged"]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GeneratedCommand {
+    /// Words of the subcommand, e.g. `["run"]` or
+    /// `["network", "create"]`.
+    pub subcommand: Vec<String>,
+
+    /// Options in the order they appear, with their value if they take
+    /// one.
+    pub flags: Vec<(String, Option<OsString>)>,
+
+    /// Arguments after the options.
+    pub positionals: Vec<OsString>,
+}
+
+impl GeneratedCommand {
+    /// Parse the arguments of a command, not including the base
+    /// command or global options.
+    pub fn parse<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut out = Self::default();
+        let mut args = args.into_iter().map(|arg| arg.as_ref().to_owned());
+
+        if let Some(first) = args.next() {
+            let first = first.to_string_lossy().into_owned();
+            let is_group = GROUPS.contains(&first.as_str());
+            out.subcommand.push(first);
+            if is_group {
+                if let Some(second) = args.next() {
+                    out.subcommand.push(second.to_string_lossy().into_owned());
+                }
+            }
+        }
+
+        while let Some(arg) = args.next() {
+            let text = arg.to_string_lossy();
+            if text == "--" {
+                out.positionals.extend(args);
+                break;
+            }
+            if !text.starts_with('-') || text == "-" {
+                out.positionals.push(arg);
+                out.positionals.extend(args);
+                break;
+            }
+            if !text.starts_with("--") {
+                out.parse_short(&text[1..], &mut args);
+                continue;
+            }
+            let flag: &str = &text;
+            if let Some((name, value)) = flag.split_once('=') {
+                out.flags.push((name.to_string(), Some(value.into())));
+            } else if SWITCHES.contains(&flag)
+                || out.by_subcommand(SUBCOMMAND_SWITCHES).contains(&flag)
+            {
+                out.flags.push((flag.to_string(), None));
+            } else {
+                out.flags.push((flag.to_string(), args.next()));
+            }
+        }
+        out
+    }
+
+    /// Get the entry in `table` for the last word of the subcommand, or
+    /// the default if there is none.
+    fn by_subcommand<T: Copy + Default>(&self, table: &[(&str, T)]) -> T {
+        self.subcommand
+            .last()
+            .and_then(|last| table.iter().find(|(name, _)| name == last))
+            .map(|(_, value)| *value)
+            .unwrap_or_default()
+    }
+
+    /// Add the options in a group of short options, such as `-it` or
+    /// `-m512m`, without the leading `-`.
+    fn parse_short(
+        &mut self,
+        group: &str,
+        args: &mut impl Iterator<Item = OsString>,
+    ) {
+        let switches = self.by_subcommand(SHORT_SWITCHES);
+        for (i, letter) in group.char_indices() {
+            let flag = format!("-{}", letter);
+            if switches.contains(letter) {
+                self.flags.push((flag, None));
+                continue;
+            }
+            let rest = &group[i + letter.len_utf8()..];
+            let value = if rest.is_empty() {
+                args.next()
+            } else {
+                Some(rest.into())
+            };
+            self.flags.push((flag, value));
+            return;
+        }
+    }
+
+    /// Whether the subcommand is `words`.
+    pub fn is(&self, words: &[&str]) -> bool {
+        self.subcommand == words
+    }
+
+    /// Whether the option `flag` (including the leading `-` or `--`)
+    /// is present.
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|(name, _)| name == flag)
+    }
+
+    /// Get the values of every occurrence of the option `flag`.
+    pub fn flag_values<'a>(
+        &'a self,
+        flag: &'a str,
+    ) -> impl Iterator<Item = &'a OsStr> + 'a {
+        self.flags
+            .iter()
+            .filter(move |(name, _)| name == flag)
+            .filter_map(|(_, value)| value.as_deref())
+    }
+
+    /// Get the value of the last occurrence of the option `flag`.
+    pub fn flag_value(&self, flag: &str) -> Option<&OsStr> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(name, _)| name == flag)
+            .and_then(|(_, value)| value.as_deref())
+    }
+}
+
+impl Launcher {
+    /// Get a [`GeneratedCommand`] view of `cmd`, which must have been
+    /// created by this launcher. The base command and global options
+    /// are skipped.
+    pub fn introspect(&self, cmd: &Command) -> GeneratedCommand {
+        let skip = self.base().args.len();
+        GeneratedCommand::parse(cmd.args.iter().skip(skip))
+    }
+}
@@ -0,0 +1,306 @@
+//! Export [`RunOpt`]s as a Kubernetes manifest. Requires the
+//! `kubernetes` feature.
+//!
+//! This produces a minimal starting point for migrating to
+//! Kubernetes rather than a complete translation. Options that have no
+//! equivalent (such as [`RunOpt::network`], [`RunOpt::remove`], and
+//! `extra_args`) are ignored.
+
+use crate::{Access, MountType, NameOrId, Protocol, RunOpt, VolumeSource};
+use serde_yaml::{Mapping, Value};
+use std::ffi::OsStr;
+
+fn lossy<S: AsRef<OsStr>>(s: S) -> Value {
+    Value::String(s.as_ref().to_string_lossy().into_owned())
+}
+
+fn insert<K: Into<String>, V: Into<Value>>(
+    map: &mut Mapping,
+    key: K,
+    value: V,
+) {
+    map.insert(Value::String(key.into()), value.into());
+}
+
+/// Build a mapping from key/value pairs.
+fn mapping<const N: usize>(pairs: [(&str, Value); N]) -> Mapping {
+    let mut map = Mapping::new();
+    for (key, value) in pairs {
+        insert(&mut map, key, value);
+    }
+    map
+}
+
+/// Kind of workload to generate.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Workload {
+    /// A bare `Pod`.
+    #[default]
+    Pod,
+
+    /// A `Deployment` with the given number of replicas.
+    Deployment {
+        /// Number of pods to run.
+        replicas: u32,
+    },
+}
+
+/// Group of containers to write as a Kubernetes pod or deployment.
+///
+/// Volumes are converted as follows: host paths become `hostPath`
+/// volumes, named volumes become `persistentVolumeClaim`s with the
+/// same name, and anonymous volumes and tmpfs mounts become
+/// `emptyDir`s.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{KubernetesPod, RunOpt, Workload};
+/// let mut pod = KubernetesPod::new("web");
+/// pod.workload = Workload::Deployment { replicas: 2 };
+/// let mut opt = RunOpt::new("nginx:1");
+/// opt.publish = vec!["8080:80".parse().unwrap()];
+/// pod.add_container("nginx", opt);
+/// let yaml = pod.to_yaml().unwrap();
+/// assert!(yaml.starts_with("apiVersion: apps/v1\nkind: Deployment\n"));
+/// assert!(yaml.contains("    - containerPort: 80\n"));
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct KubernetesPod {
+    /// Name of the pod or deployment. This is also used for the `app`
+    /// label.
+    pub name: String,
+
+    /// Containers in the pod, keyed by container name.
+    pub containers: Vec<(String, RunOpt)>,
+
+    /// Kind of workload to generate.
+    pub workload: Workload,
+}
+
+impl KubernetesPod {
+    /// Create an empty `KubernetesPod` with the given `name`.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Add a container to the pod.
+    pub fn add_container<S: Into<String>>(&mut self, name: S, opt: RunOpt) {
+        self.containers.push((name.into(), opt));
+    }
+
+    /// Convert to a YAML value.
+    pub fn to_value(&self) -> Value {
+        let labels = mapping([("app", self.name.as_str().into())]);
+        let spec = self.pod_spec();
+
+        let root = match self.workload {
+            Workload::Pod => mapping([
+                ("apiVersion", "v1".into()),
+                ("kind", "Pod".into()),
+                (
+                    "metadata",
+                    mapping([
+                        ("name", self.name.as_str().into()),
+                        ("labels", labels.into()),
+                    ])
+                    .into(),
+                ),
+                ("spec", spec.into()),
+            ]),
+            Workload::Deployment { replicas } => mapping([
+                ("apiVersion", "apps/v1".into()),
+                ("kind", "Deployment".into()),
+                (
+                    "metadata",
+                    mapping([("name", self.name.as_str().into())]).into(),
+                ),
+                (
+                    "spec",
+                    mapping([
+                        ("replicas", replicas.into()),
+                        (
+                            "selector",
+                            mapping([("matchLabels", labels.clone().into())])
+                                .into(),
+                        ),
+                        (
+                            "template",
+                            mapping([
+                                (
+                                    "metadata",
+                                    mapping([("labels", labels.into())]).into(),
+                                ),
+                                ("spec", spec.into()),
+                            ])
+                            .into(),
+                        ),
+                    ])
+                    .into(),
+                ),
+            ]),
+        };
+        Value::Mapping(root)
+    }
+
+    /// Format as YAML.
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(&self.to_value())
+    }
+
+    fn pod_spec(&self) -> Mapping {
+        let mut volumes = Vec::new();
+        let containers: Vec<Value> = self
+            .containers
+            .iter()
+            .map(|(name, opt)| {
+                container(name, opt, &self.workload, &mut volumes).into()
+            })
+            .collect();
+
+        let mut spec = mapping([("containers", containers.into())]);
+        if !volumes.is_empty() {
+            insert(&mut spec, "volumes", volumes);
+        }
+        spec
+    }
+}
+
+/// Add a pod volume and return its name.
+fn add_volume(volumes: &mut Vec<Value>, source: (&str, Value)) -> String {
+    let name = format!("volume-{}", volumes.len());
+    volumes.push(
+        mapping([("name", name.as_str().into()), (source.0, source.1)]).into(),
+    );
+    name
+}
+
+/// Convert one `RunOpt` to a container definition, adding any volumes
+/// it uses to `volumes`.
+fn container(
+    name: &str,
+    opt: &RunOpt,
+    workload: &Workload,
+    volumes: &mut Vec<Value>,
+) -> Mapping {
+    let mut map = mapping([
+        ("name", name.into()),
+        ("image", opt.image.to_string().into()),
+    ]);
+
+    // The docker command replaces the image's CMD, not its
+    // ENTRYPOINT, which is what `args` does in Kubernetes.
+    let mut args: Vec<Value> = Vec::new();
+    if let Some(command) = &opt.command {
+        args.push(lossy(command));
+    }
+    args.extend(opt.args.iter().map(lossy));
+    if !args.is_empty() {
+        insert(&mut map, "args", args);
+    }
+
+    if !opt.env.is_empty() {
+        let env: Vec<Value> = opt
+            .env
+            .iter()
+            .map(|(k, v)| {
+                mapping([("name", lossy(k)), ("value", lossy(v))]).into()
+            })
+            .collect();
+        insert(&mut map, "env", env);
+    }
+
+    let mut ports = Vec::new();
+    for publish in &opt.publish {
+        for (i, port) in publish.container.0.clone().enumerate() {
+            let mut entry = mapping([("containerPort", port.into())]);
+            // Host ports only make sense for a single pod.
+            if let (Workload::Pod, Some(host)) = (workload, &publish.host) {
+                insert(&mut entry, "hostPort", host.0.start() + i as u16);
+            }
+            if publish.protocol != Protocol::Tcp {
+                let protocol = publish.protocol.as_str().to_uppercase();
+                insert(&mut entry, "protocol", protocol);
+            }
+            ports.push(Value::from(entry));
+        }
+    }
+    if !ports.is_empty() {
+        insert(&mut map, "ports", ports);
+    }
+
+    let mut mounts = Vec::new();
+    for vol in &opt.volumes {
+        let source = match &vol.src {
+            VolumeSource::HostPath(path) => {
+                ("hostPath", mapping([("path", lossy(path))]).into())
+            }
+            VolumeSource::Named(claim) => (
+                "persistentVolumeClaim",
+                mapping([("claimName", claim.as_str().into())]).into(),
+            ),
+            VolumeSource::Anonymous => ("emptyDir", Mapping::new().into()),
+        };
+        let volume = add_volume(volumes, source);
+        let mut mount =
+            mapping([("name", volume.into()), ("mountPath", lossy(&vol.dst))]);
+        if vol.access == Access::ReadOnly {
+            insert(&mut mount, "readOnly", true);
+        }
+        mounts.push(Value::from(mount));
+    }
+    for m in &opt.mounts {
+        let source = match (m.kind, &m.source) {
+            (MountType::Bind, Some(path)) => {
+                ("hostPath", mapping([("path", lossy(path))]).into())
+            }
+            (MountType::Volume, Some(claim)) => (
+                "persistentVolumeClaim",
+                mapping([("claimName", lossy(claim))]).into(),
+            ),
+            (MountType::Tmpfs, _) => {
+                ("emptyDir", mapping([("medium", "Memory".into())]).into())
+            }
+            _ => ("emptyDir", Mapping::new().into()),
+        };
+        let volume = add_volume(volumes, source);
+        let mut mount =
+            mapping([("name", volume.into()), ("mountPath", lossy(&m.target))]);
+        if m.read_only {
+            insert(&mut mount, "readOnly", true);
+        }
+        mounts.push(Value::from(mount));
+    }
+    if !mounts.is_empty() {
+        insert(&mut map, "volumeMounts", mounts);
+    }
+
+    let mut security = Mapping::new();
+    if opt.read_only {
+        insert(&mut security, "readOnlyRootFilesystem", true);
+    }
+    if let Some(user) = &opt.user {
+        if let NameOrId::Id(uid) = user.user {
+            insert(&mut security, "runAsUser", uid);
+        }
+        if let Some(NameOrId::Id(gid)) = user.group {
+            insert(&mut security, "runAsGroup", gid);
+        }
+    }
+    if !security.is_empty() {
+        insert(&mut map, "securityContext", security);
+    }
+
+    if opt.interactive {
+        insert(&mut map, "stdin", true);
+    }
+    if opt.tty {
+        insert(&mut map, "tty", true);
+    }
+
+    map
+}
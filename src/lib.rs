@@ -1,4 +1,7 @@
 #![deny(missing_docs)]
+// Errors from running commands contain the whole `Command`; that size
+// is not a concern for this library.
+#![allow(clippy::result_large_err)]
 
 //! Create [`Command`]s for running Docker or Docker-compatible clients.
 //!
@@ -6,15 +9,178 @@
 //! produces commands that can be run in a subprocess to invoke the
 //! Docker client (or a compatible client such as Podman).
 //!
+//! # Extra arguments
+//!
+//! Most option structs, such as [`RunOpt`], have an `extra_args` field
+//! for options that the struct does not support yet. These arguments
+//! are added after the options that the struct sets and before any
+//! positional arguments, such as the image of a [`RunOpt`], so they
+//! apply to the same subcommand. The exception is [`ComposeOpt`], whose
+//! options apply to every compose subcommand: its extra arguments come
+//! before the subcommand.
+//!
+//! ```
+//! use docker_command::{BaseCommand, Launcher, RunOpt};
+//! let mut opt = RunOpt::new("alpine");
+//! opt.extra_args = vec!["--shm-size=1g".into()];
+//! let cmd = Launcher::from(BaseCommand::Docker).run(opt);
+//! assert_eq!(cmd.args, ["run", "--shm-size=1g", "alpine"]);
+//! ```
+//!
 //! [`Command`]: https://docs.rs/command-run/latest/command_run/struct.Command.html
 
 pub use command_run;
 
-use command_run::Command;
+pub mod args;
+mod audit;
+mod batch;
+#[cfg(feature = "bollard")]
+mod bollard;
+mod cancel;
+mod capability;
+mod child;
+#[cfg(feature = "compose")]
+pub mod compose;
+mod compose_cli;
+mod compose_logs;
+mod config;
+mod container;
+pub mod cosign;
+mod defaults;
+pub mod discovery;
+mod disk_usage;
+mod error;
+mod filter;
+#[cfg(feature = "git")]
+mod git;
+mod health;
+mod host;
+mod image;
+mod image_config;
+mod interpolate;
+mod introspect;
+#[cfg(feature = "kubernetes")]
+mod kubernetes;
+#[cfg(feature = "mock")]
+mod mock;
+pub mod oci;
+mod paths;
+mod pin;
+mod policy;
+mod ports;
+mod probe;
+mod profile;
+mod progress;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+mod reconcile;
+mod redact;
+mod replace;
+mod retag;
+mod retention;
+mod retry;
+mod runner;
+mod runtime_error;
+mod secret;
+mod security;
+pub mod shell;
+pub mod skopeo;
+mod stats;
+mod system_info;
+mod systemd;
+mod tags;
+mod temp;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "tracing")]
+mod trace;
+pub mod trivy;
+mod untrusted;
+
+pub use audit::Drift;
+pub use batch::Batch;
+pub use cancel::{CancelToken, SpawnedCommand, WaitFuture};
+pub use capability::{Backend, Capability, Version};
+#[cfg(feature = "compose")]
+pub use compose::ComposeProject;
+pub use compose_cli::{
+    Ansi, Compose, ComposeConfigOpt, ComposeDownOpt, ComposeFlavor,
+    ComposeLogsOpt, ComposeOpt, ComposeUpOpt,
+};
+pub use compose_logs::{
+    ServiceLogLine, ServiceLogObserver, ServiceLogParser, ServiceLogRouter,
+};
+pub use config::{Engine, LauncherConfig, SudoPolicy, ENV_PREFIX};
+pub use container::{ContainerId, ContainerName, ContainerRef};
+pub use defaults::Defaults;
+pub use discovery::AutoOpt;
+pub use disk_usage::{DiskUsage, DiskUsageKind};
+pub use error::{Error, ValidationError};
+pub use filter::Filter;
+#[cfg(feature = "git")]
+pub use git::GitMetadata;
+pub use health::HealthOutcome;
+pub use host::DaemonHost;
+pub use image::{ImageRef, DEFAULT_REGISTRY, DEFAULT_TAG};
+pub use image_config::ImageConfig;
+pub use interpolate::Interpolator;
+pub use introspect::GeneratedCommand;
+#[cfg(feature = "kubernetes")]
+pub use kubernetes::{KubernetesPod, Workload};
+#[cfg(feature = "mock")]
+pub use mock::{MockLauncher, MockResponse};
+pub use paths::{RelativePaths, WindowsPathStyle};
+pub use policy::{Policy, PolicyViolation, StandardPolicy};
+pub use ports::PortConflict;
+pub use probe::{Probe, ProbeKind};
+pub use profile::Profiles;
+pub use progress::{
+    ProgressEvent, ProgressObserver, ProgressParser, ProgressStage,
+};
+pub use reconcile::{
+    Action, Deployment, Plan, Step, CONFIG_LABEL, DEPLOYMENT_LABEL,
+};
+pub use redact::{CommandHook, Redactor, REDACTED};
+pub use retention::{ImageSummary, RetentionPolicy};
+pub use retry::{Failure, RetryPolicy};
+pub use runner::StopOutcome;
+pub use runtime_error::RuntimeError;
+pub use secret::{SecretSource, SecretTarget, SECRETS_DIR};
+pub use security::{SeccompProfile, SecurityOpt};
+pub use stats::ContainerStats;
+pub use system_info::SystemInfo;
+pub use systemd::SystemdUnitOpt;
+pub use temp::{TempNetwork, TempVolume};
+pub use untrusted::{check_arg, Untrusted};
+
+use child::RunningChild;
+#[cfg(feature = "logging")]
+use command_run::LogTo;
+use command_run::{Command, Output};
 use std::ffi::{OsStr, OsString};
+use std::fmt;
 use std::ops::RangeInclusive;
-use std::path::PathBuf;
-use std::{env, fmt};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Put the `base` pairs whose keys are not in `values` before `values`.
+fn merge_pairs<K: PartialEq + Clone, V: Clone>(
+    base: &[(K, V)],
+    values: &mut Vec<(K, V)>,
+) {
+    if base.is_empty() {
+        return;
+    }
+    let mut merged: Vec<(K, V)> = base
+        .iter()
+        .filter(|(key, _)| values.iter().all(|(k, _)| k != key))
+        .cloned()
+        .collect();
+    merged.append(values);
+    *values = merged;
+}
 
 /// Preset base commands that a [`Launcher`] can be constructed from.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -29,31 +195,183 @@ pub enum BaseCommand {
     Podman,
 }
 
-// TODO: is there a good existing crate for this? I found a few on
-// crates.io that didn't look quite right.
-fn is_exe_in_path(exe_name: &OsStr) -> bool {
-    let paths = if let Some(paths) = env::var_os("PATH") {
-        paths
-    } else {
-        return false;
-    };
+/// Extension methods for [`Command`].
+pub trait CommandExt {
+    /// Format as a command line that can be pasted into a POSIX shell.
+    ///
+    /// Unlike [`Command::command_line_lossy`], every word is quoted
+    /// correctly (see [`shell::quote`]), and environment variables and
+    /// the working directory are included. See [`shell::command_line`]
+    /// for details.
+    fn command_line_shell(&self) -> String;
+
+    /// Run the command, logging it with [`command_line_shell`] instead
+    /// of [`Command::command_line_lossy`].
+    ///
+    /// The `log_command` and `log_to` fields are respected. All other
+    /// behavior is the same as [`Command::run`].
+    ///
+    /// [`command_line_shell`]: CommandExt::command_line_shell
+    fn run_logged(&self) -> Result<Output, command_run::Error>;
+
+    /// Run the command like [`run_logged`], but kill it if it has not
+    /// exited after `timeout`.
+    ///
+    /// If the timeout is reached, the process is stopped like a
+    /// cancelled [`SpawnedCommand`]: it is sent `SIGTERM`, and killed
+    /// if it is still running after
+    /// [`SpawnedCommand::DEFAULT_GRACE_PERIOD`]. Then
+    /// [`Error::TimedOut`] is returned. Any output captured before
+    /// then is discarded.
+    ///
+    /// Note that only the process itself is stopped. If the base
+    /// command uses `sudo`, `SIGTERM` is passed on to the engine but
+    /// `SIGKILL` only reaches `sudo`, so a client that ignores
+    /// `SIGTERM` keeps running. Likewise, a container started by
+    /// `docker run` without `--rm` keeps running after the client
+    /// exits.
+    ///
+    /// [`run_logged`]: CommandExt::run_logged
+    fn run_with_timeout(&self, timeout: Duration) -> Result<Output, Error>;
+
+    /// Start the command without waiting for it to finish, returning a
+    /// handle that can be used to wait for or cancel it.
+    ///
+    /// The command is logged like [`run_logged`]. See
+    /// [`SpawnedCommand`] for details.
+    ///
+    /// [`run_logged`]: CommandExt::run_logged
+    fn spawn_cancellable(&self) -> Result<SpawnedCommand, Error>;
 
-    env::split_paths(&paths).any(|path| path.join(exe_name).exists())
+    /// Run the command like [`run_logged`], passing its output through
+    /// a [`ProgressParser`] to `observer` as it is produced.
+    ///
+    /// This is intended for `pull`, and for `build` with
+    /// `--progress=plain`. The output is always captured, with stderr
+    /// combined into stdout.
+    ///
+    /// [`run_logged`]: CommandExt::run_logged
+    fn run_with_progress(
+        &self,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Output, Error>;
+
+    /// Run the command with its stdout connected to the stdin of
+    /// `sink`, like `self | sink` in a shell, and wait for both to
+    /// finish.
+    ///
+    /// Both commands are logged like [`run_logged`]. The `capture`
+    /// fields are ignored; stderr of both commands is inherited. If
+    /// `check` is set on either command and it fails, an error is
+    /// returned for it.
+    ///
+    /// [`run_logged`]: CommandExt::run_logged
+    fn pipe_to(&self, sink: &Command) -> Result<(), Error>;
 }
 
-// TODO: consider using nix or some other crate.
-fn is_user_in_group(target_group: &str) -> bool {
-    let mut cmd = Command::new("groups");
-    cmd.log_command = false;
-    cmd.capture = true;
-    cmd.log_output_on_error = true;
-    let output = if let Ok(output) = cmd.run() {
-        output
-    } else {
-        return false;
-    };
-    let stdout = output.stdout_string_lossy();
-    stdout.split_whitespace().any(|group| group == target_group)
+/// Log `cmd` with [`CommandExt::command_line_shell`] if `log_command`
+/// is set.
+fn log_command(cmd: &Command) {
+    if cmd.log_command {
+        let line = cmd.command_line_shell();
+
+        #[cfg(feature = "logging")]
+        if cmd.log_to == LogTo::Log {
+            log::info!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+
+        #[cfg(not(feature = "logging"))]
+        println!("{}", line);
+    }
+}
+
+/// Call `run`, which runs `cmd`, in a span for `cmd` if the `tracing`
+/// feature is enabled.
+#[cfg(feature = "tracing")]
+fn traced<R: trace::Outcome>(cmd: &Command, run: impl FnOnce() -> R) -> R {
+    trace::in_span(cmd, run)
+}
+
+/// Call `run`, which runs `cmd`, in a span for `cmd` if the `tracing`
+/// feature is enabled.
+#[cfg(not(feature = "tracing"))]
+fn traced<R>(_cmd: &Command, run: impl FnOnce() -> R) -> R {
+    run()
+}
+
+impl CommandExt for Command {
+    fn command_line_shell(&self) -> String {
+        shell::command_line(self)
+    }
+
+    fn run_logged(&self) -> Result<Output, command_run::Error> {
+        log_command(self);
+
+        #[cfg(feature = "mock")]
+        if let Some(result) = mock::intercept_checked(self) {
+            return result;
+        }
+
+        let mut cmd = self.clone();
+        cmd.log_command = false;
+        traced(&cmd, || cmd.run())
+    }
+
+    fn run_with_timeout(&self, timeout: Duration) -> Result<Output, Error> {
+        log_command(self);
+
+        #[cfg(feature = "mock")]
+        if let Some(result) = mock::intercept_checked(self) {
+            return Ok(result?);
+        }
+
+        traced(self, || {
+            let mut child = RunningChild::spawn(self)?;
+            let deadline = Instant::now().checked_add(timeout);
+            match child.wait_until(deadline)? {
+                Some(status) => Ok(child.finish(status)?),
+                None => {
+                    child.terminate(SpawnedCommand::DEFAULT_GRACE_PERIOD)?;
+                    Err(Error::TimedOut {
+                        command: self.command_line_shell(),
+                        timeout,
+                    })
+                }
+            }
+        })
+    }
+
+    fn spawn_cancellable(&self) -> Result<SpawnedCommand, Error> {
+        log_command(self);
+
+        #[cfg(feature = "mock")]
+        assert!(!mock::is_mocked(self), "MockLauncher cannot spawn commands");
+
+        traced(self, || cancel::spawn(self))
+    }
+
+    fn run_with_progress(
+        &self,
+        observer: &mut dyn ProgressObserver,
+    ) -> Result<Output, Error> {
+        log_command(self);
+
+        #[cfg(feature = "mock")]
+        assert!(
+            !mock::is_mocked(self),
+            "MockLauncher cannot run commands with progress"
+        );
+
+        traced(self, || progress::run(self, observer))
+    }
+
+    fn pipe_to(&self, sink: &Command) -> Result<(), Error> {
+        log_command(self);
+        log_command(sink);
+        traced(self, || runner::pipe(self, sink))
+    }
 }
 
 /// Base container command used for building and running containers.
@@ -63,13 +381,27 @@ fn is_user_in_group(target_group: &str) -> bool {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Launcher {
     base_command: Command,
+    global_opts: GlobalOpts,
+    defaults: Defaults,
+    hook: Option<redact::Hook>,
+    policies: policy::Policies,
+    windows_paths: Option<WindowsPathStyle>,
+    relative_paths: RelativePaths,
 }
 
 impl Launcher {
     /// Create a new `Launcher` with the specified base [`Command`]. The
     /// base command is used to create all the other commands.
     pub fn new(base_command: Command) -> Self {
-        Self { base_command }
+        Self {
+            base_command,
+            global_opts: GlobalOpts::default(),
+            defaults: Defaults::default(),
+            hook: None,
+            policies: policy::Policies::default(),
+            windows_paths: None,
+            relative_paths: RelativePaths::Keep,
+        }
     }
 
     /// Automatically choose a base command.
@@ -78,21 +410,10 @@ impl Launcher {
     /// * Otherwise chooses `docker` if it is in the `$PATH`.
     ///   * If the current user is not in a `docker` group, `sudo` is added.
     ///
-    /// If neither command is in the `$PATH`, returns `None`.
+    /// If neither command is in the `$PATH`, returns `None`. Use
+    /// [`Launcher::auto_with`] to change these choices.
     pub fn auto() -> Option<Self> {
-        let docker = OsStr::new("docker");
-        let podman = OsStr::new("podman");
-        if is_exe_in_path(podman) {
-            Some(BaseCommand::Podman.into())
-        } else if is_exe_in_path(docker) {
-            Some(if is_user_in_group("docker") {
-                BaseCommand::Docker.into()
-            } else {
-                BaseCommand::SudoDocker.into()
-            })
-        } else {
-            None
-        }
+        Self::auto_with(&AutoOpt::default())
     }
 
     /// Whether the base command appears to be the given `program`. This
@@ -123,407 +444,3150 @@ impl Launcher {
         &self.base_command
     }
 
-    /// Create a [`Command`] for building a container.
-    pub fn build(&self, opt: BuildOpt) -> Command {
-        let mut cmd = self.base_command.clone();
-        cmd.add_arg("build");
-
-        // --build-arg
-        for (key, value) in opt.build_args {
-            cmd.add_arg_pair("--build-arg", format!("{}={}", key, value));
-        }
-
-        // --file
-        if let Some(dockerfile) = &opt.dockerfile {
-            cmd.add_arg_pair("--file", dockerfile);
-        }
+    /// Get the global options.
+    pub fn global_opts(&self) -> &GlobalOpts {
+        &self.global_opts
+    }
 
-        // --iidfile
-        if let Some(iidfile) = &opt.iidfile {
-            cmd.add_arg_pair("--iidfile", iidfile);
-        }
+    /// Set global options that are added before the subcommand of
+    /// every command created by this launcher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, GlobalOpts, Launcher, StopOpt};
+    /// let mut global_opts = GlobalOpts::default();
+    /// global_opts.context = Some("remote".into());
+    /// let launcher =
+    ///     Launcher::from(BaseCommand::Docker).with_global_opts(global_opts);
+    /// assert_eq!(
+    ///     launcher.stop(StopOpt::default()).command_line_lossy(),
+    ///     "docker --context remote stop"
+    /// );
+    /// ```
+    pub fn with_global_opts(mut self, opts: GlobalOpts) -> Self {
+        self.global_opts = opts;
+        self
+    }
 
-        // --no-cache
-        if opt.no_cache {
-            cmd.add_arg("--no-cache");
-        }
+    /// Call `hook` with every command created by this launcher, for
+    /// example to log commands or count them. Sensitive values in the
+    /// command are redacted with `redactor` before calling the hook.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, CommandExt, Launcher, Redactor, RunOpt};
+    /// use docker_command::command_run::Command;
+    ///
+    /// let launcher = Launcher::from(BaseCommand::Docker).with_hook(
+    ///     |cmd: &Command| eprintln!("{}", cmd.command_line_shell()),
+    ///     Redactor::default(),
+    /// );
+    /// let cmd = launcher.run(RunOpt::new("alpine"));
+    /// ```
+    pub fn with_hook<H>(mut self, hook: H, redactor: Redactor) -> Self
+    where
+        H: CommandHook + 'static,
+    {
+        self.hook = Some(redact::Hook {
+            hook: Arc::new(hook),
+            redactor,
+        });
+        self
+    }
 
-        // --pull
-        if opt.pull {
-            cmd.add_arg("--pull");
-        }
+    /// Pass `cmd` to the hook, if any.
+    fn observe(&self, cmd: &Command) {
+        self.observe_with(cmd, None, None);
+    }
 
-        // --quiet
-        if opt.quiet {
-            cmd.add_arg("--quiet");
-        }
+    /// Pass `cmd` to the hook, if any. With the `tracing` feature, a
+    /// span is also emitted with the `image` and `container` name.
+    fn observe_with(
+        &self,
+        cmd: &Command,
+        image: Option<&ImageRef>,
+        container: Option<&str>,
+    ) {
+        #[cfg(feature = "tracing")]
+        trace::generated(self, cmd, image, container);
+        #[cfg(not(feature = "tracing"))]
+        let _ = (image, container);
 
-        // --tag
-        if let Some(tag) = &opt.tag {
-            cmd.add_arg_pair("--tag", tag);
+        if let Some(hook) = &self.hook {
+            hook.call(cmd);
         }
+    }
 
-        cmd.add_arg(opt.context);
+    /// Pass `cmd` to the hook, if any, and return it.
+    fn finish(&self, cmd: Command) -> Command {
+        self.observe(&cmd);
         cmd
     }
 
-    /// Create a [`Command`] for creating a network.
-    pub fn create_network(&self, opt: CreateNetworkOpt) -> Command {
+    /// Get a copy of the base command with the global options added.
+    fn base(&self) -> Command {
         let mut cmd = self.base_command.clone();
-        cmd.add_arg_pair("network", "create");
-        cmd.add_arg(opt.name);
-
+        self.add_global_args(&mut cmd);
         cmd
     }
 
-    /// Create a [`Command`] for removing a network.
-    pub fn remove_network(&self, name: &str) -> Command {
-        let mut cmd = self.base_command.clone();
-        cmd.add_arg_pair("network", "rm");
-        cmd.add_arg(name);
-
-        cmd
+    /// Overwrite `cmd` with the base command and global options,
+    /// reusing its existing allocations where possible.
+    fn base_into(&self, cmd: &mut Command) {
+        let base = &self.base_command;
+        cmd.program.clone_from(&base.program);
+        cmd.args.clone_from(&base.args);
+        cmd.dir.clone_from(&base.dir);
+        cmd.log_to = base.log_to;
+        cmd.log_command = base.log_command;
+        cmd.log_output_on_error = base.log_output_on_error;
+        cmd.check = base.check;
+        cmd.capture = base.capture;
+        cmd.combine_output = base.combine_output;
+        cmd.clear_env = base.clear_env;
+        cmd.env.clone_from(&base.env);
+        self.add_global_args(cmd);
     }
 
-    /// Create a [`Command`] for running a container.
-    pub fn run(&self, opt: RunOpt) -> Command {
-        let mut cmd = self.base_command.clone();
-        cmd.add_arg("run");
+    /// Add the global options to `cmd`.
+    fn add_global_args(&self, cmd: &mut Command) {
+        let opt = &self.global_opts;
 
-        // --detach
-        if opt.detach {
-            cmd.add_arg("--detach");
+        // --config
+        if let Some(config) = &opt.config {
+            cmd.add_arg_pair("--config", config);
         }
 
-        // --env
-        for (key, value) in &opt.env {
-            let mut arg = OsString::new();
-            arg.push(key);
-            arg.push("=");
-            arg.push(value);
-            cmd.add_arg_pair("--env", arg);
+        // --context
+        if let Some(context) = &opt.context {
+            cmd.add_arg_pair("--context", context);
         }
 
-        // --init
-        if opt.init {
-            cmd.add_arg("--init");
+        // --debug
+        if opt.debug {
+            cmd.add_arg("--debug");
         }
 
-        // --interactive
-        if opt.interactive {
-            cmd.add_arg("--interactive");
+        // --host
+        if let Some(host) = &opt.host {
+            cmd.add_arg_pair("--host", host);
         }
 
-        // --name
-        if let Some(name) = &opt.name {
-            cmd.add_arg_pair("--name", name);
+        // --log-level
+        if let Some(log_level) = opt.log_level {
+            cmd.add_arg_pair("--log-level", log_level.as_str());
         }
 
-        // --network
-        if let Some(network) = &opt.network {
-            cmd.add_arg_pair("--network", network);
+        // --tls
+        if opt.tls {
+            cmd.add_arg("--tls");
         }
 
-        // --publish
-        for publish in &opt.publish {
-            cmd.add_arg_pair("--publish", publish.arg());
+        // --tlscacert
+        if let Some(path) = &opt.tls_ca_cert {
+            cmd.add_arg_pair("--tlscacert", path);
         }
 
-        // --read-only
-        if opt.read_only {
-            cmd.add_arg("--read-only");
+        // --tlscert
+        if let Some(path) = &opt.tls_cert {
+            cmd.add_arg_pair("--tlscert", path);
         }
 
-        // --rm
-        if opt.remove {
-            cmd.add_arg("--rm");
+        // --tlskey
+        if let Some(path) = &opt.tls_key {
+            cmd.add_arg_pair("--tlskey", path);
         }
 
-        // --tty
-        if opt.tty {
-            cmd.add_arg("--tty");
+        // --tlsverify
+        if opt.tls_verify {
+            cmd.add_arg("--tlsverify");
         }
 
-        // --user
-        if let Some(user) = &opt.user {
-            cmd.add_arg_pair("--user", user.arg());
-        }
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+    }
 
-        // --volume
-        for vol in &opt.volumes {
-            cmd.add_arg_pair("--volume", vol.arg());
+    /// Set an environment variable on every command created by this
+    /// launcher, for example `DOCKER_BUILDKIT=1` or `DOCKER_HOST`.
+    ///
+    /// If the base command is run with `sudo`, which removes most
+    /// variables from the environment, the variable is also added to
+    /// `sudo --preserve-env`. The sudo policy must allow this (e.g.
+    /// with `SETENV` in sudoers), otherwise sudo refuses to run the
+    /// command rather than silently dropping the variable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, BuildOpt, Launcher};
+    /// use std::ffi::OsString;
+    /// let launcher = Launcher::from(BaseCommand::Docker)
+    ///     .with_env("DOCKER_BUILDKIT", "1");
+    /// let cmd = launcher.build(BuildOpt::default());
+    /// assert_eq!(
+    ///     cmd.env.get(&OsString::from("DOCKER_BUILDKIT")),
+    ///     Some(&OsString::from("1"))
+    /// );
+    ///
+    /// let launcher = Launcher::from(BaseCommand::SudoDocker)
+    ///     .with_env("DOCKER_BUILDKIT", "1")
+    ///     .with_env("DOCKER_HOST", "ssh://builder");
+    /// assert_eq!(
+    ///     launcher.build(BuildOpt::new("/ctx")).command_line_lossy(),
+    ///     "sudo --preserve-env=DOCKER_BUILDKIT,DOCKER_HOST docker build /ctx"
+    /// );
+    /// ```
+    pub fn with_env<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        let key = key.as_ref();
+        if self.base_command.program == Path::new("sudo") {
+            self.preserve_env(key);
         }
+        self.base_command
+            .env
+            .insert(key.into(), value.as_ref().into());
+        self
+    }
 
-        // Add image and command+args
-        cmd.add_arg(opt.image);
-        if let Some(command) = &opt.command {
-            cmd.add_arg(command);
+    /// Add `key` to the `--preserve-env` option of a `sudo` base
+    /// command, adding the option if needed.
+    fn preserve_env(&mut self, key: &OsStr) {
+        const OPTION: &str = "--preserve-env=";
+        let args = &mut self.base_command.args;
+        // Only look at sudo's own options, before the program it runs.
+        let sudo_opts = args
+            .iter()
+            .position(|arg| !arg.to_string_lossy().starts_with('-'))
+            .unwrap_or(args.len());
+        let existing = args[..sudo_opts]
+            .iter_mut()
+            .find(|arg| arg.to_string_lossy().starts_with(OPTION));
+        match existing {
+            Some(arg) => {
+                let listed = arg.to_string_lossy()[OPTION.len()..]
+                    .split(',')
+                    .any(|name| OsStr::new(name) == key);
+                if !listed {
+                    arg.push(",");
+                    arg.push(key);
+                }
+            }
+            None => {
+                let mut arg = OsString::from(OPTION);
+                arg.push(key);
+                args.insert(0, arg);
+            }
         }
-        cmd.add_args(&opt.args);
-        cmd
     }
 
-    /// Create a [`Command`] for stopping containers.
-    pub fn stop(&self, opt: StopOpt) -> Command {
-        let mut cmd = self.base_command.clone();
-        cmd.add_arg("stop");
+    /// Create a [`Command`] for building several images at once with
+    /// `buildx bake`. Docker only.
+    pub fn bake(&self, opt: BakeOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_bake_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-        if let Some(time) = opt.time {
-            cmd.add_arg_pair("--time", &time.to_string());
-        }
+    /// Create a [`Command`] for building a container.
+    pub fn build(&self, mut opt: BuildOpt) -> Command {
+        self.defaults.apply_to_build(&mut opt);
+        let mut cmd = self.base();
+        args::add_build_args(&mut cmd, &opt, self.is_podman());
+        self.finish(cmd)
+    }
 
-        cmd.add_args(&opt.containers);
+    /// Overwrite `cmd` with a command for building a container.
+    ///
+    /// This produces the same command as [`Launcher::build`], but
+    /// reuses the allocations in `cmd` and does not consume `opt`. See
+    /// [`Launcher::run_into`].
+    pub fn build_into(&self, opt: &BuildOpt, cmd: &mut Command) {
+        let opt = &*self.build_opt_with_defaults(opt);
+        self.base_into(cmd);
+        args::add_build_args(cmd, opt, self.is_podman());
+        self.observe(cmd);
+    }
 
-        cmd
+    /// Create a [`Command`] for copying files between a container and
+    /// the host.
+    pub fn cp(&self, opt: CpOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_cp_args(&mut cmd, &opt);
+        self.finish(cmd)
     }
-}
 
-impl From<BaseCommand> for Launcher {
-    fn from(bc: BaseCommand) -> Launcher {
-        let docker = "docker";
-        let podman = "podman";
-        Self {
-            base_command: match bc {
-                BaseCommand::Docker => Command::new(docker),
-                BaseCommand::SudoDocker => {
-                    Command::with_args("sudo", &[docker])
-                }
-                BaseCommand::Podman => Command::new(podman),
-            },
-        }
+    /// Create a [`Command`] for creating a network.
+    pub fn create_network(&self, opt: CreateNetworkOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_create_network_args(&mut cmd, &opt);
+        self.finish(cmd)
     }
-}
 
-/// Options for building a container.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct BuildOpt {
-    /// Build-time variables.
-    pub build_args: Vec<(String, String)>,
+    /// Create a [`Command`] for creating a volume.
+    pub fn create_volume(&self, opt: CreateVolumeOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_create_volume_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-    /// Root directory containing files that can be pulled into the
+    /// Create a [`Command`] for running a command in a running
     /// container.
-    pub context: PathBuf,
+    pub fn exec(&self, opt: ExecOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_exec_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-    /// Dockerfile to build. This must be somewhere in the `context`
-    /// directory. If not set (the default) then
-    /// `<context>/Dockerfile` is used.
-    pub dockerfile: Option<PathBuf>,
+    /// Create a [`Command`] for generating a systemd unit file for a
+    /// container with `podman generate systemd`. Podman only; see
+    /// [`Launcher::systemd_unit`] for an alternative that works with
+    /// docker.
+    pub fn generate_systemd(&self, opt: GenerateSystemdOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_generate_systemd_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-    /// If set, the image ID will be written to this path.
-    pub iidfile: Option<PathBuf>,
+    /// Create a [`Command`] for listing images.
+    pub fn images(&self, opt: ImagesOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_images_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-    /// Do not use cache when building the image.
-    pub no_cache: bool,
+    /// Create a [`Command`] for displaying system-wide information.
+    pub fn info(&self, opt: InfoOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_info_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-    /// Always attempt to pull a newer version of the image.
-    pub pull: bool,
+    /// Create a [`Command`] for displaying low-level information about
+    /// containers, images, networks, or volumes.
+    pub fn inspect(&self, opt: InspectOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_inspect_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-    /// Suppress the build output and print image ID on success.
-    pub quiet: bool,
+    /// Create a [`Command`] for killing running containers.
+    pub fn kill(&self, opt: KillOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_kill_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-    /// If set, the image will be tagged with this name.
-    pub tag: Option<String>,
-}
+    /// Create a [`Command`] for listing networks.
+    pub fn list_networks(&self, opt: ListNetworksOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_list_networks_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-/// Options for creating a network.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct CreateNetworkOpt {
-    /// Network name.
-    pub name: String,
-}
+    /// Create a [`Command`] for listing volumes.
+    pub fn list_volumes(&self, opt: ListVolumesOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_list_volumes_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-/// Port or range of ports.
-///
-/// # Examples
-///
-/// Specify a single port:
-///
-/// ```
-/// use docker_command::PortRange;
-/// let port = PortRange::from(123);
-/// assert_eq!(port, PortRange(123..=123));
-/// ```
-///
-/// Specify a port range:
-///
-/// ```
-/// use docker_command::PortRange;
-/// PortRange(123..=567);
-/// ```
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PortRange(pub RangeInclusive<u16>);
+    /// Create a [`Command`] for loading images from a tar archive.
+    pub fn load(&self, opt: LoadOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_load_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-impl Default for PortRange {
-    fn default() -> Self {
-        Self(0..=0)
+    /// Create a [`Command`] for removing stopped containers.
+    pub fn prune_containers(&self, opt: PruneOpt) -> Command {
+        self.prune("container", opt)
     }
-}
 
-impl fmt::Display for PortRange {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.0.start() == self.0.end() {
-            write!(f, "{}", self.0.start())
-        } else {
-            write!(f, "{}-{}", self.0.start(), self.0.end())
-        }
+    /// Create a [`Command`] for removing unused images.
+    pub fn prune_images(&self, opt: PruneOpt) -> Command {
+        self.prune("image", opt)
     }
-}
 
-impl From<u16> for PortRange {
-    fn from(port: u16) -> Self {
-        Self(port..=port)
+    /// Create a [`Command`] for removing unused networks.
+    pub fn prune_networks(&self, opt: PruneOpt) -> Command {
+        self.prune("network", opt)
     }
-}
 
-/// Options for publishing ports from a container to the host.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct PublishPorts {
-    /// Port or port range in the container to publish.
-    pub container: PortRange,
+    /// Create a [`Command`] for removing unused volumes.
+    pub fn prune_volumes(&self, opt: PruneOpt) -> Command {
+        self.prune("volume", opt)
+    }
 
-    /// Port or port range on the host.
-    pub host: Option<PortRange>,
+    fn prune(&self, object: &str, opt: PruneOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_prune_args(&mut cmd, object, &opt);
+        self.finish(cmd)
+    }
 
-    /// Host IP. If set to `0.0.0.0` or `None`, the port will be bound
-    /// to all IPs on the host.
-    pub ip: Option<String>,
-}
+    /// Create a [`Command`] for listing the host addresses that
+    /// `container`'s ports are published on. If `private_port` is set,
+    /// only the addresses for that container port are listed.
+    pub fn port(
+        &self,
+        container: &ContainerRef,
+        private_port: Option<(u16, Protocol)>,
+    ) -> Command {
+        let mut cmd = self.base();
+        args::add_port_args(&mut cmd, container, private_port);
+        self.finish(cmd)
+    }
 
-impl PublishPorts {
-    /// Format as an argument.
-    pub fn arg(&self) -> String {
-        match (&self.ip, &self.host) {
-            (Some(ip), Some(host_ports)) => {
-                format!("{}:{}:{}", ip, host_ports, self.container)
-            }
-            (Some(ip), None) => {
-                format!("{}::{}", ip, self.container)
-            }
-            (None, Some(host_ports)) => {
-                format!("{}:{}", host_ports, self.container)
-            }
-            (None, None) => format!("{}", self.container),
-        }
+    /// Create a [`Command`] for listing containers.
+    pub fn ps(&self, opt: PsOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_ps_args(&mut cmd, &opt);
+        self.finish(cmd)
     }
-}
 
-/// Name or numeric ID for a user or group.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum NameOrId {
-    /// Name or the user or group.
-    Name(String),
+    /// Create a [`Command`] for pulling an image from a registry.
+    pub fn pull(&self, opt: PullOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_pull_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-    /// Numeric ID of the user or group.
-    Id(u32),
-}
+    /// Create a [`Command`] for pushing an image to a registry.
+    pub fn push(&self, opt: PushOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_push_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
 
-impl fmt::Display for NameOrId {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Self::Name(name) => write!(f, "{}", name),
-            Self::Id(id) => write!(f, "{}", id),
-        }
+    /// Create a [`Command`] for removing containers.
+    pub fn remove_containers(&self, opt: RemoveContainersOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_remove_containers_args(&mut cmd, &opt);
+        self.finish(cmd)
     }
-}
 
-impl From<String> for NameOrId {
-    fn from(name: String) -> Self {
-        Self::Name(name)
+    /// Create a [`Command`] for removing images.
+    pub fn remove_images(&self, opt: RemoveImagesOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_remove_images_args(&mut cmd, &opt);
+        self.finish(cmd)
     }
-}
 
-impl From<u32> for NameOrId {
-    fn from(id: u32) -> Self {
-        Self::Id(id)
+    /// Create a [`Command`] for removing a network.
+    pub fn remove_network(&self, name: &str) -> Command {
+        let mut cmd = self.base();
+        args::add_remove_network_args(&mut cmd, name);
+        self.finish(cmd)
     }
-}
 
-/// User and (optionally) group.
-#[derive(Clone, Debug, Eq, PartialEq)]
+    /// Create a [`Command`] for removing a volume.
+    pub fn remove_volume(&self, name: &str) -> Command {
+        let mut cmd = self.base();
+        args::add_remove_volume_args(&mut cmd, name);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for renaming a container.
+    pub fn rename(
+        &self,
+        container: &ContainerRef,
+        new_name: &ContainerName,
+    ) -> Command {
+        let mut cmd = self.base();
+        args::add_rename_args(&mut cmd, container, new_name);
+        self.finish(cmd)
+    }
+
+    /// Run the container as `user` such that files it writes to bind
+    /// mounts are owned by the invoking user on the host.
+    ///
+    /// This sets `opt.user`. For podman, which is assumed to be
+    /// running rootless, it also sets `opt.userns` to
+    /// `keep-id:uid=<uid>,gid=<gid>` so that `user` inside the
+    /// container maps to the invoking user rather than to a subordinate
+    /// ID. Podman needs numeric IDs for this, so an error is returned
+    /// if `user` has a name instead.
+    ///
+    /// For older versions of podman that do not support options to
+    /// `keep-id`, see [`IdMap::keep_id`].
+    pub fn map_user(
+        &self,
+        opt: &mut RunOpt,
+        user: UserAndGroup,
+    ) -> Result<(), ValidationError> {
+        if self.is_podman() {
+            let uid = user.user.id()?;
+            let gid = user.group.as_ref().map(NameOrId::id).transpose()?;
+            opt.userns = Some(UserNamespace::KeepId {
+                uid: Some(uid),
+                gid,
+            });
+        }
+        opt.user = Some(user);
+        Ok(())
+    }
+
+    /// Create a [`Command`] for running a container.
+    pub fn run(&self, mut opt: RunOpt) -> Command {
+        self.defaults.apply_to_run(&mut opt);
+        let mut cmd = self.base();
+        args::add_run_args(
+            &mut cmd,
+            &opt,
+            self.is_podman(),
+            self.windows_paths.as_ref(),
+        );
+        self.observe_with(&cmd, Some(&opt.image), opt.name.as_deref());
+        cmd
+    }
+
+    /// Overwrite `cmd` with a command for running a container.
+    ///
+    /// This produces the same command as [`Launcher::run`], but reuses
+    /// the allocations in `cmd` and does not consume `opt`. This is
+    /// useful when generating a large number of commands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, Launcher, RunOpt};
+    /// use docker_command::command_run::Command;
+    /// let launcher = Launcher::from(BaseCommand::Docker);
+    /// let mut cmd = Command::new("");
+    /// for image in ["alpine", "debian"] {
+    ///     launcher.run_into(&RunOpt::new(image), &mut cmd);
+    ///     assert_eq!(cmd.args, ["run", image]);
+    /// }
+    /// ```
+    pub fn run_into(&self, opt: &RunOpt, cmd: &mut Command) {
+        let opt = &*self.run_opt_with_defaults(opt);
+        self.base_into(cmd);
+        args::add_run_args(
+            cmd,
+            opt,
+            self.is_podman(),
+            self.windows_paths.as_ref(),
+        );
+        self.observe_with(cmd, Some(&opt.image), opt.name.as_deref());
+    }
+
+    /// Create a [`Command`] for saving images to a tar archive.
+    pub fn save(&self, opt: SaveOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_save_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for searching registries for images.
+    pub fn search(&self, opt: SearchOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_search_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for scanning an image for vulnerabilities
+    /// with `docker scout cves`. Docker only.
+    pub fn scout_cves(&self, opt: ScoutCvesOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_scout_cves_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for an interactive shell.
+    ///
+    /// For a [`ShellTarget::Container`] this is `exec --interactive
+    /// --tty <container> <shell>`. For a [`ShellTarget::Image`] a
+    /// temporary container is started with `run --rm --interactive
+    /// --tty --entrypoint <shell> <image>`, so the shell is used even
+    /// if the image has its own entrypoint.
+    ///
+    /// If [`ShellOpt::shell`] is `None`, `sh` is run with a short
+    /// script that switches to `bash` if it is installed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, Launcher, ShellOpt};
+    /// let launcher = Launcher::from(BaseCommand::Docker);
+    /// let mut opt = ShellOpt::default();
+    /// opt.shell = Some("bash".into());
+    /// assert_eq!(
+    ///     launcher.shell("debian", opt).command_line_lossy(),
+    ///     "docker run --interactive --rm --tty --entrypoint bash debian"
+    /// );
+    /// ```
+    pub fn shell<T: Into<ShellTarget>>(
+        &self,
+        target: T,
+        opt: ShellOpt,
+    ) -> Command {
+        let (shell, args) = match opt.shell {
+            Some(shell) => (shell, Vec::new()),
+            None => ("sh".into(), vec!["-c".into(), SHELL_PROBE.into()]),
+        };
+        match target.into() {
+            ShellTarget::Container(container) => self.exec(ExecOpt {
+                args,
+                env: opt.env,
+                interactive: true,
+                tty: true,
+                user: opt.user,
+                workdir: opt.workdir,
+                extra_args: opt.extra_args,
+                ..ExecOpt::new(container, shell)
+            }),
+            ShellTarget::Image(image) => {
+                let mut extra_args = vec!["--entrypoint".into(), shell];
+                if let Some(workdir) = opt.workdir {
+                    extra_args.push("--workdir".into());
+                    extra_args.push(workdir.into());
+                }
+                extra_args.extend(opt.extra_args);
+                self.run(RunOpt {
+                    env: opt.env,
+                    interactive: true,
+                    remove: true,
+                    tty: true,
+                    user: opt.user,
+                    args,
+                    extra_args,
+                    ..RunOpt::new(image)
+                })
+            }
+        }
+    }
+
+    /// Create a [`Command`] for starting stopped containers.
+    pub fn start(&self, containers: &[ContainerRef]) -> Command {
+        let mut cmd = self.base();
+        args::add_start_args(&mut cmd, containers);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for showing resource usage of containers.
+    /// See [`Launcher::container_stats`] to parse the output.
+    pub fn stats(&self, opt: StatsOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_stats_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for stopping containers.
+    pub fn stop(&self, opt: StopOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_stop_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for showing disk usage.
+    pub fn system_df(&self, opt: SystemDfOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_system_df_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for running `podman system service`, which
+    /// serves the Docker-compatible REST API on a socket. Podman only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, Launcher, SystemServiceOpt};
+    /// let mut opt = SystemServiceOpt::new("unix:///tmp/podman.sock");
+    /// opt.time = Some(0.into());
+    /// assert_eq!(
+    ///     Launcher::from(BaseCommand::Podman)
+    ///         .system_service(opt)
+    ///         .command_line_lossy(),
+    ///     "podman system service --time 0 unix:///tmp/podman.sock"
+    /// );
+    /// ```
+    pub fn system_service(&self, opt: SystemServiceOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_system_service_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for adding the name `target` to the image
+    /// `source`.
+    pub fn tag(&self, source: &ImageRef, target: &ImageRef) -> Command {
+        let mut cmd = self.base();
+        args::add_tag_args(&mut cmd, source, target);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for displaying the signatures of images
+    /// with `docker trust inspect`. Docker only.
+    pub fn trust_inspect(&self, opt: TrustInspectOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_trust_inspect_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for signing an image with `docker trust
+    /// sign`. Docker only.
+    pub fn trust_sign(&self, opt: TrustSignOpt) -> Command {
+        let mut cmd = self.base();
+        args::add_trust_sign_args(&mut cmd, &opt);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for running `podman system migrate`, which
+    /// migrates containers to the current podman version and applies
+    /// changes to the user's UID/GID mappings. Podman only.
+    pub fn system_migrate(&self) -> Command {
+        let mut cmd = self.base();
+        args::add_system_migrate_args(&mut cmd);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] for running `podman system reset`, which
+    /// removes all containers, images, networks, and volumes. If
+    /// `force` is true, do not prompt for confirmation. Podman only.
+    pub fn system_reset(&self, force: bool) -> Command {
+        let mut cmd = self.base();
+        args::add_system_reset_args(&mut cmd, force);
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] that runs `command` inside podman's user
+    /// namespace with `podman unshare`. This is useful for changing the
+    /// ownership of files in bind-mounted directories when running
+    /// rootless. Podman only.
+    ///
+    /// The working directory and environment variables of `command`
+    /// are carried over.
+    pub fn unshare(&self, command: &Command) -> Command {
+        let mut cmd = self.base();
+        args::add_unshare_args(&mut cmd, command);
+        if command.dir.is_some() {
+            cmd.dir.clone_from(&command.dir);
+        }
+        cmd.env
+            .extend(command.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.finish(cmd)
+    }
+
+    /// Create a [`Command`] that blocks until `containers` stop, then
+    /// prints their exit codes, one per line.
+    pub fn wait(&self, containers: &[ContainerRef]) -> Command {
+        let mut cmd = self.base();
+        args::add_wait_args(&mut cmd, containers);
+        self.finish(cmd)
+    }
+}
+
+impl From<BaseCommand> for Launcher {
+    fn from(bc: BaseCommand) -> Launcher {
+        let docker = "docker";
+        let podman = "podman";
+        Self::new(match bc {
+            BaseCommand::Docker => Command::new(docker),
+            BaseCommand::SudoDocker => Command::with_args("sudo", [docker]),
+            BaseCommand::Podman => Command::new(podman),
+        })
+    }
+}
+
+/// Logging level for the client.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LogLevel {
+    /// `debug`
+    Debug,
+
+    /// `info`
+    Info,
+
+    /// `warn`
+    Warn,
+
+    /// `error`
+    Error,
+
+    /// `fatal`
+    Fatal,
+}
+
+impl LogLevel {
+    /// Get the level name as used on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+            Self::Fatal => "fatal",
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "warn" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            "fatal" => Ok(Self::Fatal),
+            _ => Err(ValidationError::new("log level", s, "unknown level")),
+        }
+    }
+}
+
+/// Options that apply to every command, passed before the subcommand.
+///
+/// These are the Docker client's global flags. Podman supports
+/// `--log-level` but uses different flags for the others.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct GlobalOpts {
+    /// Location of the client config directory.
+    pub config: Option<PathBuf>,
+
+    /// Name of the context to use.
+    pub context: Option<String>,
+
+    /// Enable debug mode.
+    pub debug: bool,
+
+    /// Daemon socket to connect to, e.g. `unix:///var/run/docker.sock`
+    /// or `ssh://user@host`.
+    pub host: Option<String>,
+
+    /// Logging level.
+    pub log_level: Option<LogLevel>,
+
+    /// Use TLS; implied by `tls_verify`.
+    pub tls: bool,
+
+    /// Trust certs signed only by this CA.
+    pub tls_ca_cert: Option<PathBuf>,
+
+    /// Path to the TLS certificate file.
+    pub tls_cert: Option<PathBuf>,
+
+    /// Path to the TLS key file.
+    pub tls_key: Option<PathBuf>,
+
+    /// Use TLS and verify the remote.
+    pub tls_verify: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for building images with `buildx bake`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct BakeOpt {
+    /// Bake files to read, in HCL, JSON, or compose format. If empty,
+    /// the default files (such as `docker-bake.hcl` and `compose.yaml`)
+    /// in the current directory are used.
+    pub files: Vec<PathBuf>,
+
+    /// Targets or groups to build. If empty, the `default` group is
+    /// built.
+    pub targets: Vec<String>,
+
+    /// Override target attributes, as `(key, value)` pairs passed as
+    /// `--set key=value`. The key has the form `target.attribute`,
+    /// where `target` may be a pattern, e.g. `*.platform`.
+    pub set: Vec<(String, String)>,
+
+    /// Load the built images into the local image store.
+    pub load: bool,
+
+    /// Push the built images to their registries.
+    pub push: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl BakeOpt {
+    /// Create a `BakeOpt` that builds `targets`. All other fields are
+    /// set to their defaults.
+    pub fn new<I>(targets: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            targets: targets.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for building a container.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct BuildOpt {
+    /// Annotations to add to the image manifest, e.g. from
+    /// [`oci::OciMetadata`]. Requires BuildKit with Docker.
+    pub annotations: Vec<(String, String)>,
+
+    /// Build-time variables. The same rules as for [`RunOpt::env`]
+    /// apply; see [`BuildOpt::validate`].
+    pub build_args: Vec<BuildArg>,
+
+    /// Root directory containing files that can be pulled into the
+    /// container.
+    pub context: PathBuf,
+
+    /// If set, pass `--disable-content-trust` with this value. Docker
+    /// skips image verification by default, so set this to `false` to
+    /// require signed images. Docker only.
+    pub disable_content_trust: Option<bool>,
+
+    /// Dockerfile to build. This must be somewhere in the `context`
+    /// directory. If not set (the default) then
+    /// `<context>/Dockerfile` is used.
+    pub dockerfile: Option<PathBuf>,
+
+    /// If set, the image ID will be written to this path.
+    pub iidfile: Option<PathBuf>,
+
+    /// Labels to add to the image config, e.g. from
+    /// [`oci::OciMetadata`].
+    pub labels: Vec<(String, String)>,
+
+    /// Networking mode for `RUN` instructions. Only
+    /// [`NetworkMode::Host`], [`NetworkMode::None`], and the default
+    /// are supported by BuildKit.
+    pub network: Option<NetworkMode>,
+
+    /// Do not use cache when building the image.
+    pub no_cache: bool,
+
+    /// Do not use cache for these build stages. Docker only.
+    pub no_cache_filter: Vec<String>,
+
+    /// Provenance attestation to attach to the image, e.g. `true`,
+    /// `false`, or `mode=max`. Requires BuildKit (Docker only).
+    pub provenance: Option<String>,
+
+    /// When to pull the base images. If `None`, the client's default
+    /// is used.
+    pub pull: Option<PullPolicy>,
+
+    /// Suppress the build output and print image ID on success.
+    pub quiet: bool,
+
+    /// SBOM attestation to attach to the image, e.g. `true` or
+    /// `generator=<image>`. Requires BuildKit (Docker only).
+    pub sbom: Option<String>,
+
+    /// If set, the image will be tagged with this name.
+    pub tag: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl BuildOpt {
+    /// Create a `BuildOpt` with the given `context` directory. All
+    /// other fields are set to their defaults.
+    pub fn new<P: Into<PathBuf>>(context: P) -> Self {
+        Self {
+            context: context.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Add the pairs from [`OciMetadata::pairs`] to `labels`.
+    ///
+    /// [`OciMetadata::pairs`]: oci::OciMetadata::pairs
+    pub fn add_oci_labels(&mut self, meta: &oci::OciMetadata) {
+        self.labels.extend(meta.pairs());
+    }
+
+    /// Add the pairs from [`OciMetadata::pairs`] to `annotations`.
+    ///
+    /// [`OciMetadata::pairs`]: oci::OciMetadata::pairs
+    pub fn add_oci_annotations(&mut self, meta: &oci::OciMetadata) {
+        self.annotations.extend(meta.pairs());
+    }
+
+    /// Check that the build args, labels, and annotations are valid
+    /// `KEY=value` pairs (see [`validate_key_value`]).
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        for (key, value) in &self.labels {
+            validate_key_value("label", key, value)?;
+        }
+        for (key, value) in &self.annotations {
+            validate_key_value("annotation", key, value)?;
+        }
+        for build_arg in &self.build_args {
+            let value = match build_arg {
+                BuildArg::Set(_, value) => value.as_str(),
+                BuildArg::Inherit(_) => "",
+            };
+            validate_key_value("build arg", build_arg.key(), value)?;
+        }
+        Ok(())
+    }
+}
+
+/// When to pull images, passed with `--pull`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PullPolicy {
+    /// Always attempt to pull a newer version of the image.
+    Always,
+
+    /// Only pull the image if it is not present locally.
+    Missing,
+
+    /// Never pull the image. Docker has no equivalent, so this is the
+    /// same as [`PullPolicy::Missing`] with Docker.
+    Never,
+}
+
+impl PullPolicy {
+    /// Get the policy name as used by podman.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::Missing => "missing",
+            Self::Never => "never",
+        }
+    }
+}
+
+/// Build-time variable, passed with `--build-arg`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BuildArg {
+    /// Set the variable to a value: `--build-arg KEY=value`.
+    Set(String, String),
+
+    /// Take the value from the environment of the client:
+    /// `--build-arg KEY`. If the variable is not set, the build arg is
+    /// left unset.
+    Inherit(String),
+}
+
+impl BuildArg {
+    /// Name of the variable.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Set(key, _) | Self::Inherit(key) => key,
+        }
+    }
+
+    /// Format as an argument for `--build-arg`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::BuildArg;
+    /// assert_eq!(BuildArg::from(("VERSION", "1.2")).arg(), "VERSION=1.2");
+    /// assert_eq!(BuildArg::Inherit("HTTP_PROXY".into()).arg(), "HTTP_PROXY");
+    /// ```
+    pub fn arg(&self) -> String {
+        match self {
+            Self::Set(key, value) => format!("{}={}", key, value),
+            Self::Inherit(key) => key.clone(),
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> From<(K, V)> for BuildArg {
+    fn from((key, value): (K, V)) -> Self {
+        Self::Set(key.into(), value.into())
+    }
+}
+
+/// Check that `key` and `value` can be passed as a `key=value`
+/// argument, such as `--env` or `--build-arg`, and read back
+/// unchanged.
+///
+/// The key must be non-empty and must not contain `=` or NUL. The
+/// value may contain anything except NUL. Other characters are
+/// allowed in keys, since Docker passes them through, but keys that
+/// are not of the form `[A-Za-z_][A-Za-z0-9_]*` cannot be used from
+/// most shells.
+///
+/// The error includes the key but not the value, which may be a
+/// secret.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::validate_key_value;
+/// assert!(validate_key_value("environment variable", "PATH", "/bin").is_ok());
+/// assert!(validate_key_value("environment variable", "A=B", "c").is_err());
+/// assert!(validate_key_value("environment variable", "", "c").is_err());
+/// ```
+pub fn validate_key_value<K, V>(
+    what: &'static str,
+    key: K,
+    value: V,
+) -> Result<(), ValidationError>
+where
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    let key = key.as_ref().to_string_lossy();
+    let value = value.as_ref().to_string_lossy();
+    // Only the key goes in the error, since the value may be a secret.
+    let err = |reason| ValidationError::new(what, key.as_ref(), reason);
+    if key.is_empty() {
+        Err(err("empty key"))
+    } else if key.contains('=') {
+        Err(err("key contains '='"))
+    } else if key.contains('\0') {
+        Err(err("key contains NUL"))
+    } else if value.contains('\0') {
+        Err(err("value contains NUL"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Source or destination of [`Launcher::cp`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CopyLocation {
+    /// Path on the host. Use `-` for a tar archive on stdin or stdout.
+    Host(PathBuf),
+
+    /// Path in a container.
+    Container(ContainerRef, PathBuf),
+}
+
+impl CopyLocation {
+    /// Format as an argument, e.g. `web:/etc/nginx`.
+    ///
+    /// A trailing `/.` on a source directory copies the contents of the
+    /// directory rather than the directory itself.
+    pub fn arg(&self) -> OsString {
+        match self {
+            Self::Host(path) => path.into(),
+            Self::Container(container, path) => {
+                let mut out = OsString::from(container.to_string());
+                out.push(":");
+                out.push(path);
+                out
+            }
+        }
+    }
+}
+
+/// Options for copying files between a container and the host.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct CpOpt {
+    /// Where to copy from.
+    pub src: CopyLocation,
+
+    /// Where to copy to.
+    pub dst: CopyLocation,
+
+    /// Preserve the UID and GID of the copied files.
+    pub archive: bool,
+
+    /// Follow symbolic links in the source path.
+    pub follow_link: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl CpOpt {
+    /// Create a `CpOpt` that copies from `src` to `dst`. All other
+    /// fields are set to their defaults.
+    pub fn new(src: CopyLocation, dst: CopyLocation) -> Self {
+        Self {
+            src,
+            dst,
+            archive: false,
+            follow_link: false,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// Options for creating a network.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct CreateNetworkOpt {
+    /// Network name.
+    pub name: String,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl CreateNetworkOpt {
+    /// Create a `CreateNetworkOpt` with the given network `name`. All
+    /// other fields are set to their defaults.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for creating a volume.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct CreateVolumeOpt {
+    /// Volume name.
+    pub name: String,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl CreateVolumeOpt {
+    /// Create a `CreateVolumeOpt` with the given volume `name`. All
+    /// other fields are set to their defaults.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Length of time, formatted the way the Docker CLI expects.
+///
+/// Docker parses most duration flags with Go's `time.ParseDuration`, so
+/// `Display` produces that format, e.g. `10s`, `1m30s`, or `250ms`.
+/// Some flags (such as `stop --time`) take whole seconds instead; use
+/// [`DurationArg::secs_arg`] for those.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::DurationArg;
+/// use std::time::Duration;
+/// assert_eq!(DurationArg::from(90).to_string(), "1m30s");
+/// assert_eq!(DurationArg::from(Duration::from_millis(1500)).to_string(), "1.5s");
+/// assert_eq!(DurationArg::from(Duration::from_millis(1500)).secs_arg(), "2");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DurationArg(pub Duration);
+
+impl DurationArg {
+    /// Format as a whole number of seconds. Partial seconds are rounded
+    /// up so that a short non-zero duration does not become zero.
+    pub fn secs_arg(&self) -> String {
+        let mut secs = self.0.as_secs();
+        if self.0.subsec_nanos() != 0 {
+            secs += 1;
+        }
+        secs.to_string()
+    }
+}
+
+impl fmt::Display for DurationArg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.0.as_secs();
+        let nanos = self.0.subsec_nanos();
+
+        if secs == 0 {
+            return if nanos == 0 {
+                write!(f, "0s")
+            } else if nanos.is_multiple_of(1_000_000) {
+                write!(f, "{}ms", nanos / 1_000_000)
+            } else if nanos.is_multiple_of(1_000) {
+                write!(f, "{}us", nanos / 1_000)
+            } else {
+                write!(f, "{}ns", nanos)
+            };
+        }
+
+        let hours = secs / 3600;
+        let minutes = (secs % 3600) / 60;
+        let secs = secs % 60;
+        if hours != 0 {
+            write!(f, "{}h", hours)?;
+        }
+        if minutes != 0 {
+            write!(f, "{}m", minutes)?;
+        }
+        if secs != 0 || nanos != 0 {
+            write!(f, "{}", secs)?;
+            if nanos != 0 {
+                let frac = format!("{:09}", nanos);
+                write!(f, ".{}", frac.trim_end_matches('0'))?;
+            }
+            write!(f, "s")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Duration> for DurationArg {
+    fn from(d: Duration) -> Self {
+        Self(d)
+    }
+}
+
+impl From<u32> for DurationArg {
+    fn from(secs: u32) -> Self {
+        Self(Duration::from_secs(secs.into()))
+    }
+}
+
+/// Options for running a command in a running container.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ExecOpt {
+    /// Container to run the command in.
+    pub container: ContainerRef,
+
+    /// Command to run.
+    pub command: OsString,
+
+    /// Arguments to pass to the command.
+    pub args: Vec<OsString>,
+
+    /// Run the command in the background.
+    pub detach: bool,
+
+    /// Set environment variables.
+    pub env: Vec<(OsString, OsString)>,
+
+    /// Keep stdin open even if not attached.
+    pub interactive: bool,
+
+    /// Allocate a pseudo-TTY.
+    pub tty: bool,
+
+    /// User (and optionally group) to run the command as.
+    pub user: Option<UserAndGroup>,
+
+    /// Working directory for the command.
+    pub workdir: Option<PathBuf>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl ExecOpt {
+    /// Create an `ExecOpt` that runs `command` in `container`. All
+    /// other fields are set to their defaults.
+    pub fn new<C: Into<OsString>>(container: ContainerRef, command: C) -> Self {
+        Self {
+            container,
+            command: command.into(),
+            args: Vec::new(),
+            detach: false,
+            env: Vec::new(),
+            interactive: false,
+            tty: false,
+            user: None,
+            workdir: None,
+            extra_args: Vec::new(),
+        }
+    }
+
+    /// Set `interactive` and `tty` according to `interactivity`.
+    pub fn set_interactivity(&mut self, interactivity: Interactivity) {
+        (self.interactive, self.tty) = interactivity.flags();
+    }
+}
+
+/// Whether a container's process is attached to the terminal, setting
+/// `--interactive` and `--tty`. See [`RunOpt::set_interactivity`] and
+/// [`ExecOpt::set_interactivity`].
+///
+/// Passing `--tty` when the output is not a terminal, as in CI, mixes
+/// stderr into stdout and adds carriage returns, so prefer `Auto` over
+/// `Attached` unless a TTY is always wanted.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Interactivity {
+    /// Neither `--interactive` nor `--tty` (the default).
+    #[default]
+    None,
+
+    /// Both `--interactive` and `--tty`.
+    Attached,
+
+    /// `--interactive` if stdin is a terminal, and `--tty` if both
+    /// stdin and stdout are terminals.
+    Auto,
+}
+
+impl Interactivity {
+    /// Get the `(interactive, tty)` flags, checking the terminal for
+    /// `Auto`.
+    pub fn flags(&self) -> (bool, bool) {
+        match self {
+            Self::None => (false, false),
+            Self::Attached => (true, true),
+            Self::Auto => {
+                use std::io::IsTerminal;
+                let stdin = std::io::stdin().is_terminal();
+                (stdin, stdin && std::io::stdout().is_terminal())
+            }
+        }
+    }
+}
+
+/// Options for generating a systemd unit file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct GenerateSystemdOpt {
+    /// Container to generate the unit for.
+    pub container: String,
+
+    /// Write the unit to a file in the current directory instead of
+    /// printing it.
+    pub files: bool,
+
+    /// Refer to the container by name rather than ID.
+    pub name: bool,
+
+    /// Create a new container when the unit starts, and remove it
+    /// when the unit stops, rather than starting and stopping the
+    /// existing container.
+    pub new: bool,
+
+    /// Restart policy of the unit, e.g. `on-failure` or `always`.
+    pub restart_policy: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl GenerateSystemdOpt {
+    /// Create a `GenerateSystemdOpt` for `container`. All other fields
+    /// are set to their defaults.
+    pub fn new<S: Into<String>>(container: S) -> Self {
+        Self {
+            container: container.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for listing images.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ImagesOpt {
+    /// Show all images, including intermediate images.
+    pub all: bool,
+
+    /// Show digests.
+    pub digests: bool,
+
+    /// Only show images matching these filters.
+    pub filters: Vec<Filter>,
+
+    /// Format the output using a Go template, or `json`.
+    pub format: Option<String>,
+
+    /// Do not truncate the output.
+    pub no_trunc: bool,
+
+    /// Only show image IDs.
+    pub quiet: bool,
+
+    /// Only show images from this repository (optionally with a tag).
+    pub repository: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for displaying system-wide information.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct InfoOpt {
+    /// Format the output using a Go template, or `json`.
+    pub format: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Kind of object to inspect.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ObjectKind {
+    /// Container.
+    Container,
+
+    /// Image.
+    Image,
+
+    /// Network.
+    Network,
+
+    /// Volume.
+    Volume,
+}
+
+impl ObjectKind {
+    /// Get the kind as a string, e.g. `container`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Container => "container",
+            Self::Image => "image",
+            Self::Network => "network",
+            Self::Volume => "volume",
+        }
+    }
+}
+
+/// Options for inspecting objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct InspectOpt {
+    /// Names or IDs of the objects to inspect.
+    pub objects: Vec<String>,
+
+    /// Format the output using a Go template.
+    pub format: Option<String>,
+
+    /// Only inspect objects of this kind. If not set, the first object
+    /// of any kind matching the name or ID is used.
+    pub kind: Option<ObjectKind>,
+
+    /// Show the total file size of containers.
+    pub size: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl InspectOpt {
+    /// Create an `InspectOpt` for the given `objects`. All other fields
+    /// are set to their defaults.
+    pub fn new<I, S>(objects: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            objects: objects.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for killing containers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct KillOpt {
+    /// Containers to kill, specified as names or IDs.
+    pub containers: Vec<ContainerRef>,
+
+    /// Signal to send, e.g. `SIGKILL` or `9`. Defaults to `SIGKILL`.
+    pub signal: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl KillOpt {
+    /// Create a `KillOpt` for the given `containers`. All other fields
+    /// are set to their defaults.
+    pub fn new<I>(containers: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<ContainerRef>,
+    {
+        Self {
+            containers: containers.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for listing networks.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ListNetworksOpt {
+    /// Only show networks matching these filters.
+    pub filters: Vec<Filter>,
+
+    /// Format the output using a Go template, or `json`.
+    pub format: Option<String>,
+
+    /// Do not truncate the output.
+    pub no_trunc: bool,
+
+    /// Only show network IDs.
+    pub quiet: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for listing volumes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ListVolumesOpt {
+    /// Only show volumes matching these filters.
+    pub filters: Vec<Filter>,
+
+    /// Format the output using a Go template, or `json`.
+    pub format: Option<String>,
+
+    /// Only show volume names.
+    pub quiet: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for loading images from a tar archive.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct LoadOpt {
+    /// Read the archive from this file. If `None`, the archive is read
+    /// from stdin.
+    pub input: Option<PathBuf>,
+
+    /// Suppress the load output.
+    pub quiet: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for pruning containers, images, networks, or volumes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PruneOpt {
+    /// For images, remove all unused images rather than just dangling
+    /// ones. For volumes, remove named volumes as well as anonymous
+    /// ones. Not supported for containers or networks.
+    pub all: bool,
+
+    /// Only prune objects matching these filters.
+    pub filters: Vec<Filter>,
+
+    /// Do not prompt for confirmation.
+    pub force: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for listing containers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PsOpt {
+    /// Show all containers, not just running ones.
+    pub all: bool,
+
+    /// Only show containers matching these filters.
+    pub filters: Vec<Filter>,
+
+    /// Format the output using a Go template, or `json`.
+    pub format: Option<String>,
+
+    /// Do not truncate the output.
+    pub no_trunc: bool,
+
+    /// Only show container IDs.
+    pub quiet: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for pulling an image.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PullOpt {
+    /// Image to pull.
+    pub image: ImageRef,
+
+    /// Pull all tagged images in the repository.
+    pub all_tags: bool,
+
+    /// If set, pass `--disable-content-trust` with this value. Docker
+    /// skips image verification by default, so set this to `false` to
+    /// require signed images. Docker only.
+    pub disable_content_trust: Option<bool>,
+
+    /// Pull the image for this platform, e.g. `linux/arm64`.
+    pub platform: Option<String>,
+
+    /// Suppress verbose output.
+    pub quiet: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl PullOpt {
+    /// Create a `PullOpt` with the given `image`. All other fields are
+    /// set to their defaults.
+    pub fn new<I: Into<ImageRef>>(image: I) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for pushing an image.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PushOpt {
+    /// Image to push.
+    pub image: ImageRef,
+
+    /// Push all tags of the repository.
+    pub all_tags: bool,
+
+    /// If set, pass `--disable-content-trust` with this value. Docker
+    /// skips image verification by default, so set this to `false` to
+    /// require signed images. Docker only.
+    pub disable_content_trust: Option<bool>,
+
+    /// Suppress verbose output.
+    pub quiet: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl PushOpt {
+    /// Create a `PushOpt` with the given `image`. All other fields are
+    /// set to their defaults.
+    pub fn new<I: Into<ImageRef>>(image: I) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Port or range of ports.
+///
+/// # Examples
+///
+/// Specify a single port:
+///
+/// ```
+/// use docker_command::PortRange;
+/// let port = PortRange::from(123);
+/// assert_eq!(port, PortRange(123..=123));
+/// ```
+///
+/// Specify a port range:
+///
+/// ```
+/// use docker_command::PortRange;
+/// PortRange(123..=567);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PortRange(pub RangeInclusive<u16>);
+
+impl Default for PortRange {
+    fn default() -> Self {
+        Self(0..=0)
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.start() == self.0.end() {
+            write!(f, "{}", self.0.start())
+        } else {
+            write!(f, "{}-{}", self.0.start(), self.0.end())
+        }
+    }
+}
+
+impl From<u16> for PortRange {
+    fn from(port: u16) -> Self {
+        Self(port..=port)
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = ValidationError;
+
+    /// Parse a single port (`80`) or an inclusive range (`8000-8010`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("port range", s, reason);
+        let parse_port = |port: &str| {
+            port.parse::<u16>().map_err(|_| err("invalid port number"))
+        };
+        match s.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse_port(start)?, parse_port(end)?);
+                if start > end {
+                    return Err(err("start of range is after the end"));
+                }
+                Ok(Self(start..=end))
+            }
+            None => Ok(Self::from(parse_port(s)?)),
+        }
+    }
+}
+
+/// Options for publishing ports from a container to the host.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PublishPorts {
+    /// Port or port range in the container to publish.
+    pub container: PortRange,
+
+    /// Port or port range on the host. If None, the engine picks a
+    /// free port; see [`PublishPorts::ephemeral`].
+    pub host: Option<PortRange>,
+
+    /// Host IP. If set to `0.0.0.0` or `None`, the port will be bound
+    /// to all IPs on the host.
+    pub ip: Option<String>,
+
+    /// Transport protocol. Defaults to TCP.
+    pub protocol: Protocol,
+}
+
+impl PublishPorts {
+    /// Publish `container` on a host port picked by the engine, so
+    /// that many containers can run at once without clashing. Use
+    /// [`Launcher::host_port`] after the container starts to find out
+    /// which port was picked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::PublishPorts;
+    /// assert_eq!(PublishPorts::ephemeral(80).arg(), "80");
+    /// ```
+    pub fn ephemeral<P: Into<PortRange>>(container: P) -> Self {
+        Self {
+            container: container.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Format as an argument. IPv6 addresses are enclosed in square
+    /// brackets.
+    pub fn arg(&self) -> String {
+        let ip = self.ip.as_ref().map(|ip| {
+            if ip.contains(':') && !ip.starts_with('[') {
+                format!("[{}]", ip)
+            } else {
+                ip.clone()
+            }
+        });
+        let mut out = match (&ip, &self.host) {
+            (Some(ip), Some(host_ports)) => {
+                format!("{}:{}:{}", ip, host_ports, self.container)
+            }
+            (Some(ip), None) => {
+                format!("{}::{}", ip, self.container)
+            }
+            (None, Some(host_ports)) => {
+                format!("{}:{}", host_ports, self.container)
+            }
+            (None, None) => format!("{}", self.container),
+        };
+        if self.protocol != Protocol::Tcp {
+            out.push('/');
+            out.push_str(self.protocol.as_str());
+        }
+        out
+    }
+}
+
+impl FromStr for PublishPorts {
+    type Err = ValidationError;
+
+    /// Parse the `--publish` syntax: `[[ip:][host]:]container[/proto]`.
+    /// IPv6 addresses must be enclosed in square brackets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{PortRange, Protocol, PublishPorts};
+    /// let ports: PublishPorts = "127.0.0.1:8125:8125/udp".parse().unwrap();
+    /// assert_eq!(ports.ip.as_deref(), Some("127.0.0.1"));
+    /// assert_eq!(ports.host, Some(PortRange::from(8125)));
+    /// assert_eq!(ports.container, PortRange::from(8125));
+    /// assert_eq!(ports.protocol, Protocol::Udp);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("published port", s, reason);
+
+        let (rest, protocol) = match s.rsplit_once('/') {
+            Some((rest, protocol)) => (rest, protocol.parse()?),
+            None => (s, Protocol::Tcp),
+        };
+
+        let (ip, rest) = if let Some(bracketed) = rest.strip_prefix('[') {
+            let (ip, rest) = bracketed
+                .split_once("]:")
+                .ok_or_else(|| err("unterminated IPv6 address"))?;
+            (Some(ip), rest)
+        } else {
+            (None, rest)
+        };
+
+        let parts: Vec<&str> = rest.split(':').collect();
+        let (ip, host, container) = match (ip, parts.as_slice()) {
+            (None, &[container]) => (None, None, container),
+            (None, &[host, container]) => (None, Some(host), container),
+            (None, &[ip, host, container]) | (Some(ip), &[host, container]) => {
+                (Some(ip), Some(host), container)
+            }
+            _ => return Err(err("too many colons")),
+        };
+
+        Ok(Self {
+            container: container.parse()?,
+            host: match host {
+                Some("") | None => None,
+                Some(host) => Some(host.parse()?),
+            },
+            ip: ip.map(|ip| ip.to_string()),
+            protocol,
+        })
+    }
+}
+
+/// Transport protocol for published ports.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Protocol {
+    /// TCP (the default).
+    #[default]
+    Tcp,
+
+    /// UDP.
+    Udp,
+
+    /// SCTP.
+    Sctp,
+}
+
+impl Protocol {
+    /// Get the protocol name as used on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Udp => "udp",
+            Self::Sctp => "sctp",
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tcp" => Ok(Self::Tcp),
+            "udp" => Ok(Self::Udp),
+            "sctp" => Ok(Self::Sctp),
+            _ => Err(ValidationError::new("protocol", s, "unknown protocol")),
+        }
+    }
+}
+
+/// Name or numeric ID for a user or group.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NameOrId {
+    /// Name or the user or group.
+    Name(String),
+
+    /// Numeric ID of the user or group.
+    Id(u32),
+}
+
+impl fmt::Display for NameOrId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{}", name),
+            Self::Id(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+impl NameOrId {
+    /// Get the numeric ID, or an error if this is a name.
+    pub fn id(&self) -> Result<u32, ValidationError> {
+        match self {
+            Self::Name(name) => Err(ValidationError::new(
+                "user or group",
+                name,
+                "must be a numeric ID",
+            )),
+            Self::Id(id) => Ok(*id),
+        }
+    }
+}
+
+impl From<String> for NameOrId {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl From<u32> for NameOrId {
+    fn from(id: u32) -> Self {
+        Self::Id(id)
+    }
+}
+
+/// User and (optionally) group.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UserAndGroup {
     /// User or UID.
     pub user: NameOrId,
 
-    /// Group or GID.
-    pub group: Option<NameOrId>,
+    /// Group or GID.
+    pub group: Option<NameOrId>,
+}
+
+impl UserAndGroup {
+    /// Create a `UserAndGroup` from a numeric UID and GID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::UserAndGroup;
+    /// assert_eq!(UserAndGroup::from_ids(1000, 100).arg(), "1000:100");
+    /// ```
+    pub fn from_ids(uid: u32, gid: u32) -> Self {
+        Self {
+            user: uid.into(),
+            group: Some(gid.into()),
+        }
+    }
+
+    /// Get a `UserAndGroup` with the current UID and GID set.
+    ///
+    /// This requires the `users` feature, which is enabled by default.
+    #[cfg(feature = "users")]
+    pub fn current() -> Self {
+        Self {
+            user: users::get_current_uid().into(),
+            group: Some(users::get_current_gid().into()),
+        }
+    }
+
+    /// Get a `UserAndGroup` with UID and GID set to zero.
+    pub fn root() -> Self {
+        Self::from_ids(0, 0)
+    }
+
+    /// Format as an argument. If `group` is set, the format is
+    /// `<user>:<group>`, otherwise just `<user>`.
+    pub fn arg(&self) -> String {
+        let mut out = self.user.to_string();
+        if let Some(group) = &self.group {
+            out.push(':');
+            out.push_str(&group.to_string());
+        }
+        out
+    }
+}
+
+/// User namespace mode for a container.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UserNamespace {
+    /// Use the host's user namespace.
+    Host,
+
+    /// Map the invoking user to the same ID in the container (podman
+    /// only). If `uid` or `gid` are set, the invoking user is mapped
+    /// to those IDs instead.
+    KeepId {
+        /// UID in the container to map the invoking user to.
+        uid: Option<u32>,
+
+        /// GID in the container to map the invoking user's group to.
+        gid: Option<u32>,
+    },
+
+    /// Any other mode, passed through as-is.
+    Other(String),
+}
+
+impl UserNamespace {
+    /// Format as an argument for `--userns`.
+    pub fn arg(&self) -> String {
+        match self {
+            Self::Host => "host".into(),
+            Self::KeepId { uid, gid } => {
+                let mut opts = Vec::new();
+                if let Some(uid) = uid {
+                    opts.push(format!("uid={}", uid));
+                }
+                if let Some(gid) = gid {
+                    opts.push(format!("gid={}", gid));
+                }
+                if opts.is_empty() {
+                    "keep-id".into()
+                } else {
+                    format!("keep-id:{}", opts.join(","))
+                }
+            }
+            Self::Other(mode) => mode.clone(),
+        }
+    }
+}
+
+/// IPC namespace mode for a container, passed with `--ipc`.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::IpcMode;
+/// let ipc: IpcMode = "container:db".parse().unwrap();
+/// assert_eq!(ipc, IpcMode::Container("db".parse().unwrap()));
+/// assert_eq!(IpcMode::Shareable.arg(), "shareable");
+/// assert!("container:".parse::<IpcMode>().is_err());
+/// assert!("sharable".parse::<IpcMode>().is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum IpcMode {
+    /// Own IPC namespace, without `/dev/shm` mounted.
+    None,
+
+    /// Own IPC namespace that other containers cannot join.
+    Private,
+
+    /// Own IPC namespace that other containers can join with
+    /// [`IpcMode::Container`]. Requires podman 4.0 or newer.
+    Shareable,
+
+    /// Use the host's IPC namespace.
+    Host,
+
+    /// Join the IPC namespace of another container. With Docker, that
+    /// container must have been started with [`IpcMode::Shareable`]
+    /// (the default depends on the daemon's `default-ipc-mode`); with
+    /// podman, any container that is not private can be joined.
+    Container(ContainerRef),
+}
+
+impl IpcMode {
+    /// Format as an argument for `--ipc`.
+    pub fn arg(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for IpcMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::Private => f.write_str("private"),
+            Self::Shareable => f.write_str("shareable"),
+            Self::Host => f.write_str("host"),
+            Self::Container(container) => write!(f, "container:{}", container),
+        }
+    }
+}
+
+impl FromStr for IpcMode {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "private" => Ok(Self::Private),
+            "shareable" => Ok(Self::Shareable),
+            "host" => Ok(Self::Host),
+            _ => match s.strip_prefix("container:") {
+                Some(container) => Ok(Self::Container(container.parse()?)),
+                None => {
+                    Err(ValidationError::new("IPC mode", s, "unknown mode"))
+                }
+            },
+        }
+    }
+}
+
+/// Bandwidth limit for reads from or writes to a block device, used
+/// with `--device-read-bps` and `--device-write-bps`.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::DeviceThrottle;
+/// let throttle: DeviceThrottle = "/dev/sda:10mb".parse().unwrap();
+/// assert_eq!(throttle.rate, 10 * 1024 * 1024);
+/// assert_eq!(throttle.arg(), "/dev/sda:10485760");
+/// assert!("/dev/sda".parse::<DeviceThrottle>().is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DeviceThrottle {
+    /// Path of the device on the host, e.g. `/dev/sda`.
+    pub device: PathBuf,
+
+    /// Maximum rate in bytes per second.
+    pub rate: u64,
 }
 
-impl UserAndGroup {
-    /// Get a `UserAndGroup` with the current UID and GID set.
-    pub fn current() -> Self {
+impl DeviceThrottle {
+    /// Create a `DeviceThrottle` limiting `device` to `rate` bytes per
+    /// second.
+    pub fn new<P: Into<PathBuf>>(device: P, rate: u64) -> Self {
         Self {
-            user: users::get_current_uid().into(),
-            group: Some(users::get_current_gid().into()),
+            device: device.into(),
+            rate,
+        }
+    }
+
+    /// Format as an argument for `--device-read-bps` or
+    /// `--device-write-bps`.
+    pub fn arg(&self) -> OsString {
+        let mut out = OsString::from(&self.device);
+        out.push(format!(":{}", self.rate));
+        out
+    }
+}
+
+impl FromStr for DeviceThrottle {
+    type Err = ValidationError;
+
+    /// Parse `<device>:<rate>`, where the rate is a number of bytes
+    /// optionally followed by a `k`, `m`, or `g` unit (with or without
+    /// a trailing `b`, in any case). Units are powers of 1024, matching
+    /// docker and podman.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("device throttle", s, reason);
+        let (device, rate) = s
+            .rsplit_once(':')
+            .ok_or_else(|| err("expected <device>:<rate>"))?;
+        if device.is_empty() {
+            return Err(err("missing device"));
+        }
+        let rate = rate.to_ascii_lowercase();
+        let rate = rate.strip_suffix('b').unwrap_or(&rate);
+        let (digits, shift) = if let Some(n) = rate.strip_suffix('k') {
+            (n, 10)
+        } else if let Some(n) = rate.strip_suffix('m') {
+            (n, 20)
+        } else if let Some(n) = rate.strip_suffix('g') {
+            (n, 30)
+        } else {
+            (rate, 0)
+        };
+        let rate = digits
+            .parse::<u64>()
+            .ok()
+            .and_then(|n| n.checked_mul(1 << shift))
+            .ok_or_else(|| err("invalid rate"))?;
+        Ok(Self::new(device, rate))
+    }
+}
+
+/// Restart policy of a container, passed with `--restart`.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::RestartPolicy;
+/// assert_eq!(RestartPolicy::OnFailure(Some(3)).arg(), "on-failure:3");
+/// assert_eq!("unless-stopped".parse(), Ok(RestartPolicy::UnlessStopped));
+/// assert!("sometimes".parse::<RestartPolicy>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum RestartPolicy {
+    /// Do not restart the container (the default).
+    #[default]
+    No,
+
+    /// Always restart the container, including when the daemon
+    /// starts.
+    Always,
+
+    /// Restart the container unless it was stopped explicitly.
+    UnlessStopped,
+
+    /// Restart the container if it exits with a non-zero status,
+    /// optionally at most this many times.
+    OnFailure(Option<u32>),
+}
+
+impl RestartPolicy {
+    /// Format as an argument for `--restart`.
+    pub fn arg(&self) -> String {
+        self.to_string()
+    }
+
+    /// Name of the policy without the retry count, e.g. `on-failure`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::No => "no",
+            Self::Always => "always",
+            Self::UnlessStopped => "unless-stopped",
+            Self::OnFailure(_) => "on-failure",
+        }
+    }
+}
+
+impl fmt::Display for RestartPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())?;
+        if let Self::OnFailure(Some(count)) = self {
+            write!(f, ":{}", count)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RestartPolicy {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("restart policy", s, reason);
+        let (name, count) = match s.split_once(':') {
+            Some((name, count)) => (name, Some(count)),
+            None => (s, None),
+        };
+        match (name, count) {
+            ("no", None) => Ok(Self::No),
+            ("always", None) => Ok(Self::Always),
+            ("unless-stopped", None) => Ok(Self::UnlessStopped),
+            ("on-failure", None) => Ok(Self::OnFailure(None)),
+            ("on-failure", Some(count)) => count
+                .parse()
+                .map(|count| Self::OnFailure(Some(count)))
+                .map_err(|_| err("invalid retry count")),
+            _ => Err(err("unknown policy")),
+        }
+    }
+}
+
+/// Range of user or group IDs mapped into a container with `--uidmap`
+/// or `--gidmap`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IdMap {
+    /// First ID in the container.
+    pub container: u32,
+
+    /// First ID on the host. For rootless podman this is an ID in the
+    /// intermediate namespace, where zero is the invoking user and the
+    /// subordinate IDs start at one.
+    pub host: u32,
+
+    /// Number of IDs in the range.
+    pub size: u32,
+}
+
+impl IdMap {
+    /// Number of IDs typically available to a rootless user.
+    pub const DEFAULT_SIZE: u32 = 65536;
+
+    /// Get the mappings for rootless podman that map `id` in the
+    /// container to the invoking user, and the other IDs below `size`
+    /// to subordinate IDs. This is equivalent to
+    /// `--userns=keep-id:uid=<id>` on podman versions that support it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::IdMap;
+    /// let args: Vec<_> = IdMap::keep_id(1000, IdMap::DEFAULT_SIZE)
+    ///     .iter()
+    ///     .map(IdMap::arg)
+    ///     .collect();
+    /// assert_eq!(args, ["0:1:1000", "1000:0:1", "1001:1001:64535"]);
+    /// ```
+    pub fn keep_id(id: u32, size: u32) -> Vec<Self> {
+        let mut maps = Vec::new();
+        if id > 0 {
+            maps.push(Self {
+                container: 0,
+                host: 1,
+                size: id,
+            });
+        }
+        maps.push(Self {
+            container: id,
+            host: 0,
+            size: 1,
+        });
+        let next = id.saturating_add(1);
+        if next < size {
+            maps.push(Self {
+                container: next,
+                host: next,
+                size: size - next,
+            });
+        }
+        maps
+    }
+
+    /// Format as an argument, e.g. `0:1:1000`.
+    pub fn arg(&self) -> String {
+        format!("{}:{}:{}", self.container, self.host, self.size)
+    }
+}
+
+/// Source of a [`Volume`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum VolumeSource {
+    /// Path on the host to bind mount.
+    HostPath(PathBuf),
+
+    /// Name of a volume managed by Docker.
+    Named(String),
+
+    /// Anonymous volume created for the container. Only the
+    /// destination is passed to Docker.
+    #[default]
+    Anonymous,
+}
+
+impl VolumeSource {
+    /// Guess the kind of source from a string. Valid volume names (see
+    /// [`ContainerName`] for the pattern) are treated as named volumes;
+    /// anything else is treated as a host path.
+    pub fn guess(s: &str) -> Self {
+        if s.parse::<ContainerName>().is_ok() {
+            Self::Named(s.to_string())
+        } else {
+            Self::HostPath(s.into())
+        }
+    }
+}
+
+impl From<PathBuf> for VolumeSource {
+    fn from(path: PathBuf) -> Self {
+        Self::HostPath(path)
+    }
+}
+
+impl From<&Path> for VolumeSource {
+    fn from(path: &Path) -> Self {
+        Self::HostPath(path.into())
+    }
+}
+
+/// Volume specification used when running a container.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Volume {
+    /// Host path, volume name, or anonymous.
+    pub src: VolumeSource,
+
+    /// Absolute path in the container where the volume will be
+    /// mounted.
+    pub dst: PathBuf,
+
+    /// Access mode. Defaults to [`Access::Default`], which adds no
+    /// suffix and so leaves the volume read-write.
+    pub access: Access,
+
+    /// Additional options to set on the volume.
+    pub options: Vec<VolumeOption>,
+}
+
+impl FromStr for Volume {
+    type Err = ValidationError;
+
+    /// Parse the `--volume` syntax: `[src:]dst[:options]`, where
+    /// options are comma-separated. If neither `ro` nor `rw` is present
+    /// the access is [`Access::Default`].
+    ///
+    /// The source is interpreted with [`VolumeSource::guess`]; if it is
+    /// omitted the volume is anonymous. A Windows drive prefix on the
+    /// source (e.g. `C:\src`) is kept as part of the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{Access, Volume, VolumeOption, VolumeSource};
+    /// let vol: Volume = "/src:/dst:ro,z".parse().unwrap();
+    /// assert_eq!(vol.access, Access::ReadOnly);
+    /// assert_eq!(vol.options, [VolumeOption::SelinuxShared]);
+    ///
+    /// let vol: Volume = "cache:/cache".parse().unwrap();
+    /// assert_eq!(vol.src, VolumeSource::Named("cache".into()));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("volume", s, reason);
+
+        // Skip over a drive letter so its colon isn't taken as a
+        // separator.
+        let has_drive = |s: &str| {
+            let bytes = s.as_bytes();
+            bytes.len() > 2
+                && bytes[0].is_ascii_alphabetic()
+                && bytes[1] == b':'
+                && (bytes[2] == b'\\' || bytes[2] == b'/')
+        };
+        let skip = if has_drive(s) { 2 } else { 0 };
+        let mut parts: Vec<&str> = s[skip..].splitn(3, ':').collect();
+        parts[0] = &s[..skip + parts[0].len()];
+
+        let is_path = |s: &str| s.contains(['/', '\\']);
+        let (src, dst, options) = match *parts.as_slice() {
+            [dst] => (None, dst, ""),
+            [dst, options] if !is_path(options) => (None, dst, options),
+            [src, dst] => (Some(src), dst, ""),
+            [src, dst, options] => (Some(src), dst, options),
+            _ => unreachable!(),
+        };
+        if src == Some("") || dst.is_empty() {
+            return Err(err("empty source or destination"));
+        }
+
+        let mut vol = Self {
+            src: src.map(VolumeSource::guess).unwrap_or_default(),
+            dst: dst.into(),
+            access: Access::Default,
+            options: Vec::new(),
+        };
+        for opt in options.split(',') {
+            match opt {
+                "" => {}
+                "ro" => vol.access = Access::ReadOnly,
+                "rw" => vol.access = Access::ReadWrite,
+                _ => vol.options.push(opt.into()),
+            }
+        }
+        Ok(vol)
+    }
+}
+
+impl Volume {
+    /// Create a volume that bind mounts `src` from the host.
+    pub fn bind<S: Into<PathBuf>, D: Into<PathBuf>>(src: S, dst: D) -> Self {
+        Self {
+            src: VolumeSource::HostPath(src.into()),
+            dst: dst.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Create a volume that mounts the named volume `name`.
+    pub fn named<S: Into<String>, D: Into<PathBuf>>(name: S, dst: D) -> Self {
+        Self {
+            src: VolumeSource::Named(name.into()),
+            dst: dst.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Create an anonymous volume.
+    pub fn anonymous<D: Into<PathBuf>>(dst: D) -> Self {
+        Self {
+            dst: dst.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Check that the options are compatible with each other and with
+    /// the source:
+    ///
+    /// * At most one of `z` and `Z` may be set.
+    /// * At most one consistency option may be set.
+    /// * At most one propagation option may be set.
+    /// * `nocopy` is only valid for named or anonymous volumes.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let count = |f: fn(&VolumeOption) -> bool| {
+            self.options.iter().filter(|opt| f(opt)).count()
+        };
+        let reason = if count(|opt| {
+            matches!(
+                opt,
+                VolumeOption::SelinuxShared | VolumeOption::SelinuxPrivate
+            )
+        }) > 1
+        {
+            "conflicting SELinux label options"
+        } else if count(|opt| {
+            matches!(
+                opt,
+                VolumeOption::Cached
+                    | VolumeOption::Delegated
+                    | VolumeOption::Consistent
+            )
+        }) > 1
+        {
+            "conflicting consistency options"
+        } else if count(|opt| matches!(opt, VolumeOption::Propagation(_))) > 1 {
+            "conflicting propagation options"
+        } else if matches!(self.src, VolumeSource::HostPath(_))
+            && self.options.contains(&VolumeOption::NoCopy)
+        {
+            "nocopy is not valid for a host path"
+        } else {
+            return Ok(());
+        };
+        Err(ValidationError::new(
+            "volume",
+            self.arg().to_string_lossy(),
+            reason,
+        ))
+    }
+
+    /// Format as an argument.
+    pub fn arg(&self) -> OsString {
+        self.arg_with(false)
+    }
+
+    /// Format as an argument, optionally dropping the consistency
+    /// options (which podman does not support).
+    fn arg_with(&self, skip_consistency: bool) -> OsString {
+        let mut out = OsString::new();
+        match &self.src {
+            VolumeSource::HostPath(path) => {
+                out.push(path);
+                out.push(":");
+            }
+            VolumeSource::Named(name) => {
+                out.push(name);
+                out.push(":");
+            }
+            VolumeSource::Anonymous => {}
+        }
+        out.push(&self.dst);
+
+        let access = match self.access {
+            Access::Default => None,
+            Access::ReadWrite => Some("rw"),
+            Access::ReadOnly => Some("ro"),
+        };
+        let options = self
+            .options
+            .iter()
+            .filter(|opt| !(skip_consistency && opt.is_consistency()))
+            .map(VolumeOption::as_str);
+        let mut sep = ":";
+        for opt in access.into_iter().chain(options) {
+            out.push(sep);
+            out.push(opt);
+            sep = ",";
+        }
+        out
+    }
+}
+
+/// Access mode for a [`Volume`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Access {
+    /// Don't specify the mode, leaving it up to the container engine.
+    /// Docker and podman both default to read-write.
+    #[default]
+    Default,
+
+    /// `rw`: read-write.
+    ReadWrite,
+
+    /// `ro`: read-only.
+    ReadOnly,
+}
+
+/// Mount propagation mode for a [`Volume`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Propagation {
+    /// `shared`
+    Shared,
+
+    /// `rshared`
+    RShared,
+
+    /// `slave`
+    Slave,
+
+    /// `rslave`
+    RSlave,
+
+    /// `private`
+    Private,
+
+    /// `rprivate`
+    RPrivate,
+}
+
+impl Propagation {
+    /// Get the option name as used on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Shared => "shared",
+            Self::RShared => "rshared",
+            Self::Slave => "slave",
+            Self::RSlave => "rslave",
+            Self::Private => "private",
+            Self::RPrivate => "rprivate",
+        }
+    }
+}
+
+/// Option for a [`Volume`].
+///
+/// Converting from a string never fails; unrecognized options become
+/// [`VolumeOption::Other`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum VolumeOption {
+    /// `z`: relabel for SELinux, shared between containers.
+    SelinuxShared,
+
+    /// `Z`: relabel for SELinux, private to this container.
+    SelinuxPrivate,
+
+    /// `cached`: the host's view is authoritative (Docker Desktop
+    /// only). Not passed to podman.
+    Cached,
+
+    /// `delegated`: the container's view is authoritative (Docker
+    /// Desktop only). Not passed to podman.
+    Delegated,
+
+    /// `consistent`: perfect consistency (Docker Desktop only). Not
+    /// passed to podman.
+    Consistent,
+
+    /// Mount propagation mode.
+    Propagation(Propagation),
+
+    /// `nocopy`: do not copy data from the image into a new volume.
+    NoCopy,
+
+    /// Any other option, passed through as-is.
+    Other(String),
+}
+
+impl VolumeOption {
+    /// Get the option as used on the command line.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::SelinuxShared => "z",
+            Self::SelinuxPrivate => "Z",
+            Self::Cached => "cached",
+            Self::Delegated => "delegated",
+            Self::Consistent => "consistent",
+            Self::Propagation(propagation) => propagation.as_str(),
+            Self::NoCopy => "nocopy",
+            Self::Other(other) => other,
+        }
+    }
+
+    fn is_consistency(&self) -> bool {
+        matches!(self, Self::Cached | Self::Delegated | Self::Consistent)
+    }
+}
+
+impl From<&str> for VolumeOption {
+    fn from(s: &str) -> Self {
+        match s {
+            "z" => Self::SelinuxShared,
+            "Z" => Self::SelinuxPrivate,
+            "cached" => Self::Cached,
+            "delegated" => Self::Delegated,
+            "consistent" => Self::Consistent,
+            "shared" => Self::Propagation(Propagation::Shared),
+            "rshared" => Self::Propagation(Propagation::RShared),
+            "slave" => Self::Propagation(Propagation::Slave),
+            "rslave" => Self::Propagation(Propagation::RSlave),
+            "private" => Self::Propagation(Propagation::Private),
+            "rprivate" => Self::Propagation(Propagation::RPrivate),
+            "nocopy" => Self::NoCopy,
+            _ => Self::Other(s.to_string()),
+        }
+    }
+}
+
+/// Type of [`Mount`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MountType {
+    /// Mount a file or directory from the host.
+    Bind,
+
+    /// Mount a named or anonymous volume (the default).
+    #[default]
+    Volume,
+
+    /// Mount a tmpfs.
+    Tmpfs,
+}
+
+impl MountType {
+    /// Get the type name as used on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bind => "bind",
+            Self::Volume => "volume",
+            Self::Tmpfs => "tmpfs",
+        }
+    }
+}
+
+impl FromStr for MountType {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bind" => Ok(Self::Bind),
+            "volume" => Ok(Self::Volume),
+            "tmpfs" => Ok(Self::Tmpfs),
+            _ => Err(ValidationError::new("mount type", s, "unknown type")),
+        }
+    }
+}
+
+/// Mount specification used when running a container, passed with
+/// `--mount`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Mount {
+    /// Type of mount.
+    pub kind: MountType,
+
+    /// Path on the host for bind mounts, or the volume name for volume
+    /// mounts. Leave unset for tmpfs and anonymous volumes.
+    pub source: Option<PathBuf>,
+
+    /// Absolute path in the container where the mount is placed.
+    pub target: PathBuf,
+
+    /// Mount read-only.
+    pub read_only: bool,
+
+    /// Additional `key=value` (or bare `key`) options, such as
+    /// `bind-propagation=rslave` or `tmpfs-size=64m`.
+    pub options: Vec<String>,
+}
+
+impl Mount {
+    /// Format as an argument.
+    ///
+    /// Values containing commas are not quoted, so they will not be
+    /// parsed correctly by Docker.
+    pub fn arg(&self) -> OsString {
+        let mut out = OsString::from("type=");
+        out.push(self.kind.as_str());
+        if let Some(source) = &self.source {
+            out.push(",source=");
+            out.push(source);
+        }
+        out.push(",target=");
+        out.push(&self.target);
+        if self.read_only {
+            out.push(",readonly");
+        }
+        for opt in &self.options {
+            out.push(",");
+            out.push(opt);
+        }
+        out
+    }
+}
+
+impl FromStr for Mount {
+    type Err = ValidationError;
+
+    /// Parse the `--mount` syntax: a comma-separated list of
+    /// `key=value` pairs. The aliases `src`, `dst`, `destination`, and
+    /// `ro` are accepted. Unrecognized keys are kept in `options`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{Mount, MountType};
+    /// let mount: Mount = "type=bind,src=/src,dst=/dst,ro".parse().unwrap();
+    /// assert_eq!(mount.kind, MountType::Bind);
+    /// assert!(mount.read_only);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("mount", s, reason);
+
+        let mut mount = Self::default();
+        let mut target = None;
+        for field in s.split(',') {
+            let (key, value) = match field.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (field, None),
+            };
+            match (key, value) {
+                ("type", Some(value)) => mount.kind = value.parse()?,
+                ("source" | "src", Some(value)) => {
+                    mount.source = Some(value.into())
+                }
+                ("target" | "destination" | "dst", Some(value)) => {
+                    target = Some(value)
+                }
+                ("readonly" | "ro", None | Some("true" | "1")) => {
+                    mount.read_only = true
+                }
+                ("readonly" | "ro", Some("false" | "0")) => {
+                    mount.read_only = false
+                }
+                ("", None) => return Err(err("empty field")),
+                _ => mount.options.push(field.to_string()),
+            }
+        }
+
+        match target {
+            Some(target) if !target.is_empty() => mount.target = target.into(),
+            _ => return Err(err("missing target")),
+        }
+        Ok(mount)
+    }
+}
+
+/// Networking mode of a container or build, passed with `--network`.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::NetworkMode;
+/// assert_eq!(NetworkMode::from("host"), NetworkMode::Host);
+/// assert_eq!(
+///     NetworkMode::from("container:db"),
+///     NetworkMode::Container("db".into())
+/// );
+/// assert_eq!(NetworkMode::Named("backend".into()).arg(), "backend");
+/// assert!("container:".parse::<NetworkMode>().is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum NetworkMode {
+    /// The default bridge network.
+    Bridge,
+
+    /// Use the host's network stack.
+    Host,
+
+    /// No networking.
+    None,
+
+    /// Share the network stack of another container, specified by name
+    /// or ID.
+    Container(String),
+
+    /// A user-defined network.
+    Named(String),
+}
+
+impl NetworkMode {
+    /// Format as an argument.
+    pub fn arg(&self) -> String {
+        self.to_string()
+    }
+
+    /// Check that container and network names are not empty and only
+    /// contain characters Docker allows.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let (what, name) = match self {
+            Self::Bridge | Self::Host | Self::None => return Ok(()),
+            Self::Container(name) => ("network container", name),
+            Self::Named(name) => ("network name", name),
+        };
+        let err = |reason| ValidationError::new(what, name.as_str(), reason);
+        if name.is_empty() {
+            return Err(err("empty name"));
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c))
+        {
+            return Err(err("only [a-zA-Z0-9_.-] are allowed"));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for NetworkMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Bridge => f.write_str("bridge"),
+            Self::Host => f.write_str("host"),
+            Self::None => f.write_str("none"),
+            Self::Container(name) => write!(f, "container:{}", name),
+            Self::Named(name) => f.write_str(name),
         }
     }
+}
 
-    /// Get a `UserAndGroup` with UID and GID set to zero.
-    pub fn root() -> Self {
-        Self {
-            user: 0.into(),
-            group: Some(0.into()),
+/// Convert without validation. Use [`str::parse`] to validate.
+impl From<&str> for NetworkMode {
+    fn from(s: &str) -> Self {
+        match s {
+            "bridge" => Self::Bridge,
+            "host" => Self::Host,
+            "none" => Self::None,
+            _ => match s.strip_prefix("container:") {
+                Some(name) => Self::Container(name.to_string()),
+                None => Self::Named(s.to_string()),
+            },
         }
     }
+}
 
-    /// Format as an argument. If `group` is set, the format is
-    /// `<user>:<group>`, otherwise just `<user>`.
-    pub fn arg(&self) -> String {
-        let mut out = self.user.to_string();
-        if let Some(group) = &self.group {
-            out.push(':');
-            out.push_str(&group.to_string());
-        }
-        out
+impl From<String> for NetworkMode {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
     }
 }
 
-/// Volume specification used when running a container.
+impl FromStr for NetworkMode {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mode = Self::from(s);
+        mode.validate()?;
+        Ok(mode)
+    }
+}
+
+/// Network to connect a container to, passed with `--network`.
+///
+/// If only `name` is set, the argument is just the network name.
+/// Otherwise the `name=...,alias=...` syntax is used, which requires
+/// Docker 25 or newer.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Volume {
-    /// Either a path on the host (if an absolute path) or the name of
-    /// a volume.
-    pub src: PathBuf,
+pub struct NetworkAttachment {
+    /// Name of the network.
+    pub name: String,
 
-    /// Absolute path in the container where the volume will be
-    /// mounted.
-    pub dst: PathBuf,
+    /// Additional names the container can be reached by on this
+    /// network.
+    pub aliases: Vec<String>,
 
-    /// If true, mount the volume read-write. Defaults to `false`.
-    pub read_write: bool,
+    /// IPv4 address of the container on this network.
+    pub ip: Option<String>,
 
-    /// Additional options to set on the volume.
+    /// IPv6 address of the container on this network.
+    pub ip6: Option<String>,
+
+    /// Additional `key=value` options, such as `mac-address=...` or
+    /// `driver-opt=...`.
     pub options: Vec<String>,
 }
 
-impl Volume {
+impl NetworkAttachment {
+    /// Create a `NetworkAttachment` for the network called `name`.
+    /// All other fields are set to their defaults.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
     /// Format as an argument.
-    pub fn arg(&self) -> OsString {
-        let mut out = OsString::new();
-        out.push(&self.src);
-        out.push(":");
-        out.push(&self.dst);
-        if self.read_write {
-            out.push(":rw");
-        } else {
-            out.push(":ro");
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::NetworkAttachment;
+    /// let mut network = NetworkAttachment::new("backend");
+    /// assert_eq!(network.arg(), "backend");
+    /// network.aliases = vec!["db".into()];
+    /// network.ip = Some("10.0.0.5".into());
+    /// assert_eq!(network.arg(), "name=backend,alias=db,ip=10.0.0.5");
+    /// ```
+    pub fn arg(&self) -> String {
+        if self.aliases.is_empty()
+            && self.ip.is_none()
+            && self.ip6.is_none()
+            && self.options.is_empty()
+        {
+            return self.name.clone();
+        }
+
+        let mut out = format!("name={}", self.name);
+        for alias in &self.aliases {
+            out.push_str(",alias=");
+            out.push_str(alias);
+        }
+        if let Some(ip) = &self.ip {
+            out.push_str(",ip=");
+            out.push_str(ip);
+        }
+        if let Some(ip6) = &self.ip6 {
+            out.push_str(",ip6=");
+            out.push_str(ip6);
         }
         for opt in &self.options {
-            out.push(",");
-            out.push(opt);
+            out.push(',');
+            out.push_str(opt);
         }
         out
     }
 }
 
+impl FromStr for NetworkAttachment {
+    type Err = ValidationError;
+
+    /// Parse either a bare network name or the `name=...,alias=...`
+    /// syntax. Unrecognized keys are kept in `options`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason| ValidationError::new("network", s, reason);
+
+        if !s.contains('=') {
+            if s.is_empty() || s.contains(',') {
+                return Err(err("invalid network name"));
+            }
+            return Ok(Self::new(s));
+        }
+
+        let mut network = Self::default();
+        for field in s.split(',') {
+            match field.split_once('=') {
+                Some(("name", value)) => network.name = value.to_string(),
+                Some(("alias", value)) => {
+                    network.aliases.push(value.to_string())
+                }
+                Some(("ip", value)) => network.ip = Some(value.to_string()),
+                Some(("ip6", value)) => network.ip6 = Some(value.to_string()),
+                Some(_) => network.options.push(field.to_string()),
+                None => return Err(err("expected key=value")),
+            }
+        }
+        if network.name.is_empty() {
+            return Err(err("missing name"));
+        }
+        Ok(network)
+    }
+}
+
 /// Options for running a container.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
 pub struct RunOpt {
     /// Container image to run.
-    pub image: String,
+    pub image: ImageRef,
 
-    /// Set environment variables.
+    /// Set environment variables. Keys must not be empty or contain
+    /// `=`, and neither keys nor values may contain NUL; see
+    /// [`RunOpt::validate`].
     pub env: Vec<(OsString, OsString)>,
 
+    /// Parent cgroup to create the container's cgroup under.
+    pub cgroup_parent: Option<String>,
+
+    /// Length of the CFS scheduler period, used together with
+    /// `cpu_quota`. Must be between 1ms and 1s, and is passed in
+    /// microseconds.
+    pub cpu_period: Option<Duration>,
+
+    /// CPU time the container may use in each scheduler period. Must
+    /// be at least 1ms, and is passed in microseconds.
+    pub cpu_quota: Option<Duration>,
+
+    /// Limit the read rate from block devices.
+    pub device_read_bps: Vec<DeviceThrottle>,
+
+    /// Limit the write rate to block devices.
+    pub device_write_bps: Vec<DeviceThrottle>,
+
+    /// If set, pass `--disable-content-trust` with this value. Docker
+    /// skips image verification by default, so set this to `false` to
+    /// require signed images. Docker only.
+    pub disable_content_trust: Option<bool>,
+
+    /// Map container group IDs to host group IDs.
+    pub gidmap: Vec<IdMap>,
+
     /// If true, run the container in the background and print
     /// container ID. Defaults to `false`.
     pub detach: bool,
@@ -532,21 +3596,55 @@ pub struct RunOpt {
     /// reaps processes.
     pub init: bool,
 
+    /// Path of the init binary to use with `init`. Podman only; this
+    /// is not passed to docker, which uses the `init-path` setting in
+    /// the daemon's `daemon.json` instead. See also
+    /// [`Launcher::check_init`].
+    pub init_path: Option<PathBuf>,
+
     /// Keep stdin open even if not attached.
     pub interactive: bool,
 
+    /// IPC namespace mode.
+    pub ipc: Option<IpcMode>,
+
+    /// Labels to add to the container.
+    pub labels: Vec<(String, String)>,
+
+    /// Mounts to attach to the container.
+    pub mounts: Vec<Mount>,
+
     /// Optional name to give the container.
     pub name: Option<String>,
 
-    /// Connect a container to a network.
-    pub network: Option<String>,
+    /// Networking mode, or a network to connect the container to.
+    /// This is passed before any `networks`.
+    pub network: Option<NetworkMode>,
+
+    /// Networks to connect the container to, with optional aliases and
+    /// addresses. Multiple networks require Docker 25 or newer, or
+    /// podman.
+    pub networks: Vec<NetworkAttachment>,
 
     /// User (and optionally) group to use inside the container.
     pub user: Option<UserAndGroup>,
 
+    /// Map container user IDs to host user IDs.
+    pub uidmap: Vec<IdMap>,
+
+    /// User namespace mode. See also [`Launcher::map_user`].
+    pub userns: Option<UserNamespace>,
+
     /// Publish ports from the container to the host.
     pub publish: Vec<PublishPorts>,
 
+    /// Publish all exposed ports to random ports on the host.
+    pub publish_all: bool,
+
+    /// When to pull the image. If `None`, the image is only pulled if
+    /// it is missing.
+    pub pull: Option<PullPolicy>,
+
     /// Mount the container's root filesystem as read only.
     pub read_only: bool,
 
@@ -554,6 +3652,15 @@ pub struct RunOpt {
     /// exits. Defaults to `false`.
     pub remove: bool,
 
+    /// When to restart the container.
+    pub restart: Option<RestartPolicy>,
+
+    /// Secrets to pass to the container.
+    pub secrets: Vec<SecretSource>,
+
+    /// Security options such as seccomp and AppArmor profiles.
+    pub security_opts: Vec<SecurityOpt>,
+
     /// Allocate a psuedo-TTY.
     pub tty: bool,
 
@@ -565,15 +3672,627 @@ pub struct RunOpt {
 
     /// Optional arguments to pass to the command.
     pub args: Vec<OsString>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl RunOpt {
+    /// Create a `RunOpt` with the given `image`. All other fields are
+    /// set to their defaults.
+    pub fn new<I: Into<ImageRef>>(image: I) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set `interactive` and `tty` according to `interactivity`. If
+    /// this allocates a TTY, `init` is set as well so that signals
+    /// such as Ctrl-C reach the process, which would otherwise run as
+    /// PID 1 and ignore them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, Interactivity, Launcher, RunOpt};
+    /// let mut opt = RunOpt::new("alpine");
+    /// opt.set_interactivity(Interactivity::Attached);
+    /// assert_eq!(
+    ///     Launcher::from(BaseCommand::Docker).run(opt).command_line_lossy(),
+    ///     "docker run --init --interactive --tty alpine"
+    /// );
+    /// ```
+    pub fn set_interactivity(&mut self, interactivity: Interactivity) {
+        (self.interactive, self.tty) = interactivity.flags();
+        self.init |= self.tty;
+    }
+
+    /// Layer `overrides` on top of these options, returning the
+    /// result.
+    ///
+    /// * `image` is replaced unless `overrides.image` is the default.
+    /// * `Option` fields are replaced if set in `overrides`.
+    /// * `bool` fields are true if they are true in either.
+    /// * `env` and `labels` are combined, with values from `overrides`
+    ///   replacing any with the same key.
+    /// * `command` and `args` are replaced together if either is set in
+    ///   `overrides`, as are `gidmap` and `uidmap` if either is
+    ///   non-empty, since these only make sense as a whole.
+    /// * All other lists are appended.
+    ///
+    /// Merging is one-way for `bool` fields: `false` in `overrides`
+    /// means "not set", so an override can turn on flags such as
+    /// `remove`, `read_only`, `init`, and `tty`, but can never turn
+    /// them off. To turn a flag off, set it to `false` on the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::RunOpt;
+    /// let mut base = RunOpt::new("app:1.0");
+    /// base.env = vec![("LOG".into(), "info".into())];
+    /// base.read_only = true;
+    /// let mut prod = RunOpt::default();
+    /// prod.env = vec![("LOG".into(), "warn".into())];
+    /// prod.name = Some("app".into());
+    /// let opt = base.merge(&prod);
+    /// assert_eq!(opt.image.to_string(), "app:1.0");
+    /// assert_eq!(opt.env, [("LOG".into(), "warn".into())]);
+    /// assert!(opt.read_only);
+    /// assert_eq!(opt.name.as_deref(), Some("app"));
+    ///
+    /// // An override cannot turn a flag off.
+    /// let mut writable = RunOpt::default();
+    /// writable.read_only = false;
+    /// assert!(base.merge(&writable).read_only);
+    /// ```
+    pub fn merge(&self, overrides: &RunOpt) -> RunOpt {
+        // Destructure so that new fields are not missed.
+        let RunOpt {
+            image,
+            env,
+            cgroup_parent,
+            cpu_period,
+            cpu_quota,
+            device_read_bps,
+            device_write_bps,
+            disable_content_trust,
+            gidmap,
+            detach,
+            init,
+            init_path,
+            interactive,
+            ipc,
+            labels,
+            mounts,
+            name,
+            network,
+            networks,
+            user,
+            uidmap,
+            userns,
+            publish,
+            publish_all,
+            pull,
+            read_only,
+            remove,
+            restart,
+            secrets,
+            security_opts,
+            tty,
+            volumes,
+            command,
+            args,
+            extra_args,
+        } = overrides.clone();
+
+        fn append<T>(mut base: Vec<T>, mut more: Vec<T>) -> Vec<T> {
+            base.append(&mut more);
+            base
+        }
+
+        let mut out = self.clone();
+        if image != ImageRef::default() {
+            out.image = image;
+        }
+        let mut env = env;
+        merge_pairs(&self.env, &mut env);
+        out.env = env;
+        out.cgroup_parent = cgroup_parent.or(out.cgroup_parent);
+        out.cpu_period = cpu_period.or(out.cpu_period);
+        out.cpu_quota = cpu_quota.or(out.cpu_quota);
+        out.device_read_bps = append(out.device_read_bps, device_read_bps);
+        out.device_write_bps = append(out.device_write_bps, device_write_bps);
+        out.disable_content_trust =
+            disable_content_trust.or(out.disable_content_trust);
+        if !gidmap.is_empty() || !uidmap.is_empty() {
+            out.gidmap = gidmap;
+            out.uidmap = uidmap;
+        }
+        out.detach |= detach;
+        out.init |= init;
+        out.init_path = init_path.or(out.init_path);
+        out.interactive |= interactive;
+        out.ipc = ipc.or(out.ipc);
+        let mut labels = labels;
+        merge_pairs(&self.labels, &mut labels);
+        out.labels = labels;
+        out.mounts = append(out.mounts, mounts);
+        out.name = name.or(out.name);
+        out.network = network.or(out.network);
+        out.networks = append(out.networks, networks);
+        out.user = user.or(out.user);
+        out.userns = userns.or(out.userns);
+        out.publish = append(out.publish, publish);
+        out.publish_all |= publish_all;
+        out.pull = pull.or(out.pull);
+        out.read_only |= read_only;
+        out.remove |= remove;
+        out.restart = restart.or(out.restart);
+        out.secrets = append(out.secrets, secrets);
+        out.security_opts = append(out.security_opts, security_opts);
+        out.tty |= tty;
+        out.volumes = append(out.volumes, volumes);
+        if command.is_some() || !args.is_empty() {
+            out.command = command;
+            out.args = args;
+        }
+        out.extra_args = append(out.extra_args, extra_args);
+        out
+    }
+
+    /// Check the options that can be validated without running
+    /// anything:
+    ///
+    /// * The image reference (see [`ImageRef::validate`]).
+    /// * Environment variables and labels (see [`validate_key_value`]).
+    /// * The CPU period and quota ranges.
+    /// * The network mode (see [`NetworkMode::validate`]).
+    /// * Volumes (see [`Volume::validate`]).
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.image.validate()?;
+        for (key, value) in &self.env {
+            validate_key_value("environment variable", key, value)?;
+        }
+        for (key, value) in &self.labels {
+            validate_key_value("label", key, value)?;
+        }
+        if let Some(period) = self.cpu_period {
+            if period < Duration::from_millis(1)
+                || period > Duration::from_secs(1)
+            {
+                return Err(ValidationError::new(
+                    "CPU period",
+                    format!("{:?}", period),
+                    "must be between 1ms and 1s",
+                ));
+            }
+        }
+        if let Some(quota) = self.cpu_quota {
+            if quota < Duration::from_millis(1) {
+                return Err(ValidationError::new(
+                    "CPU quota",
+                    format!("{:?}", quota),
+                    "must be at least 1ms",
+                ));
+            }
+        }
+        if let Some(network) = &self.network {
+            network.validate()?;
+        }
+        for volume in &self.volumes {
+            volume.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for removing containers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct RemoveContainersOpt {
+    /// Containers to remove, specified as names or IDs.
+    pub containers: Vec<ContainerRef>,
+
+    /// Remove the container even if it is running, killing it first.
+    pub force: bool,
+
+    /// Also remove anonymous volumes attached to the container.
+    pub volumes: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl RemoveContainersOpt {
+    /// Create a `RemoveContainersOpt` for the given `containers`. All
+    /// other fields are set to their defaults.
+    pub fn new<I>(containers: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<ContainerRef>,
+    {
+        Self {
+            containers: containers.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for removing images.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct RemoveImagesOpt {
+    /// Images to remove, specified as references or IDs. Removing a
+    /// reference only untags the image if it has other tags.
+    pub images: Vec<String>,
+
+    /// Remove the image even if it is in use by a stopped container or
+    /// has multiple tags.
+    pub force: bool,
+
+    /// Do not delete untagged parent images. Docker only.
+    pub no_prune: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl RemoveImagesOpt {
+    /// Create a `RemoveImagesOpt` for the given `images`. All other
+    /// fields are set to their defaults.
+    pub fn new<I, S>(images: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            images: images.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for saving images to a tar archive.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SaveOpt {
+    /// Images to save.
+    pub images: Vec<ImageRef>,
+
+    /// Write the archive to this file. If `None`, the archive is
+    /// written to stdout.
+    pub output: Option<PathBuf>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl SaveOpt {
+    /// Create a `SaveOpt` for the given `images`. All other fields are
+    /// set to their defaults.
+    pub fn new<I>(images: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<ImageRef>,
+    {
+        Self {
+            images: images.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for searching registries for images.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SearchOpt {
+    /// Search term, or with [`SearchOpt::list_tags`] the repository
+    /// whose tags to list.
+    pub term: String,
+
+    /// Format the output using a Go template, or `json`.
+    pub format: Option<String>,
+
+    /// Maximum number of results. With [`SearchOpt::list_tags`],
+    /// podman defaults to 25 tags.
+    pub limit: Option<u32>,
+
+    /// List the tags of the repository [`SearchOpt::term`] instead of
+    /// searching. Podman only.
+    pub list_tags: bool,
+
+    /// Do not truncate the output.
+    pub no_trunc: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl SearchOpt {
+    /// Create a `SearchOpt` for `term`. All other fields are set to
+    /// their defaults.
+    pub fn new<S: Into<String>>(term: S) -> Self {
+        Self {
+            term: term.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for scanning an image with `docker scout cves`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ScoutCvesOpt {
+    /// Image to scan.
+    pub image: ImageRef,
+
+    /// Exit with status 2 if vulnerabilities are found.
+    pub exit_code: bool,
+
+    /// Output format, e.g. `packages`, `sarif`, or `markdown`.
+    pub format: Option<String>,
+
+    /// Only report vulnerabilities that have a fix available.
+    pub only_fixed: bool,
+
+    /// Only report vulnerabilities with these severities, e.g.
+    /// `critical` and `high`.
+    pub only_severity: Vec<String>,
+
+    /// Write the report to a file instead of stdout.
+    pub output: Option<PathBuf>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl ScoutCvesOpt {
+    /// Create a `ScoutCvesOpt` for `image`. All other fields are set to
+    /// their defaults.
+    pub fn new<I: Into<ImageRef>>(image: I) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Script used by [`Launcher::shell`] to prefer `bash` over `sh`.
+const SHELL_PROBE: &str =
+    "if command -v bash >/dev/null 2>&1; then exec bash; fi; exec sh";
+
+/// Where [`Launcher::shell`] opens a shell.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ShellTarget {
+    /// Run the shell in an existing container.
+    Container(ContainerRef),
+
+    /// Run the shell in a new, temporary container created from an
+    /// image.
+    Image(ImageRef),
+}
+
+impl From<ContainerRef> for ShellTarget {
+    fn from(container: ContainerRef) -> Self {
+        Self::Container(container)
+    }
+}
+
+impl From<ImageRef> for ShellTarget {
+    fn from(image: ImageRef) -> Self {
+        Self::Image(image)
+    }
+}
+
+impl From<&str> for ShellTarget {
+    /// Treat the string as an image reference.
+    fn from(image: &str) -> Self {
+        Self::Image(image.into())
+    }
+}
+
+/// Options for opening an interactive shell.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ShellOpt {
+    /// Shell to run, e.g. `bash`. If `None`, `bash` is used if it is
+    /// installed, otherwise `sh`.
+    pub shell: Option<OsString>,
+
+    /// Set environment variables.
+    pub env: Vec<(OsString, OsString)>,
+
+    /// User (and optionally group) to run the shell as.
+    pub user: Option<UserAndGroup>,
+
+    /// Working directory for the shell.
+    pub workdir: Option<PathBuf>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for showing disk usage.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SystemDfOpt {
+    /// Format the output using a Go template, or `json`.
+    pub format: Option<String>,
+
+    /// Show detailed information on space usage.
+    pub verbose: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+/// Options for serving the API with `podman system service`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SystemServiceOpt {
+    /// URI to listen on, e.g. `unix:///run/user/1000/podman/podman.sock`
+    /// or `tcp://localhost:8080`. If not set, podman's default socket
+    /// is used.
+    pub uri: Option<String>,
+
+    /// Exit after this long without any requests. Zero means never
+    /// exit. If not set, podman's default of five seconds is used.
+    /// Partial seconds are rounded up.
+    pub time: Option<DurationArg>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl SystemServiceOpt {
+    /// Create a `SystemServiceOpt` that listens on `uri`. All other
+    /// fields are set to their defaults.
+    pub fn new<S: Into<String>>(uri: S) -> Self {
+        Self {
+            uri: Some(uri.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for showing resource usage of containers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct StatsOpt {
+    /// Containers to show, specified as names or IDs. If empty, all
+    /// running containers are shown.
+    pub containers: Vec<ContainerRef>,
+
+    /// Show all containers, not just running ones.
+    pub all: bool,
+
+    /// Format the output using a Go template, or `json`.
+    pub format: Option<String>,
+
+    /// Print the current usage once and exit, instead of updating it
+    /// continuously.
+    pub no_stream: bool,
+
+    /// Do not truncate the output.
+    pub no_trunc: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl StatsOpt {
+    /// Create a `StatsOpt` for the given `containers`. All other
+    /// fields are set to their defaults.
+    pub fn new<I>(containers: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<ContainerRef>,
+    {
+        Self {
+            containers: containers.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
 }
 
 /// Options for stopping a container.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
 pub struct StopOpt {
     /// Containers to stop, specified as names or IDs.
-    pub containers: Vec<String>,
+    ///
+    /// This used to be a `Vec<String>`. Parse each string into a
+    /// [`ContainerRef`] (or a [`ContainerName`] or [`ContainerId`]),
+    /// or pass them to [`StopOpt::new`].
+    pub containers: Vec<ContainerRef>,
+
+    /// Time to wait for stop before killing the container. If None,
+    /// defaults to 10 seconds. Partial seconds are rounded up.
+    pub time: Option<DurationArg>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl StopOpt {
+    /// Create a `StopOpt` for the given `containers`. All other fields
+    /// are set to their defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{ContainerName, StopOpt};
+    /// let opt = StopOpt::new([
+    ///     "web".parse::<ContainerName>()?,
+    ///     "db".parse::<ContainerName>()?,
+    /// ]);
+    /// assert_eq!(opt.containers.len(), 2);
+    /// # Ok::<(), docker_command::ValidationError>(())
+    /// ```
+    pub fn new<I>(containers: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<ContainerRef>,
+    {
+        Self {
+            containers: containers.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for displaying the signatures of images.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct TrustInspectOpt {
+    /// Images to inspect.
+    pub images: Vec<ImageRef>,
 
-    /// Seconds to wait for stop before killing the container. If None,
-    /// defaults to 10 seconds.
-    pub time: Option<u32>,
+    /// Print the information in a human-friendly format.
+    pub pretty: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl TrustInspectOpt {
+    /// Create a `TrustInspectOpt` for the given `images`. All other
+    /// fields are set to their defaults.
+    pub fn new<I>(images: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<ImageRef>,
+    {
+        Self {
+            images: images.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for signing an image.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct TrustSignOpt {
+    /// Image to sign. This must include a tag.
+    pub image: ImageRef,
+
+    /// Sign the local image without pushing it.
+    pub local: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl TrustSignOpt {
+    /// Create a `TrustSignOpt` for `image`. All other fields are set to
+    /// their defaults.
+    pub fn new<I: Into<ImageRef>>(image: I) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
 }
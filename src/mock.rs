@@ -0,0 +1,282 @@
+//! Fake engine for testing code that uses the runner helpers.
+
+use crate::{BaseCommand, Error, Launcher};
+use command_run::{Command, ErrorKind, Output};
+use std::ffi::OsString;
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+/// Environment variable set on the base command of a
+/// [`MockLauncher`]'s launcher, identifying the mock.
+const MOCK_ID_VAR: &str = "DOCKER_COMMAND_MOCK_ID";
+
+/// Canned result of a command run through a [`MockLauncher`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct MockResponse {
+    /// Data written to stdout.
+    pub stdout: String,
+
+    /// Data written to stderr.
+    pub stderr: String,
+
+    /// Exit code.
+    pub exit_code: i32,
+}
+
+impl MockResponse {
+    /// Exit successfully after writing `stdout`.
+    pub fn success<S: Into<String>>(stdout: S) -> Self {
+        Self {
+            stdout: stdout.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Exit with `exit_code` after writing `stderr`.
+    pub fn failure<S: Into<String>>(exit_code: i32, stderr: S) -> Self {
+        Self {
+            stderr: stderr.into(),
+            exit_code,
+            ..Default::default()
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            status: exit_status(self.exit_code),
+            stdout: self.stdout.clone().into_bytes(),
+            stderr: self.stderr.clone().into_bytes(),
+        }
+    }
+}
+
+/// Get an `ExitStatus` for a process that exited with `code`.
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+/// Get an `ExitStatus` for a process that exited with `code`.
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
+#[derive(Debug)]
+struct Expectation {
+    args: Vec<OsString>,
+    response: MockResponse,
+    used: bool,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    /// Number of arguments in the base command, which are not
+    /// compared.
+    skip: usize,
+    expectations: Vec<Expectation>,
+    calls: Vec<Vec<OsString>>,
+}
+
+type SharedState = Arc<Mutex<MockState>>;
+
+/// Live mocks, by ID.
+static REGISTRY: Mutex<Vec<(u64, SharedState)>> = Mutex::new(Vec::new());
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A panic in one test must not break the mocks of others.
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Whether `cmd` was created by a [`MockLauncher`]'s launcher.
+pub(crate) fn is_mocked(cmd: &Command) -> bool {
+    cmd.env.contains_key(&OsString::from(MOCK_ID_VAR))
+}
+
+/// Return the canned output for `cmd` if it was created by a
+/// [`MockLauncher`]'s launcher.
+///
+/// Panics if the mock has no matching expectation.
+pub(crate) fn intercept(cmd: &Command) -> Option<Output> {
+    let id: u64 = cmd
+        .env
+        .get(&OsString::from(MOCK_ID_VAR))?
+        .to_str()?
+        .parse()
+        .ok()?;
+    let state = lock(&REGISTRY)
+        .iter()
+        .find(|(other, _)| *other == id)
+        .map(|(_, state)| state.clone())?;
+    let mut state = lock(&state);
+
+    let args = cmd.args[state.skip.min(cmd.args.len())..].to_vec();
+    state.calls.push(args.clone());
+    let Some(expectation) = state
+        .expectations
+        .iter_mut()
+        .find(|expectation| !expectation.used && expectation.args == args)
+    else {
+        drop(state);
+        panic!("unexpected command: {:?}", args);
+    };
+    expectation.used = true;
+    Some(expectation.response.output())
+}
+
+/// Like [`intercept`], but return an error for a failure if `check`
+/// is set, as [`Command::run`] does.
+pub(crate) fn intercept_checked(
+    cmd: &Command,
+) -> Option<Result<Output, command_run::Error>> {
+    let output = intercept(cmd)?;
+    if cmd.check && !output.status.success() {
+        return Some(Err(command_run::Error {
+            command: cmd.clone(),
+            kind: ErrorKind::Exit(output.status),
+        }));
+    }
+    Some(Ok(output))
+}
+
+/// Pipe `source` into `sink` where either may be mocked. A mocked
+/// command's canned stdout is passed on, and a mocked sink ignores its
+/// input. A command that is not mocked is run for real.
+pub(crate) fn pipe(source: &Command, sink: &Command) -> Result<(), Error> {
+    let input = match intercept_checked(source) {
+        Some(output) => output?.stdout,
+        None => {
+            let mut source = source.clone();
+            source.capture = true;
+            source.log_command = false;
+            source.run()?.stdout
+        }
+    };
+    match intercept_checked(sink) {
+        Some(output) => {
+            output?;
+            Ok(())
+        }
+        None => {
+            let mut sink = sink.clone();
+            sink.log_command = false;
+            crate::runner::run_with_stdin(&sink, &input)
+        }
+    }
+}
+
+/// [`Launcher`] whose commands are answered from a list of expected
+/// commands instead of being run, for offline tests of code built on
+/// the helpers that run commands.
+///
+/// Commands run with [`CommandExt::run_logged`],
+/// [`CommandExt::run_with_timeout`], and [`CommandExt::pipe_to`], and
+/// by helpers that write to a command's stdin such as
+/// [`Launcher::write_file`], are matched against the expectations by
+/// their arguments, leaving out the base command. Each expectation is
+/// used once, and they can be matched in any order. A command with no
+/// matching expectation causes a panic, as does dropping the mock with
+/// expectations left unused. [`CommandExt::spawn_cancellable`] and
+/// [`CommandExt::run_with_progress`] are not mocked, and panic if
+/// given a mocked command.
+///
+/// The launcher marks its commands with an environment variable so
+/// that they can be recognized.
+///
+/// [`CommandExt::run_logged`]: crate::CommandExt::run_logged
+/// [`CommandExt::run_with_timeout`]: crate::CommandExt::run_with_timeout
+/// [`CommandExt::pipe_to`]: crate::CommandExt::pipe_to
+/// [`CommandExt::spawn_cancellable`]: crate::CommandExt::spawn_cancellable
+/// [`CommandExt::run_with_progress`]: crate::CommandExt::run_with_progress
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{BaseCommand, MockLauncher, MockResponse};
+/// let mock = MockLauncher::new(BaseCommand::Docker);
+/// mock.expect(
+///     ["volume", "ls", "--filter", "name=data", "--format", "{{.Name}}"],
+///     MockResponse::success("data\n"),
+/// );
+/// assert!(mock.launcher().volume_exists("data").unwrap());
+/// ```
+#[derive(Debug)]
+pub struct MockLauncher {
+    id: u64,
+    state: SharedState,
+    launcher: Launcher,
+}
+
+impl MockLauncher {
+    /// Create a mock that behaves like `base`.
+    pub fn new(base: BaseCommand) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+        let mut launcher = Launcher::from(base);
+        launcher
+            .base_command
+            .env
+            .insert(MOCK_ID_VAR.into(), id.to_string().into());
+        let state = SharedState::default();
+        lock(&state).skip = launcher.base_command.args.len();
+        lock(&REGISTRY).push((id, state.clone()));
+        Self {
+            id,
+            state,
+            launcher,
+        }
+    }
+
+    /// Launcher whose commands are answered by the mock.
+    pub fn launcher(&self) -> &Launcher {
+        &self.launcher
+    }
+
+    /// Expect a command with `args` (not including the base command),
+    /// answering it with `response`.
+    pub fn expect<I, S>(&self, args: I, response: MockResponse) -> &Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        lock(&self.state).expectations.push(Expectation {
+            args: args.into_iter().map(Into::into).collect(),
+            response,
+            used: false,
+        });
+        self
+    }
+
+    /// Arguments of the commands run so far, in order.
+    pub fn calls(&self) -> Vec<Vec<OsString>> {
+        lock(&self.state).calls.clone()
+    }
+
+    /// Panic if any expectation has not been used.
+    pub fn verify(&self) {
+        let unused: Vec<_> = lock(&self.state)
+            .expectations
+            .iter()
+            .filter(|expectation| !expectation.used)
+            .map(|expectation| expectation.args.clone())
+            .collect();
+        if !unused.is_empty() {
+            panic!("expected commands were not run: {:?}", unused);
+        }
+    }
+}
+
+impl Drop for MockLauncher {
+    fn drop(&mut self) {
+        lock(&REGISTRY).retain(|(id, _)| *id != self.id);
+        if !thread::panicking() {
+            self.verify();
+        }
+    }
+}
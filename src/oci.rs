@@ -0,0 +1,147 @@
+//! Standard [OCI image annotations], for use as labels or annotations
+//! on built images.
+//!
+//! [OCI image annotations]: https://github.com/opencontainers/image-spec/blob/main/annotations.md
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Date and time the image was built, in RFC 3339 format.
+pub const CREATED: &str = "org.opencontainers.image.created";
+
+/// Contact details of the people or organization responsible for the
+/// image.
+pub const AUTHORS: &str = "org.opencontainers.image.authors";
+
+/// URL to find more information about the image.
+pub const URL: &str = "org.opencontainers.image.url";
+
+/// URL to get documentation about the image.
+pub const DOCUMENTATION: &str = "org.opencontainers.image.documentation";
+
+/// URL to get the source code for building the image.
+pub const SOURCE: &str = "org.opencontainers.image.source";
+
+/// Version of the packaged software.
+pub const VERSION: &str = "org.opencontainers.image.version";
+
+/// Source control revision identifier, such as a git commit hash.
+pub const REVISION: &str = "org.opencontainers.image.revision";
+
+/// Name of the distributing entity, organization, or individual.
+pub const VENDOR: &str = "org.opencontainers.image.vendor";
+
+/// License(s) under which the contained software is distributed, as an
+/// SPDX expression.
+pub const LICENSES: &str = "org.opencontainers.image.licenses";
+
+/// Human-readable title of the image.
+pub const TITLE: &str = "org.opencontainers.image.title";
+
+/// Human-readable description of the software packaged in the image.
+pub const DESCRIPTION: &str = "org.opencontainers.image.description";
+
+/// Format `time` as an RFC 3339 timestamp in UTC with second
+/// precision, as expected for [`CREATED`].
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::oci;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// assert_eq!(oci::format_time(time), "2023-11-14T22:13:20Z");
+/// ```
+pub fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+
+    // Howard Hinnant's `civil_from_days`.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Traceability metadata for an image, converted to the standard OCI
+/// annotation keys by [`OciMetadata::pairs`].
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::oci::OciMetadata;
+/// use docker_command::BuildOpt;
+///
+/// let mut meta = OciMetadata::default();
+/// meta.source = Some("https://github.com/example/app".into());
+/// meta.revision = Some("0123abc".into());
+///
+/// let mut opt = BuildOpt::new(".");
+/// opt.add_oci_labels(&meta);
+/// assert_eq!(
+///     opt.labels[1],
+///     ("org.opencontainers.image.revision".into(), "0123abc".into())
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct OciMetadata {
+    /// Value for [`CREATED`]. See [`format_time`].
+    pub created: Option<String>,
+
+    /// Value for [`SOURCE`].
+    pub source: Option<String>,
+
+    /// Value for [`REVISION`].
+    pub revision: Option<String>,
+
+    /// Value for [`VERSION`].
+    pub version: Option<String>,
+
+    /// Any other annotations, as `(key, value)` pairs.
+    pub other: Vec<(String, String)>,
+}
+
+impl OciMetadata {
+    /// Set `created` to the current time.
+    pub fn created_now(mut self) -> Self {
+        self.created = Some(format_time(SystemTime::now()));
+        self
+    }
+
+    /// Get the metadata as `(key, value)` pairs. Fields that are not
+    /// set are skipped.
+    pub fn pairs(&self) -> Vec<(String, String)> {
+        let mut pairs: Vec<(String, String)> = [
+            (CREATED, &self.created),
+            (SOURCE, &self.source),
+            (REVISION, &self.revision),
+            (VERSION, &self.version),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| {
+            value.as_ref().map(|value| (key.to_string(), value.clone()))
+        })
+        .collect();
+        pairs.extend(self.other.iter().cloned());
+        pairs
+    }
+}
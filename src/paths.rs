@@ -0,0 +1,261 @@
+use crate::{
+    BuildOpt, Error, Launcher, Mount, MountType, RunOpt, ValidationError,
+    Volume, VolumeSource,
+};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// What to do with relative host paths. See
+/// [`Launcher::with_relative_paths`].
+///
+/// Docker requires bind mount sources to be absolute, and resolves a
+/// relative build context against the working directory of the client
+/// rather than of the program that built the command. Windows paths
+/// with a drive letter count as absolute.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum RelativePaths {
+    /// Leave relative paths as they are (the default).
+    #[default]
+    Keep,
+
+    /// Join relative paths onto the given base directory, without
+    /// checking that the result exists.
+    Join(PathBuf),
+
+    /// Join relative paths onto the given base directory and
+    /// canonicalize the result, which fails if it does not exist.
+    Canonicalize(PathBuf),
+
+    /// Fail on relative paths.
+    Reject,
+}
+
+impl RelativePaths {
+    /// Apply the policy to `path`, using `what` to describe it in
+    /// errors.
+    fn resolve(
+        &self,
+        what: &'static str,
+        path: &mut PathBuf,
+    ) -> Result<(), ValidationError> {
+        if path.is_absolute() || split_drive(path).is_some() {
+            return Ok(());
+        }
+        match self {
+            Self::Keep => {}
+            Self::Join(base) => *path = base.join(&*path),
+            Self::Canonicalize(base) => {
+                *path = base.join(&*path).canonicalize().map_err(|err| {
+                    ValidationError::new(
+                        what,
+                        path.to_string_lossy(),
+                        err.to_string(),
+                    )
+                })?;
+            }
+            Self::Reject => {
+                return Err(ValidationError::new(
+                    what,
+                    path.to_string_lossy(),
+                    "relative paths are not allowed",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How to rewrite Windows host paths such as `C:\src` in bind mounts.
+/// See [`Launcher::with_windows_paths`].
+///
+/// Only absolute paths starting with a drive letter are rewritten.
+/// Backslashes are changed to forward slashes and the drive letter is
+/// lowercased.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::WindowsPathStyle;
+/// use std::path::Path;
+/// let path = Path::new(r"C:\src\app");
+/// assert_eq!(WindowsPathStyle::Posix.translate(path), Path::new("/c/src/app"));
+/// assert_eq!(
+///     WindowsPathStyle::DoubleSlash.translate(path),
+///     Path::new("//c/src/app")
+/// );
+/// assert_eq!(
+///     WindowsPathStyle::Wsl("/mnt".into()).translate(path),
+///     Path::new("/mnt/c/src/app")
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum WindowsPathStyle {
+    /// `/c/src`, as used by Docker Toolbox and Git Bash.
+    Posix,
+
+    /// `//c/src`, which keeps MSYS-based shells (such as Git Bash)
+    /// from converting the path back to a Windows path.
+    DoubleSlash,
+
+    /// Drive mounted under the given directory, e.g. `/mnt` for
+    /// `/mnt/c/src` as seen from a WSL distribution.
+    Wsl(PathBuf),
+}
+
+/// Split a Windows path into its lowercase drive letter and the rest
+/// of the path with forward slashes, or None if `path` does not start
+/// with a drive letter.
+fn split_drive(path: &Path) -> Option<(char, String)> {
+    let s = path.to_str()?;
+    let mut chars = s.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+    if chars.next() != Some(':') {
+        return None;
+    }
+    let rest = chars.as_str();
+    if !(rest.is_empty() || rest.starts_with(['\\', '/'])) {
+        return None;
+    }
+    let rest = rest.trim_start_matches(['\\', '/']).replace('\\', "/");
+    Some((drive.to_ascii_lowercase(), rest))
+}
+
+impl WindowsPathStyle {
+    /// Rewrite `path` if it is a Windows path with a drive letter,
+    /// otherwise return it unchanged.
+    pub fn translate<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        let Some((drive, rest)) = split_drive(path) else {
+            return Cow::Borrowed(path);
+        };
+        let mut out = match self {
+            Self::Posix => format!("/{}", drive),
+            Self::DoubleSlash => format!("//{}", drive),
+            Self::Wsl(root) => {
+                let root = root.to_string_lossy();
+                format!("{}/{}", root.trim_end_matches('/'), drive)
+            }
+        };
+        if !rest.is_empty() {
+            out.push('/');
+            out.push_str(&rest);
+        }
+        Cow::Owned(out.into())
+    }
+}
+
+impl Launcher {
+    /// Rewrite Windows host paths in the [`Volume`]s and bind
+    /// [`Mount`]s of containers created by this launcher, for engines
+    /// that expect a Unix-style path. Named volumes and paths without
+    /// a drive letter are left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, Launcher, RunOpt, Volume, WindowsPathStyle};
+    /// let launcher = Launcher::from(BaseCommand::Docker)
+    ///     .with_windows_paths(WindowsPathStyle::Posix);
+    /// let mut opt = RunOpt::new("alpine");
+    /// opt.volumes = vec![Volume::bind(r"C:\src", "/src")];
+    /// assert_eq!(
+    ///     launcher.run(opt).command_line_lossy(),
+    ///     "docker run --volume /c/src:/src alpine"
+    /// );
+    /// ```
+    pub fn with_windows_paths(mut self, style: WindowsPathStyle) -> Self {
+        self.windows_paths = Some(style);
+        self
+    }
+
+    /// Set what to do with relative host paths.
+    ///
+    /// The policy is applied by [`Launcher::try_run`] and
+    /// [`Launcher::try_build`], and by the helpers that run containers
+    /// or builds, such as [`Launcher::build_and_run`] and
+    /// [`Launcher::reconcile`]. The infallible [`Launcher::run`] and
+    /// [`Launcher::build`] leave paths unchanged; call
+    /// [`Launcher::resolve_run_paths`] or
+    /// [`Launcher::resolve_build_paths`] first when using them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, Launcher, RelativePaths, RunOpt, Volume};
+    /// let launcher = Launcher::from(BaseCommand::Docker)
+    ///     .with_relative_paths(RelativePaths::Join("/work".into()));
+    /// let mut opt = RunOpt::new("alpine");
+    /// opt.volumes = vec![Volume::bind("src", "/src")];
+    /// assert_eq!(
+    ///     launcher.try_run(opt).unwrap().command_line_lossy(),
+    ///     "docker run --volume /work/src:/src alpine"
+    /// );
+    /// ```
+    pub fn with_relative_paths(mut self, policy: RelativePaths) -> Self {
+        self.relative_paths = policy;
+        self
+    }
+
+    /// Apply the launcher's [`RelativePaths`] policy to the host paths
+    /// of the [`Volume`]s and bind [`Mount`]s in `opt`. Call this
+    /// before [`Launcher::run`] to fail early on a path the engine
+    /// would reject.
+    pub fn resolve_run_paths(&self, opt: &mut RunOpt) -> Result<(), Error> {
+        for vol in &mut opt.volumes {
+            if let VolumeSource::HostPath(path) = &mut vol.src {
+                self.relative_paths.resolve("volume source", path)?;
+            }
+        }
+        for mount in &mut opt.mounts {
+            if let (MountType::Bind, Some(source)) =
+                (mount.kind, &mut mount.source)
+            {
+                self.relative_paths.resolve("mount source", source)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the launcher's [`RelativePaths`] policy to the context
+    /// and Dockerfile in `opt`. Call this before [`Launcher::build`]
+    /// to fail early on a path the engine would reject.
+    pub fn resolve_build_paths(&self, opt: &mut BuildOpt) -> Result<(), Error> {
+        self.relative_paths
+            .resolve("build context", &mut opt.context)?;
+        if let Some(dockerfile) = &mut opt.dockerfile {
+            self.relative_paths.resolve("Dockerfile", dockerfile)?;
+        }
+        Ok(())
+    }
+}
+
+/// Get `vol` with its host path translated with `style`, if needed.
+pub(crate) fn host_volume<'a>(
+    style: Option<&WindowsPathStyle>,
+    vol: &'a Volume,
+) -> Cow<'a, Volume> {
+    if let (Some(style), VolumeSource::HostPath(path)) = (style, &vol.src) {
+        if let Cow::Owned(path) = style.translate(path) {
+            let mut vol = vol.clone();
+            vol.src = VolumeSource::HostPath(path);
+            return Cow::Owned(vol);
+        }
+    }
+    Cow::Borrowed(vol)
+}
+
+/// Get `mount` with its host path translated with `style`, if needed.
+pub(crate) fn host_mount<'a>(
+    style: Option<&WindowsPathStyle>,
+    mount: &'a Mount,
+) -> Cow<'a, Mount> {
+    if let (Some(style), MountType::Bind, Some(source)) =
+        (style, mount.kind, &mount.source)
+    {
+        if let Cow::Owned(source) = style.translate(source) {
+            let mut mount = mount.clone();
+            mount.source = Some(source);
+            return Cow::Owned(mount);
+        }
+    }
+    Cow::Borrowed(mount)
+}
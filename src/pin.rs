@@ -0,0 +1,78 @@
+use crate::runner::stdout;
+use crate::skopeo::{InspectOpt, Location, Skopeo};
+use crate::{
+    CommandExt, Error, ImageRef, Launcher, PullOpt, RunOpt, ValidationError,
+};
+
+impl Launcher {
+    /// Pull `image` and get a reference to it pinned to its digest in
+    /// the registry, e.g. `alpine:3` becomes `alpine@sha256:...`.
+    ///
+    /// The digest is taken from the image's `RepoDigests`, choosing
+    /// the entry for the same registry and repository as `image`. An
+    /// image that already has a digest is returned unchanged, without
+    /// pulling.
+    pub fn resolve_digest(&self, image: &ImageRef) -> Result<ImageRef, Error> {
+        if image.digest().is_some() {
+            return Ok(image.clone());
+        }
+        self.enforce(self.pull(PullOpt::new(image.clone())))?
+            .run_logged()?;
+        let config = self.image_config(image)?;
+        let name = image.normalize().name();
+        config
+            .repo_digests
+            .iter()
+            .map(|repo_digest| ImageRef::from(repo_digest.as_str()))
+            .find(|repo_digest| repo_digest.normalize().name() == name)
+            .and_then(|repo_digest| {
+                repo_digest.digest().map(|d| image.pinned(d))
+            })
+            .ok_or_else(|| {
+                ValidationError::new(
+                    "image",
+                    image.to_string(),
+                    "no digest for this repository",
+                )
+                .into()
+            })
+    }
+
+    /// Replace [`RunOpt::image`] with the result of
+    /// [`Launcher::resolve_digest`], so that the same image is run even
+    /// if the tag is later moved.
+    pub fn pin_image(&self, opt: &mut RunOpt) -> Result<(), Error> {
+        opt.image = self.resolve_digest(&opt.image)?;
+        Ok(())
+    }
+}
+
+impl Skopeo {
+    /// Get a reference to `image` pinned to the digest reported by
+    /// `skopeo inspect`, without pulling the image. An image that
+    /// already has a digest is returned unchanged.
+    pub fn resolve_digest(&self, image: &ImageRef) -> Result<ImageRef, Error> {
+        if image.digest().is_some() {
+            return Ok(image.clone());
+        }
+        let mut opt = InspectOpt::new(Location::Registry(image.clone()));
+        opt.format = Some("{{.Digest}}".into());
+        let cmd = self.inspect(opt);
+        let output = stdout(cmd.clone())?;
+        let pinned = image.pinned(output.as_str());
+        match pinned.validate() {
+            Ok(()) => Ok(pinned),
+            _ => Err(Error::UnexpectedOutput {
+                command: cmd.command_line_shell(),
+                output,
+            }),
+        }
+    }
+
+    /// Replace [`RunOpt::image`] with the result of
+    /// [`Skopeo::resolve_digest`].
+    pub fn pin_image(&self, opt: &mut RunOpt) -> Result<(), Error> {
+        opt.image = self.resolve_digest(&opt.image)?;
+        Ok(())
+    }
+}
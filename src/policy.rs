@@ -0,0 +1,241 @@
+use crate::{
+    BuildOpt, Error, GeneratedCommand, ImageRef, Launcher, PullOpt, RunOpt,
+};
+use command_run::Command;
+use std::fmt;
+use std::sync::Arc;
+
+/// A command that a [`Policy`] does not allow.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyViolation {
+    /// Command line of the command that was checked.
+    pub command: String,
+
+    /// Why the command is not allowed.
+    pub reason: String,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' is not allowed: {}", self.command, self.reason)
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// Rule that commands created by a [`Launcher`] must follow. See
+/// [`Launcher::with_policy`].
+///
+/// This is implemented for any `Fn(&GeneratedCommand) -> Result<(),
+/// String>` closure, where the error is the reason the command is not
+/// allowed.
+pub trait Policy: Send + Sync {
+    /// Check a command, returning the reason it is not allowed if it
+    /// breaks the policy.
+    fn check(&self, cmd: &GeneratedCommand) -> Result<(), String>;
+}
+
+impl<F> Policy for F
+where
+    F: Fn(&GeneratedCommand) -> Result<(), String> + Send + Sync,
+{
+    fn check(&self, cmd: &GeneratedCommand) -> Result<(), String> {
+        self(cmd)
+    }
+}
+
+/// Common guardrails for containers. Each rule is off by default.
+///
+/// The rules apply to `run` and `create`, and `allowed_registries`
+/// also applies to `pull`. Containers created from a local image ID
+/// (`sha256:` followed by 64 hex digits, or just the digits) are
+/// allowed by `allowed_registries`. Other commands are always allowed. Options
+/// passed in `extra_args` are checked too.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{BaseCommand, Launcher, RunOpt, StandardPolicy};
+/// let mut policy = StandardPolicy::default();
+/// policy.deny_privileged = true;
+/// policy.allowed_registries = vec!["registry.example.com".into()];
+/// let launcher = Launcher::from(BaseCommand::Docker).with_policy(policy);
+///
+/// assert!(launcher.try_run(RunOpt::new("registry.example.com/app")).is_ok());
+/// assert!(launcher.try_run(RunOpt::new("alpine")).is_err());
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct StandardPolicy {
+    /// Deny `--privileged` containers.
+    pub deny_privileged: bool,
+
+    /// Require `--read-only` root filesystems.
+    pub require_read_only: bool,
+
+    /// If not empty, only allow images from these registries. Images
+    /// without a registry are from [`DEFAULT_REGISTRY`].
+    ///
+    /// [`DEFAULT_REGISTRY`]: crate::DEFAULT_REGISTRY
+    pub allowed_registries: Vec<String>,
+
+    /// Require a `--memory` (`-m`) limit.
+    pub require_memory_limit: bool,
+
+    /// Require a CPU limit: `--cpus`, `--cpu-quota`, or
+    /// `--cpu-shares` (`-c`).
+    pub require_cpu_limit: bool,
+}
+
+/// Whether `image` is a full image ID, with or without the `sha256:`
+/// prefix that docker includes and podman does not.
+fn is_image_id(image: &str) -> bool {
+    let hex = image.strip_prefix("sha256:").unwrap_or(image);
+    hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+impl Policy for StandardPolicy {
+    fn check(&self, cmd: &GeneratedCommand) -> Result<(), String> {
+        let creates = cmd.is(&["run"])
+            || cmd.is(&["create"])
+            || cmd.is(&["container", "run"])
+            || cmd.is(&["container", "create"]);
+        let pulls = cmd.is(&["pull"]) || cmd.is(&["image", "pull"]);
+        if !creates && !pulls {
+            return Ok(());
+        }
+
+        if !self.allowed_registries.is_empty() {
+            // A local image ID, such as the one used by
+            // `Launcher::build_and_run`, is not from any registry.
+            let image = cmd
+                .positionals
+                .first()
+                .map(|image| image.to_string_lossy())
+                .filter(|image| !(creates && is_image_id(image)));
+            if let Some(image) = image {
+                let image = ImageRef::from(image.as_ref());
+                let registry = image.normalize();
+                let registry = registry.registry().unwrap_or_default();
+                if !self.allowed_registries.iter().any(|r| r == registry) {
+                    return Err(format!(
+                        "registry {} is not allowed",
+                        registry
+                    ));
+                }
+            }
+        }
+        if !creates {
+            return Ok(());
+        }
+
+        if self.deny_privileged && cmd.has_flag("--privileged") {
+            return Err("privileged containers are not allowed".into());
+        }
+        if self.require_read_only && !cmd.has_flag("--read-only") {
+            return Err("the root filesystem must be read-only".into());
+        }
+        if self.require_memory_limit
+            && !["--memory", "-m"].iter().any(|f| cmd.has_flag(f))
+        {
+            return Err("a memory limit is required".into());
+        }
+        if self.require_cpu_limit
+            && !["--cpus", "--cpu-quota", "--cpu-shares", "-c"]
+                .iter()
+                .any(|f| cmd.has_flag(f))
+        {
+            return Err("a CPU limit is required".into());
+        }
+        Ok(())
+    }
+}
+
+/// List of policies that must all pass.
+#[derive(Clone, Default)]
+pub(crate) struct Policies(pub(crate) Vec<Arc<dyn Policy>>);
+
+impl fmt::Debug for Policies {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Policies")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+impl PartialEq for Policies {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().zip(&other.0).all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl Eq for Policies {}
+
+impl Launcher {
+    /// Add a policy that commands created by this launcher must
+    /// follow. If called more than once, every policy must pass.
+    ///
+    /// The helpers that run commands, such as
+    /// [`Launcher::pull_if_missing`] and [`Launcher::reconcile`], check
+    /// every command before running any of it. The builder methods
+    /// cannot fail, so commands created with them must be passed
+    /// through [`Launcher::enforce`] before running them, or created
+    /// with a fallible builder such as [`Launcher::try_run`].
+    pub fn with_policy<P: Policy + 'static>(mut self, policy: P) -> Self {
+        self.policies.0.push(Arc::new(policy));
+        self
+    }
+
+    /// Check `cmd` against the launcher's policies.
+    pub fn check_policy(&self, cmd: &Command) -> Result<(), PolicyViolation> {
+        if self.policies.0.is_empty() {
+            return Ok(());
+        }
+        let generated = self.introspect(cmd);
+        for policy in &self.policies.0 {
+            policy.check(&generated).map_err(|reason| PolicyViolation {
+                command: crate::shell::command_line(cmd),
+                reason,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Return `cmd` if it follows the launcher's policies.
+    pub fn enforce(&self, cmd: Command) -> Result<Command, Error> {
+        self.check_policy(&cmd)?;
+        Ok(cmd)
+    }
+
+    /// Like [`Launcher::run`], but validate `opt`, apply the
+    /// launcher's [`RelativePaths`] policy with
+    /// [`Launcher::resolve_run_paths`], and check the command against
+    /// the launcher's policies.
+    ///
+    /// [`RelativePaths`]: crate::RelativePaths
+    pub fn try_run(&self, mut opt: RunOpt) -> Result<Command, Error> {
+        opt.validate()?;
+        self.resolve_run_paths(&mut opt)?;
+        self.enforce(self.run(opt))
+    }
+
+    /// Like [`Launcher::build`], but validate `opt`, apply the
+    /// launcher's [`RelativePaths`] policy with
+    /// [`Launcher::resolve_build_paths`], and check the command against
+    /// the launcher's policies.
+    ///
+    /// [`RelativePaths`]: crate::RelativePaths
+    pub fn try_build(&self, mut opt: BuildOpt) -> Result<Command, Error> {
+        opt.validate()?;
+        self.resolve_build_paths(&mut opt)?;
+        self.enforce(self.build(opt))
+    }
+
+    /// Like [`Launcher::pull`], but validate the image and check the
+    /// command against the launcher's policies.
+    pub fn try_pull(&self, opt: PullOpt) -> Result<Command, Error> {
+        opt.image.validate()?;
+        self.enforce(self.pull(opt))
+    }
+}
@@ -0,0 +1,208 @@
+use crate::runner::stdout;
+use crate::{
+    CommandExt, ContainerRef, Error, Launcher, Protocol, PsOpt, RunOpt,
+};
+use std::fmt;
+use std::io::ErrorKind;
+use std::net::{TcpListener, UdpSocket};
+
+/// Host port requested in [`RunOpt::publish`] that is already in use.
+/// See [`Launcher::check_ports`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PortConflict {
+    /// Host IP the port was requested on, or None for all IPs.
+    pub ip: Option<String>,
+
+    /// Host port.
+    pub port: u16,
+
+    /// Transport protocol.
+    pub protocol: Protocol,
+
+    /// Name of the container that publishes the port, or None if the
+    /// port is bound by something other than a container.
+    pub container: Option<String>,
+}
+
+impl fmt::Display for PortConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "host port ")?;
+        if let Some(ip) = &self.ip {
+            write!(f, "{}:", ip)?;
+        }
+        write!(f, "{}/{} is already in use", self.port, self.protocol)?;
+        if let Some(container) = &self.container {
+            write!(f, " by container {}", container)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PortConflict {}
+
+/// Whether `ip` means all addresses. Docker and podman print these in
+/// a few ways.
+fn is_any_ip(ip: &str) -> bool {
+    matches!(ip, "" | "0.0.0.0" | "::" | "[::]")
+}
+
+/// A port published by an existing container, parsed from the `ps`
+/// column, e.g. `0.0.0.0:8000-8001->80-81/tcp`.
+struct Published<'a> {
+    ip: &'a str,
+    start: u16,
+    end: u16,
+    protocol: &'a str,
+}
+
+impl<'a> Published<'a> {
+    fn parse(s: &'a str) -> Option<Self> {
+        let (host, container) = s.split_once("->")?;
+        let (ip, ports) = host.rsplit_once(':')?;
+        let (_, protocol) = container.split_once('/')?;
+        let (start, end) = match ports.split_once('-') {
+            Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+            None => {
+                let port = ports.parse().ok()?;
+                (port, port)
+            }
+        };
+        Some(Self {
+            ip,
+            start,
+            end,
+            protocol,
+        })
+    }
+
+    fn conflicts(
+        &self,
+        ip: Option<&str>,
+        port: u16,
+        protocol: Protocol,
+    ) -> bool {
+        let ip = ip.unwrap_or_default();
+        let same_ip = is_any_ip(ip)
+            || is_any_ip(self.ip)
+            || ip == self.ip.trim_matches(['[', ']']);
+        same_ip
+            && (self.start..=self.end).contains(&port)
+            && self.protocol == protocol.as_str()
+    }
+}
+
+/// Try binding `port` to see if something else has it. Only a
+/// definite "address in use" counts; other failures, such as lacking
+/// permission for low ports, are left for the engine to report.
+fn is_bound(ip: Option<&str>, port: u16, protocol: Protocol) -> bool {
+    let ip = match ip {
+        Some(ip) if !is_any_ip(ip) => ip.trim_matches(['[', ']']),
+        _ => "0.0.0.0",
+    };
+    let result = match protocol {
+        Protocol::Tcp => TcpListener::bind((ip, port)).map(drop),
+        Protocol::Udp => UdpSocket::bind((ip, port)).map(drop),
+        // The standard library cannot open SCTP sockets.
+        Protocol::Sctp => return false,
+    };
+    matches!(result, Err(err) if err.kind() == ErrorKind::AddrInUse)
+}
+
+impl Launcher {
+    /// Check that the host ports in [`RunOpt::publish`] are free,
+    /// returning [`Error::PortConflict`] for the first one that is
+    /// not. This gives a clearer error than the engine does, and
+    /// names the container holding the port if there is one.
+    ///
+    /// Ports published by other containers are found with `ps`. Other
+    /// processes are found by briefly binding each port, which is only
+    /// meaningful when the engine runs on this machine; set
+    /// `probe_sockets` to false for a remote engine.
+    ///
+    /// Entries without a host port, and entries with a host range
+    /// that is larger than the container range (where the engine picks
+    /// any free port in the range), are not checked. If nothing needs
+    /// checking, no commands are run.
+    pub fn check_ports(
+        &self,
+        opt: &RunOpt,
+        probe_sockets: bool,
+    ) -> Result<(), Error> {
+        let mut requested = Vec::new();
+        for publish in &opt.publish {
+            let Some(host) = &publish.host else {
+                continue;
+            };
+            if host.0.len() != publish.container.0.len() {
+                continue;
+            }
+            for port in host.0.clone() {
+                requested.push((publish.ip.as_deref(), port, publish.protocol));
+            }
+        }
+        if requested.is_empty() {
+            return Ok(());
+        }
+
+        let ps_opt = PsOpt {
+            format: Some("{{.Names}} {{.Ports}}".into()),
+            ..Default::default()
+        };
+        let output = stdout(self.enforce(self.ps(ps_opt))?)?;
+        let published: Vec<_> = output
+            .lines()
+            .filter_map(|line| line.trim().split_once(' '))
+            .flat_map(|(name, ports)| {
+                ports
+                    .split(", ")
+                    .filter_map(Published::parse)
+                    .map(move |published| (name, published))
+            })
+            .collect();
+
+        for (ip, port, protocol) in requested {
+            let container = published
+                .iter()
+                .find(|(_, published)| published.conflicts(ip, port, protocol))
+                .map(|(name, _)| name.to_string());
+            if container.is_some()
+                || (probe_sockets && is_bound(ip, port, protocol))
+            {
+                return Err(PortConflict {
+                    ip: ip.map(Into::into),
+                    port,
+                    protocol,
+                    container,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the host port that `port` in `container` is published on,
+    /// as assigned by the engine for [`PublishPorts::ephemeral`].
+    ///
+    /// If the port is published on more than one address (e.g. IPv4
+    /// and IPv6), the first is used.
+    ///
+    /// [`PublishPorts::ephemeral`]: crate::PublishPorts::ephemeral
+    pub fn host_port(
+        &self,
+        container: &ContainerRef,
+        port: u16,
+        protocol: Protocol,
+    ) -> Result<u16, Error> {
+        let cmd = self.enforce(self.port(container, Some((port, protocol))))?;
+        let output = stdout(cmd.clone())?;
+        output
+            .lines()
+            .next()
+            .and_then(|line| line.trim().rsplit_once(':'))
+            .and_then(|(_, port)| port.parse().ok())
+            .ok_or_else(|| Error::UnexpectedOutput {
+                command: cmd.command_line_shell(),
+                output,
+            })
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{
+    shell, CommandExt, ContainerRef, Error, ExecOpt, Launcher, RetryPolicy,
+};
+use command_run::{Command, ErrorKind};
+use std::ffi::OsString;
+use std::thread;
+use std::time::Duration;
+
+/// What a [`Probe`] checks. Each check runs inside the container with
+/// `exec`, so it sees the container's own network.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ProbeKind {
+    /// Run a command, e.g. `pg_isready`. The first element is the
+    /// program. Ready when it exits successfully.
+    Exec(Vec<OsString>),
+
+    /// Connect to a TCP port on `127.0.0.1`, with `nc -z`, or bash's
+    /// `/dev/tcp` if `nc` is not installed.
+    Tcp(u16),
+
+    /// Fetch `http://127.0.0.1:{port}{path}` with `curl`, or `wget` if
+    /// `curl` is not installed. Ready on a 2xx or 3xx response.
+    Http {
+        /// Port to connect to.
+        port: u16,
+
+        /// Path to request, starting with `/`.
+        path: String,
+    },
+}
+
+/// Readiness check for a running container, retried with backoff.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{BaseCommand, Launcher, Probe};
+/// let launcher = Launcher::from(BaseCommand::Docker);
+/// let probe = Probe::exec(["pg_isready", "-U", "postgres"]);
+/// assert_eq!(
+///     launcher.probe_command(&"db".parse().unwrap(), &probe).command_line_lossy(),
+///     "docker exec db pg_isready -U postgres"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Probe {
+    /// What to check.
+    pub kind: ProbeKind,
+
+    /// How many times to check and how long to wait in between.
+    /// [`RetryPolicy::retry_unknown`] is ignored; every failure is
+    /// retried until the attempts run out. Defaults to 30 attempts,
+    /// with delays starting at half a second and growing to at most
+    /// five seconds.
+    pub retry: RetryPolicy,
+}
+
+impl Probe {
+    /// Create a probe of `kind` with the default retry policy.
+    pub fn new(kind: ProbeKind) -> Self {
+        Self {
+            kind,
+            retry: RetryPolicy {
+                max_attempts: 30,
+                initial_delay: Duration::from_millis(500),
+                max_delay: Duration::from_secs(5),
+                multiplier: 1.5,
+                retry_unknown: true,
+            },
+        }
+    }
+
+    /// Create a [`ProbeKind::Exec`] probe.
+    pub fn exec<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        Self::new(ProbeKind::Exec(args.into_iter().map(Into::into).collect()))
+    }
+
+    /// Create a [`ProbeKind::Tcp`] probe.
+    pub fn tcp(port: u16) -> Self {
+        Self::new(ProbeKind::Tcp(port))
+    }
+
+    /// Create a [`ProbeKind::Http`] probe.
+    pub fn http<S: Into<String>>(port: u16, path: S) -> Self {
+        Self::new(ProbeKind::Http {
+            port,
+            path: path.into(),
+        })
+    }
+
+    /// Get the command to run in the container.
+    fn args(&self) -> Vec<OsString> {
+        let script = match &self.kind {
+            ProbeKind::Exec(args) => return args.clone(),
+            ProbeKind::Tcp(port) => format!(
+                "if command -v nc >/dev/null 2>&1; \
+                 then nc -z 127.0.0.1 {0}; \
+                 else bash -c 'exec 3<>/dev/tcp/127.0.0.1/{0}'; fi",
+                port
+            ),
+            ProbeKind::Http { port, path } => {
+                let url =
+                    shell::quote(format!("http://127.0.0.1:{}{}", port, path));
+                format!(
+                    "if command -v curl >/dev/null 2>&1; \
+                     then curl -fsS -o /dev/null {0}; \
+                     else wget -q -O /dev/null {0}; fi",
+                    url
+                )
+            }
+        };
+        vec!["sh".into(), "-c".into(), script.into()]
+    }
+}
+
+impl Launcher {
+    /// Create a [`Command`] that runs `probe` once in `container`.
+    pub fn probe_command(
+        &self,
+        container: &ContainerRef,
+        probe: &Probe,
+    ) -> Command {
+        let mut args = probe.args().into_iter();
+        let mut opt =
+            ExecOpt::new(container.clone(), args.next().unwrap_or_default());
+        opt.args = args.collect();
+        self.exec(opt)
+    }
+
+    /// Run `probe` once in `container` and return whether it passed.
+    pub fn probe(
+        &self,
+        container: &ContainerRef,
+        probe: &Probe,
+    ) -> Result<bool, Error> {
+        let mut cmd = self.enforce(self.probe_command(container, probe))?;
+        cmd.capture = true;
+        cmd.check = false;
+        Ok(cmd.run_logged()?.status.success())
+    }
+
+    /// Run `probe` in `container` until it passes, waiting between
+    /// attempts as described by [`Probe::retry`]. If the last attempt
+    /// fails, its exit status is returned as an [`Error::Run`].
+    pub fn wait_ready(
+        &self,
+        container: &ContainerRef,
+        probe: &Probe,
+    ) -> Result<(), Error> {
+        let mut cmd = self.enforce(self.probe_command(container, probe))?;
+        cmd.capture = true;
+        cmd.check = false;
+
+        let mut retry = 0;
+        loop {
+            let output = cmd.run_logged()?;
+            if output.status.success() {
+                return Ok(());
+            }
+            if retry + 1 >= probe.retry.max_attempts.max(1) {
+                return Err(Error::Run(command_run::Error {
+                    command: cmd,
+                    kind: ErrorKind::Exit(output.status),
+                }));
+            }
+            thread::sleep(probe.retry.delay(retry));
+            retry += 1;
+        }
+    }
+}
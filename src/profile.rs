@@ -0,0 +1,81 @@
+use crate::{RunOpt, ValidationError};
+use std::collections::BTreeMap;
+
+/// Registry of named [`RunOpt`] fragments, such as "gpu" or "debug",
+/// that can be layered onto a `RunOpt`. This is useful for programs
+/// that let their users pick presets with something like a `--profile`
+/// flag.
+///
+/// Profiles are applied with [`RunOpt::merge`], in the order given.
+///
+/// **Flags can only be turned on.** A `bool` field that is `false` in
+/// a profile is treated as not set, so a profile cannot undo `remove`,
+/// `read_only`, `init`, `tty`, or any other flag set by the base
+/// options or an earlier profile. Set the field to `false` on the
+/// result of [`Profiles::apply`] instead.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{Profiles, RunOpt, SecurityOpt};
+/// let mut profiles = Profiles::default();
+/// let mut low_priv = RunOpt::default();
+/// low_priv.read_only = true;
+/// low_priv.security_opts = vec![SecurityOpt::NoNewPrivileges];
+/// profiles.define("low-priv", low_priv);
+///
+/// let opt = profiles.apply(&RunOpt::new("alpine"), ["low-priv"]).unwrap();
+/// assert!(opt.read_only);
+/// assert!(profiles.apply(&opt, ["gpu"]).is_err());
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Profiles {
+    profiles: BTreeMap<String, RunOpt>,
+}
+
+impl Profiles {
+    /// Add a profile, replacing any existing profile with the same
+    /// name.
+    pub fn define<S: Into<String>>(&mut self, name: S, fragment: RunOpt) {
+        self.profiles.insert(name.into(), fragment);
+    }
+
+    /// Remove a profile, returning it if it existed.
+    pub fn remove(&mut self, name: &str) -> Option<RunOpt> {
+        self.profiles.remove(name)
+    }
+
+    /// Get a profile by name.
+    pub fn get(&self, name: &str) -> Option<&RunOpt> {
+        self.profiles.get(name)
+    }
+
+    /// Iterate over the profile names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Merge the named profiles onto `opt` in order, so later
+    /// profiles take precedence over earlier ones.
+    ///
+    /// Returns an error if any profile is not defined.
+    pub fn apply<I, S>(
+        &self,
+        opt: &RunOpt,
+        names: I,
+    ) -> Result<RunOpt, ValidationError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut out = opt.clone();
+        for name in names {
+            let name = name.as_ref();
+            let fragment = self.get(name).ok_or_else(|| {
+                ValidationError::new("profile", name, "not defined")
+            })?;
+            out = out.merge(fragment);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,294 @@
+use crate::child::combine_output;
+use crate::disk_usage::parse_size;
+use crate::Error;
+use command_run::{Command, ErrorKind, Output};
+use std::process::{self, Stdio};
+
+/// Stage of a layer download or build step.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProgressStage {
+    /// The layer is queued for download.
+    Waiting,
+
+    /// The layer is being downloaded.
+    Downloading,
+
+    /// The checksum of a downloaded layer is being verified.
+    Verifying,
+
+    /// The layer has been downloaded but not yet extracted.
+    Downloaded,
+
+    /// The layer is being extracted.
+    Extracting,
+
+    /// The layer has been pulled, or the build step has finished.
+    Complete,
+
+    /// The layer was already present locally.
+    AlreadyExists,
+
+    /// The build step is running.
+    Running,
+
+    /// The build step was skipped because its result was cached.
+    Cached,
+
+    /// The build step failed.
+    Error,
+
+    /// Any other status.
+    Other(String),
+}
+
+/// One update parsed from the output of a pull or build.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressEvent {
+    /// Layer ID for pulls (e.g. `f56be85fc22e`), or step number for
+    /// BuildKit builds (e.g. `#5`).
+    pub id: String,
+
+    /// Current stage of the layer or step.
+    pub stage: ProgressStage,
+
+    /// Percent complete (0 to 100), if the output includes sizes.
+    pub percent: Option<f64>,
+
+    /// The line the event was parsed from.
+    pub message: String,
+}
+
+/// Receives [`ProgressEvent`]s from a [`ProgressParser`].
+///
+/// This is implemented for closures that take a `&ProgressEvent`.
+pub trait ProgressObserver {
+    /// Called for each line of output that was recognized.
+    fn on_progress(&mut self, event: &ProgressEvent);
+}
+
+impl<F> ProgressObserver for F
+where
+    F: FnMut(&ProgressEvent),
+{
+    fn on_progress(&mut self, event: &ProgressEvent) {
+        self(event)
+    }
+}
+
+/// Calculate a percentage from the first `current / total` or
+/// `current/total` pair of sizes in `s`.
+fn parse_percent(s: &str) -> Option<f64> {
+    let s = s.replace(" / ", "/");
+    s.split_whitespace().find_map(|word| {
+        let (current, total) = word.split_once('/')?;
+        let current = parse_size(current)? as f64;
+        let total = parse_size(total)? as f64;
+        if total > 0.0 {
+            Some((current / total * 100.0).min(100.0))
+        } else {
+            None
+        }
+    })
+}
+
+/// Parse a line of BuildKit plain progress output, without the leading
+/// `#N `.
+fn parse_buildkit(rest: &str) -> (ProgressStage, Option<f64>) {
+    let first = rest.split_whitespace().next().unwrap_or_default();
+    if first == "DONE" {
+        (ProgressStage::Complete, Some(100.0))
+    } else if first == "CACHED" {
+        (ProgressStage::Cached, None)
+    } else if first.starts_with("ERROR") || first == "CANCELED" {
+        (ProgressStage::Error, None)
+    } else if first.starts_with("sha256:") {
+        if rest.ends_with(" done") {
+            (ProgressStage::Downloaded, Some(100.0))
+        } else {
+            (ProgressStage::Downloading, parse_percent(rest))
+        }
+    } else if first == "extracting" {
+        if rest.ends_with(" done") {
+            (ProgressStage::Complete, Some(100.0))
+        } else {
+            (ProgressStage::Extracting, None)
+        }
+    } else {
+        (ProgressStage::Running, None)
+    }
+}
+
+/// Parse a line of `podman pull` output, without the leading
+/// `Copying blob ` or `Copying config `.
+fn parse_podman(rest: &str) -> (&str, ProgressStage, Option<f64>) {
+    let (id, status) = rest.split_once(' ').unwrap_or((rest, ""));
+    let id = id.strip_prefix("sha256:").unwrap_or(id);
+    let status = status.trim();
+    let (stage, percent) = if status == "done" || status.ends_with(" done") {
+        (ProgressStage::Complete, Some(100.0))
+    } else if status.contains("already exists") {
+        (ProgressStage::AlreadyExists, None)
+    } else {
+        (ProgressStage::Downloading, parse_percent(status))
+    };
+    (id, stage, percent)
+}
+
+/// Parse a line of `docker pull` output, without the leading layer ID.
+fn parse_docker(status: &str) -> (ProgressStage, Option<f64>) {
+    let percent = parse_percent(status);
+    if status.starts_with("Waiting") || status.starts_with("Pulling fs layer") {
+        (ProgressStage::Waiting, None)
+    } else if status.starts_with("Downloading") {
+        (ProgressStage::Downloading, percent)
+    } else if status.starts_with("Verifying Checksum") {
+        (ProgressStage::Verifying, None)
+    } else if status.starts_with("Download complete") {
+        (ProgressStage::Downloaded, None)
+    } else if status.starts_with("Extracting") {
+        (ProgressStage::Extracting, percent)
+    } else if status.starts_with("Pull complete") {
+        (ProgressStage::Complete, Some(100.0))
+    } else if status.starts_with("Already exists") {
+        (ProgressStage::AlreadyExists, None)
+    } else {
+        (ProgressStage::Other(status.to_string()), percent)
+    }
+}
+
+/// Parser for the progress output of `docker pull`, `podman pull`,
+/// and BuildKit builds run with `--progress=plain`.
+///
+/// Output can be fed in arbitrary chunks; lines are split on both `\n`
+/// and `\r`. Lines that do not describe a layer or build step (such as
+/// `Digest: ...`) are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{ProgressEvent, ProgressParser, ProgressStage};
+///
+/// let mut events = Vec::new();
+/// let mut parser = ProgressParser::new();
+/// parser.feed(
+///     b"f56be85fc22e: Downloading  1MB/4MB\n#5 CACHED\n",
+///     &mut |event: &ProgressEvent| events.push(event.clone()),
+/// );
+/// assert_eq!(events[0].id, "f56be85fc22e");
+/// assert_eq!(events[0].stage, ProgressStage::Downloading);
+/// assert_eq!(events[0].percent, Some(25.0));
+/// assert_eq!(events[1].id, "#5");
+/// assert_eq!(events[1].stage, ProgressStage::Cached);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ProgressParser {
+    partial: Vec<u8>,
+}
+
+impl ProgressParser {
+    /// Create a parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a single line of output.
+    pub fn parse_line(line: &str) -> Option<ProgressEvent> {
+        let line = line.trim();
+        let event = |id: &str, (stage, percent)| ProgressEvent {
+            id: id.to_string(),
+            stage,
+            percent,
+            message: line.to_string(),
+        };
+
+        // BuildKit: "#5 [2/3] RUN make"
+        if let Some(rest) = line.strip_prefix('#') {
+            let (num, rest) = rest.split_once(' ')?;
+            if num.is_empty() || !num.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            return Some(event(&line[..=num.len()], parse_buildkit(rest)));
+        }
+
+        // Podman: "Copying blob sha256:0123... done"
+        if let Some(rest) = line
+            .strip_prefix("Copying blob ")
+            .or_else(|| line.strip_prefix("Copying config "))
+        {
+            let (id, stage, percent) = parse_podman(rest);
+            return Some(event(id, (stage, percent)));
+        }
+
+        // Docker: "f56be85fc22e: Pull complete"
+        let (id, status) = line.split_once(": ")?;
+        if id.len() < 12 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(event(id, parse_docker(status)))
+    }
+
+    /// Feed a chunk of output to the parser, passing any complete lines
+    /// that are recognized to `observer`.
+    pub fn feed(&mut self, data: &[u8], observer: &mut dyn ProgressObserver) {
+        for &byte in data {
+            if byte == b'\n' || byte == b'\r' {
+                self.flush(observer);
+            } else {
+                self.partial.push(byte);
+            }
+        }
+    }
+
+    /// Parse any remaining output that did not end with a newline.
+    pub fn flush(&mut self, observer: &mut dyn ProgressObserver) {
+        if !self.partial.is_empty() {
+            let line = String::from_utf8_lossy(&self.partial);
+            if let Some(event) = Self::parse_line(&line) {
+                observer.on_progress(&event);
+            }
+            self.partial.clear();
+        }
+    }
+}
+
+/// Run `cmd`, passing its combined stdout and stderr through a
+/// [`ProgressParser`] as it is produced.
+pub(crate) fn run(
+    cmd: &Command,
+    observer: &mut dyn ProgressObserver,
+) -> Result<Output, Error> {
+    let to_error = |err| command_run::Error {
+        command: cmd.clone(),
+        kind: ErrorKind::Run(err),
+    };
+
+    let mut proc: process::Command = cmd.into();
+    proc.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = proc.spawn().map_err(to_error)?;
+    let (Some(stdout), Some(stderr)) =
+        (child.stdout.take(), child.stderr.take())
+    else {
+        unreachable!("stdout and stderr are piped");
+    };
+
+    let mut parser = ProgressParser::new();
+    let mut output = Vec::new();
+    for chunk in combine_output(stdout, stderr) {
+        parser.feed(&chunk, observer);
+        output.extend_from_slice(&chunk);
+    }
+    parser.flush(observer);
+
+    let status = child.wait().map_err(to_error)?;
+    if cmd.check && !status.success() {
+        return Err(Error::Run(command_run::Error {
+            command: cmd.clone(),
+            kind: ErrorKind::Exit(status),
+        }));
+    }
+    Ok(Output {
+        status,
+        stdout: output,
+        stderr: Vec::new(),
+    })
+}
@@ -0,0 +1,236 @@
+//! Export container stats as Prometheus metrics. Requires the
+//! `prometheus` feature.
+
+use crate::ContainerStats;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Type of a [`MetricSample`], as reported in the `# TYPE` line.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MetricKind {
+    /// Value that can go up and down.
+    Gauge,
+
+    /// Value that only goes up (until the container restarts).
+    Counter,
+}
+
+impl MetricKind {
+    /// Get the kind as used in the text format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gauge => "gauge",
+            Self::Counter => "counter",
+        }
+    }
+}
+
+/// One value of one metric for one container.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricSample {
+    /// Metric name, including the exporter's prefix, e.g.
+    /// `container_memory_usage_bytes`.
+    pub name: String,
+
+    /// Description of the metric.
+    pub help: &'static str,
+
+    /// Type of the metric.
+    pub kind: MetricKind,
+
+    /// Labels: `id` and `name` for the container, followed by the
+    /// exporter's constant labels.
+    pub labels: Vec<(String, String)>,
+
+    /// Value of the metric.
+    pub value: f64,
+}
+
+type Getter = fn(&ContainerStats) -> f64;
+
+/// Name suffix, help, kind, and value of each exported metric.
+const METRICS: &[(&str, &str, MetricKind, Getter)] = {
+    use MetricKind::*;
+    &[
+        (
+            "cpu_percent",
+            "CPU usage, where 100 is one core.",
+            Gauge,
+            |s| s.cpu_percent,
+        ),
+        ("memory_usage_bytes", "Memory used.", Gauge, |s| {
+            s.mem_usage as f64
+        }),
+        ("memory_limit_bytes", "Memory limit.", Gauge, |s| {
+            s.mem_limit as f64
+        }),
+        (
+            "memory_percent",
+            "Memory used as a percentage of the limit.",
+            Gauge,
+            |s| s.mem_percent,
+        ),
+        (
+            "network_receive_bytes_total",
+            "Bytes received over the network.",
+            Counter,
+            |s| s.net_rx as f64,
+        ),
+        (
+            "network_transmit_bytes_total",
+            "Bytes sent over the network.",
+            Counter,
+            |s| s.net_tx as f64,
+        ),
+        (
+            "block_read_bytes_total",
+            "Bytes read from block devices.",
+            Counter,
+            |s| s.block_read as f64,
+        ),
+        (
+            "block_write_bytes_total",
+            "Bytes written to block devices.",
+            Counter,
+            |s| s.block_write as f64,
+        ),
+        ("pids", "Number of processes.", Gauge, |s| s.pids as f64),
+    ]
+};
+
+/// Escape a label value for the text format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+/// Holds the latest [`ContainerStats`] and formats them as Prometheus
+/// metrics, modeled on the metrics cAdvisor exports.
+///
+/// The exporter can be cloned cheaply; clones share the same stats,
+/// so one clone can be updated by [`Launcher::watch_stats`] while
+/// another serves scrapes.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::prometheus::StatsExporter;
+/// use docker_command::ContainerStats;
+///
+/// let stats = ContainerStats::parse_json(
+///     r#"{"BlockIO":"0B / 0B","CPUPerc":"1.50%","ID":"0123456789ab","MemPerc":"0.50%","MemUsage":"40MiB / 7.6GiB","Name":"web","NetIO":"1.5kB / 648B","PIDs":"3"}"#,
+/// ).unwrap();
+/// let exporter = StatsExporter::new().with_label("host", "build-1");
+/// exporter.update(&stats);
+/// let text = exporter.render();
+/// assert!(text.contains("# TYPE container_cpu_percent gauge\n"));
+/// assert!(text.contains(
+///     "container_cpu_percent{id=\"0123456789ab\",name=\"web\",host=\"build-1\"} 1.5\n"
+/// ));
+/// ```
+///
+/// [`Launcher::watch_stats`]: crate::Launcher::watch_stats
+#[derive(Clone, Debug)]
+pub struct StatsExporter {
+    prefix: String,
+    labels: Vec<(String, String)>,
+    stats: Arc<Mutex<Vec<ContainerStats>>>,
+}
+
+impl Default for StatsExporter {
+    fn default() -> Self {
+        Self {
+            prefix: "container".into(),
+            labels: Vec::new(),
+            stats: Arc::default(),
+        }
+    }
+}
+
+impl StatsExporter {
+    /// Create an exporter with no stats, using the `container` prefix
+    /// for metric names.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the prefix for metric names. It should only contain ASCII
+    /// letters, digits, and underscores.
+    pub fn with_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Add a label to every sample, e.g. the host name.
+    pub fn with_label<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.labels.push((key.into(), value.into()));
+        self
+    }
+
+    /// Replace the stats with `stats`. Containers that are not in
+    /// `stats` are no longer exported.
+    pub fn update(&self, stats: &[ContainerStats]) {
+        *self.stats.lock().unwrap() = stats.to_vec();
+    }
+
+    /// Pass each sample to `callback`, grouped by metric. This can be
+    /// used to send the metrics somewhere other than Prometheus.
+    pub fn for_each_sample(&self, callback: &mut dyn FnMut(&MetricSample)) {
+        let stats = self.stats.lock().unwrap();
+        for (suffix, help, kind, get) in METRICS {
+            for container in stats.iter() {
+                let mut labels = vec![
+                    ("id".to_string(), container.id.clone()),
+                    ("name".to_string(), container.name.clone()),
+                ];
+                labels.extend(self.labels.iter().cloned());
+                callback(&MetricSample {
+                    name: format!("{}_{}", self.prefix, suffix),
+                    help,
+                    kind: *kind,
+                    labels,
+                    value: get(container),
+                });
+            }
+        }
+    }
+
+    /// Format the metrics in the Prometheus text exposition format,
+    /// suitable for serving with the content type
+    /// `text/plain; version=0.0.4`.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut last_name = String::new();
+        self.for_each_sample(&mut |sample| {
+            if sample.name != last_name {
+                let _ = writeln!(out, "# HELP {} {}", sample.name, sample.help);
+                let _ = writeln!(
+                    out,
+                    "# TYPE {} {}",
+                    sample.name,
+                    sample.kind.as_str()
+                );
+                last_name.clone_from(&sample.name);
+            }
+            let labels: Vec<String> = sample
+                .labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+                .collect();
+            let _ = writeln!(
+                out,
+                "{}{{{}}} {}",
+                sample.name,
+                labels.join(","),
+                sample.value
+            );
+        });
+        out
+    }
+}
@@ -0,0 +1,305 @@
+use crate::audit::container_name;
+use crate::runner::stdout;
+use crate::{
+    shell, CommandExt, ContainerName, CreateNetworkOpt, CreateVolumeOpt, Drift,
+    Error, Filter, Launcher, ObjectKind, PsOpt, RemoveContainersOpt, RunOpt,
+    ValidationError,
+};
+use command_run::Command;
+use std::fmt;
+
+/// Label added to containers created by [`Launcher::reconcile`]. The
+/// value is the [`Deployment::name`].
+pub const DEPLOYMENT_LABEL: &str = "docker-command.deployment";
+
+/// Label added to containers created by [`Launcher::reconcile`]. The
+/// value is a hash of the `run` arguments, used to detect changes to
+/// the desired options.
+pub const CONFIG_LABEL: &str = "docker-command.config";
+
+/// Desired state of a single host, for use with
+/// [`Launcher::reconcile`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Deployment {
+    /// Name of the deployment. Containers created for it are labeled
+    /// with [`DEPLOYMENT_LABEL`], so that containers that are no
+    /// longer wanted can be found and removed.
+    pub name: String,
+
+    /// Networks that should exist.
+    pub networks: Vec<CreateNetworkOpt>,
+
+    /// Volumes that should exist.
+    pub volumes: Vec<CreateVolumeOpt>,
+
+    /// Containers that should exist. Each must have a
+    /// [`RunOpt::name`]. They are created in order, so a container
+    /// should come after the containers it depends on.
+    pub containers: Vec<RunOpt>,
+}
+
+impl Deployment {
+    /// Create an empty `Deployment` with the given `name`.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Change made by a [`Step`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    /// Create a missing network.
+    CreateNetwork(String),
+
+    /// Create a missing volume.
+    CreateVolume(String),
+
+    /// Create a missing container.
+    CreateContainer(String),
+
+    /// Remove a container and create it again with new options.
+    ReplaceContainer(String),
+
+    /// Remove a container that is no longer in the deployment.
+    RemoveContainer(String),
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::CreateNetwork(name) => write!(f, "create network {}", name),
+            Self::CreateVolume(name) => write!(f, "create volume {}", name),
+            Self::CreateContainer(name) => {
+                write!(f, "create container {}", name)
+            }
+            Self::ReplaceContainer(name) => {
+                write!(f, "replace container {}", name)
+            }
+            Self::RemoveContainer(name) => {
+                write!(f, "remove container {}", name)
+            }
+        }
+    }
+}
+
+/// One change in a [`Plan`], along with the commands that make it.
+#[derive(Clone, Debug)]
+pub struct Step {
+    /// The change.
+    pub action: Action,
+
+    /// Commands to run, in order.
+    pub commands: Vec<Command>,
+}
+
+/// Changes needed to bring a host in line with a [`Deployment`]. See
+/// [`Launcher::plan`].
+///
+/// The `Display` implementation lists each action followed by its
+/// commands, indented, in shell syntax.
+#[derive(Clone, Debug, Default)]
+pub struct Plan {
+    /// Steps to run, in order.
+    pub steps: Vec<Step>,
+}
+
+impl Plan {
+    /// Whether the host already matches the deployment.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Iterate over the commands of every step, in order.
+    pub fn commands(&self) -> impl Iterator<Item = &Command> {
+        self.steps.iter().flat_map(|step| &step.commands)
+    }
+
+    /// Run the commands, stopping at the first failure.
+    pub fn apply(&self) -> Result<(), Error> {
+        for cmd in self.commands() {
+            cmd.run_logged()?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step.action)?;
+            for cmd in &step.commands {
+                writeln!(f, "  {}", shell::command_line(cmd))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 64-bit FNV-1a hash of `args`. This is used rather than `std`'s
+/// hasher so that the value stays the same across Rust versions.
+fn config_hash(args: &[std::ffi::OsString]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for arg in args {
+        for byte in arg.to_string_lossy().bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+impl Launcher {
+    /// Work out the changes needed to bring the host in line with
+    /// `deployment`, without making them.
+    ///
+    /// * Networks and volumes are created if they are missing. Existing
+    ///   ones are left as is, and none are removed.
+    /// * Containers that are missing are created with `run --detach`.
+    /// * Containers whose options have changed, or whose image
+    ///   reference now points to a different image locally (see
+    ///   [`Drift::Image`]), are removed and created again.
+    /// * Containers labeled as belonging to the deployment that are no
+    ///   longer in it are removed.
+    ///
+    /// Options are compared using the [`CONFIG_LABEL`] label, so a
+    /// container with the right name that was not created by
+    /// [`reconcile`] is always replaced.
+    ///
+    /// The options of every container are validated, as with
+    /// [`Launcher::try_run`], and every command in the plan is checked
+    /// against the launcher's policies (see [`Launcher::with_policy`]),
+    /// so a plan that breaks one is never returned.
+    ///
+    /// [`reconcile`]: Launcher::reconcile
+    pub fn plan(&self, deployment: &Deployment) -> Result<Plan, Error> {
+        let names = deployment
+            .containers
+            .iter()
+            .map(container_name)
+            .collect::<Result<Vec<_>, _>>()?;
+        for opt in &deployment.containers {
+            opt.validate()?;
+        }
+        let mut plan = Plan::default();
+
+        for opt in &deployment.networks {
+            if !self.network_exists(&opt.name)? {
+                plan.steps.push(Step {
+                    action: Action::CreateNetwork(opt.name.clone()),
+                    commands: vec![
+                        self.enforce(self.create_network(opt.clone()))?
+                    ],
+                });
+            }
+        }
+        for opt in &deployment.volumes {
+            if !self.volume_exists(&opt.name)? {
+                plan.steps.push(Step {
+                    action: Action::CreateVolume(opt.name.clone()),
+                    commands: vec![
+                        self.enforce(self.create_volume(opt.clone()))?
+                    ],
+                });
+            }
+        }
+
+        let ps_opt = PsOpt {
+            all: true,
+            filters: vec![Filter::Label(format!(
+                "{}={}",
+                DEPLOYMENT_LABEL, deployment.name
+            ))],
+            format: Some("{{.Names}}".into()),
+            ..Default::default()
+        };
+        for existing in stdout(self.enforce(self.ps(ps_opt))?)?.lines() {
+            let existing = existing.trim();
+            if !existing.is_empty() && !names.iter().any(|n| n == existing) {
+                plan.steps.push(Step {
+                    action: Action::RemoveContainer(existing.into()),
+                    commands: vec![
+                        self.enforce(self.remove_container_forced(existing)?)?
+                    ],
+                });
+            }
+        }
+
+        for (name, opt) in names.into_iter().zip(&deployment.containers) {
+            let mut opt = opt.clone();
+            self.resolve_run_paths(&mut opt)?;
+            opt.detach = true;
+            opt.labels
+                .push((DEPLOYMENT_LABEL.into(), deployment.name.clone()));
+            let run = self.run(opt.clone());
+            let hash = config_hash(&run.args[self.base().args.len()..]);
+            opt.labels.push((CONFIG_LABEL.into(), hash.clone()));
+            let run = self.enforce(self.run(opt.clone()))?;
+
+            let format =
+                format!("{{{{index .Config.Labels {:?}}}}}", CONFIG_LABEL);
+            let action = match self.inspect_format(
+                ObjectKind::Container,
+                name.clone(),
+                &format,
+            )? {
+                None => Action::CreateContainer(name),
+                Some((_, label)) if label != hash => {
+                    Action::ReplaceContainer(name)
+                }
+                Some(_) => {
+                    let drift = self.container_drift(&name, &opt)?;
+                    if drift.iter().any(|d| matches!(d, Drift::Image { .. })) {
+                        Action::ReplaceContainer(name)
+                    } else {
+                        continue;
+                    }
+                }
+            };
+            let commands = match &action {
+                Action::ReplaceContainer(name) => {
+                    vec![
+                        self.enforce(self.remove_container_forced(name)?)?,
+                        run,
+                    ]
+                }
+                _ => vec![run],
+            };
+            plan.steps.push(Step { action, commands });
+        }
+
+        Ok(plan)
+    }
+
+    /// Bring the host in line with `deployment`. See [`Launcher::plan`]
+    /// for what is changed.
+    ///
+    /// If `dry_run` is true, the plan is only returned, not applied.
+    /// Nothing is printed; [`Plan`] implements [`fmt::Display`] for
+    /// callers that want to show it.
+    pub fn reconcile(
+        &self,
+        deployment: &Deployment,
+        dry_run: bool,
+    ) -> Result<Plan, Error> {
+        let plan = self.plan(deployment)?;
+        if !dry_run {
+            plan.apply()?;
+        }
+        Ok(plan)
+    }
+
+    fn remove_container_forced(
+        &self,
+        name: &str,
+    ) -> Result<Command, ValidationError> {
+        let opt = RemoveContainersOpt {
+            force: true,
+            ..RemoveContainersOpt::new([name.parse::<ContainerName>()?])
+        };
+        Ok(self.remove_containers(opt))
+    }
+}
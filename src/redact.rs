@@ -0,0 +1,200 @@
+use crate::shell;
+use command_run::Command;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::sync::Arc;
+
+/// Replacement for redacted values.
+pub const REDACTED: &str = "***";
+
+/// Hides sensitive values in a [`Command`] so that it can be logged.
+///
+/// The following are redacted:
+///
+/// * The value of any option in [`Redactor::flags`], in either the
+///   `--flag value` or `--flag=value` form.
+/// * The value part of `KEY=VALUE` arguments to options in
+///   [`Redactor::key_value_flags`], if the key matches one of
+///   [`Redactor::patterns`].
+/// * The values of environment variables set on the command whose name
+///   matches one of the patterns.
+///
+/// Patterns match if the key contains them, ignoring case.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{BaseCommand, Launcher, Redactor, RunOpt};
+/// let mut opt = RunOpt::new("app");
+/// opt.env = vec![
+///     ("DB_PASSWORD".into(), "hunter2".into()),
+///     ("RUST_LOG".into(), "debug".into()),
+/// ];
+/// let cmd = Launcher::from(BaseCommand::Docker).run(opt);
+/// assert_eq!(
+///     Redactor::default().command_line(&cmd),
+///     "docker run --env 'DB_PASSWORD=***' --env RUST_LOG=debug app"
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Redactor {
+    /// Options whose value is always redacted.
+    pub flags: Vec<String>,
+
+    /// Options whose value has the form `KEY=VALUE`.
+    pub key_value_flags: Vec<String>,
+
+    /// Patterns for keys whose values are sensitive.
+    pub patterns: Vec<String>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        let strings =
+            |list: &[&str]| list.iter().map(|s| s.to_string()).collect();
+        Self {
+            flags: strings(&[
+                "--creds",
+                "--dest-creds",
+                "--password",
+                "--secret",
+                "--src-creds",
+            ]),
+            key_value_flags: strings(&["--build-arg", "--env", "-e"]),
+            patterns: strings(&[
+                "CREDENTIAL",
+                "KEY",
+                "PASSWD",
+                "PASSWORD",
+                "SECRET",
+                "TOKEN",
+            ]),
+        }
+    }
+}
+
+impl Redactor {
+    /// Whether `key` matches one of the patterns.
+    pub fn is_sensitive_key(&self, key: &str) -> bool {
+        let key = key.to_uppercase();
+        self.patterns
+            .iter()
+            .any(|pattern| key.contains(&pattern.to_uppercase()))
+    }
+
+    /// Redact the value of a `KEY=VALUE` argument if the key is
+    /// sensitive.
+    fn redact_key_value(&self, arg: &OsStr) -> Option<OsString> {
+        let arg = arg.to_string_lossy();
+        let (key, _) = arg.split_once('=')?;
+        if self.is_sensitive_key(key) {
+            Some(format!("{}={}", key, REDACTED).into())
+        } else {
+            None
+        }
+    }
+
+    /// Get a copy of `cmd` with sensitive values replaced by
+    /// [`REDACTED`].
+    pub fn redact(&self, cmd: &Command) -> Command {
+        let mut out = cmd.clone();
+
+        let mut prev: Option<&OsStr> = None;
+        for (arg, original) in out.args.iter_mut().zip(&cmd.args) {
+            let lossy = original.to_string_lossy();
+            let is_flag = |flags: &[String], name: &OsStr| {
+                flags.iter().any(|flag| OsStr::new(flag) == name)
+            };
+
+            if let Some(prev) = prev {
+                if is_flag(&self.flags, prev) {
+                    *arg = REDACTED.into();
+                } else if is_flag(&self.key_value_flags, prev) {
+                    if let Some(redacted) = self.redact_key_value(original) {
+                        *arg = redacted;
+                    }
+                }
+            }
+
+            if let Some((flag, value)) = lossy.split_once('=') {
+                if flag.starts_with('-') {
+                    if self.flags.iter().any(|f| f == flag) {
+                        *arg = format!("{}={}", flag, REDACTED).into();
+                    } else if self.key_value_flags.iter().any(|f| f == flag) {
+                        if let Some(redacted) =
+                            self.redact_key_value(OsStr::new(value))
+                        {
+                            let mut new = OsString::from(flag);
+                            new.push("=");
+                            new.push(redacted);
+                            *arg = new;
+                        }
+                    }
+                }
+            }
+
+            prev = Some(original);
+        }
+
+        for (key, value) in out.env.iter_mut() {
+            if self.is_sensitive_key(&key.to_string_lossy()) {
+                *value = REDACTED.into();
+            }
+        }
+
+        out
+    }
+
+    /// Format a redacted copy of `cmd` as a line of shell. See
+    /// [`shell::command_line`].
+    pub fn command_line(&self, cmd: &Command) -> String {
+        shell::command_line(&self.redact(cmd))
+    }
+}
+
+/// Observer for commands created by a [`Launcher`].
+///
+/// This is implemented for any `Fn(&Command)` closure.
+///
+/// [`Launcher`]: crate::Launcher
+pub trait CommandHook: Send + Sync {
+    /// Called with a redacted copy of each command as it is created.
+    fn on_command(&self, cmd: &Command);
+}
+
+impl<F: Fn(&Command) + Send + Sync> CommandHook for F {
+    fn on_command(&self, cmd: &Command) {
+        self(cmd)
+    }
+}
+
+/// A [`CommandHook`] together with the [`Redactor`] applied before
+/// calling it.
+#[derive(Clone)]
+pub(crate) struct Hook {
+    pub(crate) hook: Arc<dyn CommandHook>,
+    pub(crate) redactor: Redactor,
+}
+
+impl Hook {
+    pub(crate) fn call(&self, cmd: &Command) {
+        self.hook.on_command(&self.redactor.redact(cmd));
+    }
+}
+
+impl fmt::Debug for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hook")
+            .field("redactor", &self.redactor)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Hook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.hook, &other.hook) && self.redactor == other.redactor
+    }
+}
+
+impl Eq for Hook {}
@@ -0,0 +1,125 @@
+use crate::audit::container_name;
+use crate::runner::{parse_container_id, stdout};
+use crate::{
+    CommandExt, ContainerId, ContainerName, ContainerRef, Error, Launcher,
+    RemoveContainersOpt, RunOpt, StopOpt, ValidationError,
+};
+use std::time::Duration;
+
+impl Launcher {
+    /// Replace the container named [`RunOpt::name`] with a new one
+    /// created from `opt`, rolling back if the new container does not
+    /// become healthy.
+    ///
+    /// The old container is first renamed aside (the name with
+    /// `-prev` appended), then the new container is started under the
+    /// name and waited on with [`Launcher::wait_healthy`]. Once it is
+    /// healthy the old container is stopped and removed. If that
+    /// fails, the error is returned but the new container is left in
+    /// place.
+    ///
+    /// Host ports cannot be bound by two containers at once, so if
+    /// [`RunOpt::publish`] is not empty the old container is also
+    /// stopped before the new one is started, and there is a short gap
+    /// in service.
+    ///
+    /// If the new container cannot be started or does not become
+    /// healthy, it is removed and the old container gets its name
+    /// back, and is started again if it was stopped. Errors during
+    /// rollback are ignored in favor of the error that caused it.
+    ///
+    /// If a rollback fails, the old container may be left under the
+    /// `-prev` name. It is never removed automatically; instead an
+    /// [`Error::Invalid`] naming it is returned until it is removed or
+    /// renamed.
+    ///
+    /// Returns the ID of the new container.
+    pub fn replace_container(
+        &self,
+        mut opt: RunOpt,
+        timeout: Duration,
+    ) -> Result<ContainerId, Error> {
+        let name: ContainerName = container_name(&opt)?.parse()?;
+        let prev: ContainerName = format!("{}-prev", name).parse()?;
+        let name_ref = ContainerRef::Name(name.clone());
+        let prev_ref = ContainerRef::Name(prev.clone());
+        let old_exists = self.container_exists(&name_ref)?;
+        if old_exists && self.container_exists(&prev_ref)? {
+            return Err(ValidationError::new(
+                "container name",
+                prev.as_str(),
+                format!(
+                    "already in use, possibly left over from an earlier \
+                     replacement of {:?}; remove or rename it first",
+                    name.as_str()
+                ),
+            )
+            .into());
+        }
+        let stop_first = old_exists && !opt.publish.is_empty();
+
+        // Create every command up front, so that nothing is changed if
+        // one of them breaks the launcher's policies.
+        opt.detach = true;
+        let cmd = self.try_run(opt)?;
+        let set_aside = self.enforce(self.rename(&name_ref, &prev))?;
+        let mut restore = self.enforce(self.rename(&prev_ref, &name))?;
+        restore.check = false;
+        let stop_old =
+            self.enforce(self.stop(StopOpt::new([prev_ref.clone()])))?;
+        let mut restart_old =
+            self.enforce(self.start(std::slice::from_ref(&name_ref)))?;
+        restart_old.check = false;
+        let remove_old = self.enforce(
+            self.remove_containers(RemoveContainersOpt::new([prev_ref])),
+        )?;
+        let mut remove_new =
+            self.enforce(self.remove_containers(RemoveContainersOpt {
+                force: true,
+                ..RemoveContainersOpt::new([name_ref])
+            }))?;
+        remove_new.check = false;
+
+        // Undo the changes made before the new container was started.
+        let roll_back = |stopped: bool| {
+            let _ = restore.run_logged();
+            if stopped {
+                let _ = restart_old.run_logged();
+            }
+        };
+
+        if old_exists {
+            set_aside.run_logged()?;
+            if stop_first {
+                if let Err(err) = stop_old.run_logged() {
+                    roll_back(false);
+                    return Err(err.into());
+                }
+            }
+        }
+        let started = stdout(cmd.clone())
+            .and_then(|output| parse_container_id(&cmd, &output))
+            .and_then(|id| {
+                self.wait_healthy(&id.clone().into(), timeout)?;
+                Ok(id)
+            });
+        let id = match started {
+            Ok(id) => id,
+            Err(err) => {
+                let _ = remove_new.run_logged();
+                if old_exists {
+                    roll_back(stop_first);
+                }
+                return Err(err);
+            }
+        };
+
+        if old_exists {
+            if !stop_first {
+                stop_old.run_logged()?;
+            }
+            remove_old.run_logged()?;
+        }
+        Ok(id)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    Batch, CommandExt, Error, ImageRef, Launcher, PushOpt, DEFAULT_TAG,
+};
+use command_run::Command;
+
+/// Get `target`, with the tag of `source` if it has neither a tag nor
+/// a digest.
+fn full_target(source: &ImageRef, target: &ImageRef) -> ImageRef {
+    if target.tag().is_some() || target.digest().is_some() {
+        target.clone()
+    } else {
+        target.with_tag(source.tag().unwrap_or(DEFAULT_TAG))
+    }
+}
+
+impl Launcher {
+    /// Create the `tag` and `push` commands that publish the local
+    /// image `source` as each of `targets`, in order. A target without
+    /// a tag gets the tag of `source`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, ImageRef, Launcher};
+    /// let launcher = Launcher::from(BaseCommand::Docker);
+    /// let commands = launcher.retag_and_push_commands(
+    ///     &"app:1.2".into(),
+    ///     &["ghcr.io/owner/app".into(), "registry.example.com/app:stable".into()],
+    /// );
+    /// let lines: Vec<_> = commands.iter().map(|c| c.command_line_lossy()).collect();
+    /// assert_eq!(
+    ///     lines,
+    ///     [
+    ///         "docker tag app:1.2 ghcr.io/owner/app:1.2",
+    ///         "docker push ghcr.io/owner/app:1.2",
+    ///         "docker tag app:1.2 registry.example.com/app:stable",
+    ///         "docker push registry.example.com/app:stable",
+    ///     ]
+    /// );
+    /// ```
+    pub fn retag_and_push_commands(
+        &self,
+        source: &ImageRef,
+        targets: &[ImageRef],
+    ) -> Vec<Command> {
+        targets
+            .iter()
+            .flat_map(|target| {
+                let target = full_target(source, target);
+                [self.tag(source, &target), self.push(PushOpt::new(target))]
+            })
+            .collect()
+    }
+
+    /// Publish the local image `source` as each of `targets`, as
+    /// described in [`Launcher::retag_and_push_commands`].
+    ///
+    /// The images are tagged one at a time, then pushed with a
+    /// [`Batch`] running at most `concurrency` pushes at once. A
+    /// failure for one target does not stop the others. Returns each
+    /// target (with its tag filled in) and its result, in the order of
+    /// `targets`; if tagging fails, that target is not pushed.
+    pub fn retag_and_push(
+        &self,
+        source: &ImageRef,
+        targets: &[ImageRef],
+        concurrency: usize,
+    ) -> Vec<(ImageRef, Result<(), Error>)> {
+        let mut results: Vec<(ImageRef, Result<(), Error>)> = targets
+            .iter()
+            .map(|target| {
+                let target = full_target(source, target);
+                let tagged =
+                    self.enforce(self.tag(source, &target)).and_then(|cmd| {
+                        cmd.run_logged()?;
+                        Ok(())
+                    });
+                (target, tagged)
+            })
+            .collect();
+
+        let mut pushing = Vec::new();
+        let mut batch = Batch::new();
+        for (i, (target, result)) in results.iter_mut().enumerate() {
+            if result.is_err() {
+                continue;
+            }
+            match self.enforce(self.push(PushOpt::new(target.clone()))) {
+                Ok(cmd) => {
+                    pushing.push(i);
+                    batch.push(cmd);
+                }
+                Err(err) => *result = Err(err),
+            }
+        }
+        let pushed = batch.with_concurrency(concurrency).run();
+        for (i, result) in pushing.into_iter().zip(pushed) {
+            results[i].1 = result.map(|_| ()).map_err(Error::Run);
+        }
+        results
+    }
+}
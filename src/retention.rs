@@ -0,0 +1,202 @@
+use crate::runner::{parse_timestamp, stdout};
+use crate::{
+    CommandExt, DurationArg, Error, Filter, ImagesOpt, Launcher, PruneOpt,
+    RemoveImagesOpt,
+};
+use command_run::Command;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// Template used to list images. Tabs separate the fields.
+const IMAGES_FORMAT: &str =
+    "{{.ID}}\t{{.Repository}}\t{{.Tag}}\t{{.CreatedAt}}";
+
+/// Summary of a local image, as listed by `images`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImageSummary {
+    /// Image ID.
+    pub id: String,
+
+    /// Repository, or `<none>` for an untagged image.
+    pub repository: String,
+
+    /// Tag, or `<none>` for an untagged image.
+    pub tag: String,
+
+    /// When the image was created.
+    pub created: SystemTime,
+}
+
+impl ImageSummary {
+    /// Whether the image has a repository and tag.
+    pub fn is_tagged(&self) -> bool {
+        self.repository != "<none>" && self.tag != "<none>"
+    }
+
+    /// Reference in the form `repository:tag`.
+    pub fn reference(&self) -> String {
+        format!("{}:{}", self.repository, self.tag)
+    }
+}
+
+/// Rules for which images to remove.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{BaseCommand, ImageSummary, Launcher, RetentionPolicy};
+/// use std::time::{Duration, SystemTime};
+///
+/// let now = SystemTime::now();
+/// let image = |tag: &str, age_secs| ImageSummary {
+///     id: tag.into(),
+///     repository: "app".into(),
+///     tag: tag.into(),
+///     created: now - Duration::from_secs(age_secs),
+/// };
+/// let images = [image("v1", 300), image("v2", 200), image("v3", 100)];
+///
+/// let mut policy = RetentionPolicy::default();
+/// policy.keep_recent = Some(2);
+/// let commands = policy.commands(&Launcher::from(BaseCommand::Docker), &images);
+/// assert_eq!(commands[0].command_line_lossy(), "docker rmi app:v1");
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct RetentionPolicy {
+    /// Keep this many of the most recently created tags in each
+    /// repository, and remove the older tags.
+    pub keep_recent: Option<usize>,
+
+    /// Prune images created longer ago than this.
+    pub max_age: Option<Duration>,
+
+    /// Images with this label (`key` or `key=value`) are never pruned
+    /// for being too old. Note that this does not affect
+    /// `keep_recent`.
+    pub keep_label: Option<String>,
+
+    /// Prune all unused images rather than just dangling ones.
+    pub all: bool,
+}
+
+impl RetentionPolicy {
+    /// Get the tags that `keep_recent` would remove from `images`.
+    pub fn tags_to_remove<'a>(
+        &self,
+        images: &'a [ImageSummary],
+    ) -> Vec<&'a ImageSummary> {
+        let Some(keep) = self.keep_recent else {
+            return Vec::new();
+        };
+
+        let mut by_repo: HashMap<&str, Vec<&ImageSummary>> = HashMap::new();
+        for image in images.iter().filter(|image| image.is_tagged()) {
+            by_repo.entry(&image.repository).or_default().push(image);
+        }
+
+        let mut remove: Vec<&ImageSummary> = Vec::new();
+        for tags in by_repo.values_mut() {
+            // Newest first.
+            tags.sort_by_key(|image| Reverse(image.created));
+            remove.extend(tags.iter().skip(keep));
+        }
+        // Sort so that the output is stable.
+        remove.sort_by_key(|image| image.reference());
+        remove
+    }
+
+    /// Generate the commands that apply the policy to `images`.
+    ///
+    /// This is an `rmi` command for the tags removed by `keep_recent`,
+    /// followed by an `image prune` command if `max_age` is set.
+    pub fn commands(
+        &self,
+        launcher: &Launcher,
+        images: &[ImageSummary],
+    ) -> Vec<Command> {
+        let mut commands = Vec::new();
+
+        let remove = self.tags_to_remove(images);
+        if !remove.is_empty() {
+            commands.push(launcher.remove_images(RemoveImagesOpt::new(
+                remove.iter().map(|image| image.reference()),
+            )));
+        }
+
+        if let Some(max_age) = self.max_age {
+            let mut filters =
+                vec![Filter::Until(DurationArg(max_age).to_string())];
+            if let Some(label) = &self.keep_label {
+                filters.push(Filter::LabelNot(label.clone()));
+            }
+            let opt = PruneOpt {
+                all: self.all,
+                filters,
+                force: true,
+                ..Default::default()
+            };
+            commands.push(launcher.prune_images(opt));
+        }
+
+        commands
+    }
+}
+
+/// Parse the output of `images` with [`IMAGES_FORMAT`].
+fn parse_images(output: &str) -> Option<Vec<ImageSummary>> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut fields = line.split('\t');
+            Some(ImageSummary {
+                id: fields.next()?.to_string(),
+                repository: fields.next()?.to_string(),
+                tag: fields.next()?.to_string(),
+                created: parse_timestamp(fields.next()?)?,
+            })
+        })
+        .collect()
+}
+
+impl Launcher {
+    /// List local images.
+    pub fn image_summaries(&self) -> Result<Vec<ImageSummary>, Error> {
+        let opt = ImagesOpt {
+            format: Some(IMAGES_FORMAT.into()),
+            no_trunc: true,
+            ..Default::default()
+        };
+        let cmd = self.enforce(self.images(opt))?;
+        let output = stdout(cmd.clone())?;
+        parse_images(&output).ok_or_else(|| Error::UnexpectedOutput {
+            command: cmd.command_line_shell(),
+            output,
+        })
+    }
+
+    /// Apply a retention policy to the local images.
+    ///
+    /// The generated commands are returned. They are only run if
+    /// `execute` is true, so passing false gives a dry run.
+    pub fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        execute: bool,
+    ) -> Result<Vec<Command>, Error> {
+        let images = self.image_summaries()?;
+        let commands = policy
+            .commands(self, &images)
+            .into_iter()
+            .map(|cmd| self.enforce(cmd))
+            .collect::<Result<Vec<_>, _>>()?;
+        if execute {
+            for cmd in &commands {
+                cmd.run_logged()?;
+            }
+        }
+        Ok(commands)
+    }
+}
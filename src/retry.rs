@@ -0,0 +1,189 @@
+use crate::{CommandExt, Error};
+use command_run::{Command, ErrorKind, Output};
+use std::thread;
+use std::time::Duration;
+
+/// Output that indicates a failure that will not go away by retrying,
+/// such as a missing manifest or bad credentials. These are checked
+/// before [`TRANSIENT_PATTERNS`], since a message like "not found"
+/// may also contain text that looks transient.
+const PERMANENT_PATTERNS: &[&str] = &[
+    "manifest unknown",
+    "name unknown",
+    "not found",
+    "unauthorized",
+    "authentication required",
+    "denied",
+    "invalid reference format",
+    "no such image",
+    "already exists",
+];
+
+/// Output that indicates a failure that is likely to succeed if
+/// retried, such as a network hiccup, a rate limit, or a lock held by
+/// a concurrent command.
+const TRANSIENT_PATTERNS: &[&str] = &[
+    "i/o timeout",
+    "tls handshake timeout",
+    "timeout exceeded",
+    "context deadline exceeded",
+    "connection reset",
+    "connection refused",
+    "broken pipe",
+    "unexpected eof",
+    "temporary failure in name resolution",
+    "no such host",
+    "toomanyrequests",
+    "too many requests",
+    "500 internal server error",
+    "502 bad gateway",
+    "503 service unavailable",
+    "504 gateway timeout",
+    "database is locked",
+    "resource temporarily unavailable",
+    "is being used by another process",
+];
+
+/// How a failed command's output was classified by
+/// [`RetryPolicy::classify`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Failure {
+    /// The failure is likely to go away if the command is retried.
+    Transient,
+
+    /// Retrying will not help.
+    Permanent,
+
+    /// The output did not match any known pattern.
+    Unknown,
+}
+
+/// Retry commands whose failures are typically transient, such as
+/// `pull`, `push`, `login`, and `network create` when racing with
+/// another process.
+///
+/// The delay before each retry starts at `initial_delay` and is
+/// multiplied by `multiplier` after each attempt, up to `max_delay`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use docker_command::{BaseCommand, Launcher, PullOpt, RetryPolicy};
+/// let launcher = Launcher::from(BaseCommand::Docker);
+/// let pull = launcher.pull(PullOpt::new("alpine"));
+/// RetryPolicy::default().run(&pull).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct RetryPolicy {
+    /// Maximum number of times to run the command, including the first
+    /// attempt. Values less than one are treated as one.
+    pub max_attempts: u32,
+
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+
+    /// Upper bound on the delay between attempts.
+    pub max_delay: Duration,
+
+    /// Factor the delay is multiplied by after each retry.
+    pub multiplier: f64,
+
+    /// Whether to retry failures whose output does not match any known
+    /// pattern ([`Failure::Unknown`]).
+    pub retry_unknown: bool,
+}
+
+impl RetryPolicy {
+    /// Policy that runs the command only once.
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Delay before retry number `retry`, starting at zero.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(retry as i32);
+        self.initial_delay
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_delay)
+    }
+
+    /// Classify the output of a failed command.
+    pub fn classify(output: &str) -> Failure {
+        let output = output.to_ascii_lowercase();
+        let matches = |patterns: &[&str]| {
+            patterns.iter().any(|pattern| output.contains(pattern))
+        };
+        if matches(PERMANENT_PATTERNS) {
+            Failure::Permanent
+        } else if matches(TRANSIENT_PATTERNS) {
+            Failure::Transient
+        } else {
+            Failure::Unknown
+        }
+    }
+
+    /// Whether a failure should be retried under this policy.
+    fn should_retry(&self, failure: Failure) -> bool {
+        match failure {
+            Failure::Transient => true,
+            Failure::Permanent => false,
+            Failure::Unknown => self.retry_unknown,
+        }
+    }
+
+    /// Run `cmd` with [`CommandExt::run_logged`], retrying if it fails
+    /// with a transient error.
+    ///
+    /// The output is always captured so that it can be classified. If
+    /// `cmd.check` is false, the output of the final attempt is
+    /// returned even if it failed.
+    pub fn run(&self, cmd: &Command) -> Result<Output, Error> {
+        let mut attempt = cmd.clone();
+        attempt.capture = true;
+        attempt.check = false;
+
+        let mut retry = 0;
+        loop {
+            let output = attempt.run_logged()?;
+            if output.status.success() {
+                return Ok(output);
+            }
+
+            let text = format!(
+                "{}\n{}",
+                output.stdout_string_lossy(),
+                output.stderr_string_lossy()
+            );
+            let last = retry + 1 >= self.max_attempts.max(1);
+            if last || !self.should_retry(Self::classify(&text)) {
+                if !cmd.check {
+                    return Ok(output);
+                }
+                return Err(Error::Run(command_run::Error {
+                    command: cmd.clone(),
+                    kind: ErrorKind::Exit(output.status),
+                }));
+            }
+
+            thread::sleep(self.delay(retry));
+            retry += 1;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Up to four attempts, with delays of 1, 2, and 4 seconds.
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            retry_unknown: false,
+        }
+    }
+}
@@ -0,0 +1,842 @@
+//! Helpers that run the generated commands and interpret their output.
+
+use crate::{
+    BuildOpt, CommandExt, ContainerId, ContainerName, ContainerRef,
+    CopyLocation, CpOpt, CreateNetworkOpt, CreateVolumeOpt, DiskUsage, Error,
+    ExecOpt, Filter, ImageConfig, ImageRef, InfoOpt, InspectOpt, KillOpt,
+    Launcher, ListNetworksOpt, ListVolumesOpt, LoadOpt, ObjectKind, PsOpt,
+    PullOpt, RunOpt, RuntimeError, SaveOpt, StopOpt, SystemDfOpt, SystemInfo,
+    ValidationError,
+};
+use command_run::{Command, ErrorKind, Output};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fs, process, thread};
+
+/// What happened to a container in [`Launcher::stop_or_kill`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StopOutcome {
+    /// The container stopped within the grace period (or was not
+    /// running to begin with).
+    Stopped,
+
+    /// The container was still running after the grace period and
+    /// was killed.
+    Killed,
+
+    /// The container is still running even after being killed.
+    StillRunning,
+
+    /// The container does not exist.
+    NotFound,
+}
+
+/// Run `cmd`, capturing its output, and return stdout with surrounding
+/// whitespace removed.
+pub(crate) fn stdout(mut cmd: Command) -> Result<String, Error> {
+    cmd.capture = true;
+    cmd.log_output_on_error = true;
+    let output = cmd.run_logged()?;
+    Ok(output.stdout_string_lossy().trim().to_string())
+}
+
+/// Run `source` with its stdout connected to the stdin of `sink`.
+pub(crate) fn pipe(source: &Command, sink: &Command) -> Result<(), Error> {
+    fn run_error(cmd: &Command, err: io::Error) -> Error {
+        Error::Run(command_run::Error {
+            command: cmd.clone(),
+            kind: ErrorKind::Run(err),
+        })
+    }
+
+    #[cfg(feature = "mock")]
+    if crate::mock::is_mocked(source) || crate::mock::is_mocked(sink) {
+        return crate::mock::pipe(source, sink);
+    }
+
+    let mut source_proc: process::Command = source.into();
+    source_proc.stdout(Stdio::piped());
+    let mut source_child =
+        source_proc.spawn().map_err(|err| run_error(source, err))?;
+    let Some(stdout) = source_child.stdout.take() else {
+        unreachable!("stdout is piped");
+    };
+
+    let mut sink_proc: process::Command = sink.into();
+    sink_proc.stdin(stdout);
+    let sink_status = sink_proc
+        .spawn()
+        .and_then(|mut child| child.wait())
+        .map_err(|err| run_error(sink, err));
+    if sink_status.is_err() {
+        // Don't leave the source blocked on a pipe nobody reads.
+        let _ = source_child.kill();
+    }
+    let source_status =
+        source_child.wait().map_err(|err| run_error(source, err));
+
+    // Check the sink first: if it fails, the source usually fails too
+    // because its output pipe was closed.
+    for (cmd, status) in [(sink, sink_status?), (source, source_status?)] {
+        if cmd.check && !status.success() {
+            return Err(Error::Run(command_run::Error {
+                command: cmd.clone(),
+                kind: ErrorKind::Exit(status),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Run `cmd` with `input` written to its stdin. Stdout is discarded.
+pub(crate) fn run_with_stdin(cmd: &Command, input: &[u8]) -> Result<(), Error> {
+    let to_error = |err| {
+        Error::Run(command_run::Error {
+            command: cmd.clone(),
+            kind: ErrorKind::Run(err),
+        })
+    };
+
+    crate::log_command(cmd);
+
+    #[cfg(feature = "mock")]
+    if let Some(output) = crate::mock::intercept_checked(cmd) {
+        output?;
+        return Ok(());
+    }
+
+    let mut proc: process::Command = cmd.into();
+    proc.stdin(Stdio::piped()).stdout(Stdio::null());
+    let mut child = proc.spawn().map_err(to_error)?;
+    let Some(mut stdin) = child.stdin.take() else {
+        unreachable!("stdin is piped");
+    };
+    // Write on another thread so that a child that does not read all
+    // of its input can't deadlock this one.
+    let input = input.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let status = child.wait().map_err(to_error)?;
+    let written = writer.join().unwrap_or(Ok(()));
+    if cmd.check && !status.success() {
+        return Err(Error::Run(command_run::Error {
+            command: cmd.clone(),
+            kind: ErrorKind::Exit(status),
+        }));
+    }
+    written.map_err(to_error)
+}
+
+/// Parse `true` or `false` from the output of `cmd`.
+fn parse_bool(cmd: &Command, output: &str) -> Result<bool, Error> {
+    match output {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(Error::UnexpectedOutput {
+            command: cmd.command_line_shell(),
+            output: output.to_string(),
+        }),
+    }
+}
+
+/// Check the output of an `inspect` command run without `check`.
+/// Returns false if it failed with `missing`, meaning the object does
+/// not exist, and an error if it failed for any other reason.
+fn found(
+    cmd: &Command,
+    output: &Output,
+    missing: RuntimeError,
+) -> Result<bool, Error> {
+    match RuntimeError::from_output(output) {
+        None => Ok(true),
+        Some(err) if err == missing => Ok(false),
+        Some(_) => Err(Error::Run(command_run::Error {
+            command: cmd.clone(),
+            kind: ErrorKind::Exit(output.status),
+        })),
+    }
+}
+
+/// Get a path in the temporary directory that is unique to this call.
+pub(crate) fn temp_path(prefix: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    env::temp_dir().join(format!("{}-{}-{}", prefix, process::id(), n))
+}
+
+/// Parse a container ID printed by `run --detach` and similar.
+pub(crate) fn parse_container_id(
+    cmd: &Command,
+    output: &str,
+) -> Result<ContainerId, Error> {
+    output.parse().map_err(|_| Error::UnexpectedOutput {
+        command: cmd.command_line_shell(),
+        output: output.to_string(),
+    })
+}
+
+/// Parse a timestamp as printed by docker or podman.
+///
+/// Both RFC 3339 (`2024-01-02T03:04:05.123Z`) and Go's default time
+/// format (`2024-01-02 03:04:05.123 +0000 UTC`) are accepted.
+pub(crate) fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    fn num(s: &str) -> Option<i64> {
+        if s.chars().all(|c| c.is_ascii_digit()) {
+            s.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    let s = s.trim();
+    let b = s.as_bytes();
+    if b.len() < 19
+        || b[4] != b'-'
+        || b[7] != b'-'
+        || !(b[10] == b'T' || b[10] == b' ')
+        || b[13] != b':'
+        || b[16] != b':'
+    {
+        return None;
+    }
+    let year = num(&s[0..4])?;
+    let month = num(&s[5..7])?;
+    let day = num(&s[8..10])?;
+    let hour = num(&s[11..13])?;
+    let minute = num(&s[14..16])?;
+    let second = num(&s[17..19])?;
+
+    // Fractional seconds.
+    let mut rest = &s[19..];
+    let mut nanos = 0;
+    if let Some(frac) = rest.strip_prefix('.') {
+        let len = frac
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(frac.len());
+        let digits = &frac[..len.min(9)];
+        nanos = num(digits)? as u32 * 10u32.pow(9 - digits.len() as u32);
+        rest = &frac[len..];
+    }
+
+    // Offset from UTC.
+    let rest = rest.trim_start();
+    let offset = if rest.starts_with('Z') {
+        0
+    } else if let Some(sign) = rest
+        .strip_prefix('+')
+        .map(|r| (1, r))
+        .or_else(|| rest.strip_prefix('-').map(|r| (-1, r)))
+    {
+        let (sign, r) = sign;
+        let r = r.split(' ').next()?.replace(':', "");
+        if r.len() != 4 {
+            return None;
+        }
+        sign * (num(&r[..2])? * 3600 + num(&r[2..])? * 60)
+    } else {
+        return None;
+    };
+
+    // Days since the epoch, from Howard Hinnant's `days_from_civil`.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second - offset;
+    let secs = u64::try_from(secs).ok()?;
+    Some(UNIX_EPOCH + Duration::new(secs, nanos))
+}
+
+/// Check whether `name` is one of the lines in `output`.
+fn has_line(output: &str, name: &str) -> bool {
+    output.lines().any(|line| line.trim() == name)
+}
+
+impl Launcher {
+    /// Check whether the container engine is running in rootless mode.
+    ///
+    /// For podman this queries `podman info`. For docker it checks
+    /// whether the daemon's security options include `rootless`.
+    pub fn is_rootless(&self) -> Result<bool, Error> {
+        let mut opt = InfoOpt::default();
+        if self.is_podman() {
+            opt.format = Some("{{.Host.Security.Rootless}}".into());
+            let cmd = self.enforce(self.info(opt))?;
+            let output = stdout(cmd.clone())?;
+            parse_bool(&cmd, &output)
+        } else {
+            opt.format = Some("{{json .SecurityOptions}}".into());
+            let output = stdout(self.enforce(self.info(opt))?)?;
+            Ok(output.contains("name=rootless"))
+        }
+    }
+
+    /// Check whether a network named `name` exists.
+    pub fn network_exists(&self, name: &str) -> Result<bool, Error> {
+        // The name filter matches substrings, so compare the names
+        // exactly as well.
+        let opt = ListNetworksOpt {
+            filters: vec![Filter::Name(name.into())],
+            format: Some("{{.Name}}".into()),
+            ..Default::default()
+        };
+        let output = stdout(self.enforce(self.list_networks(opt))?)?;
+        Ok(has_line(&output, name))
+    }
+
+    /// Check whether a volume named `name` exists.
+    pub fn volume_exists(&self, name: &str) -> Result<bool, Error> {
+        let opt = ListVolumesOpt {
+            filters: vec![Filter::Name(name.into())],
+            format: Some("{{.Name}}".into()),
+            ..Default::default()
+        };
+        let output = stdout(self.enforce(self.list_volumes(opt))?)?;
+        Ok(has_line(&output, name))
+    }
+
+    /// Create a network unless one with the same name already exists.
+    ///
+    /// Returns true if the network was created. An existing network is
+    /// left as is, even if its settings differ from `opt`.
+    pub fn ensure_network(&self, opt: CreateNetworkOpt) -> Result<bool, Error> {
+        if self.network_exists(&opt.name)? {
+            return Ok(false);
+        }
+        let name = opt.name.clone();
+        if let Err(err) = self.enforce(self.create_network(opt))?.run_logged() {
+            // Another process may have created the network since it
+            // was checked for above.
+            if self.network_exists(&name)? {
+                return Ok(false);
+            }
+            return Err(err.into());
+        }
+        Ok(true)
+    }
+
+    /// Create a volume unless one with the same name already exists.
+    ///
+    /// Returns true if the volume was created. An existing volume is
+    /// left as is, even if its settings differ from `opt`.
+    pub fn ensure_volume(&self, opt: CreateVolumeOpt) -> Result<bool, Error> {
+        if self.volume_exists(&opt.name)? {
+            return Ok(false);
+        }
+        let name = opt.name.clone();
+        if let Err(err) = self.enforce(self.create_volume(opt))?.run_logged() {
+            // Another process may have created the volume since it was
+            // checked for above.
+            if self.volume_exists(&name)? {
+                return Ok(false);
+            }
+            return Err(err.into());
+        }
+        Ok(true)
+    }
+
+    /// Build an image and then run it in a detached container.
+    ///
+    /// If `build.iidfile` is set, the image ID is read from it.
+    /// Otherwise `quiet` is forced on and the ID is taken from the
+    /// output of the build. The image in `run` is replaced with the ID
+    /// of the newly built image, and `detach` is forced on so that the
+    /// container ID can be returned.
+    pub fn build_and_run(
+        &self,
+        mut build: BuildOpt,
+        mut run: RunOpt,
+    ) -> Result<ContainerId, Error> {
+        let iidfile = build.iidfile.clone();
+        if iidfile.is_none() {
+            build.quiet = true;
+        }
+        let cmd = self.try_build(build)?;
+        let output = stdout(cmd.clone())?;
+        let image_id = match iidfile {
+            Some(path) => fs::read_to_string(path)?,
+            None => output.lines().last().unwrap_or_default().to_string(),
+        };
+        let image_id = image_id.trim();
+        if image_id.is_empty() {
+            return Err(Error::UnexpectedOutput {
+                command: cmd.command_line_shell(),
+                output,
+            });
+        }
+
+        run.image = image_id.into();
+        run.detach = true;
+        let cmd = self.try_run(run)?;
+        let output = stdout(cmd.clone())?;
+        parse_container_id(&cmd, &output)
+    }
+
+    /// Check whether `image` is present locally.
+    pub fn image_exists(&self, image: &ImageRef) -> Result<bool, Error> {
+        Ok(self.image_created(image)?.is_some())
+    }
+
+    /// Check whether `container` exists, whether or not it is running.
+    pub fn container_exists(
+        &self,
+        container: &ContainerRef,
+    ) -> Result<bool, Error> {
+        // As with networks, the name filter matches substrings, and the
+        // ID filter matches prefixes, so check the output as well.
+        let (filter, format) = match container {
+            ContainerRef::Name(name) => {
+                (Filter::Name(name.to_string()), "{{.Names}}")
+            }
+            ContainerRef::Id(id) => (Filter::Id(id.to_string()), "{{.ID}}"),
+        };
+        let opt = PsOpt {
+            all: true,
+            filters: vec![filter],
+            format: Some(format.into()),
+            no_trunc: true,
+            ..Default::default()
+        };
+        let output = stdout(self.enforce(self.ps(opt))?)?;
+        Ok(match container {
+            ContainerRef::Name(name) => has_line(&output, name.as_str()),
+            ContainerRef::Id(id) => output
+                .lines()
+                .any(|line| line.trim().starts_with(id.as_str())),
+        })
+    }
+
+    /// Stop the running containers that match `filter`, like `stop
+    /// $(ps --quiet --filter ...)` in a shell.
+    ///
+    /// The matching containers are added to `opt.containers`. If
+    /// nothing matches (and `opt.containers` is empty), `stop` is not
+    /// run. Returns the IDs of the matching containers.
+    pub fn stop_by_filter(
+        &self,
+        filter: Filter,
+        mut opt: StopOpt,
+    ) -> Result<Vec<ContainerId>, Error> {
+        let ps_opt = PsOpt {
+            filters: vec![filter],
+            no_trunc: true,
+            quiet: true,
+            ..Default::default()
+        };
+        let cmd = self.enforce(self.ps(ps_opt))?;
+        let output = stdout(cmd.clone())?;
+        let ids = output
+            .lines()
+            .map(|line| parse_container_id(&cmd, line.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        opt.containers
+            .extend(ids.iter().cloned().map(ContainerRef::Id));
+        if !opt.containers.is_empty() {
+            self.enforce(self.stop(opt))?.run_logged()?;
+        }
+        Ok(ids)
+    }
+
+    /// Get the creation time of `image`, or None if the image is not
+    /// present locally. Other failures of `inspect`, such as the
+    /// daemon not running, are returned as errors.
+    fn image_created(
+        &self,
+        image: &ImageRef,
+    ) -> Result<Option<SystemTime>, Error> {
+        let mut opt = InspectOpt::new([image.to_string()]);
+        opt.format = Some("{{.Created}}".into());
+        opt.kind = Some(ObjectKind::Image);
+        let mut cmd = self.enforce(self.inspect(opt))?;
+        cmd.check = false;
+        cmd.capture = true;
+        let output = cmd.run_logged()?;
+        if !found(&cmd, &output, RuntimeError::NoSuchImage)? {
+            return Ok(None);
+        }
+        let output = output.stdout_string_lossy();
+        match parse_timestamp(&output) {
+            Some(created) => Ok(Some(created)),
+            None => Err(Error::UnexpectedOutput {
+                command: cmd.command_line_shell(),
+                output: output.trim().to_string(),
+            }),
+        }
+    }
+
+    /// Get the configuration of `image`, which must be present
+    /// locally. See [`ImageConfig::parse_json`] for details.
+    pub fn image_config(&self, image: &ImageRef) -> Result<ImageConfig, Error> {
+        let mut opt = InspectOpt::new([image.to_string()]);
+        opt.kind = Some(ObjectKind::Image);
+        let cmd = self.enforce(self.inspect(opt))?;
+        let output = stdout(cmd.clone())?;
+        ImageConfig::parse_json(&output).map_err(|_| Error::UnexpectedOutput {
+            command: cmd.command_line_shell(),
+            output,
+        })
+    }
+
+    /// Pull `image` unless it is already present locally.
+    ///
+    /// If `max_age` is set, the image is also pulled if it was created
+    /// longer ago than that. Note that this is when the image was
+    /// built, not when it was last pulled.
+    ///
+    /// Returns true if the image was pulled.
+    pub fn pull_if_missing(
+        &self,
+        image: &ImageRef,
+        max_age: Option<Duration>,
+    ) -> Result<bool, Error> {
+        if let Some(created) = self.image_created(image)? {
+            let fresh = match max_age {
+                Some(max_age) => {
+                    SystemTime::now()
+                        .duration_since(created)
+                        .unwrap_or_default()
+                        <= max_age
+                }
+                None => true,
+            };
+            if fresh {
+                return Ok(false);
+            }
+        }
+        self.enforce(self.pull(PullOpt::new(image.clone())))?
+            .run_logged()?;
+        Ok(true)
+    }
+
+    /// Check whether `container` is running. Returns None if the
+    /// container does not exist, and an error if `inspect` fails for
+    /// any other reason.
+    fn is_running(
+        &self,
+        container: &ContainerRef,
+    ) -> Result<Option<bool>, Error> {
+        let mut opt = InspectOpt::new([container.to_string()]);
+        opt.format = Some("{{.State.Running}}".into());
+        opt.kind = Some(ObjectKind::Container);
+        let mut cmd = self.enforce(self.inspect(opt))?;
+        cmd.check = false;
+        cmd.capture = true;
+        let output = cmd.run_logged()?;
+        if !found(&cmd, &output, RuntimeError::NoSuchContainer)? {
+            return Ok(None);
+        }
+        let output = output.stdout_string_lossy();
+        parse_bool(&cmd, output.trim()).map(Some)
+    }
+
+    /// Stop `containers`, killing any that are still running after
+    /// `grace`.
+    ///
+    /// Failures from `stop` and `kill` themselves are not returned as
+    /// errors; instead the state of each container is checked
+    /// afterwards and reported in the same order as `containers`.
+    pub fn stop_or_kill(
+        &self,
+        containers: &[ContainerRef],
+        grace: Duration,
+    ) -> Result<Vec<(ContainerRef, StopOutcome)>, Error> {
+        if containers.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut opt = StopOpt::new(containers.iter().cloned());
+        opt.time = Some(grace.into());
+        let mut cmd = self.enforce(self.stop(opt))?;
+        cmd.check = false;
+        cmd.run_logged()?;
+
+        let mut outcomes = Vec::with_capacity(containers.len());
+        for container in containers {
+            let outcome = match self.is_running(container)? {
+                None => StopOutcome::NotFound,
+                Some(false) => StopOutcome::Stopped,
+                Some(true) => {
+                    let mut cmd = self.enforce(
+                        self.kill(KillOpt::new([container.clone()])),
+                    )?;
+                    cmd.check = false;
+                    cmd.run_logged()?;
+                    match self.is_running(container)? {
+                        None | Some(false) => StopOutcome::Killed,
+                        Some(true) => StopOutcome::StillRunning,
+                    }
+                }
+            };
+            outcomes.push((container.clone(), outcome));
+        }
+        Ok(outcomes)
+    }
+
+    /// Get the disk usage of images, containers, volumes, and the
+    /// build cache. See [`DiskUsage::parse_json`] for details.
+    pub fn disk_usage(&self) -> Result<Vec<DiskUsage>, Error> {
+        let opt = SystemDfOpt {
+            format: Some("json".into()),
+            ..Default::default()
+        };
+        let cmd = self.enforce(self.system_df(opt))?;
+        let output = stdout(cmd.clone())?;
+        DiskUsage::parse_json(&output).map_err(|_| Error::UnexpectedOutput {
+            command: cmd.command_line_shell(),
+            output,
+        })
+    }
+
+    /// Get information about the container engine. See
+    /// [`SystemInfo::parse_json`] for details.
+    pub fn system_info(&self) -> Result<SystemInfo, Error> {
+        let opt = InfoOpt {
+            format: Some("{{json .}}".into()),
+            ..Default::default()
+        };
+        let cmd = self.enforce(self.info(opt))?;
+        let output = stdout(cmd.clone())?;
+        SystemInfo::parse_json(&output).map_err(|_| Error::UnexpectedOutput {
+            command: cmd.command_line_shell(),
+            output,
+        })
+    }
+
+    /// Get the path of the socket that `podman system service` listens
+    /// on by default, for use with clients of the REST API. Podman
+    /// only.
+    ///
+    /// The service must be started separately, e.g. with
+    /// [`Launcher::system_service`] or a systemd socket unit.
+    pub fn podman_socket_path(&self) -> Result<PathBuf, Error> {
+        let opt = InfoOpt {
+            format: Some("{{.Host.RemoteSocket.Path}}".into()),
+            ..Default::default()
+        };
+        let cmd = self.enforce(self.info(opt))?;
+        let output = stdout(cmd.clone())?;
+        // Older versions of podman include the scheme.
+        let path = output.strip_prefix("unix://").unwrap_or(&output);
+        if path.is_empty() {
+            return Err(Error::UnexpectedOutput {
+                command: cmd.command_line_shell(),
+                output,
+            });
+        }
+        Ok(PathBuf::from(path))
+    }
+
+    /// Check that the init binary requested by `opt` is available.
+    ///
+    /// Nothing is checked unless [`RunOpt::init`] or
+    /// [`RunOpt::init_path`] is set. With podman, the init path must
+    /// be an existing file on the host. Docker does not support a
+    /// per-container init path, so setting one is an error; otherwise
+    /// the daemon is asked whether it found its init binary.
+    pub fn check_init(&self, opt: &RunOpt) -> Result<(), Error> {
+        if !opt.init && opt.init_path.is_none() {
+            return Ok(());
+        }
+
+        if self.is_podman() {
+            if let Some(path) = &opt.init_path {
+                if !path.is_file() {
+                    return Err(ValidationError::new(
+                        "init path",
+                        path.to_string_lossy(),
+                        "no such file",
+                    )
+                    .into());
+                }
+            }
+            return Ok(());
+        }
+
+        if let Some(path) = &opt.init_path {
+            return Err(ValidationError::new(
+                "init path",
+                path.to_string_lossy(),
+                "not supported by docker; set \"init-path\" in the \
+                 daemon's daemon.json instead",
+            )
+            .into());
+        }
+        let opt = InfoOpt {
+            format: Some("{{.InitBinary}} {{.InitCommit.ID}}".into()),
+            ..Default::default()
+        };
+        let output = stdout(self.enforce(self.info(opt))?)?;
+        match output.split_once(' ') {
+            Some((_, "" | "N/A")) | None => Err(ValidationError::new(
+                "init binary",
+                output.split(' ').next().unwrap_or_default(),
+                "not found by the daemon",
+            )
+            .into()),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Run a container with a name from [`ContainerName::generate`],
+    /// returning the name and the output of `run`.
+    ///
+    /// `opt.name` is replaced. If the name turns out to be in use
+    /// anyway, a new one is generated and `run` is tried again, up to
+    /// three times in total. The output of `run` is captured.
+    pub fn run_with_unique_name(
+        &self,
+        mut opt: RunOpt,
+        prefix: &str,
+    ) -> Result<(ContainerName, Output), Error> {
+        const ATTEMPTS: usize = 3;
+        let mut attempt = 1;
+        loop {
+            let name = ContainerName::generate(prefix)?;
+            opt.name = Some(name.to_string());
+            let mut cmd = self.try_run(opt.clone())?;
+            cmd.capture = true;
+            cmd.check = false;
+            let output = cmd.run_logged()?;
+            if output.status.success() {
+                return Ok((name, output));
+            }
+            if attempt == ATTEMPTS
+                || RuntimeError::from_output(&output)
+                    != Some(RuntimeError::NameConflict)
+            {
+                return Err(Error::Run(command_run::Error {
+                    command: cmd,
+                    kind: ErrorKind::Exit(output.status),
+                }));
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Wait for `container` to stop and return its exit code.
+    ///
+    /// The exit code is normally taken from the output of `wait`. If
+    /// `wait` fails or its output cannot be parsed, the exit code is
+    /// read with `inspect` instead.
+    pub fn wait_for_exit(
+        &self,
+        container: &ContainerRef,
+    ) -> Result<i32, Error> {
+        let mut cmd =
+            self.enforce(self.wait(std::slice::from_ref(container)))?;
+        cmd.check = false;
+        cmd.capture = true;
+        let output = cmd.run_logged()?;
+        if output.status.success() {
+            if let Ok(code) = output.stdout_string_lossy().trim().parse() {
+                return Ok(code);
+            }
+        }
+
+        let opt = InspectOpt {
+            format: Some("{{.State.ExitCode}}".into()),
+            kind: Some(ObjectKind::Container),
+            ..InspectOpt::new([container.to_string()])
+        };
+        let cmd = self.enforce(self.inspect(opt))?;
+        let output = stdout(cmd.clone())?;
+        output.parse().map_err(|_| Error::UnexpectedOutput {
+            command: cmd.command_line_shell(),
+            output,
+        })
+    }
+
+    /// Copy `images` to the daemon used by `dest`, by piping `save`
+    /// into `load` without writing an archive to disk.
+    ///
+    /// `dest` can run on another host, for example with
+    /// `Launcher::new(Command::with_args("ssh", ["host", "docker"]))`.
+    pub fn copy_images<I>(
+        &self,
+        images: I,
+        dest: &Launcher,
+    ) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<ImageRef>,
+    {
+        let save = self.enforce(self.save(SaveOpt::new(images)))?;
+        let load = dest.enforce(dest.load(LoadOpt::default()))?;
+        save.pipe_to(&load)
+    }
+
+    /// Copy the contents of `host_dir` into `container_dir` in a
+    /// running `container`, replacing files that already exist. This
+    /// is useful for pushing source changes into a development server
+    /// that reloads on change.
+    ///
+    /// `container_dir` is created if it does not exist, but its parent
+    /// must exist. Files that were deleted from `host_dir` are not
+    /// deleted from the container.
+    pub fn sync_dir<P, Q>(
+        &self,
+        host_dir: P,
+        container: &ContainerRef,
+        container_dir: Q,
+    ) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        // With a trailing "/." the contents of the directory are
+        // copied, not the directory itself.
+        let src = host_dir.as_ref().join(".");
+        let opt = CpOpt::new(
+            CopyLocation::Host(src),
+            CopyLocation::Container(
+                container.clone(),
+                container_dir.as_ref().into(),
+            ),
+        );
+        self.enforce(self.cp(opt))?.run_logged()?;
+        Ok(())
+    }
+
+    /// Read the file at `path` in a running `container`.
+    ///
+    /// This runs `cat` in the container, so it must be present in the
+    /// image. It is intended for small files; use [`Launcher::cp`] for
+    /// large files or directories.
+    pub fn read_file<P: AsRef<Path>>(
+        &self,
+        container: &ContainerRef,
+        path: P,
+    ) -> Result<Vec<u8>, Error> {
+        let mut opt = ExecOpt::new(container.clone(), "cat");
+        opt.args = vec!["--".into(), path.as_ref().into()];
+        let mut cmd = self.enforce(self.exec(opt))?;
+        cmd.capture = true;
+        cmd.log_output_on_error = true;
+        Ok(cmd.run_logged()?.stdout)
+    }
+
+    /// Write `contents` to the file at `path` in a running
+    /// `container`, replacing the file if it exists.
+    ///
+    /// This runs `tee` in the container with `contents` on its stdin,
+    /// so `tee` must be present in the image.
+    pub fn write_file<P: AsRef<Path>>(
+        &self,
+        container: &ContainerRef,
+        path: P,
+        contents: &[u8],
+    ) -> Result<(), Error> {
+        let mut opt = ExecOpt::new(container.clone(), "tee");
+        opt.args = vec!["--".into(), path.as_ref().into()];
+        opt.interactive = true;
+        let cmd = self.enforce(self.exec(opt))?;
+        run_with_stdin(&cmd, contents)
+    }
+}
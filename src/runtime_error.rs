@@ -0,0 +1,142 @@
+use command_run::Output;
+use std::fmt;
+
+/// Kind of failure of a docker or podman command, determined from its
+/// exit code and output by [`RuntimeError::classify`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum RuntimeError {
+    /// The container does not exist.
+    NoSuchContainer,
+
+    /// The image does not exist locally.
+    NoSuchImage,
+
+    /// The image or tag does not exist in the registry.
+    ManifestUnknown,
+
+    /// A host port requested with `--publish` is already in use.
+    PortAllocated,
+
+    /// A container with the requested name already exists.
+    NameConflict,
+
+    /// The user does not have permission to access the daemon socket.
+    SocketPermissionDenied,
+
+    /// The daemon is not running or could not be reached.
+    DaemonUnavailable,
+
+    /// Other error from the daemon or client itself (exit code 125).
+    Daemon,
+
+    /// The command in the container could not be invoked (exit code
+    /// 126), e.g. because it is not executable.
+    CommandNotExecutable,
+
+    /// The command in the container was not found (exit code 127).
+    CommandNotFound,
+
+    /// The failure did not match any known kind. This includes the
+    /// command in the container exiting non-zero on its own.
+    Other,
+}
+
+/// Patterns matched against the lowercased output, checked in order.
+const PATTERNS: &[(&str, RuntimeError)] = &[
+    ("no such container", RuntimeError::NoSuchContainer),
+    ("no such image", RuntimeError::NoSuchImage),
+    ("image not known", RuntimeError::NoSuchImage),
+    ("manifest unknown", RuntimeError::ManifestUnknown),
+    ("manifest for", RuntimeError::ManifestUnknown),
+    ("port is already allocated", RuntimeError::PortAllocated),
+    ("address already in use", RuntimeError::PortAllocated),
+    ("is already in use", RuntimeError::NameConflict),
+    (
+        "permission denied while trying to connect",
+        RuntimeError::SocketPermissionDenied,
+    ),
+    (
+        "cannot connect to the docker daemon",
+        RuntimeError::DaemonUnavailable,
+    ),
+    ("cannot connect to podman", RuntimeError::DaemonUnavailable),
+    (
+        "is the docker daemon running",
+        RuntimeError::DaemonUnavailable,
+    ),
+];
+
+impl RuntimeError {
+    /// Classify a failed command from its exit code (if it exited
+    /// normally) and its output.
+    ///
+    /// Known error messages take precedence over the exit code, since
+    /// docker uses 125 for most errors of its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::RuntimeError;
+    /// assert_eq!(
+    ///     RuntimeError::classify(
+    ///         Some(1),
+    ///         "Error response from daemon: No such container: web",
+    ///     ),
+    ///     RuntimeError::NoSuchContainer
+    /// );
+    /// assert_eq!(
+    ///     RuntimeError::classify(Some(127), "exec: \"foo\": not found"),
+    ///     RuntimeError::CommandNotFound
+    /// );
+    /// ```
+    pub fn classify(exit_code: Option<i32>, output: &str) -> Self {
+        let output = output.to_ascii_lowercase();
+        if let Some((_, kind)) = PATTERNS
+            .iter()
+            .find(|(pattern, _)| output.contains(pattern))
+        {
+            return *kind;
+        }
+        match exit_code {
+            Some(125) => Self::Daemon,
+            Some(126) => Self::CommandNotExecutable,
+            Some(127) => Self::CommandNotFound,
+            _ => Self::Other,
+        }
+    }
+
+    /// Classify the output of a command run with `capture` set.
+    /// Returns `None` if the command succeeded.
+    pub fn from_output(output: &Output) -> Option<Self> {
+        if output.status.success() {
+            return None;
+        }
+        let text = format!(
+            "{}\n{}",
+            output.stdout_string_lossy(),
+            output.stderr_string_lossy()
+        );
+        Some(Self::classify(output.status.code(), &text))
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::NoSuchContainer => "no such container",
+            Self::NoSuchImage => "no such image",
+            Self::ManifestUnknown => "manifest unknown",
+            Self::PortAllocated => "port is already allocated",
+            Self::NameConflict => "name is already in use",
+            Self::SocketPermissionDenied => {
+                "permission denied on the daemon socket"
+            }
+            Self::DaemonUnavailable => "cannot connect to the daemon",
+            Self::Daemon => "daemon error",
+            Self::CommandNotExecutable => "command cannot be invoked",
+            Self::CommandNotFound => "command not found",
+            Self::Other => "command failed",
+        })
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{Mount, MountType};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Directory that secret files are placed in by default, matching
+/// docker swarm and podman.
+pub const SECRETS_DIR: &str = "/run/secrets";
+
+/// Where a podman secret is exposed in the container.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SecretTarget {
+    /// Mount the secret as a file. Relative paths are placed under
+    /// [`SECRETS_DIR`].
+    File(PathBuf),
+
+    /// Set the secret as an environment variable with this name.
+    Env(String),
+}
+
+/// Secret passed to a container with [`RunOpt::secrets`].
+///
+/// Each source is rendered with the option that keeps the secret value
+/// off the command line:
+///
+/// * [`SecretSource::Podman`] uses `--secret`.
+/// * [`SecretSource::File`] bind-mounts the file read-only.
+/// * [`SecretSource::Env`] passes a variable by name with `--env`, so
+///   the value is taken from the environment of the docker or podman
+///   process rather than the arguments.
+/// * [`SecretSource::EnvFile`] uses `--env-file`.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::SecretSource;
+/// let secret = SecretSource::file("/etc/app/token");
+/// assert_eq!(
+///     secret.arg(),
+///     "type=bind,source=/etc/app/token,target=/run/secrets/token,readonly"
+/// );
+/// assert_eq!(SecretSource::Env("API_KEY".into()).arg(), "API_KEY");
+/// ```
+///
+/// [`RunOpt::secrets`]: crate::RunOpt::secrets
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SecretSource {
+    /// Secret created with `podman secret create`. Podman only.
+    Podman {
+        /// Name or ID of the secret.
+        id: String,
+
+        /// Where to expose the secret. If `None`, podman mounts it at
+        /// `/run/secrets/<id>`.
+        target: Option<SecretTarget>,
+    },
+
+    /// File on the host, mounted read-only into the container.
+    File {
+        /// Path of the file on the host.
+        path: PathBuf,
+
+        /// Path in the container. Relative paths are placed under
+        /// [`SECRETS_DIR`].
+        target: PathBuf,
+    },
+
+    /// Environment variable of the docker or podman process, passed
+    /// through under the same name.
+    Env(String),
+
+    /// File on the host containing `KEY=VALUE` lines to set as
+    /// environment variables.
+    EnvFile(PathBuf),
+}
+
+impl SecretSource {
+    /// Create a [`SecretSource::File`] that mounts `path` under
+    /// [`SECRETS_DIR`] with the same file name.
+    pub fn file<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let target = path.file_name().map(PathBuf::from).unwrap_or_default();
+        Self::File { path, target }
+    }
+
+    /// Option used to pass the secret, e.g. `--secret`.
+    pub fn flag(&self) -> &'static str {
+        match self {
+            Self::Podman { .. } => "--secret",
+            Self::File { .. } => "--mount",
+            Self::Env(_) => "--env",
+            Self::EnvFile(_) => "--env-file",
+        }
+    }
+
+    /// Format as an argument for [`SecretSource::flag`].
+    pub fn arg(&self) -> OsString {
+        match self {
+            Self::Podman { id, target } => {
+                let mut out = OsString::from(id);
+                match target {
+                    Some(SecretTarget::File(path)) => {
+                        out.push(",type=mount,target=");
+                        out.push(secret_path(path));
+                    }
+                    Some(SecretTarget::Env(name)) => {
+                        out.push(",type=env,target=");
+                        out.push(name);
+                    }
+                    None => {}
+                }
+                out
+            }
+            Self::File { path, target } => Mount {
+                kind: MountType::Bind,
+                source: Some(path.clone()),
+                target: secret_path(target),
+                read_only: true,
+                options: Vec::new(),
+            }
+            .arg(),
+            Self::Env(name) => name.into(),
+            Self::EnvFile(path) => path.into(),
+        }
+    }
+}
+
+fn secret_path(path: &Path) -> PathBuf {
+    Path::new(SECRETS_DIR).join(path)
+}
@@ -0,0 +1,138 @@
+use crate::{Error, RunOpt};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::{fmt, io};
+
+/// Security option passed to `run` with `--security-opt`.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::SecurityOpt;
+/// assert_eq!(SecurityOpt::NoNewPrivileges.arg(), "no-new-privileges");
+/// assert_eq!(
+///     SecurityOpt::AppArmor("my-profile".into()).arg(),
+///     "apparmor=my-profile"
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SecurityOpt {
+    /// Run under the named AppArmor profile. The profile must already
+    /// be loaded into the kernel, e.g. with `apparmor_parser`.
+    AppArmor(String),
+
+    /// Set an SELinux label option, e.g. `type:container_t` or
+    /// `disable`.
+    Label(String),
+
+    /// Stop the container's processes from gaining new privileges.
+    NoNewPrivileges,
+
+    /// Filter system calls with the seccomp profile in this file. See
+    /// [`SeccompProfile`] for writing a profile to a temporary file.
+    Seccomp(PathBuf),
+
+    /// Disable seccomp filtering.
+    SeccompUnconfined,
+
+    /// Any other option, passed through as-is.
+    Other(String),
+}
+
+impl SecurityOpt {
+    /// Format as an argument for `--security-opt`.
+    pub fn arg(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for SecurityOpt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AppArmor(profile) => write!(f, "apparmor={}", profile),
+            Self::Label(label) => write!(f, "label={}", label),
+            Self::NoNewPrivileges => f.write_str("no-new-privileges"),
+            Self::Seccomp(path) => write!(f, "seccomp={}", path.display()),
+            Self::SeccompUnconfined => f.write_str("seccomp=unconfined"),
+            Self::Other(opt) => f.write_str(opt),
+        }
+    }
+}
+
+/// Seccomp profile written to a temporary file.
+///
+/// Docker and podman only accept seccomp profiles as files, so this
+/// writes the profile to a file that only the current user can read
+/// and deletes it when dropped. Keep the value alive until the
+/// container has been started; the profile is read by the CLI when
+/// the container is created, so the file is not needed after that.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{RunOpt, SeccompProfile, SecurityOpt};
+/// let profile = SeccompProfile::from_json(&serde_json::json!({
+///     "defaultAction": "SCMP_ACT_ALLOW",
+/// }))
+/// .unwrap();
+/// let mut opt = RunOpt::new("alpine");
+/// profile.apply(&mut opt);
+/// assert_eq!(
+///     opt.security_opts,
+///     [SecurityOpt::Seccomp(profile.path().into())]
+/// );
+/// ```
+#[derive(Debug)]
+pub struct SeccompProfile {
+    path: PathBuf,
+}
+
+impl SeccompProfile {
+    /// Write `profile` (the contents of a seccomp JSON profile) to a
+    /// new temporary file.
+    pub fn from_bytes(profile: &[u8]) -> Result<Self, Error> {
+        let path = crate::runner::temp_path("docker-command-seccomp");
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)?;
+        // Create the guard before writing so that the file is removed
+        // if the write fails.
+        let profile_file = Self { path };
+        file.write_all(profile)?;
+        Ok(profile_file)
+    }
+
+    /// Serialize `profile` and write it to a new temporary file.
+    pub fn from_json(profile: &serde_json::Value) -> Result<Self, Error> {
+        let bytes = serde_json::to_vec_pretty(profile)
+            .map_err(|err| Error::Io(io::Error::other(err)))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Path of the temporary file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get a [`SecurityOpt`] that uses this profile.
+    pub fn security_opt(&self) -> SecurityOpt {
+        SecurityOpt::Seccomp(self.path.clone())
+    }
+
+    /// Add this profile to `opt`'s security options.
+    pub fn apply(&self, opt: &mut RunOpt) {
+        opt.security_opts.push(self.security_opt());
+    }
+}
+
+impl Drop for SeccompProfile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,118 @@
+//! Render [`Command`]s as POSIX shell text.
+//!
+//! This is useful for generating scripts that can be reviewed, stored,
+//! or run later, rather than running the commands immediately.
+//!
+//! [`Command`]: command_run::Command
+
+use command_run::Command;
+use std::ffi::OsStr;
+
+/// Quote a single word so that a POSIX shell will treat it as one
+/// literal argument.
+///
+/// Words made up entirely of characters that are never special to the
+/// shell are returned unchanged. Anything else is wrapped in single
+/// quotes, with embedded single quotes written as `'\''`.
+///
+/// Non-UTF-8 data is converted with [`OsStr::to_string_lossy`].
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::shell::quote;
+/// assert_eq!(quote("abc"), "abc");
+/// assert_eq!(quote("a b"), "'a b'");
+/// assert_eq!(quote("it's"), r"'it'\''s'");
+/// assert_eq!(quote(""), "''");
+/// ```
+pub fn quote<S: AsRef<OsStr>>(word: S) -> String {
+    fn is_safe(c: char) -> bool {
+        c.is_ascii_alphanumeric() || "@%+=:,./-_".contains(c)
+    }
+
+    let word = word.as_ref().to_string_lossy();
+    if !word.is_empty() && word.chars().all(is_safe) {
+        return word.into_owned();
+    }
+
+    let mut out = String::with_capacity(word.len() + 2);
+    out.push('\'');
+    for c in word.chars() {
+        if c == '\'' {
+            out.push_str(r"'\''");
+        } else {
+            out.push(c);
+        }
+    }
+    out.push('\'');
+    out
+}
+
+/// Format a [`Command`] as a single line of shell.
+///
+/// The program and arguments are quoted with [`quote`]. Environment
+/// variables set on the command are emitted as `env` assignments (with
+/// `-i` if `clear_env` is set), and if `dir` is set the command is run
+/// in a subshell that first changes to that directory.
+pub fn command_line(cmd: &Command) -> String {
+    let mut words = Vec::new();
+
+    if cmd.clear_env || !cmd.env.is_empty() {
+        words.push("env".to_string());
+        if cmd.clear_env {
+            words.push("-i".to_string());
+        }
+
+        // Sort so that the output is stable.
+        let mut env: Vec<_> = cmd.env.iter().collect();
+        env.sort();
+        for (key, value) in env {
+            let mut assignment = key.clone();
+            assignment.push("=");
+            assignment.push(value);
+            words.push(quote(assignment));
+        }
+    }
+
+    words.push(quote(&cmd.program));
+    words.extend(cmd.args.iter().map(quote));
+    let line = words.join(" ");
+
+    if let Some(dir) = &cmd.dir {
+        format!("(cd {} && {})", quote(dir), line)
+    } else {
+        line
+    }
+}
+
+/// Render commands as a POSIX shell script.
+///
+/// The script starts with a `#!/bin/sh` line and `set -eu`, so it stops
+/// at the first command that fails. Each command is written on its own
+/// line with [`command_line`].
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{shell, BaseCommand, Launcher, RunOpt};
+/// let launcher = Launcher::from(BaseCommand::Docker);
+/// let mut opt = RunOpt::new("alpine");
+/// opt.args = vec!["echo".into(), "hello world".into()];
+/// let run = launcher.run(opt);
+/// assert_eq!(
+///     shell::script(&[run]),
+///     "#!/bin/sh\nset -eu\n\ndocker run alpine echo 'hello world'\n"
+/// );
+/// ```
+pub fn script<'a, I>(commands: I) -> String
+where
+    I: IntoIterator<Item = &'a Command>,
+{
+    let mut out = String::from("#!/bin/sh\nset -eu\n\n");
+    for cmd in commands {
+        out.push_str(&command_line(cmd));
+        out.push('\n');
+    }
+    out
+}
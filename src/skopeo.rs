@@ -0,0 +1,344 @@
+//! Commands for `skopeo`, which works with images in registries and
+//! other storage without needing a container daemon.
+//!
+//! # Examples
+//!
+//! ```
+//! use docker_command::skopeo::{CopyOpt, Location, Skopeo};
+//! let cmd = Skopeo::default().copy(CopyOpt::new(
+//!     Location::Registry("ghcr.io/owner/app:v1".into()),
+//!     Location::Registry("registry.example.com/app:v1".into()),
+//! ));
+//! assert_eq!(
+//!     cmd.command_line_lossy(),
+//!     "skopeo copy docker://ghcr.io/owner/app:v1 docker://registry.example.com/app:v1"
+//! );
+//! ```
+
+use crate::ImageRef;
+use command_run::Command;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Where an image is stored, i.e. a skopeo transport and reference.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Location {
+    /// Image in a registry (`docker://`).
+    Registry(ImageRef),
+
+    /// Image in the local Docker daemon (`docker-daemon:`).
+    DockerDaemon(ImageRef),
+
+    /// Image in local containers-storage, as used by podman
+    /// (`containers-storage:`).
+    ContainersStorage(ImageRef),
+
+    /// Tarball created by `docker save` (`docker-archive:`).
+    DockerArchive(PathBuf),
+
+    /// OCI image layout directory (`oci:`).
+    OciLayout(PathBuf),
+
+    /// Directory of manifest, config, and layers (`dir:`).
+    Dir(PathBuf),
+
+    /// Any other location, passed through as-is.
+    Other(OsString),
+}
+
+impl Location {
+    /// Format as an argument, e.g. `docker://alpine:latest`.
+    pub fn arg(&self) -> OsString {
+        let (transport, value): (&str, OsString) = match self {
+            Self::Registry(image) => ("docker://", image.to_string().into()),
+            Self::DockerDaemon(image) => {
+                ("docker-daemon:", image.to_string().into())
+            }
+            Self::ContainersStorage(image) => {
+                ("containers-storage:", image.to_string().into())
+            }
+            Self::DockerArchive(path) => ("docker-archive:", path.into()),
+            Self::OciLayout(path) => ("oci:", path.into()),
+            Self::Dir(path) => ("dir:", path.into()),
+            Self::Other(other) => ("", other.clone()),
+        };
+        let mut out = OsString::from(transport);
+        out.push(value);
+        out
+    }
+}
+
+/// Options for copying an image.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct CopyOpt {
+    /// Image to copy.
+    pub src: Location,
+
+    /// Where to copy the image to.
+    pub dst: Location,
+
+    /// Copy all images in a multi-platform list, not just the one for
+    /// the current platform.
+    pub all: bool,
+
+    /// Credentials (`user[:password]`) for the destination registry.
+    pub dest_creds: Option<String>,
+
+    /// Whether to verify TLS for the destination registry. If not set,
+    /// skopeo's default is used.
+    pub dest_tls_verify: Option<bool>,
+
+    /// Credentials (`user[:password]`) for the source registry.
+    pub src_creds: Option<String>,
+
+    /// Whether to verify TLS for the source registry. If not set,
+    /// skopeo's default is used.
+    pub src_tls_verify: Option<bool>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl CopyOpt {
+    /// Create a `CopyOpt` from `src` to `dst`. All other fields are set
+    /// to their defaults.
+    pub fn new(src: Location, dst: Location) -> Self {
+        Self {
+            src,
+            dst,
+            all: false,
+            dest_creds: None,
+            dest_tls_verify: None,
+            src_creds: None,
+            src_tls_verify: None,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// Options for inspecting an image.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct InspectOpt {
+    /// Image to inspect.
+    pub image: Location,
+
+    /// Output the image's config rather than the summary.
+    pub config: bool,
+
+    /// Credentials (`user[:password]`) for the registry.
+    pub creds: Option<String>,
+
+    /// Format the output using a Go template, e.g. `{{.Digest}}`.
+    pub format: Option<String>,
+
+    /// Output the raw manifest or config rather than the summary.
+    pub raw: bool,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl InspectOpt {
+    /// Create an `InspectOpt` for `image`. All other fields are set to
+    /// their defaults.
+    pub fn new(image: Location) -> Self {
+        Self {
+            image,
+            config: false,
+            creds: None,
+            format: None,
+            raw: false,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// Options for listing the tags of a repository.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ListTagsOpt {
+    /// Repository to list, e.g. `docker://alpine`. Any tag or digest
+    /// must be left out.
+    pub repository: Location,
+
+    /// Credentials (`user[:password]`) for the registry.
+    pub creds: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl ListTagsOpt {
+    /// Create a `ListTagsOpt` for `repository`. All other fields are
+    /// set to their defaults.
+    pub fn new(repository: Location) -> Self {
+        Self {
+            repository,
+            creds: None,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// Options for deleting an image.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct DeleteOpt {
+    /// Image to delete.
+    pub image: Location,
+
+    /// Credentials (`user[:password]`) for the registry.
+    pub creds: Option<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl DeleteOpt {
+    /// Create a `DeleteOpt` for `image`. All other fields are set to
+    /// their defaults.
+    pub fn new(image: Location) -> Self {
+        Self {
+            image,
+            creds: None,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// Wrapper for creating skopeo commands.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Skopeo {
+    base_command: Command,
+}
+
+impl Skopeo {
+    /// Create a new `Skopeo` using the given base command, which is
+    /// usually just `skopeo` but could be wrapped in another command
+    /// such as `sudo`.
+    pub fn new(base_command: Command) -> Self {
+        Self { base_command }
+    }
+
+    /// Get the base command.
+    pub fn base_command(&self) -> &Command {
+        &self.base_command
+    }
+
+    /// Create a [`Command`] for copying an image.
+    pub fn copy(&self, opt: CopyOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("copy");
+
+        // --all
+        if opt.all {
+            cmd.add_arg("--all");
+        }
+
+        // --dest-creds
+        if let Some(creds) = &opt.dest_creds {
+            cmd.add_arg_pair("--dest-creds", creds);
+        }
+
+        // --dest-tls-verify
+        if let Some(verify) = opt.dest_tls_verify {
+            cmd.add_arg(format!("--dest-tls-verify={}", verify));
+        }
+
+        // --src-creds
+        if let Some(creds) = &opt.src_creds {
+            cmd.add_arg_pair("--src-creds", creds);
+        }
+
+        // --src-tls-verify
+        if let Some(verify) = opt.src_tls_verify {
+            cmd.add_arg(format!("--src-tls-verify={}", verify));
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_arg(opt.src.arg());
+        cmd.add_arg(opt.dst.arg());
+
+        cmd
+    }
+
+    /// Create a [`Command`] for deleting an image.
+    pub fn delete(&self, opt: DeleteOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("delete");
+
+        // --creds
+        if let Some(creds) = &opt.creds {
+            cmd.add_arg_pair("--creds", creds);
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_arg(opt.image.arg());
+
+        cmd
+    }
+
+    /// Create a [`Command`] for listing the tags of a repository. The
+    /// output is JSON; see [`Skopeo::repository_tags`] to parse it.
+    pub fn list_tags(&self, opt: ListTagsOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("list-tags");
+
+        // --creds
+        if let Some(creds) = &opt.creds {
+            cmd.add_arg_pair("--creds", creds);
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_arg(opt.repository.arg());
+
+        cmd
+    }
+
+    /// Create a [`Command`] for inspecting an image.
+    pub fn inspect(&self, opt: InspectOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("inspect");
+
+        // --config
+        if opt.config {
+            cmd.add_arg("--config");
+        }
+
+        // --creds
+        if let Some(creds) = &opt.creds {
+            cmd.add_arg_pair("--creds", creds);
+        }
+
+        // --format
+        if let Some(format) = &opt.format {
+            cmd.add_arg_pair("--format", format);
+        }
+
+        // --raw
+        if opt.raw {
+            cmd.add_arg("--raw");
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_arg(opt.image.arg());
+
+        cmd
+    }
+}
+
+impl Default for Skopeo {
+    fn default() -> Self {
+        Self::new(Command::new("skopeo"))
+    }
+}
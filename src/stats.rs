@@ -0,0 +1,251 @@
+use crate::child::POLL_INTERVAL;
+use crate::disk_usage::parse_size;
+use crate::runner::stdout;
+use crate::{
+    CancelToken, CommandExt, ContainerRef, Error, Launcher, StatsOpt,
+    ValidationError,
+};
+use serde_json::Value;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Resource usage of a container, as reported by `stats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContainerStats {
+    /// Container ID. Docker reports a short ID, podman the full ID.
+    pub id: String,
+
+    /// Container name.
+    pub name: String,
+
+    /// CPU usage, where 100 is one full core.
+    pub cpu_percent: f64,
+
+    /// Memory used, in bytes.
+    pub mem_usage: u64,
+
+    /// Memory limit, in bytes. Without a limit this is the memory of
+    /// the host (or VM).
+    pub mem_limit: u64,
+
+    /// Memory used as a percentage of the limit.
+    pub mem_percent: f64,
+
+    /// Bytes received over the network.
+    pub net_rx: u64,
+
+    /// Bytes sent over the network.
+    pub net_tx: u64,
+
+    /// Bytes read from block devices.
+    pub block_read: u64,
+
+    /// Bytes written to block devices.
+    pub block_write: u64,
+
+    /// Number of processes.
+    pub pids: u64,
+}
+
+/// Get a field, trying each of `names` in order.
+fn field<'a>(value: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    names.iter().find_map(|name| value.get(*name))
+}
+
+/// Whether the engine reported no value (`--`), which docker does for
+/// containers that are not running.
+fn is_missing(s: &str) -> bool {
+    s.trim().is_empty() || s.trim() == "--"
+}
+
+/// Parse a percentage such as `12.5%`.
+fn percent(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) if is_missing(s) => Some(0.0),
+        Value::String(s) => s.trim().trim_end_matches('%').parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parse a pair of sizes such as `3.5MiB / 7.6GiB`.
+fn size_pair(value: &Value) -> Option<(u64, u64)> {
+    let s = value.as_str()?;
+    if is_missing(s) {
+        return Some((0, 0));
+    }
+    let size = |s: &str| {
+        if is_missing(s) {
+            Some(0)
+        } else {
+            parse_size(s.trim())
+        }
+    };
+    let (a, b) = s.split_once('/')?;
+    Some((size(a)?, size(b)?))
+}
+
+/// Parse a count from either a number or a string.
+fn count(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) if is_missing(s) => Some(0),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+impl ContainerStats {
+    /// Parse the output of `stats --no-stream` with `--format json`
+    /// (or `--format '{{json .}}'`).
+    ///
+    /// Docker prints one JSON object per line, with sizes formatted
+    /// for humans: memory in binary units (`MiB`) and I/O in decimal
+    /// units (`kB`), only accurate to a few significant digits. Podman
+    /// prints a JSON array with lowercase keys. Values docker reports
+    /// as `--` (for containers that are not running) are zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::ContainerStats;
+    /// let stats = ContainerStats::parse_json(
+    ///     r#"{"BlockIO":"4.1MB / 0B","CPUPerc":"1.25%","ID":"0123456789ab","MemPerc":"0.50%","MemUsage":"40MiB / 7.6GiB","Name":"web","NetIO":"1.5kB / 648B","PIDs":"3"}"#,
+    /// ).unwrap();
+    /// assert_eq!(stats[0].name, "web");
+    /// assert_eq!(stats[0].cpu_percent, 1.25);
+    /// assert_eq!(stats[0].mem_usage, 40 * 1024 * 1024);
+    /// assert_eq!(stats[0].net_rx, 1500);
+    /// assert_eq!(stats[0].pids, 3);
+    /// ```
+    pub fn parse_json(output: &str) -> Result<Vec<Self>, ValidationError> {
+        let err = |reason: &str| {
+            ValidationError::new("container stats", output.trim(), reason)
+        };
+
+        let output = output.trim();
+        let values: Vec<Value> = if output.starts_with('[') {
+            serde_json::from_str(output).map_err(|e| err(&e.to_string()))?
+        } else {
+            output
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<_, _>>()
+                .map_err(|e| err(&e.to_string()))?
+        };
+
+        values
+            .iter()
+            .map(|value| {
+                let get = |names: &[&str]| {
+                    field(value, names)
+                        .ok_or_else(|| err(&format!("missing {}", names[0])))
+                };
+                let invalid = |name: &str| err(&format!("invalid {}", name));
+                let string = |names: &[&str]| {
+                    get(names)?
+                        .as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| invalid(names[0]))
+                };
+                let pair = |names: &[&str]| {
+                    size_pair(get(names)?).ok_or_else(|| invalid(names[0]))
+                };
+
+                let (mem_usage, mem_limit) = pair(&["MemUsage", "mem_usage"])?;
+                let (net_rx, net_tx) = pair(&["NetIO", "net_io"])?;
+                let (block_read, block_write) = pair(&["BlockIO", "block_io"])?;
+                Ok(Self {
+                    id: string(&["ID", "id"])?,
+                    name: string(&["Name", "name"])?,
+                    cpu_percent: percent(get(&["CPUPerc", "cpu_percent"])?)
+                        .ok_or_else(|| invalid("CPUPerc"))?,
+                    mem_usage,
+                    mem_limit,
+                    mem_percent: percent(get(&["MemPerc", "mem_percent"])?)
+                        .ok_or_else(|| invalid("MemPerc"))?,
+                    net_rx,
+                    net_tx,
+                    block_read,
+                    block_write,
+                    pids: count(get(&["PIDs", "pids"])?)
+                        .ok_or_else(|| invalid("PIDs"))?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Launcher {
+    /// Get the current resource usage of `containers`, or of all
+    /// running containers if `containers` is empty. See
+    /// [`ContainerStats::parse_json`] for details.
+    ///
+    /// Docker samples CPU usage over about a second, so this takes at
+    /// least that long.
+    pub fn container_stats(
+        &self,
+        containers: &[ContainerRef],
+    ) -> Result<Vec<ContainerStats>, Error> {
+        let mut opt = StatsOpt::new(containers.iter().cloned());
+        opt.format = Some(if self.is_podman() {
+            "json".into()
+        } else {
+            "{{json .}}".into()
+        });
+        opt.no_stream = true;
+        let cmd = self.enforce(self.stats(opt))?;
+        let output = stdout(cmd.clone())?;
+        ContainerStats::parse_json(&output).map_err(|_| {
+            Error::UnexpectedOutput {
+                command: cmd.command_line_shell(),
+                output,
+            }
+        })
+    }
+
+    /// Call [`Launcher::container_stats`] every `interval` and pass the
+    /// result to `observer`, until `token` is cancelled or an error
+    /// occurs. The interval is measured from the start of one poll to
+    /// the start of the next.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use docker_command::{BaseCommand, CancelToken, ContainerStats, Launcher};
+    /// use std::time::Duration;
+    ///
+    /// let launcher = Launcher::from(BaseCommand::Docker);
+    /// let token = CancelToken::new();
+    /// launcher
+    ///     .watch_stats(&[], Duration::from_secs(5), &token, &mut |stats: &[ContainerStats]| {
+    ///         for s in stats {
+    ///             println!("{}: {:.1}% cpu, {} bytes", s.name, s.cpu_percent, s.mem_usage);
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn watch_stats(
+        &self,
+        containers: &[ContainerRef],
+        interval: Duration,
+        token: &CancelToken,
+        observer: &mut dyn FnMut(&[ContainerStats]),
+    ) -> Result<(), Error> {
+        while !token.is_cancelled() {
+            let start = Instant::now();
+            let stats = self.container_stats(containers)?;
+            if token.is_cancelled() {
+                break;
+            }
+            observer(&stats);
+            while !token.is_cancelled() && start.elapsed() < interval {
+                thread::sleep(
+                    POLL_INTERVAL.min(interval.saturating_sub(start.elapsed())),
+                );
+            }
+        }
+        Ok(())
+    }
+}
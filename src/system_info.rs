@@ -0,0 +1,120 @@
+use crate::ValidationError;
+use serde_json::Value;
+
+/// Information about the container engine, as reported by `info`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SystemInfo {
+    /// Version of the server (the daemon for docker, or podman
+    /// itself), e.g. `24.0.7`.
+    pub server_version: String,
+
+    /// Storage driver, e.g. `overlay2` or `overlay`.
+    pub storage_driver: String,
+
+    /// Cgroup version (1 or 2), if reported.
+    pub cgroup_version: Option<u32>,
+
+    /// Whether the engine is running in rootless mode.
+    pub rootless: bool,
+
+    /// Registry mirrors configured for the engine.
+    pub registry_mirrors: Vec<String>,
+}
+
+impl SystemInfo {
+    /// Parse the output of `info --format '{{json .}}'`.
+    ///
+    /// Both docker's and podman's layouts are supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::SystemInfo;
+    /// let info = SystemInfo::parse_json(
+    ///     r#"{"ServerVersion":"24.0.7","Driver":"overlay2","CgroupVersion":"2",
+    ///         "SecurityOptions":["name=seccomp,profile=builtin"],
+    ///         "RegistryConfig":{"Mirrors":["https://mirror.example.com/"]}}"#,
+    /// ).unwrap();
+    /// assert_eq!(info.server_version, "24.0.7");
+    /// assert_eq!(info.cgroup_version, Some(2));
+    /// assert!(!info.rootless);
+    /// ```
+    pub fn parse_json(output: &str) -> Result<Self, ValidationError> {
+        let err = |reason: &str| {
+            ValidationError::new("system info", output.trim(), reason)
+        };
+        let value: Value =
+            serde_json::from_str(output).map_err(|e| err(&e.to_string()))?;
+        let str_at = |pointer: &str| {
+            value
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+
+        if let Some(host) = value.get("host") {
+            // Podman
+            let mut registry_mirrors = Vec::new();
+            if let Some(Value::Object(registries)) = value.get("registries") {
+                for registry in registries.values() {
+                    let mirrors =
+                        registry.get("Mirrors").and_then(Value::as_array);
+                    for mirror in mirrors.into_iter().flatten() {
+                        if let Some(location) =
+                            mirror.get("Location").and_then(Value::as_str)
+                        {
+                            registry_mirrors.push(location.to_string());
+                        }
+                    }
+                }
+            }
+            Ok(Self {
+                server_version: str_at("/version/Version")
+                    .ok_or_else(|| err("missing version"))?,
+                storage_driver: str_at("/store/graphDriverName")
+                    .unwrap_or_default(),
+                cgroup_version: host
+                    .get("cgroupVersion")
+                    .and_then(Value::as_str)
+                    .and_then(parse_cgroup_version),
+                rootless: host
+                    .pointer("/security/rootless")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                registry_mirrors,
+            })
+        } else {
+            // Docker
+            let rootless = value
+                .get("SecurityOptions")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .any(|opt| opt.split(',').any(|part| part == "name=rootless"));
+            Ok(Self {
+                server_version: str_at("/ServerVersion")
+                    .ok_or_else(|| err("missing ServerVersion"))?,
+                storage_driver: str_at("/Driver").unwrap_or_default(),
+                cgroup_version: str_at("/CgroupVersion")
+                    .as_deref()
+                    .and_then(parse_cgroup_version),
+                rootless,
+                registry_mirrors: value
+                    .pointer("/RegistryConfig/Mirrors")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect(),
+            })
+        }
+    }
+}
+
+/// Parse a cgroup version such as `2` or `v2`.
+fn parse_cgroup_version(s: &str) -> Option<u32> {
+    s.strip_prefix('v').unwrap_or(s).parse().ok()
+}
@@ -0,0 +1,165 @@
+use crate::{Launcher, RunOpt};
+use command_run::Command;
+use std::ffi::OsStr;
+
+/// Options for [`Launcher::systemd_unit`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SystemdUnitOpt {
+    /// Description of the unit. Defaults to the container name or
+    /// image.
+    pub description: Option<String>,
+
+    /// When to restart the service, e.g. `on-failure` or `always`.
+    pub restart: Option<String>,
+
+    /// Target that the unit is installed into.
+    pub wanted_by: String,
+}
+
+impl Default for SystemdUnitOpt {
+    fn default() -> Self {
+        Self {
+            description: None,
+            restart: Some("on-failure".into()),
+            wanted_by: "multi-user.target".into(),
+        }
+    }
+}
+
+/// Quote a word for an `Exec` line in a systemd unit.
+///
+/// Systemd expands `%` specifiers and `$` variables even inside
+/// quotes, so those are always escaped. Control characters such as
+/// newlines are written as C-style escapes inside the quotes, so that
+/// a word cannot end the line.
+fn quote<S: AsRef<OsStr>>(word: S) -> String {
+    fn is_safe(c: char) -> bool {
+        c.is_ascii_alphanumeric() || "@+=:,./-_".contains(c)
+    }
+
+    let word = word
+        .as_ref()
+        .to_string_lossy()
+        .replace('%', "%%")
+        .replace('$', "$$");
+    if !word.is_empty()
+        && word.chars().all(|c| is_safe(c) || c == '%' || c == '$')
+    {
+        return word;
+    }
+
+    let mut out = String::with_capacity(word.len() + 2);
+    out.push('"');
+    for c in word.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                out.push_str(&format!("\\u{:04x}", u32::from(c)))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Replace control characters in a setting value with spaces, so that
+/// the value cannot end the line and add more settings.
+fn single_line(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// Format a command for an `Exec` line in a systemd unit.
+fn exec_line(cmd: &Command) -> String {
+    let mut words = vec![quote(&cmd.program)];
+    words.extend(cmd.args.iter().map(quote));
+    words.join(" ")
+}
+
+impl Launcher {
+    /// Generate a systemd service unit that runs a container.
+    ///
+    /// The container runs in the foreground (`detach` is turned off)
+    /// and is removed when it exits (`remove` is turned on). If the
+    /// container has a name, any old container with that name is
+    /// removed before starting, and the container is stopped with
+    /// `stop` when the unit stops.
+    ///
+    /// Environment variables and the working directory of the base
+    /// command are not included in the unit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::{BaseCommand, Launcher, RunOpt, SystemdUnitOpt};
+    /// let mut opt = RunOpt::new("nginx");
+    /// opt.name = Some("web".into());
+    /// let unit = Launcher::from(BaseCommand::Docker)
+    ///     .systemd_unit(opt, SystemdUnitOpt::default());
+    /// assert!(unit.contains("\nExecStart=docker run --name web --rm nginx\n"));
+    /// ```
+    pub fn systemd_unit(
+        &self,
+        mut opt: RunOpt,
+        unit: SystemdUnitOpt,
+    ) -> String {
+        opt.detach = false;
+        opt.remove = true;
+        let name = opt.name.clone();
+        let description = unit.description.clone().unwrap_or_else(|| {
+            let what = name.clone().unwrap_or_else(|| opt.image.to_string());
+            format!("Container {}", what)
+        });
+
+        let mut out = String::new();
+        let mut line = |s: String| {
+            out.push_str(&s);
+            out.push('\n');
+        };
+
+        line("[Unit]".into());
+        line(format!("Description={}", single_line(&description)));
+        line("Wants=network-online.target".into());
+        if self.is_docker() {
+            line("After=network-online.target docker.service".into());
+            line("Requires=docker.service".into());
+        } else {
+            line("After=network-online.target".into());
+        }
+        line(String::new());
+
+        line("[Service]".into());
+        if let Some(restart) = &unit.restart {
+            line(format!("Restart={}", single_line(restart)));
+        }
+        if let Some(name) = &name {
+            let mut rm = self.base();
+            rm.add_args(["rm", "--force", name]);
+            // A leading "-" ignores failure, e.g. if there is no such
+            // container.
+            line(format!("ExecStartPre=-{}", exec_line(&rm)));
+        }
+        line(format!("ExecStart={}", exec_line(&self.run(opt))));
+        if let Some(name) = &name {
+            let mut stop = self.base();
+            stop.add_args(["stop", name]);
+            line(format!("ExecStop={}", exec_line(&stop)));
+        }
+        line(String::new());
+
+        line("[Install]".into());
+        line(format!("WantedBy={}", single_line(&unit.wanted_by)));
+
+        out
+    }
+}
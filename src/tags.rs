@@ -0,0 +1,87 @@
+use crate::runner::stdout;
+use crate::skopeo::{ListTagsOpt, Location, Skopeo};
+use crate::{
+    CommandExt, Error, ImageRef, Launcher, SearchOpt, ValidationError,
+};
+use command_run::Command;
+use serde_json::Value;
+
+/// Limit passed to `podman search`, which otherwise lists only 25
+/// tags.
+const MAX_TAGS: u32 = 100_000;
+
+/// Get the tags from the output of `skopeo list-tags` (an object with
+/// a `Tags` array) or `podman search --list-tags --format json` (an
+/// array of such objects).
+fn parse_tags(output: &str) -> Result<Vec<String>, ValidationError> {
+    let err =
+        |reason: &str| ValidationError::new("tag list", output.trim(), reason);
+    let value: Value =
+        serde_json::from_str(output).map_err(|e| err(&e.to_string()))?;
+    let objects = match &value {
+        Value::Array(objects) => objects.iter().collect(),
+        _ => vec![&value],
+    };
+    let mut tags = Vec::new();
+    for object in objects {
+        let list = object
+            .get("Tags")
+            .and_then(Value::as_array)
+            .ok_or_else(|| err("missing Tags"))?;
+        for tag in list {
+            let tag = tag.as_str().ok_or_else(|| err("invalid tag"))?;
+            tags.push(tag.to_string());
+        }
+    }
+    Ok(tags)
+}
+
+/// Run `cmd` and parse the tags it prints.
+fn run(cmd: Command) -> Result<Vec<String>, Error> {
+    let output = stdout(cmd.clone())?;
+    parse_tags(&output).map_err(|_| Error::UnexpectedOutput {
+        command: cmd.command_line_shell(),
+        output,
+    })
+}
+
+impl Launcher {
+    /// List the tags of `repository` in its registry, using
+    /// `search --list-tags`. Any tag or digest in `repository` is
+    /// ignored. The tags are in the order the registry returns them.
+    ///
+    /// Podman only; with docker, use [`Skopeo::repository_tags`].
+    pub fn repository_tags(
+        &self,
+        repository: &ImageRef,
+    ) -> Result<Vec<String>, Error> {
+        let mut opt = SearchOpt::new(repository.name());
+        opt.format = Some("json".into());
+        opt.limit = Some(MAX_TAGS);
+        opt.list_tags = true;
+        run(self.enforce(self.search(opt))?)
+    }
+}
+
+impl Skopeo {
+    /// List the tags of `repository` in its registry, using
+    /// `skopeo list-tags`. Any tag or digest in `repository` is
+    /// ignored. The tags are in the order the registry returns them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use docker_command::skopeo::Skopeo;
+    /// let tags = Skopeo::default()
+    ///     .repository_tags(&"ghcr.io/owner/app".into())
+    ///     .unwrap();
+    /// let releases = tags.iter().filter(|tag| tag.starts_with("1.4."));
+    /// ```
+    pub fn repository_tags(
+        &self,
+        repository: &ImageRef,
+    ) -> Result<Vec<String>, Error> {
+        let name = ImageRef::from(repository.name().as_str());
+        run(self.list_tags(ListTagsOpt::new(Location::Registry(name))))
+    }
+}
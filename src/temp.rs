@@ -0,0 +1,141 @@
+use crate::container::unique_name;
+use crate::{CommandExt, CreateNetworkOpt, CreateVolumeOpt, Error, Launcher};
+
+/// Prefix of the names of [`TempVolume`]s and [`TempNetwork`]s
+/// created with `new`.
+const DEFAULT_PREFIX: &str = "docker-command-tmp";
+
+/// Network or volume that is removed on drop.
+#[derive(Debug)]
+struct Temp {
+    launcher: Launcher,
+    name: String,
+    is_network: bool,
+    kept: bool,
+}
+
+impl Temp {
+    fn remove(&mut self) -> Result<(), Error> {
+        self.kept = true;
+        let cmd = if self.is_network {
+            self.launcher.remove_network(&self.name)
+        } else {
+            self.launcher.remove_volume(&self.name)
+        };
+        self.launcher.enforce(cmd)?.run_logged()?;
+        Ok(())
+    }
+}
+
+impl Drop for Temp {
+    fn drop(&mut self) {
+        if !self.kept {
+            let _ = self.remove();
+        }
+    }
+}
+
+/// Volume with a unique name that is removed when dropped, for use in
+/// tests.
+///
+/// Errors when removing the volume on drop are ignored; use
+/// [`TempVolume::close`] to check them.
+#[derive(Debug)]
+pub struct TempVolume(Temp);
+
+impl TempVolume {
+    /// Create a volume named `docker-command-tmp-` followed by random
+    /// hex digits.
+    pub fn new(launcher: &Launcher) -> Result<Self, Error> {
+        Self::with_opt(launcher, CreateVolumeOpt::new(DEFAULT_PREFIX))
+    }
+
+    /// Create a volume from `opt`, using `opt.name` as the prefix of
+    /// the unique name.
+    pub fn with_opt(
+        launcher: &Launcher,
+        mut opt: CreateVolumeOpt,
+    ) -> Result<Self, Error> {
+        opt.name = unique_name(&opt.name);
+        let name = opt.name.clone();
+        launcher
+            .enforce(launcher.create_volume(opt))?
+            .run_logged()?;
+        Ok(Self(Temp {
+            launcher: launcher.clone(),
+            name,
+            is_network: false,
+            kept: false,
+        }))
+    }
+
+    /// Name of the volume.
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// Remove the volume now, returning any error.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.0.remove()
+    }
+
+    /// Keep the volume instead of removing it on drop, returning its
+    /// name.
+    pub fn keep(mut self) -> String {
+        self.0.kept = true;
+        self.0.name.clone()
+    }
+}
+
+/// Network with a unique name that is removed when dropped, for use
+/// in tests.
+///
+/// Errors when removing the network on drop are ignored; use
+/// [`TempNetwork::close`] to check them. Containers must be
+/// disconnected from the network before it can be removed.
+#[derive(Debug)]
+pub struct TempNetwork(Temp);
+
+impl TempNetwork {
+    /// Create a network named `docker-command-tmp-` followed by random
+    /// hex digits.
+    pub fn new(launcher: &Launcher) -> Result<Self, Error> {
+        Self::with_opt(launcher, CreateNetworkOpt::new(DEFAULT_PREFIX))
+    }
+
+    /// Create a network from `opt`, using `opt.name` as the prefix of
+    /// the unique name.
+    pub fn with_opt(
+        launcher: &Launcher,
+        mut opt: CreateNetworkOpt,
+    ) -> Result<Self, Error> {
+        opt.name = unique_name(&opt.name);
+        let name = opt.name.clone();
+        launcher
+            .enforce(launcher.create_network(opt))?
+            .run_logged()?;
+        Ok(Self(Temp {
+            launcher: launcher.clone(),
+            name,
+            is_network: true,
+            kept: false,
+        }))
+    }
+
+    /// Name of the network.
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// Remove the network now, returning any error.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.0.remove()
+    }
+
+    /// Keep the network instead of removing it on drop, returning its
+    /// name.
+    pub fn keep(mut self) -> String {
+        self.0.kept = true;
+        self.0.name.clone()
+    }
+}
@@ -0,0 +1,111 @@
+//! Helpers for testing code that generates commands with this crate.
+//!
+//! Commands are compared in a normalized form so that tests do not
+//! break on differences that do not change what the command does:
+//!
+//! * The program is reduced to its file name, so `/usr/bin/docker`
+//!   matches `docker`.
+//! * Long options written as `--flag=value` are split into `--flag`
+//!   and `value`, so either spelling matches the other.
+//!
+//! Arguments after a `--` are left as is.
+//!
+//! # Examples
+//!
+//! ```
+//! use docker_command::{assert_cmd_eq, BaseCommand, Launcher, RunOpt};
+//! let mut opt = RunOpt::new("alpine");
+//! opt.remove = true;
+//! let cmd = Launcher::from(BaseCommand::Docker).run(opt);
+//! assert_cmd_eq!(cmd, ["docker", "run", "--rm", "alpine"]);
+//! ```
+
+use crate::shell;
+use command_run::Command;
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Normalize a program and its arguments as described in the
+/// [module documentation](self).
+fn normalize_words<I, S>(words: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut out = Vec::new();
+    let mut words = words.into_iter();
+    if let Some(program) = words.next() {
+        let program = program.as_ref();
+        let name = Path::new(program).file_name().unwrap_or(program);
+        out.push(name.to_string_lossy().into_owned());
+    }
+    let mut end_of_options = false;
+    for word in words {
+        let word = word.as_ref().to_string_lossy();
+        if end_of_options {
+            out.push(word.into_owned());
+            continue;
+        }
+        if word == "--" {
+            end_of_options = true;
+        }
+        match word.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                out.push(flag.to_string());
+                out.push(value.to_string());
+            }
+            _ => out.push(word.into_owned()),
+        }
+    }
+    out
+}
+
+/// Get the program and arguments of `cmd` in normalized form.
+pub fn normalize(cmd: &Command) -> Vec<String> {
+    normalize_words(
+        std::iter::once(cmd.program.as_os_str())
+            .chain(cmd.args.iter().map(|arg| arg.as_os_str())),
+    )
+}
+
+/// Quote and join normalized words.
+fn join(words: &[String]) -> String {
+    words.iter().map(shell::quote).collect::<Vec<_>>().join(" ")
+}
+
+/// Render `cmd` as a line of shell after normalizing it, for use in
+/// snapshot files. Environment variables and the working directory
+/// are not included.
+pub fn render(cmd: &Command) -> String {
+    join(&normalize(cmd))
+}
+
+/// Panic unless `cmd` matches `expected` after normalizing both. See
+/// [`assert_cmd_eq!`](crate::assert_cmd_eq).
+#[track_caller]
+pub fn assert_cmd_eq<S: AsRef<OsStr>>(cmd: &Command, expected: &[S]) {
+    let actual = normalize(cmd);
+    let expected = normalize_words(expected);
+    if actual != expected {
+        panic!(
+            "commands differ\n  actual: {}\nexpected: {}",
+            join(&actual),
+            join(&expected),
+        );
+    }
+}
+
+/// Assert that a [`Command`] has the given program and arguments,
+/// after normalizing both as described in the [`test_util`] module.
+///
+/// [`Command`]: command_run::Command
+/// [`test_util`]: crate::test_util
+#[macro_export]
+macro_rules! assert_cmd_eq {
+    ($cmd:expr, [$($word:expr),* $(,)?]) => {
+        $crate::test_util::assert_cmd_eq(
+            &$cmd,
+            &[$(::std::ffi::OsString::from($word)),*],
+        )
+    };
+}
@@ -0,0 +1,214 @@
+//! Spans emitted with the `tracing` feature.
+
+use crate::{Error, ImageRef, Launcher, Redactor, SpawnedCommand};
+use command_run::{Command, ErrorKind, Output};
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+use std::time::Instant;
+use tracing::field::{self, Empty};
+
+/// Subcommands that take a second word, e.g. `network create`.
+const GROUPS: &[&str] = &[
+    "container",
+    "generate",
+    "image",
+    "network",
+    "scout",
+    "system",
+    "trust",
+    "volume",
+];
+
+/// Get the subcommand from a list of arguments that starts with it.
+fn subcommand(args: &[OsString]) -> String {
+    let mut words = args.iter().map(|arg| arg.to_string_lossy());
+    let Some(first) = words.next() else {
+        return String::new();
+    };
+    match words.next() {
+        Some(second) if GROUPS.contains(&first.as_ref()) => {
+            format!("{} {}", first, second)
+        }
+        _ => first.into_owned(),
+    }
+}
+
+/// Record that `launcher` created `cmd`.
+pub(crate) fn generated(
+    launcher: &Launcher,
+    cmd: &Command,
+    image: Option<&ImageRef>,
+    container: Option<&str>,
+) {
+    let mut global = Command::new("");
+    launcher.add_global_args(&mut global);
+    let skip = launcher.base_command.args.len() + global.args.len();
+    let args = cmd.args.get(skip..).unwrap_or_default();
+
+    let _span = tracing::debug_span!(
+        "generate_command",
+        subcommand = %subcommand(args),
+        image = image.map(field::display),
+        container,
+    )
+    .entered();
+    tracing::debug!(
+        command = %Redactor::default().command_line(cmd),
+        "generated command"
+    );
+}
+
+/// Options of docker and podman that can come before the subcommand
+/// and do not take a value.
+const GLOBAL_SWITCHES: &[&str] = &[
+    "-D",
+    "--debug",
+    "--tls",
+    "--tlsverify",
+    "-r",
+    "--remote",
+    "--syslog",
+    "--transient-store",
+];
+
+/// Programs that can be the container engine, directly or behind a
+/// wrapper such as `sudo`.
+const ENGINES: &[&str] =
+    &["docker", "podman", "docker-compose", "podman-compose"];
+
+/// Get the arguments of `cmd` from the subcommand on, skipping any
+/// wrapper such as `sudo` and the global options. Options other than
+/// [`GLOBAL_SWITCHES`] are assumed to take a value unless it is
+/// attached with `=`.
+fn subcommand_args(cmd: &Command) -> &[OsString] {
+    let is_engine = |path: &OsStr| {
+        Path::new(path)
+            .file_stem()
+            .is_some_and(|stem| ENGINES.iter().any(|engine| stem == *engine))
+    };
+    let mut args = &cmd.args[..];
+    if !is_engine(cmd.program.as_os_str()) {
+        if let Some(i) = args.iter().position(|arg| is_engine(arg)) {
+            args = &args[i + 1..];
+        }
+    }
+    while let Some(arg) = args.first() {
+        let arg = arg.to_string_lossy();
+        if !arg.starts_with('-') {
+            break;
+        }
+        let takes_value =
+            !arg.contains('=') && !GLOBAL_SWITCHES.contains(&arg.as_ref());
+        args = args
+            .get(if takes_value { 2 } else { 1 }..)
+            .unwrap_or_default();
+    }
+    args
+}
+
+/// Outcome of running a command, as recorded in its span.
+pub(crate) trait Outcome {
+    /// Exit code, if the process exited with one.
+    fn status(&self) -> Option<i32> {
+        None
+    }
+
+    /// Why the command failed, or None if it did not. The command line
+    /// is not included, since it may contain secrets.
+    fn failure(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Outcome for () {}
+
+impl Outcome for SpawnedCommand {}
+
+impl Outcome for Output {
+    fn status(&self) -> Option<i32> {
+        self.status.code()
+    }
+}
+
+impl Outcome for command_run::Error {
+    fn status(&self) -> Option<i32> {
+        match &self.kind {
+            ErrorKind::Exit(status) => status.code(),
+            ErrorKind::Run(_) => None,
+        }
+    }
+
+    fn failure(&self) -> Option<String> {
+        Some(match &self.kind {
+            ErrorKind::Exit(status) => format!("exited with {}", status),
+            ErrorKind::Run(err) => format!("failed to run: {}", err),
+        })
+    }
+}
+
+impl Outcome for Error {
+    fn status(&self) -> Option<i32> {
+        match self {
+            Error::Run(err) => err.status(),
+            _ => None,
+        }
+    }
+
+    fn failure(&self) -> Option<String> {
+        Some(match self {
+            Error::Run(err) => return err.failure(),
+            Error::UnexpectedOutput { .. } => "unexpected output".into(),
+            Error::TimedOut { timeout, .. } => {
+                format!("timed out after {:?}", timeout)
+            }
+            Error::Cancelled { .. } => "cancelled".into(),
+            err => err.to_string(),
+        })
+    }
+}
+
+impl<T: Outcome, E: Outcome> Outcome for Result<T, E> {
+    fn status(&self) -> Option<i32> {
+        match self {
+            Ok(value) => value.status(),
+            Err(err) => err.status(),
+        }
+    }
+
+    fn failure(&self) -> Option<String> {
+        self.as_ref().err().and_then(Outcome::failure)
+    }
+}
+
+/// Call `run`, which runs `cmd`, in a span that records how long it
+/// took and its exit status. Failures are logged with the command line
+/// redacted.
+///
+/// For a spawned command the span only covers starting it, and for a
+/// pipe the span is for the source and covers both commands.
+pub(crate) fn in_span<R: Outcome>(cmd: &Command, run: impl FnOnce() -> R) -> R {
+    let span = tracing::info_span!(
+        "run_command",
+        program = %cmd.program.display(),
+        subcommand = %subcommand(subcommand_args(cmd)),
+        duration_ms = Empty,
+        status = Empty,
+    );
+    let _enter = span.enter();
+
+    let start = Instant::now();
+    let result = run();
+    span.record("duration_ms", start.elapsed().as_millis() as u64);
+
+    if let Some(status) = result.status() {
+        span.record("status", status);
+    }
+    if let Some(failure) = result.failure() {
+        tracing::warn!(
+            command = %Redactor::default().command_line(cmd),
+            error = %failure,
+            "command failed"
+        );
+    }
+    result
+}
@@ -0,0 +1,122 @@
+//! Commands for `trivy`, a vulnerability scanner that can be used as
+//! an alternative to [`Launcher::scout_cves`].
+//!
+//! [`Launcher::scout_cves`]: crate::Launcher::scout_cves
+
+use crate::ImageRef;
+use command_run::Command;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Options for scanning an image.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ImageOpt {
+    /// Image to scan.
+    pub image: ImageRef,
+
+    /// Exit with this status if vulnerabilities are found.
+    pub exit_code: Option<i32>,
+
+    /// Output format, e.g. `table`, `json`, or `sarif`.
+    pub format: Option<String>,
+
+    /// Only report vulnerabilities that have a fix available.
+    pub ignore_unfixed: bool,
+
+    /// Write the report to a file instead of stdout.
+    pub output: Option<PathBuf>,
+
+    /// Only report vulnerabilities with these severities, e.g.
+    /// `CRITICAL` and `HIGH`.
+    pub severity: Vec<String>,
+
+    /// Unsupported options; see [extra arguments](crate#extra-arguments).
+    pub extra_args: Vec<OsString>,
+}
+
+impl ImageOpt {
+    /// Create an `ImageOpt` for `image`. All other fields are set to
+    /// their defaults.
+    pub fn new<I: Into<ImageRef>>(image: I) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Wrapper for creating trivy commands.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trivy {
+    base_command: Command,
+}
+
+impl Trivy {
+    /// Create a new `Trivy` using the given base command, which is
+    /// usually just `trivy`.
+    pub fn new(base_command: Command) -> Self {
+        Self { base_command }
+    }
+
+    /// Get the base command.
+    pub fn base_command(&self) -> &Command {
+        &self.base_command
+    }
+
+    /// Create a [`Command`] for scanning an image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use docker_command::trivy::{ImageOpt, Trivy};
+    /// let mut opt = ImageOpt::new("alpine:3");
+    /// opt.severity = vec!["CRITICAL".into(), "HIGH".into()];
+    /// assert_eq!(
+    ///     Trivy::default().image(opt).command_line_lossy(),
+    ///     "trivy image --severity CRITICAL,HIGH alpine:3"
+    /// );
+    /// ```
+    pub fn image(&self, opt: ImageOpt) -> Command {
+        let mut cmd = self.base_command.clone();
+        cmd.add_arg("image");
+
+        // --exit-code
+        if let Some(code) = opt.exit_code {
+            cmd.add_arg_pair("--exit-code", code.to_string());
+        }
+
+        // --format
+        if let Some(format) = &opt.format {
+            cmd.add_arg_pair("--format", format);
+        }
+
+        // --ignore-unfixed
+        if opt.ignore_unfixed {
+            cmd.add_arg("--ignore-unfixed");
+        }
+
+        // --output
+        if let Some(output) = &opt.output {
+            cmd.add_arg_pair("--output", output);
+        }
+
+        // --severity
+        if !opt.severity.is_empty() {
+            cmd.add_arg_pair("--severity", opt.severity.join(","));
+        }
+
+        // Extra arguments
+        cmd.add_args(&opt.extra_args);
+
+        cmd.add_arg(opt.image.to_string());
+
+        cmd
+    }
+}
+
+impl Default for Trivy {
+    fn default() -> Self {
+        Self::new(Command::new("trivy"))
+    }
+}
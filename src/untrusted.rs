@@ -0,0 +1,85 @@
+use crate::ValidationError;
+use std::ffi::OsStr;
+use std::str::FromStr;
+
+/// Check that `value` cannot be mistaken for an option when it is
+/// passed as a positional argument: it must not be empty, start with
+/// `-`, or contain NUL.
+///
+/// Values passed as the argument of an option (such as `--env` or
+/// `--name`) are always taken as the option's value, so this matters
+/// for positional arguments such as image references, container
+/// names, and paths.
+pub fn check_arg<S: AsRef<OsStr>>(
+    what: &'static str,
+    value: S,
+) -> Result<(), ValidationError> {
+    let value = value.as_ref().to_string_lossy();
+    let err = |reason| ValidationError::new(what, value.as_ref(), reason);
+    if value.is_empty() {
+        Err(err("empty"))
+    } else if value.starts_with('-') {
+        Err(err("starts with '-'"))
+    } else if value.contains('\0') {
+        Err(err("contains NUL"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Value from an untrusted source, such as a user of a web service,
+/// that must be checked before it is used in a command.
+///
+/// The value can only be taken out with [`Untrusted::check`] or
+/// [`Untrusted::parse`], which reject values that the docker CLI could
+/// misparse as options (see [`check_arg`]). For example, a container
+/// named `--rm` could otherwise change the meaning of a command.
+///
+/// # Examples
+///
+/// ```
+/// use docker_command::{ContainerRef, Untrusted};
+/// let name = Untrusted::new("web-1").parse::<ContainerRef>().unwrap();
+/// assert_eq!(name.to_string(), "web-1");
+/// assert!(Untrusted::new("--rm").check("container").is_err());
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Untrusted<T>(T);
+
+impl<T> Untrusted<T> {
+    /// Wrap an untrusted value.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Get the value without checking it.
+    pub fn into_inner_unchecked(self) -> T {
+        self.0
+    }
+}
+
+impl<T: AsRef<OsStr>> Untrusted<T> {
+    /// Check the value with [`check_arg`], returning it if it is safe
+    /// to use. `what` describes the value in the error.
+    pub fn check(self, what: &'static str) -> Result<T, ValidationError> {
+        check_arg(what, &self.0)?;
+        Ok(self.0)
+    }
+}
+
+impl<T: AsRef<str>> Untrusted<T> {
+    /// Check the value with [`check_arg`], then parse it. This is
+    /// useful with the validated types in this crate, such as
+    /// [`ContainerName`] and [`ImageRef`].
+    ///
+    /// [`ContainerName`]: crate::ContainerName
+    /// [`ImageRef`]: crate::ImageRef
+    pub fn parse<U>(self) -> Result<U, ValidationError>
+    where
+        U: FromStr<Err = ValidationError>,
+    {
+        let value = self.0.as_ref();
+        check_arg("untrusted value", value)?;
+        value.parse()
+    }
+}
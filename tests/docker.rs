@@ -1,5 +1,6 @@
 use docker_command::*;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 fn new_path(s: &str) -> PathBuf {
     Path::new(s).into()
@@ -15,33 +16,80 @@ fn test_is_program() {
 
 #[test]
 fn test_build() {
+    let mut opt = BuildOpt::new("/myContext");
+    opt.build_args = vec![
+        ("barg1", "bval1").into(),
+        ("barg2", "bval2").into(),
+        BuildArg::Inherit("barg3".into()),
+    ];
+    opt.dockerfile = Some(new_path("/myContext/myDockerfile"));
+    opt.iidfile = Some(new_path("/myIidfile"));
+    opt.network = Some(NetworkMode::Host);
+    opt.no_cache = true;
+    opt.no_cache_filter = vec!["deps".into()];
+    opt.provenance = Some("mode=max".into());
+    opt.pull = Some(PullPolicy::Always);
+    opt.quiet = true;
+    opt.sbom = Some("true".into());
+    opt.tag = Some("myTag".into());
+    opt.extra_args = vec!["--myExtra".into()];
     assert_eq!(
         Launcher::from(BaseCommand::Docker)
-            .build(BuildOpt {
-                build_args: vec![("barg1".into(), "bval1".into()),
-                                 ("barg2".into(), "bval2".into())],
-                context: new_path("/myContext"),
-                dockerfile: Some(new_path("/myContext/myDockerfile")),
-                iidfile: Some(new_path("/myIidfile")),
-                no_cache: true,
-                pull: true,
-                quiet: true,
-                tag: Some("myTag".into()),
-            })
+            .build(opt)
             .command_line_lossy(),
-        "docker build --build-arg barg1=bval1 --build-arg barg2=bval2 --file /myContext/myDockerfile --iidfile /myIidfile --no-cache --pull --quiet --tag myTag /myContext"
+        "docker build --build-arg barg1=bval1 --build-arg barg2=bval2 --build-arg barg3 --file /myContext/myDockerfile --iidfile /myIidfile --network host --no-cache --no-cache-filter deps --provenance=mode=max --pull --quiet --sbom=true --tag myTag --myExtra /myContext"
+    );
+}
+
+#[test]
+fn test_bake() {
+    let mut opt = BakeOpt::new(["api", "web"]);
+    opt.files = vec![new_path("docker-bake.hcl"), new_path("overrides.json")];
+    opt.set = vec![
+        ("*.platform".into(), "linux/amd64,linux/arm64".into()),
+        ("api.tags".into(), "registry/api:1".into()),
+    ];
+    opt.push = true;
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .bake(opt)
+            .command_line_lossy(),
+        "docker buildx bake --file docker-bake.hcl --file overrides.json \
+         --push --set '*.platform=linux/amd64,linux/arm64' \
+         --set api.tags=registry/api:1 api web"
+    );
+}
+
+#[test]
+fn test_build_pull_policy() {
+    let build = |base, pull| {
+        let mut opt = BuildOpt::new("/ctx");
+        opt.pull = Some(pull);
+        Launcher::from(base).build(opt).command_line_lossy()
+    };
+    assert_eq!(
+        build(BaseCommand::Docker, PullPolicy::Never),
+        "docker build --pull=false /ctx"
+    );
+    assert_eq!(
+        build(BaseCommand::Podman, PullPolicy::Never),
+        "podman build --pull=never /ctx"
+    );
+    assert_eq!(
+        build(BaseCommand::Podman, PullPolicy::Always),
+        "podman build --pull=always /ctx"
     );
 }
 
 #[test]
 fn test_create_network() {
+    let mut opt = CreateNetworkOpt::new("myNetwork");
+    opt.extra_args = vec!["--myExtra".into()];
     assert_eq!(
         Launcher::from(BaseCommand::Docker)
-            .create_network(CreateNetworkOpt {
-                name: "myNetwork".into(),
-            })
+            .create_network(opt)
             .command_line_lossy(),
-        "docker network create myNetwork"
+        "docker network create --myExtra myNetwork"
     );
 }
 
@@ -49,7 +97,7 @@ fn test_create_network() {
 fn test_remove_network() {
     assert_eq!(
         Launcher::from(BaseCommand::Docker)
-            .remove_network("myNetwork".into())
+            .remove_network("myNetwork")
             .command_line_lossy(),
         "docker network rm myNetwork"
     );
@@ -72,74 +120,99 @@ fn test_user() {
 
 #[test]
 fn test_run() {
+    let mut opt = RunOpt::new("myImage");
+    opt.detach = true;
+    opt.env = vec![
+        ("key1".into(), "val1".into()),
+        ("key2".into(), "val2".into()),
+    ];
+    opt.init = true;
+    opt.interactive = true;
+    opt.mounts = vec![Mount {
+        kind: MountType::Bind,
+        source: Some(new_path("/mySrc")),
+        target: new_path("/myDst"),
+        read_only: true,
+        options: vec!["bind-propagation=rslave".into()],
+    }];
+    opt.name = Some("myName".into());
+    opt.network = Some("myNetwork".into());
+    opt.publish = vec![
+        PublishPorts {
+            ip: Some("1.2.3.4".into()),
+            container: 5678.into(),
+            host: Some(987.into()),
+            protocol: Protocol::Tcp,
+        },
+        PublishPorts {
+            ip: Some("1.2.3.4".into()),
+            container: 5678.into(),
+            host: None,
+            protocol: Protocol::Udp,
+        },
+        PublishPorts {
+            ip: None,
+            container: 5678.into(),
+            host: Some(987.into()),
+            protocol: Protocol::Sctp,
+        },
+        PublishPorts {
+            container: 5678.into(),
+            ..Default::default()
+        },
+    ];
+    opt.publish_all = true;
+    opt.read_only = true;
+    opt.remove = true;
+    opt.tty = true;
+    opt.user = Some(UserAndGroup {
+        user: NameOrId::Name("myUser".into()),
+        group: Some(NameOrId::Name("myGroup".into())),
+    });
+    opt.volumes = vec![
+        // Read-write volume
+        Volume {
+            src: new_path("/mySrc").into(),
+            dst: new_path("/myDst"),
+            access: Access::ReadWrite,
+            ..Default::default()
+        },
+        // Read-only volume with extra options
+        Volume {
+            src: new_path("/mySrc").into(),
+            dst: new_path("/myDst"),
+            access: Access::ReadOnly,
+            options: vec!["cached".into(), "z".into()],
+        },
+        // Named volume
+        Volume::named("myVolume", "/myDst"),
+        // Anonymous volume
+        Volume::anonymous("/myDst"),
+    ];
+    opt.command = Some(Path::new("myCmd").into());
+    opt.args = vec!["arg1".into(), "arg2".into()];
+    opt.extra_args = vec!["--myExtra".into()];
     assert_eq!(
         Launcher::from(BaseCommand::Docker)
-            .run(RunOpt {
-                image: "myImage".into(),
-                detach: true,
-                env: vec![("key1".into(), "val1".into()),
-                          ("key2".into(), "val2".into())],
-                init: true,
-                interactive: true,
-                name: Some("myName".into()),
-                network: Some("myNetwork".into()),
-                publish: vec![PublishPorts {
-                    ip: Some("1.2.3.4".into()),
-                    container: 5678.into(),
-                    host: Some(987.into()),
-                }, PublishPorts {
-                    ip: Some("1.2.3.4".into()),
-                    container: 5678.into(),
-                    host: None,
-                }, PublishPorts {
-                    ip: None,
-                    container: 5678.into(),
-                    host: Some(987.into()),
-                }, PublishPorts {
-                    container: 5678.into(),
-                    ..Default::default()
-                }],
-                read_only: true,
-                remove: true,
-                tty: true,
-                user: Some(UserAndGroup {
-                    user: NameOrId::Name("myUser".into()),
-                    group: Some(NameOrId::Name("myGroup".into())),
-                }),
-                volumes: vec![
-                    // Read-write volume
-                    Volume {
-                        src: new_path("/mySrc"),
-                        dst: new_path("/myDst"),
-                        read_write: true,
-                        ..Default::default()
-                    },
-                    // Read-only volume with extra options
-                    Volume {
-                        src: new_path("/mySrc"),
-                        dst: new_path("/myDst"),
-                        options: vec!["cached".into(), "z".into()],
-                        ..Default::default()
-                    }
-                ],
-                command: Some(Path::new("myCmd").into()),
-                args: vec!["arg1".into(), "arg2".into()],
-            })
+            .run(opt)
             .command_line_lossy(),
-        "docker run --detach --env key1=val1 --env key2=val2 --init --interactive --name myName --network myNetwork --publish 1.2.3.4:987:5678 --publish 1.2.3.4::5678 --publish 987:5678 --publish 5678 --read-only --rm --tty --user myUser:myGroup --volume /mySrc:/myDst:rw --volume /mySrc:/myDst:ro,cached,z myImage myCmd arg1 arg2"
+        "docker run --detach --env key1=val1 --env key2=val2 --init --interactive --mount type=bind,source=/mySrc,target=/myDst,readonly,bind-propagation=rslave --name myName --network myNetwork --publish 1.2.3.4:987:5678 --publish 1.2.3.4::5678/udp --publish 987:5678/sctp --publish 5678 --publish-all --read-only --rm --tty --user myUser:myGroup --volume /mySrc:/myDst:rw --volume /mySrc:/myDst:ro,cached,z --volume myVolume:/myDst --volume /myDst --myExtra myImage myCmd arg1 arg2"
     );
 }
 
 #[test]
 fn test_stop() {
+    let mut opt = StopOpt::new([
+        "abc".parse::<ContainerRef>().unwrap(),
+        "0123456789ab".parse().unwrap(),
+    ]);
+    opt.time = Some(123.into());
+    opt.extra_args = vec!["--myExtra".into()];
     assert_eq!(
         Launcher::from(BaseCommand::Docker)
-            .stop(StopOpt {
-                containers: vec!["abc".into(), "def".into()],
-                time: Some(123),
-            })
+            .stop(opt)
             .command_line_lossy(),
-        "docker stop --time 123 abc def"
+        "docker stop --time 123 --myExtra abc 0123456789ab"
     );
 }
 
@@ -167,3 +240,4154 @@ fn test_readme_example() {
     let readme = include_str!("../README.md");
     assert!(readme.contains(&example));
 }
+
+#[test]
+fn test_shell_script() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let mut opt = RunOpt::new("myImage");
+    opt.command = Some(Path::new("sh").into());
+    opt.args = vec!["-c".into(), "echo \"$HOME\" it's".into()];
+    let mut run = launcher.run(opt);
+    run.env.insert("B".into(), "2".into());
+    run.env.insert("A".into(), "x y".into());
+    run.dir = Some(new_path("/my dir"));
+    let stop =
+        launcher.stop(StopOpt::new(["abc".parse::<ContainerName>().unwrap()]));
+
+    assert_eq!(
+        shell::script(&[run, stop]),
+        r#"#!/bin/sh
+set -eu
+
+(cd '/my dir' && env 'A=x y' B=2 docker run myImage sh -c 'echo "$HOME" it'\''s')
+docker stop abc
+"#
+    );
+}
+
+#[test]
+fn test_command_line_shell() {
+    let mut opt = RunOpt::new("myImage");
+    opt.env = vec![("key".into(), "$val 'quoted'".into())];
+    let cmd = Launcher::from(BaseCommand::Docker).run(opt);
+    assert_eq!(
+        cmd.command_line_shell(),
+        r"docker run --env 'key=$val '\''quoted'\''' myImage"
+    );
+}
+
+#[test]
+fn test_image_ref() {
+    let image: ImageRef = "localhost:5000/my/app:v1.2@sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+        .parse()
+        .unwrap();
+    assert_eq!(image.registry(), Some("localhost:5000"));
+    assert_eq!(image.repository(), "my/app");
+    assert_eq!(image.tag(), Some("v1.2"));
+    assert_eq!(
+        image.digest(),
+        Some("sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef")
+    );
+    assert_eq!(image.name(), "localhost:5000/my/app");
+
+    // Port on the registry is not mistaken for a tag.
+    let image = ImageRef::from("localhost:5000/app");
+    assert_eq!(image.registry(), Some("localhost:5000"));
+    assert_eq!(image.tag(), None);
+    assert_eq!(image.normalize().to_string(), "localhost:5000/app:latest");
+
+    assert_eq!(
+        ImageRef::from("owner/app").normalize().to_string(),
+        "docker.io/owner/app:latest"
+    );
+    assert_eq!(ImageRef::from("My/App").to_string(), "My/App");
+
+    assert!("My/App".parse::<ImageRef>().is_err());
+    assert!("".parse::<ImageRef>().is_err());
+    assert!("app:".parse::<ImageRef>().is_err());
+    assert!("app@sha256:xyz".parse::<ImageRef>().is_err());
+}
+
+#[test]
+fn test_container_ref() {
+    let id = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+    assert_eq!(
+        id.parse::<ContainerRef>().unwrap(),
+        ContainerRef::Id(id.parse().unwrap())
+    );
+    assert_eq!(
+        "0123456789ab".parse::<ContainerRef>().unwrap(),
+        ContainerRef::Id("0123456789ab".parse().unwrap())
+    );
+    assert_eq!(
+        "abc".parse::<ContainerRef>().unwrap(),
+        ContainerRef::Name("abc".parse().unwrap())
+    );
+    assert!(id.parse::<ContainerId>().unwrap().is_full());
+
+    assert!("a".parse::<ContainerName>().is_err());
+    assert!("my container".parse::<ContainerName>().is_err());
+    assert!("ABC".parse::<ContainerId>().is_err());
+    assert!(format!("{}0", id).parse::<ContainerId>().is_err());
+}
+
+#[test]
+fn test_duration_arg() {
+    let arg = |d| DurationArg(d).to_string();
+    assert_eq!(arg(Duration::ZERO), "0s");
+    assert_eq!(arg(Duration::from_secs(10)), "10s");
+    assert_eq!(arg(Duration::from_secs(3600)), "1h");
+    assert_eq!(arg(Duration::from_secs(3723)), "1h2m3s");
+    assert_eq!(arg(Duration::from_millis(250)), "250ms");
+    assert_eq!(arg(Duration::from_micros(5)), "5us");
+    assert_eq!(arg(Duration::from_nanos(1_000_000_001)), "1.000000001s");
+
+    assert_eq!(DurationArg::from(0).secs_arg(), "0");
+    assert_eq!(DurationArg(Duration::from_millis(1)).secs_arg(), "1");
+}
+
+#[test]
+fn test_ps() {
+    let mut opt = PsOpt::default();
+    opt.all = true;
+    opt.filters = vec![
+        Filter::Label("app=web".into()),
+        Filter::Status("exited".into()),
+        Filter::Exited(0),
+    ];
+    opt.format = Some("{{.ID}}".into());
+    opt.no_trunc = true;
+    opt.quiet = true;
+    opt.extra_args = vec!["--myExtra".into()];
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker).ps(opt).command_line_lossy(),
+        "docker ps --all --filter label=app=web --filter status=exited --filter exited=0 --format '{{.ID}}' --no-trunc --quiet --myExtra"
+    );
+}
+
+#[test]
+fn test_images() {
+    let mut opt = ImagesOpt::default();
+    opt.all = true;
+    opt.digests = true;
+    opt.filters = vec![Filter::Dangling(false)];
+    opt.format = Some("json".into());
+    opt.no_trunc = true;
+    opt.quiet = true;
+    opt.repository = Some("myRepo".into());
+    opt.extra_args = vec!["--myExtra".into()];
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .images(opt)
+            .command_line_lossy(),
+        "docker images --all --digests --filter dangling=false --format json --no-trunc --quiet --myExtra myRepo"
+    );
+}
+
+#[test]
+fn test_list_networks_and_volumes() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+
+    let mut opt = ListNetworksOpt::default();
+    opt.filters = vec![Filter::Driver("bridge".into())];
+    opt.format = Some("{{.Name}}".into());
+    opt.no_trunc = true;
+    opt.quiet = true;
+    opt.extra_args = vec!["--myExtra".into()];
+    assert_eq!(
+        launcher.list_networks(opt).command_line_lossy(),
+        "docker network ls --filter driver=bridge --format '{{.Name}}' --no-trunc --quiet --myExtra"
+    );
+
+    let mut opt = ListVolumesOpt::default();
+    opt.filters = vec![Filter::Name("myVolume".into())];
+    opt.format = Some("json".into());
+    opt.quiet = true;
+    opt.extra_args = vec!["--myExtra".into()];
+    assert_eq!(
+        launcher.list_volumes(opt).command_line_lossy(),
+        "docker volume ls --filter name=myVolume --format json --quiet --myExtra"
+    );
+}
+
+#[test]
+fn test_prune() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let mut opt = PruneOpt::default();
+    opt.all = true;
+    opt.filters =
+        vec![Filter::Until("24h".into()), Filter::LabelNot("keep".into())];
+    opt.force = true;
+    opt.extra_args = vec!["--myExtra".into()];
+    assert_eq!(
+        launcher.prune_images(opt.clone()).command_line_lossy(),
+        "docker image prune --all --filter until=24h --filter 'label!=keep' --force --myExtra"
+    );
+    assert_eq!(
+        launcher
+            .prune_containers(PruneOpt::default())
+            .command_line_lossy(),
+        "docker container prune"
+    );
+    assert_eq!(
+        launcher
+            .prune_networks(PruneOpt::default())
+            .command_line_lossy(),
+        "docker network prune"
+    );
+    assert_eq!(
+        launcher.prune_volumes(opt).command_line_lossy(),
+        "docker volume prune --all --filter until=24h --filter 'label!=keep' --force --myExtra"
+    );
+}
+
+#[test]
+fn test_parse_publish() {
+    let parse = |s: &str| s.parse::<PublishPorts>().unwrap();
+
+    assert_eq!(
+        parse("80"),
+        PublishPorts {
+            container: 80.into(),
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        parse("8000-8010:80-90/sctp"),
+        PublishPorts {
+            container: PortRange(80..=90),
+            host: Some(PortRange(8000..=8010)),
+            ip: None,
+            protocol: Protocol::Sctp,
+        }
+    );
+    assert_eq!(
+        parse("1.2.3.4::80"),
+        PublishPorts {
+            container: 80.into(),
+            host: None,
+            ip: Some("1.2.3.4".into()),
+            protocol: Protocol::Tcp,
+        }
+    );
+    assert_eq!(
+        parse("[::1]:8080:80/udp"),
+        PublishPorts {
+            container: 80.into(),
+            host: Some(8080.into()),
+            ip: Some("::1".into()),
+            protocol: Protocol::Udp,
+        }
+    );
+
+    // Round trip
+    for s in ["80", "1.2.3.4:987:5678", "1.2.3.4::5678/udp", "[::1]:80:80"] {
+        assert_eq!(parse(s).arg(), s);
+    }
+
+    for s in ["", "x", "80/xtp", "1:2:3:4", "90-80", "[::1:80:80", "70000"] {
+        assert!(s.parse::<PublishPorts>().is_err(), "{}", s);
+    }
+}
+
+#[test]
+fn test_parse_volume() {
+    assert_eq!(
+        "/src:/dst".parse::<Volume>().unwrap(),
+        Volume {
+            src: VolumeSource::HostPath(new_path("/src")),
+            dst: new_path("/dst"),
+            access: Access::Default,
+            options: vec![],
+        }
+    );
+    assert_eq!(
+        "myVolume:/dst:ro,cached,z".parse::<Volume>().unwrap(),
+        Volume {
+            src: VolumeSource::Named("myVolume".into()),
+            dst: new_path("/dst"),
+            access: Access::ReadOnly,
+            options: vec![VolumeOption::Cached, VolumeOption::SelinuxShared],
+        }
+    );
+    assert_eq!(
+        "/dst:ro".parse::<Volume>().unwrap(),
+        Volume {
+            src: VolumeSource::Anonymous,
+            dst: new_path("/dst"),
+            access: Access::ReadOnly,
+            options: vec![],
+        }
+    );
+    assert_eq!(
+        "/dst".parse::<Volume>().unwrap().src,
+        VolumeSource::Anonymous
+    );
+    assert_eq!(
+        "./relative:/dst".parse::<Volume>().unwrap().src,
+        VolumeSource::HostPath(new_path("./relative"))
+    );
+    assert_eq!(
+        r"C:\src:/dst".parse::<Volume>().unwrap().src,
+        VolumeSource::HostPath(new_path(r"C:\src"))
+    );
+
+    assert!("".parse::<Volume>().is_err());
+    assert!(":/dst".parse::<Volume>().is_err());
+}
+
+#[test]
+fn test_parse_mount() {
+    assert_eq!(
+        "type=tmpfs,destination=/tmp,tmpfs-size=64m"
+            .parse::<Mount>()
+            .unwrap(),
+        Mount {
+            kind: MountType::Tmpfs,
+            source: None,
+            target: new_path("/tmp"),
+            read_only: false,
+            options: vec!["tmpfs-size=64m".into()],
+        }
+    );
+
+    // Type defaults to volume
+    let mount: Mount = "source=myVolume,target=/data,readonly=false"
+        .parse()
+        .unwrap();
+    assert_eq!(mount.kind, MountType::Volume);
+    assert!(!mount.read_only);
+
+    // Round trip
+    let s =
+        "type=bind,source=/src,target=/dst,readonly,bind-propagation=rslave";
+    assert_eq!(s.parse::<Mount>().unwrap().arg(), s);
+
+    assert!("type=bind,source=/src".parse::<Mount>().is_err());
+    assert!("type=nfs,target=/dst".parse::<Mount>().is_err());
+    assert!("target=/dst,,ro".parse::<Mount>().is_err());
+}
+
+#[test]
+fn test_volume_options() {
+    let mut vol = Volume::named("myVolume", "/dst");
+    vol.options = vec![
+        "Z".into(),
+        "delegated".into(),
+        "rslave".into(),
+        "nocopy".into(),
+        "noexec".into(),
+    ];
+    assert_eq!(
+        vol.options,
+        [
+            VolumeOption::SelinuxPrivate,
+            VolumeOption::Delegated,
+            VolumeOption::Propagation(Propagation::RSlave),
+            VolumeOption::NoCopy,
+            VolumeOption::Other("noexec".into()),
+        ]
+    );
+    assert!(vol.validate().is_ok());
+
+    // Consistency options are dropped for podman.
+    let run = |base| {
+        let mut opt = RunOpt::new("myImage");
+        opt.volumes = vec![vol.clone()];
+        Launcher::from(base).run(opt).command_line_lossy()
+    };
+    assert_eq!(
+        run(BaseCommand::Docker),
+        "docker run --volume myVolume:/dst:Z,delegated,rslave,nocopy,noexec myImage"
+    );
+    assert_eq!(
+        run(BaseCommand::Podman),
+        "podman run --volume myVolume:/dst:Z,rslave,nocopy,noexec myImage"
+    );
+
+    let invalid = |src: &str, options: &[&str]| {
+        let mut vol: Volume = src.parse().unwrap();
+        vol.options = options.iter().map(|opt| (*opt).into()).collect();
+        vol.validate().is_err()
+    };
+    assert!(invalid("v:/dst", &["z", "Z"]));
+    assert!(invalid("v:/dst", &["cached", "consistent"]));
+    assert!(invalid("v:/dst", &["shared", "rprivate"]));
+    assert!(invalid("/src:/dst", &["nocopy"]));
+}
+
+#[test]
+fn test_launcher_env() {
+    let launcher = Launcher::from(BaseCommand::Docker)
+        .with_env("DOCKER_BUILDKIT", "1")
+        .with_env("DOCKER_CONFIG", "/myConfig");
+    let cmd = launcher.stop(StopOpt::default());
+    assert_eq!(
+        cmd.command_line_shell(),
+        "env DOCKER_BUILDKIT=1 DOCKER_CONFIG=/myConfig docker stop"
+    );
+    // sudo resets the environment unless told to keep the variables.
+    let launcher = Launcher::from(BaseCommand::SudoDocker)
+        .with_env("DOCKER_BUILDKIT", "1")
+        .with_env("DOCKER_CONFIG", "/myConfig")
+        .with_env("DOCKER_BUILDKIT", "0");
+    let cmd = launcher.stop(StopOpt::default());
+    assert_eq!(
+        cmd.command_line_shell(),
+        "env DOCKER_BUILDKIT=0 DOCKER_CONFIG=/myConfig \
+         sudo --preserve-env=DOCKER_BUILDKIT,DOCKER_CONFIG docker stop"
+    );
+    assert!(launcher.is_docker());
+}
+
+#[test]
+fn test_global_opts() {
+    let mut opt = GlobalOpts::default();
+    opt.config = Some(new_path("/myConfig"));
+    opt.context = Some("myContext".into());
+    opt.debug = true;
+    opt.host = Some("tcp://myHost:2376".into());
+    opt.log_level = Some(LogLevel::Warn);
+    opt.tls = true;
+    opt.tls_ca_cert = Some(new_path("/ca.pem"));
+    opt.tls_cert = Some(new_path("/cert.pem"));
+    opt.tls_key = Some(new_path("/key.pem"));
+    opt.tls_verify = true;
+    opt.extra_args = vec!["--myExtra".into()];
+    let launcher =
+        Launcher::from(BaseCommand::SudoDocker).with_global_opts(opt);
+    assert!(launcher.is_docker());
+    assert_eq!(
+        launcher.remove_network("myNetwork").command_line_lossy(),
+        "sudo docker --config /myConfig --context myContext --debug --host tcp://myHost:2376 --log-level warn --tls --tlscacert /ca.pem --tlscert /cert.pem --tlskey /key.pem --tlsverify --myExtra network rm myNetwork"
+    );
+}
+
+#[test]
+fn test_podman_system() {
+    let launcher = Launcher::from(BaseCommand::Podman);
+    assert_eq!(
+        launcher.system_migrate().command_line_lossy(),
+        "podman system migrate"
+    );
+    assert_eq!(
+        launcher.system_reset(true).command_line_lossy(),
+        "podman system reset --force"
+    );
+
+    let mut inner =
+        command_run::Command::with_args("chown", ["-R", "1000:1000", "/data"]);
+    inner.dir = Some(new_path("/myDir"));
+    let cmd = launcher.unshare(&inner);
+    assert_eq!(
+        cmd.command_line_lossy(),
+        "podman unshare chown -R 1000:1000 /data"
+    );
+    assert_eq!(cmd.dir, Some(new_path("/myDir")));
+}
+
+#[test]
+fn test_info() {
+    let mut opt = InfoOpt::default();
+    opt.format = Some("json".into());
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .info(opt)
+            .command_line_lossy(),
+        "docker info --format json"
+    );
+}
+
+#[test]
+fn test_map_user() {
+    let user = UserAndGroup {
+        user: 1000.into(),
+        group: Some(1000.into()),
+    };
+
+    let podman = Launcher::from(BaseCommand::Podman);
+    let mut opt = RunOpt::new("alpine");
+    podman.map_user(&mut opt, user.clone()).unwrap();
+    assert_eq!(
+        podman.run(opt).command_line_lossy(),
+        "podman run --user 1000:1000 --userns=keep-id:uid=1000,gid=1000 alpine"
+    );
+
+    let docker = Launcher::from(BaseCommand::Docker);
+    let mut opt = RunOpt::new("alpine");
+    docker.map_user(&mut opt, user).unwrap();
+    assert_eq!(
+        docker.run(opt).command_line_lossy(),
+        "docker run --user 1000:1000 alpine"
+    );
+
+    let mut opt = RunOpt::new("alpine");
+    let named = UserAndGroup {
+        user: NameOrId::Name("alice".into()),
+        group: None,
+    };
+    assert!(podman.map_user(&mut opt, named).is_err());
+
+    let mut opt = RunOpt::new("alpine");
+    opt.uidmap = IdMap::keep_id(0, 10);
+    opt.gidmap = vec![IdMap {
+        container: 0,
+        host: 1,
+        size: 10,
+    }];
+    assert_eq!(
+        podman.run(opt).command_line_lossy(),
+        "podman run --gidmap 0:1:10 --uidmap 0:0:1 --uidmap 1:1:9 alpine"
+    );
+}
+
+#[test]
+fn test_volumes() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    assert_eq!(
+        launcher
+            .create_volume(CreateVolumeOpt::new("data"))
+            .command_line_lossy(),
+        "docker volume create data"
+    );
+    assert_eq!(
+        launcher.remove_volume("data").command_line_lossy(),
+        "docker volume rm data"
+    );
+}
+
+#[test]
+fn test_inspect_and_pull() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+
+    let mut opt = InspectOpt::new(["alpine"]);
+    opt.format = Some("{{.Id}}".into());
+    opt.kind = Some(ObjectKind::Image);
+    assert_eq!(
+        launcher.inspect(opt).command_line_lossy(),
+        "docker inspect --format '{{.Id}}' --type image alpine"
+    );
+
+    let mut opt = PullOpt::new("alpine:3");
+    opt.platform = Some("linux/arm64".into());
+    opt.quiet = true;
+    assert_eq!(
+        launcher.pull(opt).command_line_lossy(),
+        "docker pull --platform linux/arm64 --quiet alpine:3"
+    );
+}
+
+#[test]
+fn test_kill() {
+    let mut opt = KillOpt::new(["myapp".parse::<ContainerRef>().unwrap()]);
+    opt.signal = Some("SIGTERM".into());
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .kill(opt)
+            .command_line_lossy(),
+        "docker kill --signal SIGTERM myapp"
+    );
+}
+
+#[test]
+fn test_wait() {
+    let containers = [
+        "web".parse::<ContainerRef>().unwrap(),
+        "db".parse().unwrap(),
+    ];
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .wait(&containers)
+            .command_line_lossy(),
+        "docker wait web db"
+    );
+}
+
+#[test]
+fn test_batch() {
+    let mut batch = Batch::new().with_concurrency(2);
+    for word in ["a", "b", "c"] {
+        let mut cmd = command_run::Command::with_args("echo", [word]);
+        cmd.capture = true;
+        cmd.log_command = false;
+        batch.push(cmd);
+    }
+    let mut cmd = command_run::Command::new("false");
+    cmd.log_command = false;
+    batch.push(cmd);
+    assert_eq!(batch.concurrency(), 2);
+
+    let results = batch.run();
+    assert_eq!(results.len(), 4);
+    let stdout: Vec<_> = results[..3]
+        .iter()
+        .map(|r| r.as_ref().unwrap().stdout_string_lossy().into_owned())
+        .collect();
+    assert_eq!(stdout, ["a\n", "b\n", "c\n"]);
+    assert!(results[3].is_err());
+}
+
+#[test]
+fn test_run_into() {
+    let launcher = Launcher::from(BaseCommand::Podman)
+        .with_global_opts({
+            let mut opts = GlobalOpts::default();
+            opts.debug = true;
+            opts
+        })
+        .with_env("A", "1");
+
+    let mut cmd = command_run::Command::with_args("old", ["x", "y", "z"]);
+    cmd.dir = Some(new_path("/tmp"));
+    for image in ["alpine", "debian"] {
+        let mut opt = RunOpt::new(image);
+        opt.remove = true;
+        opt.volumes = vec![Volume::bind("/a", "/b")];
+        launcher.run_into(&opt, &mut cmd);
+        assert_eq!(cmd, launcher.run(opt));
+    }
+
+    let mut defaults = Defaults::default();
+    defaults.pull = Some(PullPolicy::Always);
+    let launcher = launcher.with_defaults(defaults);
+    for context in ["/a", "/b"] {
+        let opt = BuildOpt::new(context);
+        launcher.build_into(&opt, &mut cmd);
+        assert_eq!(cmd, launcher.build(opt));
+    }
+}
+
+#[test]
+fn test_disk_usage() {
+    let mut opt = SystemDfOpt::default();
+    opt.format = Some("json".into());
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .system_df(opt)
+            .command_line_lossy(),
+        "docker system df --format json"
+    );
+
+    let docker = r#"
+{"Active":"1","Reclaimable":"0B (0%)","Size":"7.8MB","TotalCount":"1","Type":"Images"}
+{"Active":"0","Reclaimable":"12kB","Size":"12kB","TotalCount":"2","Type":"Containers"}
+{"Active":"0","Reclaimable":"0B","Size":"0B","TotalCount":"0","Type":"Local Volumes"}
+{"Active":"0","Reclaimable":"1.5GiB","Size":"1.5GiB","TotalCount":"3","Type":"Build Cache"}
+"#;
+    let usage = DiskUsage::parse_json(docker).unwrap();
+    assert_eq!(usage.len(), 4);
+    assert_eq!(usage[0].size, 7_800_000);
+    assert_eq!(usage[1].kind, DiskUsageKind::Containers);
+    assert_eq!(usage[1].total, 2);
+    assert_eq!(usage[1].reclaimable, 12_000);
+    assert_eq!(usage[2].kind, DiskUsageKind::LocalVolumes);
+    assert_eq!(usage[3].kind, DiskUsageKind::BuildCache);
+    assert_eq!(usage[3].size, 1_610_612_736);
+
+    let podman = r#"[{"Type":"Images","Total":3,"Active":1,"RawSize":123456,"RawReclaimable":456,"Size":"123kB","Reclaimable":"456B"}]"#;
+    let usage = DiskUsage::parse_json(podman).unwrap();
+    assert_eq!(
+        usage,
+        [DiskUsage {
+            kind: DiskUsageKind::Images,
+            total: 3,
+            active: 1,
+            size: 123456,
+            reclaimable: 456,
+        }]
+    );
+
+    assert!(DiskUsage::parse_json("{}").is_err());
+    assert!(DiskUsage::parse_json("not json").is_err());
+}
+
+#[test]
+fn test_skopeo() {
+    use docker_command::skopeo::{
+        CopyOpt, DeleteOpt, InspectOpt, ListTagsOpt, Location, Skopeo,
+    };
+
+    let skopeo = Skopeo::default();
+
+    let mut opt = CopyOpt::new(
+        Location::DockerDaemon("app:dev".into()),
+        Location::Registry("localhost:5000/app:dev".into()),
+    );
+    opt.all = true;
+    opt.dest_tls_verify = Some(false);
+    assert_eq!(
+        skopeo.copy(opt).command_line_lossy(),
+        "skopeo copy --all --dest-tls-verify=false docker-daemon:app:dev docker://localhost:5000/app:dev"
+    );
+
+    let mut opt = InspectOpt::new(Location::Registry("alpine".into()));
+    opt.raw = true;
+    assert_eq!(
+        skopeo.inspect(opt).command_line_lossy(),
+        "skopeo inspect --raw docker://alpine"
+    );
+
+    let opt = DeleteOpt::new(Location::OciLayout(new_path("/tmp/oci")));
+    assert_eq!(
+        skopeo.delete(opt).command_line_lossy(),
+        "skopeo delete oci:/tmp/oci"
+    );
+
+    let mut opt = ListTagsOpt::new(Location::Registry("alpine".into()));
+    opt.creds = Some("user".into());
+    assert_eq!(
+        skopeo.list_tags(opt).command_line_lossy(),
+        "skopeo list-tags --creds user docker://alpine"
+    );
+}
+
+#[test]
+fn test_scout_cves() {
+    let mut opt = ScoutCvesOpt::new("myapp:1.0");
+    opt.exit_code = true;
+    opt.format = Some("sarif".into());
+    opt.only_severity = vec!["critical".into(), "high".into()];
+    opt.output = Some(new_path("report.json"));
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .scout_cves(opt)
+            .command_line_lossy(),
+        "docker scout cves --exit-code --format sarif --only-severity critical,high --output report.json myapp:1.0"
+    );
+}
+
+#[test]
+fn test_retention_policy() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let now = SystemTime::now();
+    let image = |repo: &str, tag: &str, age_secs| ImageSummary {
+        id: format!("{}-{}", repo, tag),
+        repository: repo.into(),
+        tag: tag.into(),
+        created: now - Duration::from_secs(age_secs),
+    };
+    let images = [
+        image("app", "v1", 300),
+        image("app", "v3", 100),
+        image("app", "v2", 200),
+        image("db", "v1", 500),
+        image("<none>", "<none>", 900),
+    ];
+
+    let mut policy = RetentionPolicy::default();
+    assert!(policy.commands(&launcher, &images).is_empty());
+
+    policy.keep_recent = Some(1);
+    policy.max_age = Some(Duration::from_secs(7 * 24 * 3600));
+    policy.keep_label = Some("keep".into());
+    let commands: Vec<_> = policy
+        .commands(&launcher, &images)
+        .iter()
+        .map(|cmd| cmd.command_line_lossy())
+        .collect();
+    assert_eq!(
+        commands,
+        [
+            "docker rmi app:v1 app:v2",
+            "docker image prune --filter until=168h --filter 'label!=keep' --force"
+        ]
+    );
+}
+
+#[test]
+fn test_content_trust() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+
+    let mut opt = PushOpt::new("registry.example.com/app:v1");
+    opt.disable_content_trust = Some(false);
+    opt.quiet = true;
+    assert_eq!(
+        launcher.push(opt).command_line_lossy(),
+        "docker push --disable-content-trust=false --quiet registry.example.com/app:v1"
+    );
+
+    let mut opt = PullOpt::new("alpine");
+    opt.disable_content_trust = Some(false);
+    assert_eq!(
+        launcher.pull(opt).command_line_lossy(),
+        "docker pull --disable-content-trust=false alpine"
+    );
+
+    let mut opt = RunOpt::new("alpine");
+    opt.disable_content_trust = Some(true);
+    assert_eq!(
+        launcher.run(opt).command_line_lossy(),
+        "docker run --disable-content-trust=true alpine"
+    );
+
+    let mut opt = TrustSignOpt::new("app:v1");
+    opt.local = true;
+    assert_eq!(
+        launcher.trust_sign(opt).command_line_lossy(),
+        "docker trust sign --local app:v1"
+    );
+
+    let mut opt = TrustInspectOpt::new(["app:v1", "app:v2"]);
+    opt.pretty = true;
+    assert_eq!(
+        launcher.trust_inspect(opt).command_line_lossy(),
+        "docker trust inspect --pretty app:v1 app:v2"
+    );
+
+    let mut opt = cosign::SignOpt::new("app@sha256:abc");
+    opt.key = Some("cosign.key".into());
+    opt.yes = true;
+    assert_eq!(
+        cosign::Cosign::default().sign(opt).command_line_lossy(),
+        "cosign sign --key cosign.key --yes 'app@sha256:abc'"
+    );
+}
+
+#[cfg(feature = "bollard")]
+#[test]
+fn test_bollard() {
+    use bollard::models::{ContainerCreateBody, PortBinding};
+    use bollard::query_parameters::BuildImageOptions;
+
+    let mut opt = RunOpt::new("alpine:3");
+    opt.env = vec![("A".into(), "1".into())];
+    opt.publish = vec!["127.0.0.1:8000-8001:80-81/udp".parse().unwrap()];
+    opt.remove = true;
+    opt.volumes = vec![Volume::bind("/src", "/dst")];
+    opt.command = Some(new_path("echo"));
+    opt.args = vec!["hi".into()];
+    let body = ContainerCreateBody::from(&opt);
+    assert_eq!(body.image.as_deref(), Some("alpine:3"));
+    assert_eq!(body.cmd, Some(vec!["echo".into(), "hi".into()]));
+    assert_eq!(body.env, Some(vec!["A=1".into()]));
+    assert_eq!(
+        body.exposed_ports,
+        Some(vec!["80/udp".into(), "81/udp".into()])
+    );
+    let host = body.host_config.unwrap();
+    assert_eq!(host.auto_remove, Some(true));
+    assert_eq!(host.binds, Some(vec!["/src:/dst".into()]));
+    assert_eq!(
+        host.port_bindings.unwrap()["81/udp"],
+        Some(vec![PortBinding {
+            host_ip: Some("127.0.0.1".into()),
+            host_port: Some("8001".into()),
+        }])
+    );
+
+    let mut opt = BuildOpt::new("/ctx");
+    opt.dockerfile = Some(new_path("/ctx/docker/Dockerfile"));
+    opt.tag = Some("app:dev".into());
+    let build = BuildImageOptions::from(&opt);
+    assert_eq!(build.dockerfile, "docker/Dockerfile");
+    assert_eq!(build.t.as_deref(), Some("app:dev"));
+}
+
+#[cfg(feature = "compose")]
+#[test]
+fn test_compose() {
+    let mut project = ComposeProject::default();
+
+    let mut db = RunOpt::new("postgres:16");
+    db.env = vec![("POSTGRES_PASSWORD".into(), "secret".into())];
+    db.volumes = vec![Volume::named("pgdata", "/var/lib/postgresql/data")];
+    project.add_service("db", db);
+
+    let mut web = RunOpt::new("nginx");
+    web.name = Some("web".into());
+    web.network = Some("host".into());
+    web.publish = vec!["8080:80".parse().unwrap()];
+    web.mounts = vec!["type=bind,src=/site,dst=/usr/share/nginx/html,ro"
+        .parse()
+        .unwrap()];
+    web.command = Some(new_path("nginx"));
+    web.args = vec!["-g".into(), "daemon off;".into()];
+    project.add_service("web", web);
+
+    project.volumes.push("pgdata".into());
+
+    assert_eq!(
+        project.to_yaml().unwrap(),
+        "\
+services:
+  db:
+    image: postgres:16
+    environment:
+      POSTGRES_PASSWORD: secret
+    volumes:
+    - pgdata:/var/lib/postgresql/data
+  web:
+    image: nginx
+    container_name: web
+    command:
+    - nginx
+    - -g
+    - daemon off;
+    network_mode: host
+    ports:
+    - 8080:80
+    volumes:
+    - type: bind
+      source: /site
+      target: /usr/share/nginx/html
+      read_only: true
+volumes:
+  pgdata: {}
+"
+    );
+}
+
+#[test]
+fn test_systemd() {
+    let mut opt = GenerateSystemdOpt::new("web");
+    opt.name = true;
+    opt.new = true;
+    opt.restart_policy = Some("always".into());
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman)
+            .generate_systemd(opt)
+            .command_line_lossy(),
+        "podman generate systemd --name --new --restart-policy always web"
+    );
+
+    let mut opt = RunOpt::new("alpine");
+    opt.name = Some("job".into());
+    opt.detach = true;
+    opt.env = vec![("MSG".into(), "100% \"done\" $HOME".into())];
+    opt.command = Some(new_path("sleep"));
+    opt.args = vec!["infinity".into()];
+    let unit = Launcher::from(BaseCommand::Docker)
+        .systemd_unit(opt, SystemdUnitOpt::default());
+    assert_eq!(
+        unit,
+        r#"[Unit]
+Description=Container job
+Wants=network-online.target
+After=network-online.target docker.service
+Requires=docker.service
+
+[Service]
+Restart=on-failure
+ExecStartPre=-docker rm --force job
+ExecStart=docker run --env "MSG=100%% \"done\" $$HOME" --name job --rm alpine sleep infinity
+ExecStop=docker stop job
+
+[Install]
+WantedBy=multi-user.target
+"#
+    );
+
+    // Newlines cannot add directives.
+    let mut opt = RunOpt::new("alpine");
+    opt.env = vec![("A".into(), "1\nExecStartPost=/bin/evil".into())];
+    let mut unit_opt = SystemdUnitOpt::default();
+    unit_opt.description = Some("app\nExecStartPost=/bin/evil".into());
+    let unit = Launcher::from(BaseCommand::Docker).systemd_unit(opt, unit_opt);
+    assert!(unit.contains("Description=app ExecStartPost=/bin/evil\n"));
+    assert!(unit.contains(r#"--env "A=1\nExecStartPost=/bin/evil""#));
+    assert!(!unit.contains("\nExecStartPost"));
+}
+
+#[cfg(feature = "kubernetes")]
+#[test]
+fn test_kubernetes() {
+    let mut opt = RunOpt::new("postgres:16");
+    opt.env = vec![("POSTGRES_PASSWORD".into(), "secret".into())];
+    opt.publish = vec!["5432:5432".parse().unwrap(), "53/udp".parse().unwrap()];
+    opt.volumes = vec![
+        Volume::named("pgdata", "/var/lib/postgresql/data"),
+        "/etc/pg:/etc/postgresql:ro".parse().unwrap(),
+    ];
+    opt.mounts = vec!["type=tmpfs,dst=/tmp".parse().unwrap()];
+    opt.user = Some(UserAndGroup {
+        user: 999.into(),
+        group: Some(999.into()),
+    });
+    opt.read_only = true;
+    opt.command = Some("postgres".into());
+    opt.args = vec!["-c".into(), "fsync=off".into()];
+
+    let mut pod = KubernetesPod::new("db");
+    pod.add_container("postgres", opt);
+    assert_eq!(
+        pod.to_yaml().unwrap(),
+        "\
+apiVersion: v1
+kind: Pod
+metadata:
+  name: db
+  labels:
+    app: db
+spec:
+  containers:
+  - name: postgres
+    image: postgres:16
+    args:
+    - postgres
+    - -c
+    - fsync=off
+    env:
+    - name: POSTGRES_PASSWORD
+      value: secret
+    ports:
+    - containerPort: 5432
+      hostPort: 5432
+    - containerPort: 53
+      protocol: UDP
+    volumeMounts:
+    - name: volume-0
+      mountPath: /var/lib/postgresql/data
+    - name: volume-1
+      mountPath: /etc/postgresql
+      readOnly: true
+    - name: volume-2
+      mountPath: /tmp
+    securityContext:
+      readOnlyRootFilesystem: true
+      runAsUser: 999
+      runAsGroup: 999
+  volumes:
+  - name: volume-0
+    persistentVolumeClaim:
+      claimName: pgdata
+  - name: volume-1
+    hostPath:
+      path: /etc/pg
+  - name: volume-2
+    emptyDir:
+      medium: Memory
+"
+    );
+}
+
+#[test]
+fn test_redaction_hook() {
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    let launcher = Launcher::from(BaseCommand::Docker)
+        .with_env("REGISTRY_TOKEN", "abc")
+        .with_hook(
+            move |cmd: &command_run::Command| {
+                seen2.lock().unwrap().push(cmd.command_line_shell());
+            },
+            Redactor::default(),
+        );
+
+    let mut opt = BuildOpt::new("/ctx");
+    opt.build_args =
+        vec![("NPM_TOKEN", "s3cret").into(), ("VERSION", "1").into()];
+    opt.extra_args = vec!["--secret=id=npm,src=/npmrc".into()];
+    let cmd = launcher.build(opt);
+    // The command itself is not changed.
+    assert!(cmd.args.contains(&"NPM_TOKEN=s3cret".into()));
+
+    let mut cmd = launcher.run(RunOpt::new("alpine"));
+    let mut opt = RunOpt::new("alpine");
+    opt.env = vec![("API_KEY".into(), "k".into())];
+    launcher.run_into(&opt, &mut cmd);
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            "env 'REGISTRY_TOKEN=***' docker build --build-arg 'NPM_TOKEN=***' --build-arg VERSION=1 '--secret=***' /ctx",
+            "env 'REGISTRY_TOKEN=***' docker run alpine",
+            "env 'REGISTRY_TOKEN=***' docker run --env 'API_KEY=***' alpine",
+        ]
+    );
+
+    let mut cmd = command_run::Command::with_args(
+        "skopeo",
+        ["copy", "--src-creds", "user:pw", "a", "b"],
+    );
+    cmd.args.push("--password=pw".into());
+    assert_eq!(
+        Redactor::default().command_line(&cmd),
+        "skopeo copy --src-creds '***' a b '--password=***'"
+    );
+}
+
+#[test]
+fn test_retry_policy() {
+    assert_eq!(
+        RetryPolicy::classify("Error response from daemon: manifest unknown"),
+        Failure::Permanent
+    );
+    assert_eq!(
+        RetryPolicy::classify("net/http: TLS handshake timeout"),
+        Failure::Transient
+    );
+    assert_eq!(RetryPolicy::classify("something odd"), Failure::Unknown);
+
+    let mut policy = RetryPolicy::default();
+    assert_eq!(policy.delay(0), Duration::from_secs(1));
+    assert_eq!(policy.delay(2), Duration::from_secs(4));
+    assert_eq!(policy.delay(10), Duration::from_secs(30));
+
+    // Count the attempts by appending to a file each time.
+    let counter = std::env::temp_dir()
+        .join(format!("docker-command-retry-{}", std::process::id()));
+    let attempts = |error: &str, policy: &RetryPolicy| {
+        let _ = std::fs::remove_file(&counter);
+        let script = format!(
+            "echo x >> '{}'; echo '{}' >&2; exit 1",
+            counter.display(),
+            error
+        );
+        let mut cmd = command_run::Command::with_args("sh", ["-c", &script]);
+        cmd.log_command = false;
+        assert!(policy.run(&cmd).is_err());
+        std::fs::read_to_string(&counter).unwrap().lines().count()
+    };
+
+    policy.max_attempts = 3;
+    policy.initial_delay = Duration::ZERO;
+    assert_eq!(attempts("dial tcp: i/o timeout", &policy), 3);
+    assert_eq!(attempts("manifest unknown", &policy), 1);
+    assert_eq!(attempts("something odd", &policy), 1);
+    policy.retry_unknown = true;
+    assert_eq!(attempts("something odd", &policy), 3);
+    assert_eq!(attempts("dial tcp: i/o timeout", &RetryPolicy::never()), 1);
+    let _ = std::fs::remove_file(&counter);
+
+    let mut cmd = command_run::Command::with_args("echo", ["ok"]);
+    cmd.log_command = false;
+    let output = policy.run(&cmd).unwrap();
+    assert_eq!(output.stdout_string_lossy(), "ok\n");
+}
+
+#[test]
+fn test_run_with_timeout() {
+    let mut cmd = command_run::Command::with_args("sleep", ["10"]);
+    cmd.log_command = false;
+    let start = std::time::Instant::now();
+    let err = cmd
+        .run_with_timeout(Duration::from_millis(100))
+        .unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(matches!(
+        err,
+        Error::TimedOut { timeout, .. } if timeout == Duration::from_millis(100)
+    ));
+
+    // The process gets SIGTERM first, which sudo would pass on.
+    let marker = std::env::temp_dir()
+        .join(format!("docker-command-term-{}", std::process::id()));
+    let mut cmd = command_run::Command::with_args(
+        "sh",
+        [
+            "-c",
+            "trap 'echo term > \"$0\"; kill $!; exit 1' TERM; sleep 10 & wait",
+            marker.to_str().unwrap(),
+        ],
+    );
+    cmd.log_command = false;
+    // Leave time for the trap to be set up.
+    assert!(cmd.run_with_timeout(Duration::from_secs(1)).is_err());
+    assert_eq!(std::fs::read_to_string(&marker).unwrap(), "term\n");
+    std::fs::remove_file(&marker).unwrap();
+
+    let mut cmd =
+        command_run::Command::with_args("sh", ["-c", "echo out; echo err >&2"]);
+    cmd.log_command = false;
+    cmd.capture = true;
+    let output = cmd.run_with_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(output.stdout_string_lossy(), "out\n");
+    assert_eq!(output.stderr_string_lossy(), "err\n");
+
+    cmd.combine_output = true;
+    let output = cmd.run_with_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(output.stdout_string_lossy(), "out\nerr\n");
+
+    let mut cmd = command_run::Command::new("false");
+    cmd.log_command = false;
+    assert!(matches!(
+        cmd.run_with_timeout(Duration::from_secs(10)),
+        Err(Error::Run(_))
+    ));
+}
+
+#[test]
+fn test_spawn_cancellable() {
+    let mut cmd = command_run::Command::with_args("sleep", ["10"]);
+    cmd.log_command = false;
+
+    // Cancelled from another thread.
+    let spawned = cmd.spawn_cancellable().unwrap();
+    let token = spawned.cancel_token();
+    assert!(!token.is_cancelled());
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        token.cancel();
+    });
+    let start = std::time::Instant::now();
+    assert!(matches!(spawned.wait(), Err(Error::Cancelled { .. })));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // A process that ignores SIGTERM is killed after the grace period.
+    let mut cmd = command_run::Command::with_args(
+        "sh",
+        ["-c", "trap '' TERM; while true; do sleep 0.05; done"],
+    );
+    cmd.log_command = false;
+    let token = CancelToken::new();
+    token.cancel();
+    let spawned = cmd
+        .spawn_cancellable()
+        .unwrap()
+        .with_cancel_token(token)
+        .with_grace_period(Duration::from_millis(100));
+    let start = std::time::Instant::now();
+    assert!(matches!(spawned.wait(), Err(Error::Cancelled { .. })));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Commands that finish are not affected.
+    let mut cmd = command_run::Command::with_args("echo", ["done"]);
+    cmd.log_command = false;
+    cmd.capture = true;
+    let output = cmd.spawn_cancellable().unwrap().wait().unwrap();
+    assert_eq!(output.stdout_string_lossy(), "done\n");
+}
+
+#[test]
+fn test_spawn_cancellable_async() {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    struct ThreadWaker(std::thread::Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut cmd = command_run::Command::with_args("echo", ["done"]);
+    cmd.log_command = false;
+    cmd.capture = true;
+    let mut future = Box::pin(cmd.spawn_cancellable().unwrap().wait_async());
+    let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let output = loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(result) => break result.unwrap(),
+            Poll::Pending => std::thread::park(),
+        }
+    };
+    assert_eq!(output.stdout_string_lossy(), "done\n");
+}
+
+#[test]
+fn test_progress() {
+    let event = |line| ProgressParser::parse_line(line).unwrap();
+    let stage = |line| event(line).stage;
+
+    // docker pull
+    assert_eq!(
+        ProgressParser::parse_line("latest: Pulling from library/alpine"),
+        None
+    );
+    assert_eq!(
+        stage("f56be85fc22e: Pulling fs layer"),
+        ProgressStage::Waiting
+    );
+    let e = event("f56be85fc22e: Downloading [=>   ]  1.5MB/3MB");
+    assert_eq!(e.id, "f56be85fc22e");
+    assert_eq!(e.stage, ProgressStage::Downloading);
+    assert_eq!(e.percent, Some(50.0));
+    assert_eq!(
+        stage("f56be85fc22e: Verifying Checksum"),
+        ProgressStage::Verifying
+    );
+    assert_eq!(
+        stage("f56be85fc22e: Download complete"),
+        ProgressStage::Downloaded
+    );
+    assert_eq!(
+        stage("f56be85fc22e: Pull complete"),
+        ProgressStage::Complete
+    );
+    assert_eq!(
+        stage("f56be85fc22e: Already exists"),
+        ProgressStage::AlreadyExists
+    );
+    assert_eq!(ProgressParser::parse_line("Digest: sha256:abc"), None);
+
+    // podman pull
+    let e = event("Copying blob sha256:2408cc74d12b done");
+    assert_eq!(e.id, "2408cc74d12b");
+    assert_eq!(e.stage, ProgressStage::Complete);
+    assert_eq!(
+        stage("Copying blob 2408cc74d12b skipped: already exists"),
+        ProgressStage::AlreadyExists
+    );
+    let e = event("Copying blob 2408cc74d12b [===>---] 1MiB / 4MiB");
+    assert_eq!(e.stage, ProgressStage::Downloading);
+    assert_eq!(e.percent, Some(25.0));
+
+    // BuildKit
+    let e = event("#5 [2/3] RUN make");
+    assert_eq!(e.id, "#5");
+    assert_eq!(e.stage, ProgressStage::Running);
+    assert_eq!(stage("#5 DONE 1.2s"), ProgressStage::Complete);
+    assert_eq!(stage("#6 CACHED"), ProgressStage::Cached);
+    assert_eq!(
+        stage("#7 ERROR: process did not complete"),
+        ProgressStage::Error
+    );
+    assert_eq!(
+        event("#8 sha256:abc 1.05MB / 4.2MB 0.2s").percent,
+        Some(25.0)
+    );
+
+    // Chunks split mid-line and lines ending in \r.
+    let mut ids = Vec::new();
+    let mut observer = |e: &ProgressEvent| ids.push(e.id.clone());
+    let mut parser = ProgressParser::new();
+    parser.feed(b"#1 DO", &mut observer);
+    parser.feed(b"NE 0.1s\r#2 CACHED", &mut observer);
+    parser.flush(&mut observer);
+    assert_eq!(ids, ["#1", "#2"]);
+
+    let mut cmd = command_run::Command::with_args(
+        "sh",
+        ["-c", "echo '#1 DONE 0.1s'; echo '#2 CACHED' >&2"],
+    );
+    cmd.log_command = false;
+    let mut stages = Vec::new();
+    let output = cmd
+        .run_with_progress(&mut |e: &ProgressEvent| {
+            stages.push(e.stage.clone())
+        })
+        .unwrap();
+    assert_eq!(stages, [ProgressStage::Complete, ProgressStage::Cached]);
+    assert_eq!(output.stdout_string_lossy(), "#1 DONE 0.1s\n#2 CACHED\n");
+}
+
+#[test]
+fn test_runtime_error() {
+    let classify = RuntimeError::classify;
+    assert_eq!(
+        classify(
+            Some(125),
+            "Error: no container with name or ID \"web\" found: no such container"
+        ),
+        RuntimeError::NoSuchContainer
+    );
+    assert_eq!(
+        classify(Some(125), "Error: alpine: image not known"),
+        RuntimeError::NoSuchImage
+    );
+    assert_eq!(
+        classify(
+            Some(1),
+            "Error response from daemon: manifest for alpine:nope not found: \
+             manifest unknown"
+        ),
+        RuntimeError::ManifestUnknown
+    );
+    assert_eq!(
+        classify(
+            Some(125),
+            "Bind for 0.0.0.0:80 failed: port is already allocated"
+        ),
+        RuntimeError::PortAllocated
+    );
+    assert_eq!(
+        classify(
+            Some(125),
+            "Conflict. The container name \"/web\" is already in use"
+        ),
+        RuntimeError::NameConflict
+    );
+    assert_eq!(
+        classify(
+            Some(1),
+            "permission denied while trying to connect to the Docker daemon \
+             socket at unix:///var/run/docker.sock"
+        ),
+        RuntimeError::SocketPermissionDenied
+    );
+    assert_eq!(
+        classify(
+            Some(1),
+            "Cannot connect to the Docker daemon at unix:///var/run/docker.sock"
+        ),
+        RuntimeError::DaemonUnavailable
+    );
+    assert_eq!(classify(Some(125), "unknown flag"), RuntimeError::Daemon);
+    assert_eq!(classify(Some(126), ""), RuntimeError::CommandNotExecutable);
+    assert_eq!(classify(Some(127), ""), RuntimeError::CommandNotFound);
+    assert_eq!(classify(Some(3), ""), RuntimeError::Other);
+    assert_eq!(classify(None, ""), RuntimeError::Other);
+
+    let mut cmd = command_run::Command::with_args(
+        "sh",
+        ["-c", "echo 'Error: No such image: alpine' >&2; exit 1"],
+    );
+    cmd.log_command = false;
+    cmd.capture = true;
+    cmd.check = false;
+    let output = cmd.run().unwrap();
+    assert_eq!(
+        RuntimeError::from_output(&output),
+        Some(RuntimeError::NoSuchImage)
+    );
+    let output = command_run::Command::new("true").run().unwrap();
+    assert_eq!(RuntimeError::from_output(&output), None);
+}
+
+#[test]
+fn test_run_networks() {
+    let mut opt = RunOpt::new("alpine");
+    opt.network = Some(NetworkMode::Named("front".into()));
+    let mut back = NetworkAttachment::new("back");
+    back.aliases = vec!["db".into(), "database".into()];
+    back.ip6 = Some("fd00::5".into());
+    opt.networks = vec![back.clone(), "monitoring".parse().unwrap()];
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(opt)
+            .command_line_lossy(),
+        "docker run --network front --network \
+         name=back,alias=db,alias=database,ip6=fd00::5 \
+         --network monitoring alpine"
+    );
+
+    assert_eq!(
+        "name=back,alias=db,alias=database,ip6=fd00::5".parse(),
+        Ok(back)
+    );
+    let parsed: NetworkAttachment =
+        "name=back,ip=10.0.0.2,mac-address=aa:bb".parse().unwrap();
+    assert_eq!(parsed.ip.as_deref(), Some("10.0.0.2"));
+    assert_eq!(parsed.options, ["mac-address=aa:bb"]);
+    assert!("".parse::<NetworkAttachment>().is_err());
+    assert!("alias=db".parse::<NetworkAttachment>().is_err());
+}
+
+#[cfg(feature = "compose")]
+#[test]
+fn test_compose_networks() {
+    let mut project = ComposeProject::default();
+    let mut web = RunOpt::new("nginx");
+    web.network = Some("front".into());
+    let mut back = NetworkAttachment::new("back");
+    back.aliases = vec!["web".into()];
+    web.networks = vec![back];
+    project.add_service("web", web);
+    assert_eq!(
+        project.to_yaml().unwrap(),
+        "\
+services:
+  web:
+    image: nginx
+    networks:
+      front: null
+      back:
+        aliases:
+        - web
+"
+    );
+}
+
+#[test]
+fn test_network_mode() {
+    assert_eq!(NetworkMode::from("bridge"), NetworkMode::Bridge);
+    assert_eq!(NetworkMode::from("none"), NetworkMode::None);
+    assert_eq!(
+        "backend".parse::<NetworkMode>(),
+        Ok(NetworkMode::Named("backend".into()))
+    );
+    assert_eq!(NetworkMode::Container("db".into()).arg(), "container:db");
+    assert!("".parse::<NetworkMode>().is_err());
+    assert!("bad name".parse::<NetworkMode>().is_err());
+    assert!("container:a/b".parse::<NetworkMode>().is_err());
+
+    let mut opt = RunOpt::new("alpine");
+    opt.network = Some(NetworkMode::Container("db".into()));
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman)
+            .run(opt)
+            .command_line_lossy(),
+        "podman run --network container:db alpine"
+    );
+}
+
+#[test]
+fn test_ipc() {
+    let mut opt = RunOpt::new("alpine");
+    opt.ipc = Some(IpcMode::Shareable);
+    opt.name = Some("producer".into());
+    let launcher = Launcher::from(BaseCommand::Docker);
+    assert_eq!(
+        launcher.run(opt).command_line_lossy(),
+        "docker run --ipc shareable --name producer alpine"
+    );
+
+    let mut opt = RunOpt::new("alpine");
+    opt.ipc = Some("container:producer".parse().unwrap());
+    assert_eq!(
+        launcher.run(opt).command_line_lossy(),
+        "docker run --ipc container:producer alpine"
+    );
+
+    assert_eq!("host".parse(), Ok(IpcMode::Host));
+    assert_eq!("none".parse(), Ok(IpcMode::None));
+    assert!("container:-bad".parse::<IpcMode>().is_err());
+}
+
+#[test]
+fn test_validate_env() {
+    let mut opt = RunOpt::new("alpine");
+    opt.env = vec![("PATH".into(), "/bin:/usr/bin".into())];
+    assert!(opt.validate().is_ok());
+    opt.env.push(("A=B".into(), "c".into()));
+    let err = opt.validate().unwrap_err();
+    assert_eq!(err.what, "environment variable");
+    assert_eq!(err.value, "A=B");
+    opt.env = vec![("".into(), "c".into())];
+    assert!(opt.validate().is_err());
+    opt.env = vec![("TOKEN".into(), "secret\0".into())];
+    let err = opt.validate().unwrap_err();
+    assert_eq!(err.value, "TOKEN");
+    assert!(!err.to_string().contains("secret"));
+
+    let mut opt = RunOpt::new("alpine");
+    opt.labels = vec![("team=web".into(), "infra".into())];
+    assert_eq!(opt.validate().unwrap_err().what, "label");
+
+    let mut opt = RunOpt::new("alpine");
+    opt.network = Some(NetworkMode::Named("".into()));
+    assert!(opt.validate().is_err());
+
+    let mut opt = BuildOpt::new("/ctx");
+    opt.build_args = vec![("VERSION", "1=2").into()];
+    assert!(opt.validate().is_ok());
+    opt.build_args.push(BuildArg::Inherit("BAD\0".into()));
+    assert_eq!(opt.validate().unwrap_err().what, "build arg");
+}
+
+#[test]
+fn test_build_labels() {
+    let mut meta = oci::OciMetadata::default();
+    meta.created = Some(oci::format_time(
+        SystemTime::UNIX_EPOCH + Duration::from_secs(951_782_400),
+    ));
+    meta.version = Some("1.2.3".into());
+    assert_eq!(
+        meta.pairs(),
+        [
+            (oci::CREATED.to_string(), "2000-02-29T00:00:00Z".to_string()),
+            (oci::VERSION.to_string(), "1.2.3".to_string()),
+        ]
+    );
+
+    let mut opt = BuildOpt::new("/ctx");
+    opt.add_oci_labels(&meta);
+    // Empty metadata adds nothing.
+    opt.add_oci_annotations(&oci::OciMetadata::default());
+    assert!(opt.annotations.is_empty());
+    opt.annotations = vec![(oci::TITLE.into(), "app".into())];
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .build(opt.clone())
+            .command_line_lossy(),
+        "docker build \
+         --annotation org.opencontainers.image.title=app \
+         --label org.opencontainers.image.created=2000-02-29T00:00:00Z \
+         --label org.opencontainers.image.version=1.2.3 /ctx"
+    );
+    assert!(opt.validate().is_ok());
+    opt.labels.push(("a=b".into(), "c".into()));
+    assert_eq!(opt.validate().unwrap_err().what, "label");
+}
+
+#[cfg(feature = "git")]
+#[test]
+fn test_git_metadata() {
+    let dir = std::env::temp_dir()
+        .join(format!("docker-command-git-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let git = |args: &[&str]| {
+        let mut cmd = command_run::Command::with_args(
+            "git",
+            ["-c", "user.name=test", "-c", "user.email=test@test"],
+        );
+        cmd.add_args(args);
+        cmd.set_dir(&dir);
+        cmd.log_command = false;
+        cmd.capture = true;
+        cmd.run().unwrap();
+    };
+    git(&["init", "-q", "-b", "main"]);
+    git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+    git(&["remote", "add", "origin", "git@github.com:org/app.git"]);
+
+    let meta = GitMetadata::discover(&dir).unwrap();
+    assert_eq!(meta.commit.len(), 40);
+    assert_eq!(meta.branch.as_deref(), Some("main"));
+    assert!(!meta.dirty);
+    assert_eq!(meta.source.as_deref(), Some("https://github.com/org/app"));
+
+    std::fs::write(dir.join("new"), "").unwrap();
+    let meta = GitMetadata::discover(&dir).unwrap();
+    assert!(meta.dirty);
+    assert_eq!(meta.revision(), format!("{}-dirty", meta.commit));
+
+    let mut opt = BuildOpt::new(&dir);
+    meta.add_to_build(&mut opt);
+    assert_eq!(
+        opt.labels[0],
+        (
+            oci::SOURCE.to_string(),
+            "https://github.com/org/app".to_string()
+        )
+    );
+    assert_eq!(opt.labels[1].0, oci::REVISION);
+    assert_eq!(
+        opt.build_args[1..],
+        [("GIT_BRANCH", "main").into(), ("GIT_DIRTY", "true").into()]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_save_load() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let mut opt = SaveOpt::new(["alpine:3", "debian"]);
+    opt.output = Some(new_path("/tmp/images.tar"));
+    assert_eq!(
+        launcher.save(opt).command_line_lossy(),
+        "docker save --output /tmp/images.tar alpine:3 debian"
+    );
+    let mut opt = LoadOpt::default();
+    opt.quiet = true;
+    assert_eq!(
+        launcher.load(opt).command_line_lossy(),
+        "docker load --quiet"
+    );
+}
+
+#[test]
+fn test_pipe_to() {
+    let path = std::env::temp_dir()
+        .join(format!("docker-command-pipe-{}", std::process::id()));
+    let mut source =
+        command_run::Command::with_args("sh", ["-c", "echo one; echo two"]);
+    source.log_command = false;
+    let mut sink = command_run::Command::with_args(
+        "sh",
+        ["-c", &format!("tr a-z A-Z > '{}'", path.display())],
+    );
+    sink.log_command = false;
+    source.pipe_to(&sink).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "ONE\nTWO\n");
+    std::fs::remove_file(&path).unwrap();
+
+    let mut fail = command_run::Command::new("false");
+    fail.log_command = false;
+    let err = source.pipe_to(&fail).unwrap_err();
+    assert!(
+        matches!(err, Error::Run(e) if e.command.program == new_path("false"))
+    );
+    let err = fail.pipe_to(&sink).unwrap_err();
+    assert!(
+        matches!(err, Error::Run(e) if e.command.program == new_path("false"))
+    );
+}
+
+#[test]
+fn test_cp() {
+    let web: ContainerRef = "web".parse().unwrap();
+    let mut opt = CpOpt::new(
+        CopyLocation::Host(new_path("site/.")),
+        CopyLocation::Container(web.clone(), new_path("/srv/www")),
+    );
+    opt.archive = true;
+    let launcher = Launcher::from(BaseCommand::Docker);
+    assert_eq!(
+        launcher.cp(opt).command_line_lossy(),
+        "docker cp --archive site/. web:/srv/www"
+    );
+
+    let opt = CpOpt::new(
+        CopyLocation::Container(web, new_path("/etc/nginx/nginx.conf")),
+        CopyLocation::Host(new_path("-")),
+    );
+    assert_eq!(
+        launcher.cp(opt).command_line_lossy(),
+        "docker cp web:/etc/nginx/nginx.conf -"
+    );
+}
+
+#[test]
+fn test_exec() {
+    let mut opt = ExecOpt::new("web".parse().unwrap(), "sh");
+    opt.args = vec!["-c".into(), "echo $A".into()];
+    opt.env = vec![("A".into(), "1".into())];
+    opt.interactive = true;
+    opt.tty = true;
+    opt.workdir = Some(new_path("/srv"));
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman)
+            .exec(opt)
+            .command_line_lossy(),
+        "podman exec --env A=1 --interactive --tty --workdir /srv web sh -c 'echo $A'"
+    );
+}
+
+#[test]
+fn test_read_write_file() {
+    // Stand in for `docker` with a shell that drops the arguments up
+    // to the container name and runs the rest on the host.
+    let mut base = command_run::Command::with_args(
+        "sh",
+        [
+            "-c",
+            "while [ $1 != web ]; do shift; done; shift; exec \"$@\"",
+            "sh",
+        ],
+    );
+    base.log_command = false;
+    base.dir = Some(std::env::temp_dir());
+    let launcher = Launcher::new(base);
+    let web: ContainerRef = "web".parse().unwrap();
+    let path = std::env::temp_dir()
+        .join(format!("docker-command-file-{}", std::process::id()));
+
+    launcher.write_file(&web, &path, b"hello\n").unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"hello\n");
+    assert_eq!(launcher.read_file(&web, &path).unwrap(), b"hello\n");
+    std::fs::remove_file(&path).unwrap();
+    assert!(launcher.read_file(&web, &path).is_err());
+
+    // A path that starts with a dash is not read as an option.
+    let name = format!("-docker-command-file-{}", std::process::id());
+    launcher.write_file(&web, &name, b"dash\n").unwrap();
+    assert_eq!(launcher.read_file(&web, &name).unwrap(), b"dash\n");
+    std::fs::remove_file(std::env::temp_dir().join(&name)).unwrap();
+}
+
+#[test]
+fn test_shell() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let web: ContainerRef = "web".parse().unwrap();
+
+    let mut opt = ShellOpt::default();
+    opt.user = Some(UserAndGroup::root());
+    assert_eq!(
+        launcher.shell(web, opt).command_line_lossy(),
+        "docker exec --interactive --tty --user 0:0 web sh -c 'if command -v bash >/dev/null 2>&1; then exec bash; fi; exec sh'"
+    );
+
+    let mut opt = ShellOpt::default();
+    opt.shell = Some("ash".into());
+    opt.workdir = Some(new_path("/src"));
+    assert_eq!(
+        launcher.shell("alpine", opt).command_line_lossy(),
+        "docker run --interactive --rm --tty --entrypoint ash --workdir /src alpine"
+    );
+}
+
+#[test]
+fn test_run_cgroup() {
+    let mut opt = RunOpt::new("alpine");
+    opt.cgroup_parent = Some("/batch".into());
+    opt.cpu_period = Some(Duration::from_millis(100));
+    opt.cpu_quota = Some(Duration::from_millis(50));
+    opt.device_read_bps = vec!["/dev/sda:1mb".parse().unwrap()];
+    opt.device_write_bps = vec![DeviceThrottle::new("/dev/sdb", 512)];
+    assert_eq!(opt.validate(), Ok(()));
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(opt.clone())
+            .command_line_lossy(),
+        "docker run --cgroup-parent /batch --cpu-period 100000 \
+         --cpu-quota 50000 --device-read-bps /dev/sda:1048576 \
+         --device-write-bps /dev/sdb:512 alpine"
+    );
+
+    opt.cpu_period = Some(Duration::from_secs(2));
+    assert!(opt.validate().is_err());
+    assert!("/dev/sda:fast".parse::<DeviceThrottle>().is_err());
+    assert!(":10".parse::<DeviceThrottle>().is_err());
+}
+
+#[test]
+fn test_seccomp_profile() {
+    let profile = SeccompProfile::from_bytes(b"{}").unwrap();
+    let path = profile.path().to_path_buf();
+    assert_eq!(std::fs::read(&path).unwrap(), b"{}");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let mut opt = RunOpt::new("alpine");
+    opt.security_opts = vec![SecurityOpt::NoNewPrivileges];
+    profile.apply(&mut opt);
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(opt)
+            .command_line_lossy(),
+        format!(
+            "docker run --security-opt no-new-privileges \
+             --security-opt seccomp={} alpine",
+            path.display()
+        )
+    );
+
+    drop(profile);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_secrets() {
+    let mut opt = RunOpt::new("alpine");
+    opt.secrets = vec![
+        SecretSource::Podman {
+            id: "db-password".into(),
+            target: Some(SecretTarget::Env("DB_PASSWORD".into())),
+        },
+        SecretSource::Podman {
+            id: "tls-key".into(),
+            target: Some(SecretTarget::File("/etc/tls/key.pem".into())),
+        },
+        SecretSource::file("/host/token"),
+        SecretSource::Env("API_KEY".into()),
+        SecretSource::EnvFile("app.env".into()),
+    ];
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman)
+            .run(opt)
+            .command_line_lossy(),
+        "podman run --secret db-password,type=env,target=DB_PASSWORD \
+         --secret tls-key,type=mount,target=/etc/tls/key.pem \
+         --mount type=bind,source=/host/token,target=/run/secrets/token,readonly \
+         --env API_KEY --env-file app.env alpine"
+    );
+}
+
+#[test]
+fn test_init_path() {
+    let mut opt = RunOpt::new("alpine");
+    opt.init = true;
+    opt.init_path = Some(new_path("/usr/libexec/podman/catatonit"));
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman)
+            .run(opt.clone())
+            .command_line_lossy(),
+        "podman run --init --init-path /usr/libexec/podman/catatonit alpine"
+    );
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(opt.clone())
+            .command_line_lossy(),
+        "docker run --init alpine"
+    );
+
+    // Neither of these needs to run anything.
+    opt.init_path = Some(new_path("/nonexistent/init"));
+    assert!(matches!(
+        Launcher::from(BaseCommand::Podman).check_init(&opt),
+        Err(Error::Invalid(_))
+    ));
+    assert!(matches!(
+        Launcher::from(BaseCommand::Docker).check_init(&opt),
+        Err(Error::Invalid(_))
+    ));
+    assert!(Launcher::from(BaseCommand::Docker)
+        .check_init(&RunOpt::new("alpine"))
+        .is_ok());
+}
+
+#[test]
+fn test_launcher_config() {
+    let config = LauncherConfig::parse(
+        r#"
+# Remote podman.
+engine = "podman"
+sudo = "never"
+docker_group = "dockerroot"
+context = 'remote'  # trailing comment
+debug = true
+log_level = "warn"
+global_args = ["--storage-driver", "overlay"]
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.engine, Engine::Podman);
+    assert_eq!(config.sudo, SudoPolicy::Never);
+    assert_eq!(config.docker_group.as_deref(), Some("dockerroot"));
+    let launcher = config.launcher().unwrap();
+    let cmd = launcher.stop(StopOpt::default());
+    assert_eq!(
+        cmd.command_line_lossy(),
+        "podman --context remote --debug --log-level warn \
+         --storage-driver overlay stop"
+    );
+
+    let mut config = LauncherConfig::default();
+    config.engine = Engine::Podman;
+    config.platform = Some("linux/arm64".into());
+    assert!(config.launcher().is_err());
+    config.engine = Engine::Docker;
+    config.sudo = SudoPolicy::Never;
+    assert_eq!(
+        config
+            .launcher()
+            .unwrap()
+            .stop(StopOpt::default())
+            .env
+            .get(std::ffi::OsStr::new("DOCKER_DEFAULT_PLATFORM")),
+        Some(&"linux/arm64".into())
+    );
+    // The platform is not dropped by sudo.
+    config.sudo = SudoPolicy::Always;
+    assert_eq!(
+        config
+            .launcher()
+            .unwrap()
+            .stop(StopOpt::default())
+            .command_line_lossy(),
+        "sudo --preserve-env=DOCKER_DEFAULT_PLATFORM docker stop"
+    );
+
+    let mut config = LauncherConfig::default();
+    config.engine = Engine::Docker;
+    config.sudo = SudoPolicy::Always;
+    assert!(config.launcher().unwrap().is_docker());
+    assert_eq!(
+        config
+            .launcher()
+            .unwrap()
+            .base_command()
+            .command_line_lossy(),
+        "sudo docker"
+    );
+
+    assert!(LauncherConfig::parse("engine = \"lxc\"").is_err());
+    assert!(LauncherConfig::parse("debug = \"yes\"").is_err());
+    assert!(LauncherConfig::parse("colour = \"blue\"").is_err());
+    assert!(LauncherConfig::parse("host = \"unterminated").is_err());
+    assert!(LauncherConfig::parse("[table]").is_err());
+}
+
+#[test]
+fn test_defaults() {
+    let mut defaults = Defaults::default();
+    defaults.user = Some(UserAndGroup::root());
+    defaults.labels = vec![
+        ("team".into(), "infra".into()),
+        ("env".into(), "dev".into()),
+    ];
+    defaults.network = Some(NetworkMode::Named("corp".into()));
+    defaults.pull = Some(PullPolicy::Always);
+    defaults.env = vec![("TZ".into(), "UTC".into())];
+    let launcher = Launcher::from(BaseCommand::Docker).with_defaults(defaults);
+
+    let mut opt = RunOpt::new("alpine");
+    opt.labels = vec![("env".into(), "prod".into())];
+    opt.network = Some(NetworkMode::Host);
+    let expected = "docker run --env TZ=UTC --label team=infra \
+                    --label env=prod --network host --pull=always \
+                    --user 0:0 alpine";
+    assert_eq!(launcher.run(opt.clone()).command_line_lossy(), expected);
+    let mut cmd = command_run::Command::new("");
+    launcher.run_into(&opt, &mut cmd);
+    assert_eq!(cmd.command_line_lossy(), expected);
+
+    let mut opt = BuildOpt::new(".");
+    opt.pull = Some(PullPolicy::Never);
+    assert_eq!(
+        launcher.build(opt).command_line_lossy(),
+        "docker build --label team=infra --label env=dev --network corp \
+         --pull=false ."
+    );
+}
+
+#[test]
+fn test_run_opt_merge() {
+    let mut base = RunOpt::new("app:1.0");
+    base.command = Some(new_path("serve"));
+    base.args = vec!["--port=80".into()];
+    base.labels = vec![("team".into(), "web".into())];
+    base.volumes = vec![Volume {
+        src: new_path("/data").into(),
+        dst: new_path("/data"),
+        ..Default::default()
+    }];
+    base.uidmap = vec![IdMap {
+        container: 0,
+        host: 1000,
+        size: 1,
+    }];
+
+    let mut overrides = RunOpt::new("app:2.0");
+    overrides.labels = vec![
+        ("team".into(), "platform".into()),
+        ("tier".into(), "1".into()),
+    ];
+    overrides.mounts = vec!["type=tmpfs,target=/tmp".parse().unwrap()];
+    overrides.remove = true;
+
+    let merged = base.merge(&overrides);
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(merged)
+            .command_line_lossy(),
+        "docker run --label team=platform --label tier=1 \
+         --mount type=tmpfs,target=/tmp --rm --uidmap 0:1000:1 \
+         --volume /data:/data app:2.0 serve --port=80"
+    );
+
+    overrides.args = vec!["--debug".into()];
+    let merged = base.merge(&overrides);
+    assert_eq!(merged.command, None);
+    assert_eq!(merged.args, ["--debug"]);
+    assert_eq!(base.merge(&RunOpt::default()), base);
+}
+
+#[test]
+fn test_profiles() {
+    let mut profiles = Profiles::default();
+    let mut gpu = RunOpt::default();
+    gpu.extra_args = vec!["--gpus=all".into()];
+    profiles.define("gpu", gpu);
+    let mut debug = RunOpt::default();
+    debug.env = vec![("RUST_LOG".into(), "debug".into())];
+    debug.interactive = true;
+    debug.tty = true;
+    profiles.define("debug", debug);
+    assert_eq!(profiles.names().collect::<Vec<_>>(), ["debug", "gpu"]);
+
+    let mut opt = RunOpt::new("trainer");
+    opt.env = vec![("RUST_LOG".into(), "info".into())];
+    let opt = profiles.apply(&opt, ["gpu", "debug"]).unwrap();
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(opt)
+            .command_line_lossy(),
+        "docker run --env RUST_LOG=debug --interactive --tty --gpus=all \
+         trainer"
+    );
+
+    let err = profiles.apply(&RunOpt::default(), ["gpu", "nope"]);
+    assert_eq!(err.unwrap_err().value, "nope");
+    assert!(profiles.remove("gpu").is_some());
+    assert!(profiles.get("gpu").is_none());
+}
+
+#[test]
+fn test_introspect() {
+    let mut global_opts = GlobalOpts::default();
+    global_opts.host = Some("ssh://builder".into());
+    let launcher =
+        Launcher::from(BaseCommand::SudoDocker).with_global_opts(global_opts);
+
+    let mut opt = RunOpt::new("alpine");
+    opt.env = vec![("A".into(), "1".into()), ("B".into(), "2".into())];
+    opt.pull = Some(PullPolicy::Never);
+    opt.read_only = true;
+    opt.command = Some(new_path("sh"));
+    opt.args = vec!["-c".into(), "--rm".into()];
+    let cmd = launcher.introspect(&launcher.run(opt));
+    assert!(cmd.is(&["run"]));
+    assert_eq!(cmd.flag_values("--env").collect::<Vec<_>>(), ["A=1", "B=2"]);
+    assert_eq!(cmd.flag_value("--pull").unwrap(), "never");
+    assert!(cmd.has_flag("--read-only"));
+    assert!(!cmd.has_flag("--rm"));
+    assert_eq!(cmd.positionals, ["alpine", "sh", "-c", "--rm"]);
+
+    let mut opt = GenerateSystemdOpt::new("web");
+    opt.name = true;
+    let cmd = launcher.introspect(&launcher.generate_systemd(opt));
+    assert_eq!(cmd.subcommand, ["generate", "systemd"]);
+    assert_eq!(cmd.flags, [("--name".to_string(), None)]);
+    assert_eq!(cmd.positionals, ["web"]);
+
+    let mut opt =
+        RemoveContainersOpt::new(["web".parse::<ContainerRef>().unwrap()]);
+    opt.volumes = true;
+    let cmd = launcher.introspect(&launcher.remove_containers(opt));
+    assert!(cmd.has_flag("--volumes"));
+    assert_eq!(cmd.positionals, ["web"]);
+
+    // Short options, grouped and with attached values.
+    let mut opt = RunOpt::new("alpine");
+    opt.extra_args = ["-it", "-m", "512m", "-eA=1", "--privileged"]
+        .map(Into::into)
+        .to_vec();
+    let cmd = launcher.introspect(&launcher.run(opt));
+    assert!(cmd.has_flag("-i"));
+    assert!(cmd.has_flag("-t"));
+    assert_eq!(cmd.flag_value("-m").unwrap(), "512m");
+    assert_eq!(cmd.flag_value("-e").unwrap(), "A=1");
+    assert!(cmd.has_flag("--privileged"));
+    assert_eq!(cmd.positionals, ["alpine"]);
+
+    // The same letter can be a switch in one subcommand and take a
+    // value in another.
+    let cmd = GeneratedCommand::parse(["rm", "-f", "web"]);
+    assert_eq!(cmd.flags, [("-f".to_string(), None)]);
+    assert_eq!(cmd.positionals, ["web"]);
+    let cmd = GeneratedCommand::parse(["build", "-f", "Dockerfile", "."]);
+    assert_eq!(cmd.flag_value("-f").unwrap(), "Dockerfile");
+    assert_eq!(cmd.positionals, ["."]);
+
+    // Likewise for long options.
+    let cmd = GeneratedCommand::parse(["build", "--pull", "."]);
+    assert_eq!(cmd.flags, [("--pull".to_string(), None)]);
+    assert_eq!(cmd.positionals, ["."]);
+    let cmd = GeneratedCommand::parse(["run", "--pull", "always", "alpine"]);
+    assert_eq!(cmd.flag_value("--pull").unwrap(), "always");
+    assert_eq!(cmd.positionals, ["alpine"]);
+}
+
+#[test]
+fn test_policy() {
+    let mut policy = StandardPolicy::default();
+    policy.deny_privileged = true;
+    policy.require_read_only = true;
+    policy.require_cpu_limit = true;
+    policy.allowed_registries = vec!["docker.io".into()];
+    let launcher = Launcher::from(BaseCommand::Docker)
+        .with_policy(policy)
+        .with_policy(|cmd: &GeneratedCommand| {
+            if cmd.has_flag("--ipc") {
+                Err("custom IPC modes are not allowed".to_string())
+            } else {
+                Ok(())
+            }
+        });
+
+    let mut opt = RunOpt::new("alpine");
+    opt.read_only = true;
+    opt.cpu_quota = Some(Duration::from_millis(50));
+    assert!(launcher.check_policy(&launcher.run(opt.clone())).is_ok());
+
+    let mut privileged = opt.clone();
+    privileged.extra_args = vec!["--privileged".into()];
+    let err = launcher
+        .check_policy(&launcher.run(privileged))
+        .unwrap_err();
+    assert_eq!(err.reason, "privileged containers are not allowed");
+
+    let mut writable = opt.clone();
+    writable.read_only = false;
+    assert!(launcher.check_policy(&launcher.run(writable)).is_err());
+
+    let mut quay = opt.clone();
+    quay.image = "quay.io/org/app".into();
+    assert_eq!(
+        launcher
+            .check_policy(&launcher.run(quay))
+            .unwrap_err()
+            .reason,
+        "registry quay.io is not allowed"
+    );
+
+    // Short options in extra arguments don't hide the options after
+    // them, or the image.
+    let mut short = opt.clone();
+    short.extra_args = ["-m", "512m", "--privileged"].map(Into::into).to_vec();
+    assert_eq!(
+        launcher
+            .check_policy(&launcher.run(short))
+            .unwrap_err()
+            .reason,
+        "privileged containers are not allowed"
+    );
+
+    let mut ipc = opt;
+    ipc.ipc = Some(IpcMode::Host);
+    assert!(matches!(
+        launcher.enforce(launcher.run(ipc)),
+        Err(Error::PolicyViolation(_))
+    ));
+
+    // Commands that do not create containers are not checked.
+    assert!(launcher
+        .check_policy(&launcher.stop(StopOpt::default()))
+        .is_ok());
+
+    let mut privileged = RunOpt::new("alpine");
+    privileged.extra_args = vec!["--privileged".into()];
+    assert!(matches!(
+        launcher.try_run(privileged.clone()),
+        Err(Error::PolicyViolation(_))
+    ));
+    assert!(launcher.try_pull(PullOpt::new("quay.io/org/app")).is_err());
+    assert!(launcher.try_pull(PullOpt::new("alpine")).is_ok());
+
+    // The helpers that run commands check them first. The fake engine
+    // fails if it is ever run.
+    let mut base = command_run::Command::with_args("sh", ["-c", "exit 3"]);
+    base.log_command = false;
+    let mut policy = StandardPolicy::default();
+    policy.deny_privileged = true;
+    let launcher = Launcher::new(base).with_policy(policy);
+    assert!(matches!(
+        launcher.run_with_unique_name(privileged, "test"),
+        Err(Error::PolicyViolation(_))
+    ));
+}
+
+#[test]
+fn test_untrusted() {
+    assert!(check_arg("tag", "v1.0").is_ok());
+    assert!(check_arg("tag", "").is_err());
+    assert!(check_arg("tag", "-v").is_err());
+    assert!(check_arg("tag", "a\0b").is_err());
+
+    assert_eq!(Untrusted::new("web").check("container"), Ok("web"));
+    let err = Untrusted::new("--rm").check("container").unwrap_err();
+    assert_eq!(err.what, "container");
+    assert_eq!(err.reason, "starts with '-'");
+    assert!(Untrusted::new("--privileged").parse::<ImageRef>().is_err());
+    assert!(Untrusted::new("alpine:3").parse::<ImageRef>().is_ok());
+    assert_eq!(Untrusted::new("-x").into_inner_unchecked(), "-x");
+}
+
+#[test]
+fn test_system_info() {
+    let podman = r#"{
+  "host": {
+    "arch": "amd64",
+    "cgroupVersion": "v2",
+    "security": {"rootless": true}
+  },
+  "registries": {
+    "docker.io": {
+      "Prefix": "docker.io",
+      "Mirrors": [{"Location": "mirror.example.com"}]
+    },
+    "search": ["docker.io"]
+  },
+  "store": {"graphDriverName": "overlay"},
+  "version": {"Version": "4.9.3"}
+}"#;
+    let info = SystemInfo::parse_json(podman).unwrap();
+    assert_eq!(info.server_version, "4.9.3");
+    assert_eq!(info.storage_driver, "overlay");
+    assert_eq!(info.cgroup_version, Some(2));
+    assert!(info.rootless);
+    assert_eq!(info.registry_mirrors, ["mirror.example.com"]);
+
+    let docker = r#"{"ServerVersion":"20.10.5","Driver":"overlay2",
+        "CgroupVersion":"1",
+        "SecurityOptions":["name=seccomp,profile=default","name=rootless"],
+        "RegistryConfig":{"Mirrors":[]}}"#;
+    let info = SystemInfo::parse_json(docker).unwrap();
+    assert_eq!(info.storage_driver, "overlay2");
+    assert_eq!(info.cgroup_version, Some(1));
+    assert!(info.rootless);
+    assert!(info.registry_mirrors.is_empty());
+
+    assert!(SystemInfo::parse_json("{}").is_err());
+    assert!(SystemInfo::parse_json("not json").is_err());
+}
+
+#[test]
+fn test_image_config() {
+    let output = r#"[
+  {
+    "Id": "sha256:abc",
+    "RepoDigests": ["docker.io/library/nginx@sha256:0123"],
+    "Config": {
+      "User": "",
+      "ExposedPorts": {"80/tcp": {}, "443/udp": {}},
+      "Env": ["PATH=/usr/bin:/bin", "NGINX_VERSION=1.25.3"],
+      "Entrypoint": ["/docker-entrypoint.sh"],
+      "Cmd": ["nginx", "-g", "daemon off;"],
+      "WorkingDir": "/srv"
+    }
+  }
+]"#;
+    let config = ImageConfig::parse_json(output).unwrap();
+    assert_eq!(config.repo_digests, ["docker.io/library/nginx@sha256:0123"]);
+    assert_eq!(
+        config.exposed_ports,
+        [(80, Protocol::Tcp), (443, Protocol::Udp)]
+    );
+    assert_eq!(config.env[1], ("NGINX_VERSION".into(), "1.25.3".into()));
+    assert_eq!(
+        config.default_command(),
+        ["/docker-entrypoint.sh", "nginx", "-g", "daemon off;"]
+    );
+    assert_eq!(config.working_dir.as_deref(), Some("/srv"));
+    assert_eq!(config.user, None);
+
+    // Images without a config section (e.g. scratch) have no defaults.
+    let config = ImageConfig::parse_json(r#"[{"Config":null}]"#).unwrap();
+    assert_eq!(config, ImageConfig::default());
+    assert!(ImageConfig::parse_json("[]").is_err());
+}
+
+#[test]
+fn test_stop_by_filter() {
+    let log = std::env::temp_dir()
+        .join(format!("docker-command-stop-{}", std::process::id()));
+    let a = "a".repeat(64);
+    let b = "b".repeat(64);
+    // Stand in for `docker`: `ps` prints two IDs, anything else is
+    // recorded in `log`.
+    let script = format!(
+        "if [ $1 = ps ]; then echo {}; echo {}; else echo \"$@\" > {}; fi",
+        a,
+        b,
+        log.display()
+    );
+    let mut base = command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+    base.log_command = false;
+    let launcher = Launcher::new(base);
+
+    let mut opt = StopOpt::default();
+    opt.time = Some(Duration::from_secs(1).into());
+    let ids = launcher
+        .stop_by_filter(Filter::Label("app=web".into()), opt)
+        .unwrap();
+    assert_eq!(ids, [a.parse().unwrap(), b.parse::<ContainerId>().unwrap()]);
+    assert_eq!(
+        std::fs::read_to_string(&log).unwrap(),
+        format!("stop --time 1 {} {}\n", a, b)
+    );
+    std::fs::remove_file(&log).unwrap();
+}
+
+#[test]
+fn test_audit() {
+    // Stand in for `docker inspect --format <format> --type <kind>
+    // <object>`.
+    let script = r#"case "$5 $6" in
+        "container web") echo "always 0 sha256:old" ;;
+        "container db") echo "on-failure 0 sha256:aaa" ;;
+        "image nginx") echo sha256:new ;;
+        "image postgres") echo sha256:aaa ;;
+        *) exit 1 ;;
+    esac"#;
+    let mut base = command_run::Command::with_args("sh", ["-c", script, "sh"]);
+    base.log_command = false;
+    let launcher = Launcher::new(base);
+
+    let mut web = RunOpt::new("nginx");
+    web.name = Some("web".into());
+    web.restart = Some(RestartPolicy::UnlessStopped);
+    let mut db = RunOpt::new("postgres");
+    db.name = Some("db".into());
+    db.restart = Some(RestartPolicy::OnFailure(None));
+    let mut cache = RunOpt::new("redis");
+    cache.name = Some("cache".into());
+
+    let report = launcher.audit(&[web, db, cache]).unwrap();
+    assert_eq!(
+        report,
+        [
+            (
+                "web".to_string(),
+                vec![
+                    Drift::RestartPolicy {
+                        expected: RestartPolicy::UnlessStopped,
+                        actual: RestartPolicy::Always,
+                    },
+                    Drift::Image {
+                        expected: Some("sha256:new".into()),
+                        actual: "sha256:old".into(),
+                    },
+                ]
+            ),
+            ("cache".to_string(), vec![Drift::Missing]),
+        ]
+    );
+
+    assert!(matches!(
+        launcher.audit(&[RunOpt::new("nginx")]),
+        Err(Error::Invalid(_))
+    ));
+}
+
+#[test]
+fn test_restart_policy() {
+    let mut opt = RunOpt::new("alpine");
+    opt.restart = Some(RestartPolicy::OnFailure(Some(5)));
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(opt)
+            .command_line_lossy(),
+        "docker run --restart on-failure:5 alpine"
+    );
+    assert_eq!("on-failure".parse(), Ok(RestartPolicy::OnFailure(None)));
+    assert!("on-failure:x".parse::<RestartPolicy>().is_err());
+    assert!("always:2".parse::<RestartPolicy>().is_err());
+}
+
+#[test]
+fn test_remove_containers() {
+    let mut opt =
+        RemoveContainersOpt::new(["web".parse::<ContainerName>().unwrap()]);
+    opt.force = true;
+    opt.volumes = true;
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .remove_containers(opt)
+            .command_line_lossy(),
+        "docker rm --force --volumes web"
+    );
+}
+
+#[test]
+fn test_plan() {
+    // Stand in for `docker`: no networks or volumes exist, `ps` lists
+    // two containers, and `inspect --format <format> --type <kind>
+    // <object>` answers for `web` and `cache`.
+    let script = r#"
+        if [ $1 = ps ]; then echo web; echo old; exit; fi
+        if [ $1 != inspect ]; then exit; fi
+        case "$3 $6" in
+            "{{index"*" web") echo stale ;;
+            "{{index"*" cache") echo "$HASH" ;;
+            *" cache") echo "no 0 sha256:x" ;;
+            *" redis") echo sha256:x ;;
+            *) exit 1 ;;
+        esac"#;
+    let mut base = command_run::Command::with_args("sh", ["-c", script, "sh"]);
+    base.log_command = false;
+
+    let mut deployment = Deployment::new("app");
+    deployment.networks = vec![CreateNetworkOpt::new("net")];
+    for (name, image) in
+        [("web", "nginx"), ("db", "postgres"), ("cache", "redis")]
+    {
+        let mut opt = RunOpt::new(image);
+        opt.name = Some(name.into());
+        deployment.containers.push(opt);
+    }
+
+    let plan = Launcher::new(base.clone()).plan(&deployment).unwrap();
+    let actions: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|step| step.action.to_string())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            "create network net",
+            "remove container old",
+            "replace container web",
+            "create container db",
+            "replace container cache",
+        ]
+    );
+    assert_eq!(
+        plan.steps[1].commands[0].args[3..],
+        ["rm", "--force", "old"]
+    );
+    let run = plan.steps[3].commands[0].command_line_lossy();
+    assert!(run.contains(
+        "run --detach --label docker-command.deployment=app --label \
+         docker-command.config="
+    ));
+    assert!(run.ends_with(" --name db postgres"));
+    assert!(plan.to_string().starts_with("create network net\n  sh -c"));
+
+    // Once `cache` has the label it was given, it is left alone.
+    let run = plan.steps[4].commands[1].command_line_lossy();
+    let hash = run.split("docker-command.config=").nth(1).unwrap();
+    base.env
+        .insert("HASH".into(), hash.split(' ').next().unwrap().into());
+    let plan = Launcher::new(base).plan(&deployment).unwrap();
+    assert_eq!(
+        plan.steps.last().unwrap().action,
+        Action::CreateContainer("db".into())
+    );
+    assert_eq!(plan.steps.len(), 4);
+
+    deployment.containers.push(RunOpt::new("alpine"));
+    assert!(matches!(
+        Launcher::from(BaseCommand::Docker).plan(&deployment),
+        Err(Error::Invalid(_))
+    ));
+
+    // Invalid options are rejected before anything is run.
+    deployment.containers.pop();
+    deployment.containers[1].env = vec![("A=B".into(), "1".into())];
+    let err = Launcher::from(BaseCommand::Docker)
+        .plan(&deployment)
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::Invalid(ref err) if err.what == "environment variable")
+    );
+}
+
+#[test]
+fn test_start_rename() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let web: ContainerRef = "web".parse().unwrap();
+    assert_eq!(
+        launcher
+            .start(std::slice::from_ref(&web))
+            .command_line_lossy(),
+        "docker start web"
+    );
+    assert_eq!(
+        launcher
+            .rename(&web, &"web-old".parse().unwrap())
+            .command_line_lossy(),
+        "docker rename web web-old"
+    );
+}
+
+#[test]
+fn test_replace_container() {
+    let log = std::env::temp_dir()
+        .join(format!("docker-command-replace-{}", std::process::id()));
+    let id = "a".repeat(64);
+    // Stand in for `docker`: `web` and $PREV exist, `run` prints an ID,
+    // `inspect` prints $STATUS, and anything else is recorded in
+    // `log`.
+    let script = format!(
+        "case $1 in
+            ps) echo web; echo $PREV ;;
+            run) echo {} ;;
+            inspect) echo $STATUS ;;
+            *) echo \"$@\" >> {} ;;
+        esac",
+        id,
+        log.display()
+    );
+    let mut base = command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+    base.log_command = false;
+    let mut opt = RunOpt::new("nginx");
+    opt.name = Some("web".into());
+    opt.publish = vec!["8080:80".parse().unwrap()];
+
+    base.env.insert("STATUS".into(), "healthy".into());
+    let new_id = Launcher::new(base.clone())
+        .replace_container(opt.clone(), Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(new_id, id.parse().unwrap());
+    assert_eq!(
+        std::fs::read_to_string(&log).unwrap(),
+        "rename web web-prev\nstop web-prev\nrm web-prev\n"
+    );
+    std::fs::remove_file(&log).unwrap();
+
+    base.env.insert("STATUS".into(), "unhealthy".into());
+    let err = Launcher::new(base.clone())
+        .replace_container(opt.clone(), Duration::from_secs(1))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Unhealthy { ref status, .. } if status == "unhealthy"
+    ));
+    assert_eq!(
+        std::fs::read_to_string(&log).unwrap(),
+        "rename web web-prev\nstop web-prev\nrm --force web\n\
+         rename web-prev web\nstart web\n"
+    );
+    std::fs::remove_file(&log).unwrap();
+
+    // Without published ports the old container keeps running until
+    // the new one is healthy.
+    opt.publish.clear();
+    assert!(Launcher::new(base.clone())
+        .replace_container(opt.clone(), Duration::from_secs(1))
+        .is_err());
+    assert_eq!(
+        std::fs::read_to_string(&log).unwrap(),
+        "rename web web-prev\nrm --force web\nrename web-prev web\n"
+    );
+    std::fs::remove_file(&log).unwrap();
+
+    // A container left over from a failed rollback is not touched.
+    base.env.insert("PREV".into(), "web-prev".into());
+    let err = Launcher::new(base)
+        .replace_container(opt, Duration::from_secs(1))
+        .unwrap_err();
+    assert!(err.to_string().contains("\"web-prev\""), "{}", err);
+    assert!(!log.exists());
+}
+
+#[test]
+fn test_check_ports() {
+    let script = "echo 'db 0.0.0.0:5432->5432/tcp, :::5432->5432/tcp'; \
+                  echo 'web 127.0.0.1:8000-8001->80-81/tcp'; \
+                  echo 'worker '";
+    let mut base = command_run::Command::with_args("sh", ["-c", script, "sh"]);
+    base.log_command = false;
+    let launcher = Launcher::new(base);
+    let check = |publish: &str| {
+        let mut opt = RunOpt::new("alpine");
+        opt.publish = vec![publish.parse().unwrap()];
+        match launcher.check_ports(&opt, true) {
+            Ok(()) => None,
+            Err(Error::PortConflict(conflict)) => Some(conflict),
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    };
+
+    let conflict = check("5432:5432").unwrap();
+    assert_eq!(conflict.container.as_deref(), Some("db"));
+    assert_eq!(
+        conflict.to_string(),
+        "host port 5432/tcp is already in use by container db"
+    );
+    assert_eq!(check("127.0.0.1:8001:80").unwrap().port, 8001);
+    assert_eq!(check("127.0.0.2:8001:80"), None);
+    assert_eq!(check("8001:80/udp"), None);
+
+    // A port bound by something other than a container.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let publish = format!("127.0.0.1:{}:80", port);
+    assert_eq!(check(&publish).unwrap().container, None);
+
+    // Nothing to check, so docker is not run.
+    let mut opt = RunOpt::new("alpine");
+    opt.publish = vec!["80".parse().unwrap(), "8000-9000:80".parse().unwrap()];
+    Launcher::from(BaseCommand::Docker)
+        .check_ports(&opt, true)
+        .unwrap();
+}
+
+#[test]
+fn test_host_port() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let web: ContainerRef = "web".parse().unwrap();
+    assert_eq!(
+        launcher
+            .port(&web, Some((80, Protocol::Udp)))
+            .command_line_lossy(),
+        "docker port web 80/udp"
+    );
+
+    let mut opt = RunOpt::new("nginx");
+    opt.publish = vec![PublishPorts::ephemeral(80)];
+    assert_eq!(
+        launcher.run(opt).command_line_lossy(),
+        "docker run --publish 80 nginx"
+    );
+
+    // Stand in for `docker port`.
+    let script = "echo 0.0.0.0:49153; echo '[::]:49153'";
+    let mut base = command_run::Command::with_args("sh", ["-c", script, "sh"]);
+    base.log_command = false;
+    assert_eq!(
+        Launcher::new(base)
+            .host_port(&web, 80, Protocol::Tcp)
+            .unwrap(),
+        49153
+    );
+}
+
+#[test]
+fn test_run_with_unique_name() {
+    let log = std::env::temp_dir()
+        .join(format!("docker-command-unique-{}", std::process::id()));
+    // Stand in for `docker run --name <name> alpine`: the first name
+    // is reported as in use.
+    let script = format!(
+        "echo $3 >> {0}; \
+         if [ $(wc -l < {0}) = 1 ]; then \
+             echo 'name \"'$3'\" is already in use' >&2; exit 125; \
+         fi; \
+         echo started",
+        log.display()
+    );
+    let mut base = command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+    base.log_command = false;
+    let (name, output) = Launcher::new(base)
+        .run_with_unique_name(RunOpt::new("alpine"), "test")
+        .unwrap();
+    assert_eq!(output.stdout_string_lossy(), "started\n");
+    let names = std::fs::read_to_string(&log).unwrap();
+    let names: Vec<_> = names.lines().collect();
+    assert_eq!(names.len(), 2);
+    assert_ne!(names[0], names[1]);
+    assert_eq!(names[1], name.as_str());
+    assert!(name.as_str().starts_with("test-"));
+    std::fs::remove_file(&log).unwrap();
+}
+
+#[test]
+fn test_temp_resources() {
+    let log = std::env::temp_dir()
+        .join(format!("docker-command-temp-{}", std::process::id()));
+    let script = format!("echo \"$@\" >> {}", log.display());
+    let mut base = command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+    base.log_command = false;
+    let launcher = Launcher::new(base);
+
+    let volume = TempVolume::new(&launcher).unwrap();
+    let volume_name = volume.name().to_string();
+    assert!(volume_name.starts_with("docker-command-tmp-"));
+    drop(volume);
+
+    let network =
+        TempNetwork::with_opt(&launcher, CreateNetworkOpt::new("test"))
+            .unwrap();
+    let network_name = network.name().to_string();
+    assert!(network_name.starts_with("test-"));
+    network.close().unwrap();
+
+    let kept = TempVolume::new(&launcher).unwrap().keep();
+
+    assert_eq!(
+        std::fs::read_to_string(&log).unwrap(),
+        format!(
+            "volume create {0}\nvolume rm {0}\nnetwork create {1}\n\
+             network rm {1}\nvolume create {2}\n",
+            volume_name, network_name, kept
+        )
+    );
+    std::fs::remove_file(&log).unwrap();
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_assert_cmd_eq() {
+    use docker_command::test_util;
+
+    let mut opt = RunOpt::new("alpine");
+    opt.pull = Some(PullPolicy::Always);
+    opt.args = vec!["--".into(), "--a=b".into()];
+    let mut cmd = Launcher::from(BaseCommand::Docker).run(opt);
+    cmd.program = "/usr/bin/docker".into();
+    assert_cmd_eq!(
+        cmd,
+        ["docker", "run", "--pull", "always", "alpine", "--", "--a=b"]
+    );
+    assert_cmd_eq!(
+        cmd,
+        ["docker", "run", "--pull=always", "alpine", "--", "--a=b"]
+    );
+    assert_eq!(
+        test_util::render(&cmd),
+        "docker run --pull always alpine -- --a=b"
+    );
+
+    let result = std::panic::catch_unwind(|| {
+        test_util::assert_cmd_eq(&cmd, &["docker", "run", "alpine"])
+    });
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn test_mock_launcher() {
+    let mock = MockLauncher::new(BaseCommand::SudoDocker);
+    mock.expect(
+        [
+            "network",
+            "ls",
+            "--filter",
+            "name=net",
+            "--format",
+            "{{.Name}}",
+        ],
+        MockResponse::success("net-2\n"),
+    )
+    .expect(["network", "create", "net"], MockResponse::default())
+    .expect(
+        ["pull", "alpine"],
+        MockResponse::failure(1, "manifest unknown"),
+    );
+
+    assert!(mock
+        .launcher()
+        .ensure_network(CreateNetworkOpt::new("net"))
+        .unwrap());
+    assert!(mock
+        .launcher()
+        .pull(PullOpt::new("alpine"))
+        .run_logged()
+        .is_err());
+    assert_eq!(mock.calls().len(), 3);
+    assert_eq!(mock.calls()[1], ["network", "create", "net"]);
+    mock.verify();
+
+    // Another process creates the volume between the check and the
+    // create.
+    let ls_volumes = [
+        "volume",
+        "ls",
+        "--filter",
+        "name=data",
+        "--format",
+        "{{.Name}}",
+    ];
+    let mock = MockLauncher::new(BaseCommand::Docker);
+    mock.expect(ls_volumes, MockResponse::success(""))
+        .expect(["volume", "create", "data"], MockResponse::failure(1, ""))
+        .expect(ls_volumes, MockResponse::success("data\n"));
+    assert!(!mock
+        .launcher()
+        .ensure_volume(CreateVolumeOpt::new("data"))
+        .unwrap());
+    mock.verify();
+
+    let mock = MockLauncher::new(BaseCommand::Docker);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _ = mock.launcher().image_exists(&"alpine".parse().unwrap());
+    }));
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn test_mock_stdin() {
+    let source = MockLauncher::new(BaseCommand::Docker);
+    let dest = MockLauncher::new(BaseCommand::Podman);
+    source.expect(["save", "alpine"], MockResponse::success("archive"));
+    dest.expect(["load"], MockResponse::success(""));
+    source
+        .launcher()
+        .copy_images(["alpine"], dest.launcher())
+        .unwrap();
+
+    let mock = MockLauncher::new(BaseCommand::Docker);
+    mock.expect(
+        ["exec", "--interactive", "web", "tee", "--", "/etc/app.conf"],
+        MockResponse::failure(1, ""),
+    );
+    assert!(mock
+        .launcher()
+        .write_file(&"web".parse().unwrap(), "/etc/app.conf", b"x=1")
+        .is_err());
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn test_build_and_run() {
+    let image = format!("sha256:{}", "b".repeat(64));
+    let container = "c".repeat(64);
+    let mock = MockLauncher::new(BaseCommand::Docker);
+    mock.expect(
+        ["build", "--quiet", "/ctx"],
+        MockResponse::success(format!("{}\n", image)),
+    )
+    .expect(
+        ["run", "--detach", image.as_str()],
+        MockResponse::success(format!("{}\n", container)),
+    );
+    // The local image ID is not rejected as being from docker.io.
+    let mut policy = StandardPolicy::default();
+    policy.allowed_registries = vec!["registry.example.com".into()];
+    let id = mock
+        .launcher()
+        .clone()
+        .with_policy(policy)
+        .build_and_run(BuildOpt::new("/ctx"), RunOpt::new("unused"))
+        .unwrap();
+    assert_eq!(id, container.parse().unwrap());
+    mock.verify();
+
+    let mock = MockLauncher::new(BaseCommand::Docker);
+    mock.expect(["build", "--quiet", "/ctx"], MockResponse::success(""));
+    assert!(matches!(
+        mock.launcher()
+            .build_and_run(BuildOpt::new("/ctx"), RunOpt::new("unused")),
+        Err(Error::UnexpectedOutput { .. })
+    ));
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn test_stop_or_kill() {
+    let running = |name| {
+        [
+            "inspect",
+            "--format",
+            "{{.State.Running}}",
+            "--type",
+            "container",
+            name,
+        ]
+    };
+    let mock = MockLauncher::new(BaseCommand::Docker);
+    mock.expect(
+        ["stop", "--time", "5", "web", "gone"],
+        MockResponse::success(""),
+    )
+    .expect(running("web"), MockResponse::success("false\n"))
+    .expect(
+        running("gone"),
+        MockResponse::failure(1, "Error: No such container: gone\n"),
+    );
+    let web: ContainerRef = "web".parse().unwrap();
+    let gone: ContainerRef = "gone".parse().unwrap();
+    assert_eq!(
+        mock.launcher()
+            .stop_or_kill(&[web.clone(), gone.clone()], Duration::from_secs(5))
+            .unwrap(),
+        [
+            (web.clone(), StopOutcome::Stopped),
+            (gone, StopOutcome::NotFound)
+        ]
+    );
+
+    // Other failures are errors, not a missing container.
+    let mock = MockLauncher::new(BaseCommand::Docker);
+    mock.expect(["stop", "--time", "5", "web"], MockResponse::success(""))
+        .expect(
+            running("web"),
+            MockResponse::failure(
+                1,
+                "permission denied while trying to connect to the Docker \
+                 daemon socket\n",
+            ),
+        );
+    assert!(mock
+        .launcher()
+        .stop_or_kill(&[web], Duration::from_secs(5))
+        .is_err());
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn test_image_exists() {
+    let args = ["inspect", "--format", "{{.Created}}", "--type", "image"];
+    let mock = MockLauncher::new(BaseCommand::Docker);
+    mock.expect(
+        args.iter().chain(&["alpine"]),
+        MockResponse::success("2024-01-02T03:04:05Z\n"),
+    )
+    .expect(
+        args.iter().chain(&["missing"]),
+        MockResponse::failure(1, "Error: No such image: missing\n"),
+    )
+    .expect(
+        args.iter().chain(&["other"]),
+        MockResponse::failure(
+            1,
+            "Cannot connect to the Docker daemon at unix:///var/run/docker.sock. \
+             Is the docker daemon running?\n",
+        ),
+    );
+    let launcher = mock.launcher();
+    assert!(launcher.image_exists(&"alpine".into()).unwrap());
+    assert!(!launcher.image_exists(&"missing".into()).unwrap());
+    assert!(launcher.image_exists(&"other".into()).is_err());
+}
+
+#[test]
+fn test_args() {
+    let mut opt = BuildOpt::new("/context");
+    opt.pull = Some(PullPolicy::Missing);
+    assert_eq!(
+        args::build_args(&opt, BaseCommand::Docker),
+        ["build", "--pull=false", "/context"]
+    );
+    assert_eq!(
+        args::build_args(&opt, BaseCommand::Podman),
+        ["build", "--pull=missing", "/context"]
+    );
+
+    let mut opt = RunOpt::new("alpine");
+    opt.name = Some("web".into());
+    let mut global_opts = GlobalOpts::default();
+    global_opts.debug = true;
+    let launcher =
+        Launcher::from(BaseCommand::Docker).with_global_opts(global_opts);
+    assert_eq!(
+        launcher.run(opt.clone()).args[1..],
+        args::run_args(&opt, BaseCommand::Docker)
+    );
+    opt.volumes = vec![Volume::bind("/data", "/data")];
+    opt.read_only = true;
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman).run(opt.clone()).args,
+        args::run_args(&opt, BaseCommand::Podman)
+    );
+    assert_eq!(
+        args::stop_args(&StopOpt::new(["web"
+            .parse::<ContainerName>()
+            .unwrap()])),
+        ["stop", "web"]
+    );
+
+    let web = "web".parse::<ContainerRef>().unwrap();
+    assert_eq!(
+        args::port_args(&web, Some((80, Protocol::Tcp))),
+        ["port", "web", "80/tcp"]
+    );
+    assert_eq!(
+        args::rename_args(&web, &"api".parse().unwrap()),
+        ["rename", "web", "api"]
+    );
+    let containers = [web, "db".parse().unwrap()];
+    assert_eq!(
+        args::wait_args(&containers),
+        Launcher::from(BaseCommand::Docker).wait(&containers).args
+    );
+    assert_eq!(args::remove_network_args("net"), ["network", "rm", "net"]);
+    assert_eq!(
+        args::system_reset_args(true),
+        ["system", "reset", "--force"]
+    );
+    assert_eq!(
+        args::unshare_args(&command_run::Command::with_args(
+            "chown",
+            ["-R", "0:0", "/d"]
+        )),
+        ["unshare", "chown", "-R", "0:0", "/d"]
+    );
+}
+
+#[test]
+fn test_daemon_host() {
+    let pipe = DaemonHost::NamedPipe("docker_engine".into());
+    assert_eq!(pipe.arg(), "npipe:////./pipe/docker_engine");
+    assert_eq!(
+        "npipe:////./pipe/docker_engine".parse::<DaemonHost>(),
+        Ok(pipe.clone())
+    );
+    assert_eq!(
+        r"npipe://\\.\pipe\docker_engine".parse::<DaemonHost>(),
+        Ok(pipe.clone())
+    );
+    assert_eq!(
+        "ssh://me@host".parse::<DaemonHost>(),
+        Ok(DaemonHost::Ssh("me@host".into()))
+    );
+    assert!("npipe://docker_engine".parse::<DaemonHost>().is_err());
+    assert!("http://host".parse::<DaemonHost>().is_err());
+    assert!("/var/run/docker.sock".parse::<DaemonHost>().is_err());
+
+    let unix = DaemonHost::Unix("/run/user/1000/podman/podman.sock".into());
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .with_host(pipe)
+            .ps(PsOpt::default())
+            .command_line_lossy(),
+        "docker --host npipe:////./pipe/docker_engine ps"
+    );
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman)
+            .with_host(unix)
+            .ps(PsOpt::default())
+            .command_line_lossy(),
+        "podman --url=unix:///run/user/1000/podman/podman.sock ps"
+    );
+
+    // Rootless podman socket under XDG_RUNTIME_DIR.
+    let dir = std::env::temp_dir()
+        .join(format!("docker-command-host-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("podman")).unwrap();
+    let socket = dir.join("podman/podman.sock");
+    let _ = std::fs::remove_file(&socket);
+    let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+    std::env::set_var("XDG_RUNTIME_DIR", &dir);
+    assert_eq!(DaemonHost::detect_podman(), Some(DaemonHost::Unix(socket)));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_windows_paths() {
+    let path = Path::new(r"D:\work\my app");
+    assert_eq!(
+        WindowsPathStyle::Posix.translate(path),
+        Path::new("/d/work/my app")
+    );
+    assert_eq!(
+        WindowsPathStyle::Wsl("/run/desktop/mnt/host/".into())
+            .translate(Path::new("c:/src")),
+        Path::new("/run/desktop/mnt/host/c/src")
+    );
+    assert_eq!(
+        WindowsPathStyle::DoubleSlash.translate(Path::new(r"C:\")),
+        Path::new("//c")
+    );
+    for unchanged in ["/src", "src", "C:src", "cache"] {
+        assert_eq!(
+            WindowsPathStyle::Posix.translate(Path::new(unchanged)),
+            Path::new(unchanged)
+        );
+    }
+
+    let launcher = Launcher::from(BaseCommand::Docker)
+        .with_windows_paths(WindowsPathStyle::DoubleSlash);
+    let mut opt = RunOpt::new("alpine");
+    opt.volumes = vec![
+        r"C:\src:/src:ro".parse().unwrap(),
+        "cache:/cache".parse().unwrap(),
+    ];
+    opt.mounts = vec![
+        r"type=bind,src=C:\data,dst=/data".parse().unwrap(),
+        "type=volume,src=logs,dst=/logs".parse().unwrap(),
+    ];
+    assert_eq!(
+        launcher.run(opt).command_line_lossy(),
+        "docker run --mount type=bind,source=//c/data,target=/data \
+         --mount type=volume,source=logs,target=/logs \
+         --volume //c/src:/src:ro --volume cache:/cache alpine"
+    );
+}
+
+#[test]
+fn test_relative_paths() {
+    let mut opt = RunOpt::new("alpine");
+    opt.volumes = vec![
+        Volume::bind("src", "/src"),
+        Volume::bind("/abs", "/abs"),
+        Volume::bind(r"C:\src", "/win"),
+        "cache:/cache".parse().unwrap(),
+    ];
+    opt.mounts = vec!["type=bind,src=data,dst=/data".parse().unwrap()];
+
+    // Keep is the default.
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let mut kept = opt.clone();
+    launcher.resolve_run_paths(&mut kept).unwrap();
+    assert_eq!(kept, opt);
+
+    let launcher =
+        launcher.with_relative_paths(RelativePaths::Join("/work".into()));
+    let mut joined = opt.clone();
+    launcher.resolve_run_paths(&mut joined).unwrap();
+    assert_eq!(
+        joined.volumes[0].src,
+        VolumeSource::HostPath("/work/src".into())
+    );
+    assert_eq!(joined.volumes[1..], opt.volumes[1..]);
+    assert_eq!(joined.mounts[0].source, Some("/work/data".into()));
+
+    let launcher = launcher.with_relative_paths(RelativePaths::Reject);
+    let err = launcher.resolve_run_paths(&mut opt.clone()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid volume source \"src\": relative paths are not allowed"
+    );
+    assert!(launcher.try_run(opt.clone()).is_err());
+    let mut base = command_run::Command::with_args("sh", ["-c", "exit 3"]);
+    base.log_command = false;
+    let err = Launcher::new(base)
+        .with_relative_paths(RelativePaths::Reject)
+        .run_with_unique_name(opt.clone(), "test")
+        .unwrap_err();
+    assert!(matches!(err, Error::Invalid(_)));
+
+    let dir = std::env::temp_dir()
+        .join(format!("docker-command-relative-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("app")).unwrap();
+    let dir = dir.canonicalize().unwrap();
+    let launcher =
+        launcher.with_relative_paths(RelativePaths::Canonicalize(dir.clone()));
+    let mut build = BuildOpt::new("app/.");
+    launcher.resolve_build_paths(&mut build).unwrap();
+    assert_eq!(build.context, dir.join("app"));
+    build.dockerfile = Some("missing/Dockerfile".into());
+    assert!(launcher.resolve_build_paths(&mut build).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_interactivity() {
+    let mut opt = RunOpt::new("alpine");
+    opt.set_interactivity(Interactivity::Attached);
+    assert!(opt.init && opt.interactive && opt.tty);
+    opt.set_interactivity(Interactivity::None);
+    assert!(!opt.interactive && !opt.tty);
+    // Already set, so not turned off.
+    assert!(opt.init);
+
+    let mut opt = ExecOpt::new("web".parse().unwrap(), "sh");
+    opt.set_interactivity(Interactivity::Attached);
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .exec(opt)
+            .command_line_lossy(),
+        "docker exec --interactive --tty web sh"
+    );
+
+    // The test harness does not give a TTY on stdout, whatever stdin
+    // is.
+    let mut opt = RunOpt::new("alpine");
+    opt.set_interactivity(Interactivity::Auto);
+    assert!(!opt.tty);
+}
+
+#[test]
+fn test_discovery() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir()
+        .join(format!("docker-command-discovery-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let docker = dir.join("docker");
+    std::fs::write(
+        &docker,
+        "#!/bin/sh\necho 'Docker version 24.0.7, build afdd53b'\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&docker, std::fs::Permissions::from_mode(0o755))
+        .unwrap();
+
+    let paths =
+        std::env::join_paths(["/nonexistent".as_ref(), dir.as_path()]).unwrap();
+    assert_eq!(
+        discovery::find_exe_in("docker", &paths),
+        Some(docker.clone())
+    );
+    assert_eq!(discovery::find_exe_in("podman", &paths), None);
+
+    let engines = discovery::detect_engines_in(&paths);
+    assert_eq!(engines.len(), 1);
+    assert_eq!(engines[0].engine, Engine::Docker);
+    assert_eq!(engines[0].path, docker);
+    assert_eq!(engines[0].version.as_deref(), Some("24.0.7"));
+    assert!(engines[0].launcher().is_docker());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_auto_with() {
+    let opt = AutoOpt::new();
+    assert_eq!(opt.preference, [Engine::Podman, Engine::Docker]);
+    assert_eq!(opt.sudo, SudoPolicy::Auto);
+    assert_eq!(opt.docker_group, "docker");
+
+    let mut opt = AutoOpt::new();
+    opt.preference = vec![];
+    assert!(Launcher::auto_with(&opt).is_none());
+    opt.preference = vec![Engine::Auto];
+    assert!(Launcher::auto_with(&opt).is_none());
+
+    // Only check the base command if an engine is installed.
+    opt.preference = vec![Engine::Docker];
+    opt.sudo = SudoPolicy::Always;
+    if let Some(launcher) = Launcher::auto_with(&opt) {
+        assert_eq!(launcher.base_command().command_line_lossy(), "sudo docker");
+    }
+}
+
+#[test]
+fn test_capability() {
+    assert_eq!("24.0.7".parse(), Ok(Version::new(24, 0, 7)));
+    assert_eq!("v1.7".parse(), Ok(Version::new(1, 7, 0)));
+    assert_eq!("4.9.3-dev".parse(), Ok(Version::new(4, 9, 3)));
+    assert!("dev".parse::<Version>().is_err());
+    assert_eq!(Version::new(25, 0, 1).to_string(), "25.0.1");
+
+    assert_eq!(
+        Capability::InitPath.min_version(Backend::Podman),
+        Some(Version::new(1, 0, 0))
+    );
+    assert_eq!(Capability::InitPath.min_version(Backend::Docker), None);
+    for cap in Capability::ALL {
+        assert!(!cap.name().is_empty());
+    }
+
+    let docker_24 = Version::new(24, 0, 7);
+    let mut opt = RunOpt::new("alpine");
+    opt.networks = vec![
+        NetworkAttachment::new("front"),
+        NetworkAttachment::new("back"),
+    ];
+    assert_eq!(
+        Capability::used_by_run(&opt),
+        [Capability::MultipleNetworks]
+    );
+    assert_eq!(
+        opt.validate_for(Backend::Docker, docker_24)
+            .unwrap_err()
+            .to_string(),
+        "invalid option \"multiple networks\": requires docker 25.0.0 or newer"
+    );
+    assert!(opt
+        .validate_for(Backend::Podman, Version::new(4, 0, 0))
+        .is_ok());
+
+    let mut opt = BuildOpt::new(".");
+    opt.sbom = Some("true".into());
+    assert!(opt.validate_for(Backend::Docker, docker_24).is_ok());
+    assert_eq!(
+        opt.validate_for(Backend::Podman, Version::new(5, 0, 0))
+            .unwrap_err()
+            .to_string(),
+        "invalid option \"sbom/provenance\": not supported by podman"
+    );
+}
+
+#[test]
+fn test_compose_cli() {
+    let mut opt = ComposeOpt::default();
+    opt.ansi = Some(Ansi::Never);
+    opt.files = vec![new_path("compose.yaml")];
+    opt.project_name = Some("app".into());
+    let mut up = ComposeUpOpt::new();
+    up.wait = true;
+    up.services = vec!["web".into()];
+    let mut down = ComposeDownOpt::new();
+    down.volumes = true;
+
+    let mut global_opts = GlobalOpts::default();
+    global_opts.context = Some("remote".into());
+    let docker =
+        Launcher::from(BaseCommand::SudoDocker).with_global_opts(global_opts);
+    let plugin = docker.compose(ComposeFlavor::Plugin, opt.clone());
+    assert_eq!(plugin.flavor(), ComposeFlavor::Plugin);
+    assert_eq!(
+        plugin.up(up.clone()).command_line_lossy(),
+        "sudo docker --context remote compose --ansi never \
+         --file compose.yaml --project-name app up --wait web"
+    );
+    assert_eq!(
+        plugin.down(down.clone()).command_line_lossy(),
+        "sudo docker --context remote compose --ansi never \
+         --file compose.yaml --project-name app down --volumes"
+    );
+
+    let standalone = docker.compose(ComposeFlavor::Standalone, opt.clone());
+    assert_eq!(
+        standalone.up(up.clone()).command_line_lossy(),
+        "sudo docker-compose --no-ansi --file compose.yaml \
+         --project-name app up web"
+    );
+
+    let podman =
+        Launcher::from(BaseCommand::Podman).compose(ComposeFlavor::Podman, opt);
+    assert_eq!(
+        podman.up(up).command_line_lossy(),
+        "podman-compose --file compose.yaml --project-name app up web"
+    );
+
+    // Stand in for a docker with the compose plugin.
+    let mut base = command_run::Command::with_args(
+        "sh",
+        ["-c", "test \"$1 $2\" = 'compose version'", "sh"],
+    );
+    base.log_command = false;
+    let compose = Launcher::new(base)
+        .compose_auto(ComposeOpt::default())
+        .unwrap();
+    assert_eq!(compose.flavor(), ComposeFlavor::Plugin);
+}
+
+#[cfg(feature = "compose")]
+#[test]
+fn test_compose_file() {
+    use docker_command::compose::{DependsOn, File, Service, StringOrList};
+
+    let yaml = r#"
+name: shop
+x-common: &common
+  restart: unless-stopped
+services:
+  db:
+    image: postgres:16
+    environment:
+      POSTGRES_PASSWORD: secret
+      PGPORT: 5433
+    healthcheck:
+      test: ["CMD", "pg_isready", "-U", "postgres"]
+      interval: 5s
+      retries: 3
+    volumes:
+      - data:/var/lib/postgresql/data
+  web:
+    image: shop/web:1.2
+    command: serve --title "My Shop"
+    depends_on:
+      db:
+        condition: service_healthy
+    ports:
+      - "8080:80"
+      - target: 443
+        published: 8443
+        host_ip: 127.0.0.1
+    networks:
+      front:
+        aliases: [shop]
+    restart: on-failure:3
+    user: "1000:web"
+    deploy:
+      replicas: 2
+  loop-a:
+    image: alpine
+    depends_on: [loop-b]
+  loop-b:
+    image: alpine
+    depends_on: [loop-a]
+networks:
+  front:
+volumes:
+  data: {}
+"#;
+    let file = File::from_yaml(yaml).unwrap();
+    assert_eq!(file.name.as_deref(), Some("shop"));
+    assert!(file.other.contains_key("x-common"));
+    let web = &file.services["web"];
+    assert!(web.other.contains_key("deploy"));
+    assert!(matches!(&web.depends_on, Some(DependsOn::Map(_))));
+
+    // Writing and reading back keeps everything.
+    assert_eq!(File::from_yaml(&file.to_yaml().unwrap()).unwrap(), file);
+
+    let opts = file.run_opts(&["web"]).unwrap();
+    assert_eq!(opts.len(), 2);
+    let (name, db) = &opts[0];
+    assert_eq!(name, "db");
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(db.clone())
+            .command_line_lossy(),
+        "docker run --env PGPORT=5433 --env POSTGRES_PASSWORD=secret \
+         --volume data:/var/lib/postgresql/data \
+         '--health-cmd=pg_isready -U postgres' --health-interval=5s \
+         --health-retries=3 postgres:16"
+    );
+    let (name, web) = &opts[1];
+    assert_eq!(name, "web");
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .run(web.clone())
+            .command_line_lossy(),
+        "docker run --network name=front,alias=shop --publish 8080:80 \
+         --publish 127.0.0.1:8443:443 --restart on-failure:3 \
+         --user 1000:web shop/web:1.2 serve --title 'My Shop'"
+    );
+
+    let err = file.run_opts(&["loop-a"]).unwrap_err();
+    assert_eq!(err.reason, "dependency cycle");
+    assert!(file.run_opts(&["missing"]).is_err());
+
+    // Conversion from a project uses the same service definitions.
+    let mut project = ComposeProject::default();
+    let mut opt = RunOpt::new("nginx:1");
+    opt.args = vec!["nginx".into(), "-g".into()];
+    project.add_service("web", opt.clone());
+    project.networks.push("backend".into());
+    let file = File::from(&project);
+    assert_eq!(
+        file.services["web"].command,
+        Some(StringOrList::List(vec!["nginx".into(), "-g".into()]))
+    );
+    assert_eq!(file.services["web"], Service::from_run_opt(&opt));
+    // The first word of the command becomes the command.
+    let (_, web) = &file.run_opts(&[]).unwrap()[0];
+    assert_eq!(web.command, Some(new_path("nginx")));
+    assert_eq!(web.args, ["-g"]);
+}
+
+#[test]
+fn test_compose_config() {
+    let mut opt = ComposeConfigOpt::new();
+    opt.format = Some("json".into());
+    opt.no_interpolate = true;
+    opt.quiet = true;
+    opt.resolve_image_digests = true;
+    opt.services = true;
+
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let plugin = launcher.compose(ComposeFlavor::Plugin, ComposeOpt::default());
+    assert_eq!(
+        plugin.config(opt.clone()).command_line_lossy(),
+        "docker compose config --format json --no-interpolate --quiet \
+         --resolve-image-digests --services"
+    );
+    let standalone =
+        launcher.compose(ComposeFlavor::Standalone, ComposeOpt::default());
+    assert_eq!(
+        standalone.config(opt.clone()).command_line_lossy(),
+        "docker-compose config --no-interpolate --quiet \
+         --resolve-image-digests --services"
+    );
+    let podman = Launcher::from(BaseCommand::Podman)
+        .compose(ComposeFlavor::Podman, ComposeOpt::default());
+    assert_eq!(
+        podman.config(opt).command_line_lossy(),
+        "podman-compose config --quiet --services"
+    );
+}
+
+#[test]
+fn test_interpolate() {
+    let mut vars = Interpolator::new();
+    vars.set("TAG", "1.2");
+    vars.set("EMPTY", "");
+    vars.parse_env_file(
+        "# comment\n\
+         TAG=ignored\n\
+         export NAME = web # trailing\n\
+         GREETING=\"hello world\"\n\
+         HASH='a # b'\n",
+    )
+    .unwrap();
+    assert_eq!(vars.get("TAG"), Some("1.2"));
+    assert_eq!(vars.get("NAME"), Some("web"));
+    assert_eq!(vars.get("GREETING"), Some("hello world"));
+    assert_eq!(vars.get("HASH"), Some("a # b"));
+    assert!(vars.parse_env_file("no equals sign").is_err());
+    assert!(vars.parse_env_file("KEY='unterminated").is_err());
+
+    let check = |input: &str, expected: &str| {
+        assert_eq!(vars.interpolate(input).unwrap(), expected, "{}", input);
+    };
+    check("$NAME:$TAG", "web:1.2");
+    check("${NAME}_1", "web_1");
+    check("$UNSET.", ".");
+    check("$$NAME $ 5", "$NAME $ 5");
+    check("${EMPTY:-a}${EMPTY-b}${UNSET-c}", "ac");
+    check("${UNSET:-${NAME:-x}}", "web");
+    check("${EMPTY:+a}${EMPTY+b}${NAME:+${TAG}}", "b1.2");
+    check("${EMPTY?}", "");
+
+    let err = vars.interpolate("${EMPTY:?must be set}").unwrap_err();
+    assert_eq!(err.reason, "EMPTY: must be set");
+    let err = vars.interpolate("${UNSET?}").unwrap_err();
+    assert_eq!(err.reason, "UNSET is not set");
+    assert!(vars.interpolate("${NAME").is_err());
+    assert!(vars.interpolate("${NAME!}").is_err());
+    assert!(vars.interpolate("${}").is_err());
+}
+
+#[cfg(feature = "compose")]
+#[test]
+fn test_compose_interpolated() {
+    use docker_command::compose::File;
+
+    let mut vars = Interpolator::new();
+    vars.set("TAG", "1.2");
+    let file = File::from_yaml_interpolated(
+        "services:\n  web:\n    image: shop/web:${TAG}\n",
+        &vars,
+    )
+    .unwrap();
+    assert_eq!(file.services["web"].image.as_deref(), Some("shop/web:1.2"));
+    assert!(File::from_yaml_interpolated("image: ${REQUIRED?}", &vars).is_err());
+
+    // Stand in for `docker compose config`.
+    let mut base = command_run::Command::with_args(
+        "sh",
+        [
+            "-c",
+            "test \"$1\" = compose && printf 'services:\\n  web:\\n    image: nginx\\n'",
+            "sh",
+        ],
+    );
+    base.log_command = false;
+    let compose = Launcher::new(base)
+        .compose(ComposeFlavor::Plugin, ComposeOpt::default());
+    let file = compose.load_config().unwrap();
+    assert_eq!(file.services["web"].image.as_deref(), Some("nginx"));
+
+    let mut base =
+        command_run::Command::with_args("sh", ["-c", "echo '['", "sh"]);
+    base.log_command = false;
+    let compose = Launcher::new(base)
+        .compose(ComposeFlavor::Plugin, ComposeOpt::default());
+    assert!(matches!(
+        compose.load_config(),
+        Err(Error::UnexpectedOutput { .. })
+    ));
+}
+
+#[test]
+fn test_compose_logs() {
+    let mut opt = ComposeLogsOpt::new();
+    opt.no_color = true;
+    opt.since = Some("10m".into());
+    opt.tail = Some(20);
+    opt.timestamps = true;
+    opt.services = vec!["web".into()];
+    let compose = Launcher::from(BaseCommand::Docker)
+        .compose(ComposeFlavor::Plugin, ComposeOpt::default());
+    assert_eq!(
+        compose.logs(opt).command_line_lossy(),
+        "docker compose logs --no-color --since 10m --tail 20 --timestamps web"
+    );
+
+    let line = ServiceLogParser::parse_line(
+        "\x1b[32mapi_worker_2  |\x1b[0m \x1b[1mjob 7\x1b[0m | done\r",
+    )
+    .unwrap();
+    assert_eq!(line.service, "api_worker");
+    assert_eq!(line.container, "api_worker_2");
+    assert_eq!(line.message, "job 7 | done");
+    assert_eq!(ServiceLogParser::parse_line("db |").unwrap().service, "db");
+    assert_eq!(
+        ServiceLogParser::parse_line("v2 | x").unwrap().service,
+        "v2"
+    );
+    assert!(ServiceLogParser::parse_line("Attaching to web-1").is_none());
+    assert!(ServiceLogParser::parse_line("a b | c").is_none());
+
+    let mut lines = Vec::new();
+    let mut parser = ServiceLogParser::new().with_project("shop");
+    parser.feed(
+        b"shop_web_1 | one\nshop-1 | two\nweb-1 | three\n",
+        &mut |line: &ServiceLogLine| lines.push(line.service.clone()),
+    );
+    assert_eq!(lines, ["web", "shop", "web"]);
+
+    // Stand in for `docker compose logs --follow`, with a line split
+    // across writes and no trailing newline.
+    let mut base = command_run::Command::with_args(
+        "sh",
+        [
+            "-c",
+            "test \"$1 $2\" = 'logs --follow' || exit 1
+             printf 'web-1  | one\\ndb-1   | re'
+             printf 'ady\\nweb-2  | two\\ncache-1 | hit'",
+            "sh",
+        ],
+    );
+    base.log_command = false;
+    let compose = Launcher::new(base)
+        .compose(ComposeFlavor::Standalone, ComposeOpt::default());
+
+    let web = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let other = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (web2, other2) = (web.clone(), other.clone());
+    let mut router = ServiceLogRouter::new()
+        .with_service("web", move |line| {
+            web2.lock().unwrap().push(line.message.clone())
+        })
+        .with_fallback(move |line| {
+            other2.lock().unwrap().push(line.message.clone())
+        });
+    compose
+        .follow_logs(ComposeLogsOpt::new(), &mut router)
+        .unwrap();
+    assert_eq!(*web.lock().unwrap(), ["one", "two"]);
+    assert_eq!(*other.lock().unwrap(), ["ready", "hit"]);
+
+    // Compose v1 prefixes lines with the project.
+    let mut base = command_run::Command::with_args(
+        "sh",
+        ["-c", "printf 'shop_web_1 | one\\nshop_db_1 | two\\n'"],
+    );
+    base.log_command = false;
+    let mut opt = ComposeOpt::default();
+    opt.project_name = Some("shop".into());
+    let compose = Launcher::new(base).compose(ComposeFlavor::Standalone, opt);
+    let mut services = Vec::new();
+    compose
+        .follow_logs(ComposeLogsOpt::new(), &mut |line: &ServiceLogLine| {
+            services.push(line.service.clone())
+        })
+        .unwrap();
+    assert_eq!(services, ["web", "db"]);
+}
+
+#[test]
+fn test_stats() {
+    let mut opt = StatsOpt::new(["web".parse::<ContainerRef>().unwrap()]);
+    opt.all = true;
+    opt.format = Some("json".into());
+    opt.no_stream = true;
+    opt.no_trunc = true;
+    assert_eq!(
+        Launcher::from(BaseCommand::Docker)
+            .stats(opt.clone())
+            .command_line_lossy(),
+        "docker stats --all --format json --no-stream --no-trunc web"
+    );
+    assert_eq!(
+        args::stats_args(&opt),
+        [
+            "stats",
+            "--all",
+            "--format",
+            "json",
+            "--no-stream",
+            "--no-trunc",
+            "web"
+        ]
+    );
+
+    // Docker, including a container that is not running.
+    let stats = ContainerStats::parse_json(
+        r#"{"BlockIO":"12.3MB / 4.1kB","CPUPerc":"105.20%","ID":"0123456789ab","MemPerc":"1.02%","MemUsage":"1.5GiB / 16GiB","Name":"web","NetIO":"2.5MB / 1.1MB","PIDs":"12"}
+{"BlockIO":"--","CPUPerc":"--","ID":"ba9876543210","MemPerc":"--","MemUsage":"-- / --","Name":"old","NetIO":"--","PIDs":"--"}
+"#,
+    )
+    .unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].cpu_percent, 105.2);
+    assert_eq!(stats[0].mem_usage, 1536 * 1024 * 1024);
+    assert_eq!(stats[0].mem_limit, 16 * 1024 * 1024 * 1024);
+    assert_eq!(stats[0].mem_percent, 1.02);
+    assert_eq!((stats[0].net_rx, stats[0].net_tx), (2_500_000, 1_100_000));
+    assert_eq!(
+        (stats[0].block_read, stats[0].block_write),
+        (12_300_000, 4100)
+    );
+    assert_eq!(stats[0].pids, 12);
+    assert_eq!(stats[1].name, "old");
+    assert_eq!(stats[1].cpu_percent, 0.0);
+    assert_eq!(stats[1].mem_usage, 0);
+
+    // Podman.
+    let stats = ContainerStats::parse_json(
+        r#"[{"id":"e1a2","name":"db","cpu_time":"1.2s","cpu_percent":"0.50%","avg_cpu":"0.40%","mem_usage":"25.1MB / 8.2GB","mem_percent":"0.31%","net_io":"796B / 1.37kB","block_io":"0B / 0B","pids":"4"}]"#,
+    )
+    .unwrap();
+    assert_eq!(stats[0].id, "e1a2");
+    assert_eq!(stats[0].mem_usage, 25_100_000);
+    assert_eq!(stats[0].net_tx, 1370);
+    assert_eq!(stats[0].pids, 4);
+
+    let err = ContainerStats::parse_json(r#"{"Name":"web"}"#).unwrap_err();
+    assert_eq!(err.reason, "missing MemUsage");
+    assert!(ContainerStats::parse_json(
+        r#"{"BlockIO":"0B / 0B","CPUPerc":"x%","ID":"a","MemPerc":"0%","MemUsage":"1MiB / 2MiB","Name":"web","NetIO":"0B / 0B","PIDs":"1"}"#
+    )
+    .is_err());
+
+    // Stand in for `docker stats`.
+    let mut base = command_run::Command::with_args(
+        "sh",
+        [
+            "-c",
+            r#"test "$*" = "stats --format {{json .}} --no-stream web" || exit 1
+               echo '{"BlockIO":"0B / 0B","CPUPerc":"2.00%","ID":"a","MemPerc":"50.00%","MemUsage":"1MiB / 2MiB","Name":"web","NetIO":"0B / 0B","PIDs":"1"}'"#,
+            "sh",
+        ],
+    );
+    base.log_command = false;
+    let launcher = Launcher::new(base);
+    let web: ContainerRef = "web".parse().unwrap();
+    let stats = launcher
+        .container_stats(std::slice::from_ref(&web))
+        .unwrap();
+    assert_eq!(stats[0].mem_limit, 2 * 1024 * 1024);
+
+    let token = CancelToken::new();
+    let mut polls = 0;
+    launcher
+        .watch_stats(
+            &[web],
+            Duration::from_millis(1),
+            &token,
+            &mut |stats: &[ContainerStats]| {
+                assert_eq!(stats[0].cpu_percent, 2.0);
+                polls += 1;
+                if polls == 3 {
+                    token.cancel();
+                }
+            },
+        )
+        .unwrap();
+    assert_eq!(polls, 3);
+}
+
+#[cfg(feature = "prometheus")]
+#[test]
+fn test_prometheus() {
+    use docker_command::prometheus::{MetricKind, StatsExporter};
+
+    let stats = ContainerStats::parse_json(
+        r#"[{"id":"e1a2","name":"db \"main\"","cpu_percent":"0.50%","mem_usage":"25MB / 8GB","mem_percent":"0.31%","net_io":"796B / 1.37kB","block_io":"0B / 4kB","pids":"4"},
+            {"id":"f3b4","name":"web","cpu_percent":"12%","mem_usage":"1GB / 8GB","mem_percent":"12.5%","net_io":"0B / 0B","block_io":"0B / 0B","pids":"2"}]"#,
+    )
+    .unwrap();
+    let exporter = StatsExporter::new().with_prefix("app");
+    assert_eq!(exporter.render(), "");
+
+    // Clones share the stats.
+    exporter.clone().update(&stats);
+    let text = exporter.render();
+    assert!(text.starts_with(
+        "# HELP app_cpu_percent CPU usage, where 100 is one core.\n\
+         # TYPE app_cpu_percent gauge\n\
+         app_cpu_percent{id=\"e1a2\",name=\"db \\\"main\\\"\"} 0.5\n\
+         app_cpu_percent{id=\"f3b4\",name=\"web\"} 12\n\
+         # HELP app_memory_usage_bytes Memory used.\n"
+    ));
+    assert!(text.contains(
+        "# TYPE app_network_transmit_bytes_total counter\n\
+         app_network_transmit_bytes_total{id=\"e1a2\",name=\"db \\\"main\\\"\"} 1370\n"
+    ));
+    assert_eq!(text.matches("# TYPE").count(), 9);
+
+    let mut samples = Vec::new();
+    exporter.for_each_sample(&mut |sample| samples.push(sample.clone()));
+    assert_eq!(samples.len(), 18);
+    let pids = samples.iter().find(|s| s.name == "app_pids").unwrap();
+    assert_eq!(pids.kind, MetricKind::Gauge);
+    assert_eq!(pids.value, 4.0);
+
+    exporter.update(&stats[1..]);
+    assert!(!exporter.render().contains("e1a2"));
+}
+
+#[test]
+fn test_poll_health() {
+    let count = std::env::temp_dir()
+        .join(format!("docker-command-health-{}", std::process::id()));
+    // Stand in for `docker inspect`: print `starting` for the first
+    // two checks and $STATUS after that.
+    let script = format!(
+        "echo x >> {0}
+         if [ $(wc -l < {0}) -le 2 ]; then echo starting; else echo $STATUS; fi",
+        count.display()
+    );
+    let mut base = command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+    base.log_command = false;
+    let db: ContainerRef = "db".parse().unwrap();
+    let poll = |status: &str, timeout: Duration| {
+        let _ = std::fs::remove_file(&count);
+        let mut base = base.clone();
+        base.env.insert("STATUS".into(), status.into());
+        Launcher::new(base)
+            .poll_health(&db, timeout, Duration::from_millis(1))
+            .unwrap()
+    };
+    let long = Duration::from_secs(10);
+
+    assert_eq!(poll("healthy", long), HealthOutcome::Healthy);
+    assert_eq!(std::fs::read_to_string(&count).unwrap().len(), 6);
+    assert!(poll("running", long).is_ready());
+    assert_eq!(poll("unhealthy", long), HealthOutcome::Unhealthy);
+    assert_eq!(
+        poll("exited", long),
+        HealthOutcome::Stopped("exited".into())
+    );
+    assert_eq!(
+        poll("healthy", Duration::ZERO),
+        HealthOutcome::TimedOut("starting".into())
+    );
+    assert!(!HealthOutcome::Unhealthy.is_ready());
+
+    let _ = std::fs::remove_file(&count);
+    let mut base = base.clone();
+    base.env.insert("STATUS".into(), "dead".into());
+    let err = Launcher::new(base.clone())
+        .wait_healthy(&db, long)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Unhealthy { ref status, .. } if status == "dead"
+    ));
+    let _ = std::fs::remove_file(&count);
+    let err = Launcher::new(base)
+        .wait_healthy(&db, Duration::ZERO)
+        .unwrap_err();
+    assert!(matches!(err, Error::TimedOut { .. }));
+    std::fs::remove_file(&count).unwrap();
+}
+
+#[test]
+fn test_probe() {
+    let launcher = Launcher::from(BaseCommand::Docker);
+    let web: ContainerRef = "web".parse().unwrap();
+    assert_eq!(
+        launcher.probe_command(&web, &Probe::tcp(5432)).args[4],
+        "if command -v nc >/dev/null 2>&1; \
+         then nc -z 127.0.0.1 5432; \
+         else bash -c 'exec 3<>/dev/tcp/127.0.0.1/5432'; fi"
+    );
+    let probe = Probe::http(8080, "/health?full=1");
+    assert_eq!(
+        probe.kind,
+        ProbeKind::Http {
+            port: 8080,
+            path: "/health?full=1".into()
+        }
+    );
+    let cmd = launcher.probe_command(&web, &probe);
+    assert_eq!(cmd.args[..4], ["exec", "web", "sh", "-c"]);
+    assert_eq!(
+        cmd.args[4],
+        "if command -v curl >/dev/null 2>&1; \
+         then curl -fsS -o /dev/null 'http://127.0.0.1:8080/health?full=1'; \
+         else wget -q -O /dev/null 'http://127.0.0.1:8080/health?full=1'; fi"
+    );
+
+    let count = std::env::temp_dir()
+        .join(format!("docker-command-probe-{}", std::process::id()));
+    // Stand in for `docker exec`: fail the first two attempts.
+    let script = format!(
+        "test \"$*\" = 'exec web true' || exit 2
+         echo x >> {0}
+         test $(wc -l < {0}) -gt 2",
+        count.display()
+    );
+    let mut base = command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+    base.log_command = false;
+    let launcher = Launcher::new(base);
+    let mut probe = Probe::exec(["true"]);
+    probe.retry.initial_delay = Duration::from_millis(1);
+
+    assert!(!launcher.probe(&web, &probe).unwrap());
+    std::fs::remove_file(&count).unwrap();
+    launcher.wait_ready(&web, &probe).unwrap();
+    assert_eq!(std::fs::read_to_string(&count).unwrap().len(), 6);
+    assert!(launcher.probe(&web, &probe).unwrap());
+
+    std::fs::remove_file(&count).unwrap();
+    probe.retry.max_attempts = 2;
+    let err = launcher.wait_ready(&web, &probe).unwrap_err();
+    assert!(matches!(err, Error::Run(_)));
+    std::fs::remove_file(&count).unwrap();
+}
+
+#[test]
+fn test_pin_image() {
+    use docker_command::skopeo::{InspectOpt, Location, Skopeo};
+
+    let digest = format!("sha256:{}", "a".repeat(64));
+    let other = format!("sha256:{}", "b".repeat(64));
+    // Stand in for `docker`: `pull` succeeds and `inspect` lists a
+    // digest for a mirror before the one for Docker Hub.
+    let script = format!(
+        "case $1 in
+            pull) test \"$2\" = alpine:3 ;;
+            inspect) echo '[{{\"RepoDigests\":[\"mirror.example.com/alpine@{}\",\"alpine@{}\"]}}]' ;;
+            *) exit 1 ;;
+        esac",
+        other, digest
+    );
+    let mut base = command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+    base.log_command = false;
+    let launcher = Launcher::new(base);
+
+    let mut opt = RunOpt::new("alpine:3");
+    launcher.pin_image(&mut opt).unwrap();
+    assert_eq!(opt.image.to_string(), format!("alpine@{}", digest));
+    // Already pinned, so nothing is run.
+    let pinned = ImageRef::from(format!("busybox@{}", other).as_str());
+    assert_eq!(launcher.resolve_digest(&pinned).unwrap(), pinned);
+    let err = launcher
+        .resolve_digest(&ImageRef::from("ghcr.io/owner/alpine:3"))
+        .unwrap_err();
+    assert!(matches!(err, Error::Run(_)));
+
+    let mut opt = InspectOpt::new(Location::Registry("alpine:3".into()));
+    opt.format = Some("{{.Digest}}".into());
+    assert_eq!(
+        Skopeo::default().inspect(opt).command_line_lossy(),
+        "skopeo inspect --format '{{.Digest}}' docker://alpine:3"
+    );
+
+    let skopeo = |output: &str| {
+        let mut cmd = command_run::Command::with_args(
+            "sh",
+            ["-c", &format!("echo {}", output), "sh"],
+        );
+        cmd.log_command = false;
+        Skopeo::new(cmd)
+    };
+    let mut opt = RunOpt::new("ghcr.io/owner/app:v1");
+    skopeo(&digest).pin_image(&mut opt).unwrap();
+    assert_eq!(
+        opt.image.to_string(),
+        format!("ghcr.io/owner/app@{}", digest)
+    );
+    let err = skopeo("latest")
+        .resolve_digest(&ImageRef::from("alpine"))
+        .unwrap_err();
+    assert!(matches!(err, Error::UnexpectedOutput { .. }));
+}
+
+#[test]
+fn test_retag_and_push() {
+    let source = ImageRef::from("app:1.2");
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman)
+            .tag(&source, &"app:latest".into())
+            .command_line_lossy(),
+        "podman tag app:1.2 app:latest"
+    );
+    assert_eq!(
+        args::tag_args(&source, &"app:latest".into()),
+        ["tag", "app:1.2", "app:latest"]
+    );
+    let commands = Launcher::from(BaseCommand::Docker)
+        .retag_and_push_commands(&"app".into(), &["ghcr.io/owner/app".into()]);
+    assert_eq!(
+        commands[0].command_line_lossy(),
+        "docker tag app ghcr.io/owner/app:latest"
+    );
+
+    let log = std::env::temp_dir()
+        .join(format!("docker-command-retag-{}", std::process::id()));
+    // Stand in for `docker`: tagging fails for `bad`, pushing fails
+    // for `down`, and successful pushes are recorded in `log`.
+    let script = format!(
+        "case \"$*\" in
+            'tag app:1.2 bad'*) exit 1 ;;
+            'push down'*) exit 1 ;;
+            push*) echo \"$2\" >> {} ;;
+        esac",
+        log.display()
+    );
+    let mut base = command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+    base.log_command = false;
+    let results = Launcher::new(base).retag_and_push(
+        &source,
+        &[
+            "one.example.com/app".into(),
+            "bad.example.com/app".into(),
+            "down.example.com/app:stable".into(),
+            "two.example.com/app:stable".into(),
+        ],
+        2,
+    );
+    let summary: Vec<_> = results
+        .iter()
+        .map(|(target, result)| (target.to_string(), result.is_ok()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("one.example.com/app:1.2".to_string(), true),
+            ("bad.example.com/app:1.2".to_string(), false),
+            ("down.example.com/app:stable".to_string(), false),
+            ("two.example.com/app:stable".to_string(), true),
+        ]
+    );
+    let mut pushed: Vec<_> = std::fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    pushed.sort();
+    assert_eq!(
+        pushed,
+        ["one.example.com/app:1.2", "two.example.com/app:stable"]
+    );
+    std::fs::remove_file(&log).unwrap();
+}
+
+#[test]
+fn test_repository_tags() {
+    use docker_command::skopeo::Skopeo;
+
+    let mut opt = SearchOpt::new("alpine");
+    opt.format = Some("json".into());
+    opt.limit = Some(10);
+    opt.list_tags = true;
+    opt.no_trunc = true;
+    assert_eq!(
+        Launcher::from(BaseCommand::Podman)
+            .search(opt.clone())
+            .command_line_lossy(),
+        "podman search --format json --limit 10 --list-tags --no-trunc alpine"
+    );
+    assert_eq!(args::search_args(&opt)[..3], ["search", "--format", "json"]);
+
+    let fake = |expected: &str, output: &str| {
+        let script = format!(
+            "test \"$*\" = '{}' || exit 1; echo '{}'",
+            expected, output
+        );
+        let mut cmd =
+            command_run::Command::with_args("sh", ["-c", &script, "sh"]);
+        cmd.log_command = false;
+        cmd
+    };
+    let image = ImageRef::from("ghcr.io/owner/app:1.2");
+
+    let skopeo = Skopeo::new(fake(
+        "list-tags docker://ghcr.io/owner/app",
+        r#"{"Repository":"ghcr.io/owner/app","Tags":["1.2","1.3","latest"]}"#,
+    ));
+    assert_eq!(
+        skopeo.repository_tags(&image).unwrap(),
+        ["1.2", "1.3", "latest"]
+    );
+
+    let launcher = Launcher::new(fake(
+        "search --format json --limit 100000 --list-tags ghcr.io/owner/app",
+        r#"[{"Name":"ghcr.io/owner/app","Tags":["1.2","1.3"]}]"#,
+    ));
+    assert_eq!(launcher.repository_tags(&image).unwrap(), ["1.2", "1.3"]);
+
+    let skopeo = Skopeo::new(fake(
+        "list-tags docker://ghcr.io/owner/app",
+        r#"{"Repository":"ghcr.io/owner/app"}"#,
+    ));
+    assert!(matches!(
+        skopeo.repository_tags(&image),
+        Err(Error::UnexpectedOutput { .. })
+    ));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing() {
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+
+    // Subscriber that records each span and event as a line of
+    // `name field=value ...`.
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    struct Line(String);
+
+    impl Visit for Line {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &tracing::Metadata) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes) -> Id {
+            let mut line = Line(span.metadata().name().into());
+            span.record(&mut line);
+            self.0.lock().unwrap().push(line.0);
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, values: &Record) {
+            let mut line = Line("record".into());
+            values.record(&mut line);
+            self.0.lock().unwrap().push(line.0);
+        }
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, event: &tracing::Event) {
+            let mut line = Line("event".into());
+            event.record(&mut line);
+            self.0.lock().unwrap().push(line.0);
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    // Stand in for `sudo docker`, failing every command.
+    let mut base =
+        command_run::Command::with_args("sh", ["-c", "exit 3", "docker"]);
+    base.log_command = false;
+    let mut global_opts = GlobalOpts::default();
+    global_opts.host = Some("tcp://builder".into());
+    let launcher = Launcher::new(base)
+        .with_global_opts(global_opts)
+        .with_env("REGISTRY_TOKEN", "s3cret");
+    let cmd =
+        launcher.stop(StopOpt::new(["web".parse::<ContainerRef>().unwrap()]));
+
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    tracing::subscriber::with_default(Recorder(lines.clone()), || {
+        assert!(cmd.run_logged().is_err());
+        assert!(cmd.run_with_timeout(Duration::from_secs(10)).is_err());
+    });
+    let lines = lines.lock().unwrap();
+    let spans: Vec<_> = lines
+        .iter()
+        .filter(|line| line.starts_with("run_command"))
+        .collect();
+    assert_eq!(spans.len(), 2);
+    assert!(spans.iter().all(|span| span.contains("subcommand=stop")));
+    assert!(lines.iter().any(|line| line == "record status=3"));
+    let failures: Vec<_> = lines
+        .iter()
+        .filter(|line| line.contains("command failed"))
+        .collect();
+    assert_eq!(failures.len(), 2);
+    assert!(failures
+        .iter()
+        .all(|line| line.contains("REGISTRY_TOKEN=***")
+            && !line.contains("s3cret")));
+}
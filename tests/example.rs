@@ -7,14 +7,12 @@ use std::path::Path;
 #[test]
 fn test_example() -> Result<(), Box<dyn Error>> {
     // Begin readme example
+    let mut opt = RunOpt::new("alpine:latest");
+    opt.command = Some(Path::new("echo").into());
+    opt.args = vec!["hello".into(), "world".into()];
     let output = Launcher::auto()
         .ok_or("container comand not found")?
-        .run(RunOpt {
-            image: "alpine:latest".into(),
-            command: Some(Path::new("echo").into()),
-            args: vec!["hello".into(), "world".into()],
-            ..Default::default()
-        })
+        .run(opt)
         .enable_capture()
         .run()?;
     assert_eq!(output.stdout_string_lossy(), "hello world\n");